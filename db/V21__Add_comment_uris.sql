@@ -0,0 +1,5 @@
+ALTER TABLE comments ADD COLUMN uri VARCHAR(2048) NULL;
+ALTER TABLE comments ADD COLUMN is_external BOOL NOT NULL DEFAULT false;
+UPDATE comments SET uri = CONCAT('/feed/', post_id, '/comments/', comment_id);
+ALTER TABLE comments ALTER COLUMN uri SET NOT NULL;
+CREATE UNIQUE INDEX comments_uq_uri_idx ON comments(uri);
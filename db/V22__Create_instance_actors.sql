@@ -0,0 +1,8 @@
+CREATE TABLE instance_actors (
+  instance_actor_id UUID NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  private_key VARCHAR(2048) NOT NULL,
+  public_key VARCHAR(2048) NOT NULL,
+  PRIMARY KEY (instance_actor_id)
+);
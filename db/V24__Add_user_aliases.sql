@@ -0,0 +1,2 @@
+ALTER TABLE users ADD COLUMN also_known_as TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE users ADD COLUMN moved_to_uri VARCHAR(2048) NULL;
@@ -0,0 +1,11 @@
+CREATE TABLE user_blocks (
+  user_block_id uuid NOT NULL,
+  user_id uuid NOT NULL,
+  blocked_user_id uuid NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT user_blocks_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT user_blocks_blocked_user_id_fkey FOREIGN KEY (blocked_user_id) REFERENCES users(user_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (user_block_id)
+);
+
+CREATE UNIQUE INDEX user_blocks_user_blocked_idx ON user_blocks(user_id, blocked_user_id);
@@ -0,0 +1,11 @@
+CREATE TABLE domain_blocks (
+  domain_block_id uuid NOT NULL,
+  domain VARCHAR(255) NOT NULL,
+  severity VARCHAR(32) NOT NULL,
+  public_comment TEXT NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  updated_at timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (domain_block_id)
+);
+
+CREATE UNIQUE INDEX domain_blocks_domain_idx ON domain_blocks(domain);
@@ -0,0 +1,19 @@
+CREATE TABLE reports (
+  report_id uuid NOT NULL,
+  reporter_user_id uuid NULL,
+  reporter_uri VARCHAR(2048) NULL,
+  target_user_id uuid NOT NULL,
+  post_ids uuid[] NOT NULL DEFAULT '{}',
+  comment TEXT NULL,
+  uri VARCHAR(2048) NULL,
+  is_external BOOLEAN NOT NULL DEFAULT FALSE,
+  forwarded BOOLEAN NOT NULL DEFAULT FALSE,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  updated_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT reports_reporter_user_id_fkey FOREIGN KEY (reporter_user_id) REFERENCES users(user_id) ON DELETE SET NULL ON UPDATE CASCADE,
+  CONSTRAINT reports_target_user_id_fkey FOREIGN KEY (target_user_id) REFERENCES users(user_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (report_id)
+);
+
+CREATE UNIQUE INDEX reports_uri_idx ON reports(uri);
+CREATE INDEX reports_created_at_idx ON reports(created_at);
@@ -0,0 +1,11 @@
+CREATE TABLE relays (
+  relay_id uuid NOT NULL,
+  actor_uri VARCHAR(2048) NOT NULL,
+  inbox_uri VARCHAR(2048) NOT NULL,
+  status VARCHAR(32) NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  updated_at timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (relay_id)
+);
+
+CREATE UNIQUE INDEX relays_actor_uri_idx ON relays(actor_uri);
@@ -0,0 +1,17 @@
+CREATE TABLE mentions (
+  mention_id uuid NOT NULL,
+  user_id uuid NOT NULL,
+  author_user_id uuid NOT NULL,
+  post_id uuid NULL,
+  comment_id uuid NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT mentions_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT mentions_author_user_id_fkey FOREIGN KEY (author_user_id) REFERENCES users(user_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT mentions_post_id_fkey FOREIGN KEY (post_id) REFERENCES posts(post_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT mentions_comment_id_fkey FOREIGN KEY (comment_id) REFERENCES comments(comment_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (mention_id)
+);
+
+CREATE INDEX mentions_user_created_at_idx ON mentions(user_id, created_at);
+CREATE INDEX mentions_post_id_idx ON mentions(post_id);
+CREATE INDEX mentions_comment_id_idx ON mentions(comment_id);
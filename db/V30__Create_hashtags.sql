@@ -0,0 +1,19 @@
+CREATE TABLE hashtags (
+  hashtag_id uuid NOT NULL,
+  name VARCHAR(256) NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (hashtag_id)
+);
+
+CREATE UNIQUE INDEX hashtags_name_idx ON hashtags(name);
+
+CREATE TABLE post_hashtags (
+  post_id uuid NOT NULL,
+  hashtag_id uuid NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT post_hashtags_post_id_fkey FOREIGN KEY (post_id) REFERENCES posts(post_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT post_hashtags_hashtag_id_fkey FOREIGN KEY (hashtag_id) REFERENCES hashtags(hashtag_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (post_id, hashtag_id)
+);
+
+CREATE INDEX post_hashtags_hashtag_id_idx ON post_hashtags(hashtag_id);
@@ -0,0 +1,41 @@
+CREATE TABLE polls (
+  poll_id uuid NOT NULL,
+  post_id uuid NOT NULL,
+  multiple_choice BOOLEAN NOT NULL DEFAULT FALSE,
+  expires_at timestamptz NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  updated_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT polls_post_id_fkey FOREIGN KEY (post_id) REFERENCES posts(post_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (poll_id)
+);
+
+CREATE UNIQUE INDEX polls_post_id_idx ON polls(post_id);
+
+CREATE TABLE poll_options (
+  option_id uuid NOT NULL,
+  poll_id uuid NOT NULL,
+  position INT NOT NULL,
+  name VARCHAR(512) NOT NULL,
+  -- The vote count last reported by the server a remote poll came from, as we only see our own users' votes
+  remote_votes INT NOT NULL DEFAULT 0,
+  CONSTRAINT poll_options_poll_id_fkey FOREIGN KEY (poll_id) REFERENCES polls(poll_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (option_id)
+);
+
+CREATE UNIQUE INDEX poll_options_poll_id_position_idx ON poll_options(poll_id, position);
+
+CREATE TABLE poll_votes (
+  vote_id uuid NOT NULL,
+  poll_id uuid NOT NULL,
+  option_id uuid NOT NULL,
+  user_id uuid NOT NULL,
+  uri VARCHAR(2048) NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT poll_votes_poll_id_fkey FOREIGN KEY (poll_id) REFERENCES polls(poll_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT poll_votes_option_id_fkey FOREIGN KEY (option_id) REFERENCES poll_options(option_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  CONSTRAINT poll_votes_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (vote_id)
+);
+
+CREATE UNIQUE INDEX poll_votes_option_id_user_id_idx ON poll_votes(option_id, user_id);
+CREATE INDEX poll_votes_poll_id_user_id_idx ON poll_votes(poll_id, user_id);
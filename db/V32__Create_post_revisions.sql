@@ -0,0 +1,13 @@
+CREATE TABLE post_revisions (
+  revision_id uuid NOT NULL,
+  post_id uuid NOT NULL,
+  title TEXT NULL,
+  content_md TEXT NOT NULL,
+  content_html TEXT NOT NULL,
+  -- When this version of the post was written, i.e. the post's updated_at before it was edited
+  created_at timestamptz NOT NULL,
+  CONSTRAINT post_revisions_post_id_fkey FOREIGN KEY (post_id) REFERENCES posts(post_id) ON DELETE CASCADE ON UPDATE CASCADE,
+  PRIMARY KEY (revision_id)
+);
+
+CREATE INDEX post_revisions_post_id_created_at_idx ON post_revisions(post_id, created_at);
@@ -0,0 +1,3 @@
+ALTER TABLE comments ADD COLUMN parent_comment_id uuid NULL;
+ALTER TABLE comments ADD CONSTRAINT comments_parent_comment_id_fkey FOREIGN KEY (parent_comment_id) REFERENCES comments(comment_id) ON DELETE CASCADE ON UPDATE CASCADE;
+CREATE INDEX comments_post_parent_idx ON comments(post_id, parent_comment_id);
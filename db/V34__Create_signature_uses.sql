@@ -0,0 +1,12 @@
+CREATE TABLE signature_uses (
+  key_id VARCHAR(2048) NOT NULL,
+  signature TEXT NOT NULL,
+  -- The job that handled the request, which may be retried without counting as a replay
+  job_id uuid NULL,
+  -- Signatures only need to be remembered until their request would be rejected as stale anyway
+  expires_at timestamptz NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX signature_uses_key_id_signature_idx ON signature_uses(key_id, signature);
+CREATE INDEX signature_uses_expires_at_idx ON signature_uses(expires_at);
@@ -0,0 +1,3 @@
+-- Replies outlive the comment they're replying to, rather than being deleted along with it
+ALTER TABLE comments DROP CONSTRAINT comments_parent_comment_id_fkey;
+ALTER TABLE comments ADD CONSTRAINT comments_parent_comment_id_fkey FOREIGN KEY (parent_comment_id) REFERENCES comments(comment_id) ON DELETE SET NULL ON UPDATE CASCADE;
@@ -0,0 +1,7 @@
+-- Ranks how contested a comment is the way Reddit does, by how much engagement it's drawn on either side and how
+-- evenly it's split between them. We don't have downvotes, so replies stand in for the pushback against a comment.
+CREATE FUNCTION comment_controversy(likes BIGINT, replies BIGINT) RETURNS FLOAT8 AS $$
+  SELECT CASE WHEN LEAST(likes, replies) <= 0 THEN 0
+  ELSE power((likes + replies)::float8, LEAST(likes, replies)::float8 / GREATEST(likes, replies))
+  END
+$$ LANGUAGE SQL IMMUTABLE;
@@ -0,0 +1,9 @@
+-- Whether the viewer has blocked any of the given users, or any of them have blocked the viewer. Feeds and comment
+-- threads all hide content on this, so it lives in one place rather than being repeated in each of their queries.
+CREATE FUNCTION blocked_between(viewer_id UUID, VARIADIC user_ids UUID[]) RETURNS BOOLEAN AS $$
+  SELECT EXISTS (
+    SELECT 1 FROM user_blocks b
+    WHERE (b.user_id = viewer_id AND b.blocked_user_id = ANY(user_ids))
+    OR (b.blocked_user_id = viewer_id AND b.user_id = ANY(user_ids))
+  )
+$$ LANGUAGE SQL STABLE;
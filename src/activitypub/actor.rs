@@ -32,4 +32,15 @@ pub struct ActorProps {
   pub sign_client_key: Option<String>,
   #[serde(rename = "sharedInbox", skip_serializing_if = "Option::is_none")]
   pub shared_inbox: Option<Reference<Object>>,
+  #[serde(
+    rename = "alsoKnownAs",
+    alias = "as:alsoKnownAs",
+    skip_serializing_if = "Option::is_none"
+  )]
+  pub also_known_as: Option<Reference<Object>>,
+  #[serde(rename = "movedTo", alias = "as:movedTo", skip_serializing_if = "Option::is_none")]
+  pub moved_to: Option<Reference<Object>>,
+  /// The collection of posts the actor has pinned, which Lemmy also uses for a community's pinned posts
+  #[serde(alias = "toot:featured", skip_serializing_if = "Option::is_none")]
+  pub featured: Option<Reference<Object>>,
 }
@@ -6,7 +6,7 @@ use serde_json::{Map, Value};
 use crate::{logic::LogicErr, settings::SETTINGS};
 
 use super::{
-  json_ld::{JsonLdContext, JsonLdContextEntry, JsonLdContextMapEntry},
+  json_ld::{JsonLdContext, JsonLdContextEntry, JsonLdContextMapEntry, JsonLdContextProps},
   object::Object,
 };
 
@@ -80,6 +80,22 @@ impl ActivityPubDocument {
       "summaryMd".to_string(),
       JsonLdContextMapEntry::Alias("orbit:summaryMd".to_string()),
     );
+    aliases.insert(
+      "alsoKnownAs".to_string(),
+      JsonLdContextMapEntry::Props(JsonLdContextProps {
+        id: "as:alsoKnownAs".to_string(),
+        container: None,
+        kind: Some("@id".to_string()),
+      }),
+    );
+    aliases.insert(
+      "movedTo".to_string(),
+      JsonLdContextMapEntry::Props(JsonLdContextProps {
+        id: "as:movedTo".to_string(),
+        container: None,
+        kind: Some("@id".to_string()),
+      }),
+    );
     aliases.insert(
       "orbit".to_string(),
       JsonLdContextMapEntry::Alias(format!("{}/.well-known/ns", SETTINGS.server.api_root_fqdn)),
@@ -106,7 +122,7 @@ mod tests {
   use crate::{
     activitypub::{
       document::ActivityPubDocument,
-      json_ld::{JsonLdContext, JsonLdContextEntry, JsonLdContextMapEntry},
+      json_ld::{JsonLdContext, JsonLdContextEntry, JsonLdContextMapEntry, JsonLdContextProps},
       object::Object,
     },
     settings::SETTINGS,
@@ -365,6 +381,22 @@ mod tests {
         "summaryMd".to_string(),
         JsonLdContextMapEntry::Alias("orbit:summaryMd".to_string()),
       );
+      aliases.insert(
+        "alsoKnownAs".to_string(),
+        JsonLdContextMapEntry::Props(JsonLdContextProps {
+          id: "as:alsoKnownAs".to_string(),
+          container: None,
+          kind: Some("@id".to_string()),
+        }),
+      );
+      aliases.insert(
+        "movedTo".to_string(),
+        JsonLdContextMapEntry::Props(JsonLdContextProps {
+          id: "as:movedTo".to_string(),
+          container: None,
+          kind: Some("@id".to_string()),
+        }),
+      );
       aliases.insert(
         "orbit".to_string(),
         JsonLdContextMapEntry::Alias(format!("{}/.well-known/ns", SETTINGS.server.api_root_fqdn)),
@@ -1,4 +1,8 @@
-use crate::{helpers::api::map_db_err, logic::LogicErr, model::comment_pub::CommentPub};
+use crate::{
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::{comment::Comment, comment_pub::CommentPub, comment_sort::CommentSort},
+};
 
 use super::FromRow;
 use async_trait::async_trait;
@@ -19,19 +23,51 @@ pub trait CommentRepo {
     limit: i64,
     skip: i64,
   ) -> Result<Vec<CommentPub>, LogicErr>;
+  /// Fetches the direct replies to a comment, or the top-level comments of a post if there's no parent comment
+  async fn fetch_comment_thread(
+    &self,
+    post_id: &Uuid,
+    parent_comment_id: &Option<Uuid>,
+    own_user_id: &Option<Uuid>,
+    sort: &CommentSort,
+    limit: i64,
+    skip: i64,
+  ) -> Result<Vec<CommentPub>, LogicErr>;
+  async fn fetch_comment_thread_count(
+    &self,
+    post_id: &Uuid,
+    parent_comment_id: &Option<Uuid>,
+    own_user_id: &Option<Uuid>,
+  ) -> Result<i64, LogicErr>;
+  /// Fetches up to `limit` direct replies to each of the given comments, in the given order
+  async fn fetch_comment_replies(
+    &self,
+    post_id: &Uuid,
+    parent_comment_ids: &[Uuid],
+    own_user_id: &Option<Uuid>,
+    sort: &CommentSort,
+    limit: i64,
+  ) -> Result<Vec<CommentPub>, LogicErr>;
   async fn create_comment(
     &self,
     user_id: &Uuid,
     post_id: &Uuid,
     content_md: &str,
     content_html: &str,
+    parent_comment_id: &Option<Uuid>,
   ) -> Result<Uuid, LogicErr>;
-  async fn delete_comment(&self, user_id: &Uuid, post_id: &Uuid, comment_id: &Uuid) -> Result<(), LogicErr>;
+  async fn create_comment_from(&self, comment: Comment) -> Result<(), LogicErr>;
+  async fn find_optional_by_id(&self, comment_id: &Uuid) -> Option<Comment>;
+  async fn find_optional_by_uri(&self, uri: &str) -> Option<Comment>;
+  async fn update_comment_content(&self, comment: &Comment) -> Result<(), LogicErr>;
+  /// Deletes one of a user's comments, returning the number of comments deleted
+  async fn delete_comment(&self, user_id: &Uuid, post_id: &Uuid, comment_id: &Uuid) -> Result<u64, LogicErr>;
   async fn fetch_comments_count(&self, post_id: &Uuid, own_user_id: &Option<Uuid>) -> Result<i64, LogicErr>;
   async fn create_comment_like(&self, user_id: &Uuid, comment_id: &Uuid, post_id: &Uuid) -> Result<(), LogicErr>;
   async fn delete_comment_like(&self, user_id: &Uuid, comment_id: &Uuid, post_id: &Uuid) -> Result<(), LogicErr>;
   async fn fetch_comment_count(&self) -> i64;
   async fn fetch_comment(&self, post_id: &Uuid, comment_id: &Uuid, own_user_id: &Option<Uuid>) -> Option<CommentPub>;
+  async fn delete_comment_from_uri(&self, uri: &str, user_id: &Uuid) -> Result<(), LogicErr>;
 }
 
 pub type CommentPool = Arc<dyn CommentRepo + Send + Sync>;
@@ -61,23 +97,90 @@ impl CommentRepo for DbCommentRepo {
     Ok(rows.into_iter().flat_map(CommentPub::from_row).collect())
   }
 
+  async fn fetch_comment_thread(
+    &self,
+    post_id: &Uuid,
+    parent_comment_id: &Option<Uuid>,
+    own_user_id: &Option<Uuid>,
+    sort: &CommentSort,
+    limit: i64,
+    skip: i64,
+  ) -> Result<Vec<CommentPub>, LogicErr> {
+    let sort = sort.to_string();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        include_str!("./sql/fetch_post_comment_thread.sql"),
+        &[&own_user_id, &post_id, &limit, &skip, &parent_comment_id, &sort],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(CommentPub::from_row).collect())
+  }
+
+  async fn fetch_comment_thread_count(
+    &self,
+    post_id: &Uuid,
+    parent_comment_id: &Option<Uuid>,
+    own_user_id: &Option<Uuid>,
+  ) -> Result<i64, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_one(
+        include_str!("./sql/fetch_post_comment_thread_count.sql"),
+        &[&own_user_id, &post_id, &parent_comment_id],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(row.get(0))
+  }
+
+  async fn fetch_comment_replies(
+    &self,
+    post_id: &Uuid,
+    parent_comment_ids: &[Uuid],
+    own_user_id: &Option<Uuid>,
+    sort: &CommentSort,
+    limit: i64,
+  ) -> Result<Vec<CommentPub>, LogicErr> {
+    let sort = sort.to_string();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        include_str!("./sql/fetch_post_comment_replies.sql"),
+        &[&own_user_id, &post_id, &parent_comment_ids, &sort, &limit],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(CommentPub::from_row).collect())
+  }
+
   async fn create_comment(
     &self,
     user_id: &Uuid,
     post_id: &Uuid,
     content_md: &str,
     content_html: &str,
+    parent_comment_id: &Option<Uuid>,
   ) -> Result<Uuid, LogicErr> {
     let comment_id = Uuid::new_v4();
+    let uri = format!("/feed/{}/comments/{}", post_id, comment_id);
 
     let db = self.db.get().await.map_err(map_db_err)?;
-    let row = db.query_one("INSERT INTO comments (comment_id, user_id, post_id, content_md, content_html) VALUES ($1, $2, $3, $4, $5) RETURNING comment_id",
+    let row = db.query_one("INSERT INTO comments (comment_id, user_id, post_id, content_md, content_html, uri, is_external, parent_comment_id) VALUES ($1, $2, $3, $4, $5, $6, false, $7) RETURNING comment_id",
       &[
         &comment_id,
         &user_id,
         &post_id,
         &content_md,
         &content_html,
+        &uri,
+        &parent_comment_id,
       ],
     )
     .await
@@ -86,11 +189,23 @@ impl CommentRepo for DbCommentRepo {
     Ok(row.get(0))
   }
 
-  async fn delete_comment(&self, user_id: &Uuid, post_id: &Uuid, comment_id: &Uuid) -> Result<(), LogicErr> {
+  async fn create_comment_from(&self, comment: Comment) -> Result<(), LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     db.execute(
-      "DELETE FROM comments WHERE post_id = $1 AND user_id = $2 AND comment_id = $3",
-      &[&post_id, &user_id, &comment_id],
+      "INSERT INTO comments (comment_id, user_id, post_id, content_md, content_html, uri, is_external, created_at, updated_at, parent_comment_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+      &[
+        &comment.comment_id,
+        &comment.user_id,
+        &comment.post_id,
+        &comment.content_md,
+        &comment.content_html,
+        &comment.uri,
+        &comment.is_external,
+        &comment.created_at,
+        &comment.updated_at,
+        &comment.parent_comment_id,
+      ],
     )
     .await
     .map_err(map_db_err)?;
@@ -98,6 +213,69 @@ impl CommentRepo for DbCommentRepo {
     Ok(())
   }
 
+  async fn find_optional_by_id(&self, comment_id: &Uuid) -> Option<Comment> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let row = match db
+      .query_opt("SELECT * FROM comments WHERE comment_id = $1", &[&comment_id])
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return None,
+    };
+
+    row.and_then(Comment::from_row)
+  }
+
+  async fn find_optional_by_uri(&self, uri: &str) -> Option<Comment> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let row = match db
+      .query_opt("SELECT * FROM comments WHERE uri = $1", &[&uri])
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return None,
+    };
+
+    row.and_then(Comment::from_row)
+  }
+
+  async fn update_comment_content(&self, comment: &Comment) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      "UPDATE comments SET content_html = $2, content_md = $3, updated_at = $4 WHERE comment_id = $1",
+      &[
+        &comment.comment_id,
+        &comment.content_html,
+        &comment.content_md,
+        &comment.updated_at,
+      ],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn delete_comment(&self, user_id: &Uuid, post_id: &Uuid, comment_id: &Uuid) -> Result<u64, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      "DELETE FROM comments WHERE post_id = $1 AND user_id = $2 AND comment_id = $3",
+      &[&post_id, &user_id, &comment_id],
+    )
+    .await
+    .map_err(map_db_err)
+  }
+
   async fn fetch_comments_count(&self, post_id: &Uuid, own_user_id: &Option<Uuid>) -> Result<i64, LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     let row = db
@@ -175,4 +353,16 @@ impl CommentRepo for DbCommentRepo {
 
     row.and_then(CommentPub::from_row)
   }
+
+  async fn delete_comment_from_uri(&self, uri: &str, user_id: &Uuid) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      "DELETE FROM comments WHERE uri = $1 AND user_id = $2",
+      &[&uri, &user_id],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
 }
@@ -0,0 +1,101 @@
+use super::FromRow;
+use crate::{
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::{domain_block::DomainBlock, domain_block_severity::DomainBlockSeverity},
+};
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait DomainBlockRepo {
+  async fn fetch_domain_blocks(&self) -> Result<Vec<DomainBlock>, LogicErr>;
+  /// Fetches the most severe block that applies to the given host, including blocks on any of its parent domains
+  async fn fetch_domain_block(&self, host: &str) -> Option<DomainBlock>;
+  /// Creates a block for the domain, or replaces the existing block if there is one
+  async fn create_domain_block(
+    &self,
+    domain: &str,
+    severity: &DomainBlockSeverity,
+    public_comment: &Option<String>,
+  ) -> Result<(), LogicErr>;
+  async fn delete_domain_block(&self, domain: &str) -> Result<(), LogicErr>;
+}
+
+pub type DomainBlockPool = Arc<dyn DomainBlockRepo + Send + Sync>;
+
+pub struct DbDomainBlockRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl DomainBlockRepo for DbDomainBlockRepo {
+  async fn fetch_domain_blocks(&self) -> Result<Vec<DomainBlock>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query("SELECT * FROM domain_blocks ORDER BY domain", &[])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(DomainBlock::from_row).collect())
+  }
+
+  async fn fetch_domain_block(&self, host: &str) -> Option<DomainBlock> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let row = match db
+      .query_opt(
+        r#"SELECT * FROM domain_blocks
+        WHERE right('.' || $1, length(domain) + 1) = '.' || domain
+        ORDER BY CASE severity WHEN 'suspend' THEN 0 WHEN 'silence' THEN 1 ELSE 2 END
+        LIMIT 1"#,
+        &[&host],
+      )
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return None,
+    };
+
+    row.and_then(DomainBlock::from_row)
+  }
+
+  async fn create_domain_block(
+    &self,
+    domain: &str,
+    severity: &DomainBlockSeverity,
+    public_comment: &Option<String>,
+  ) -> Result<(), LogicErr> {
+    let domain_block_id = Uuid::new_v4();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      r#"INSERT INTO domain_blocks (domain_block_id, domain, severity, public_comment) VALUES ($1, $2, $3, $4)
+      ON CONFLICT (domain) DO UPDATE SET severity = $3, public_comment = $4, updated_at = now()"#,
+      &[&domain_block_id, &domain, &severity.to_string(), &public_comment],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn delete_domain_block(&self, domain: &str) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute("DELETE FROM domain_blocks WHERE domain = $1", &[&domain])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(())
+  }
+}
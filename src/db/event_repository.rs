@@ -18,9 +18,12 @@ use mockall::automock;
 #[async_trait]
 pub trait EventRepo {
   async fn create_event(&self, event: NewEvent) -> Result<(), LogicErr>;
+  /// Creates an event unless the same one already exists, returning whether it was created
+  async fn try_create_event(&self, event: NewEvent) -> Result<bool, LogicErr>;
   async fn update_event(&self, event: &Event) -> Result<(), LogicErr>;
   async fn delete_event(&self, event_id: &Uuid) -> Result<(), LogicErr>;
   async fn delete_post_events(&self, post_id: &Uuid, user_id: &Uuid, event_type: EventType) -> Result<(), LogicErr>;
+  async fn user_boosted_post(&self, user_id: &Uuid, post_id: &Uuid) -> bool;
 }
 
 pub type EventPool = Arc<dyn EventRepo + Send + Sync>;
@@ -53,6 +56,31 @@ impl EventRepo for DbEventRepo {
     Ok(())
   }
 
+  async fn try_create_event(&self, event: NewEvent) -> Result<bool, LogicErr> {
+    let event_id = Uuid::new_v4();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let count = db
+      .execute(
+        r#"INSERT INTO events (event_id, source_user_id, target_user_id, visibility, post_id, like_id, comment_id, event_type)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT DO NOTHING"#,
+        &[
+          &event_id,
+          &event.source_user_id,
+          &event.target_user_id,
+          &event.visibility.to_string(),
+          &event.post_id,
+          &event.like_id,
+          &event.comment_id,
+          &event.event_type.to_string(),
+        ],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(count > 0)
+  }
+
   async fn update_event(&self, event: &Event) -> Result<(), LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     db.execute(
@@ -95,4 +123,26 @@ impl EventRepo for DbEventRepo {
 
     Ok(())
   }
+
+  async fn user_boosted_post(&self, user_id: &Uuid, post_id: &Uuid) -> bool {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return false,
+    };
+
+    let row = match db
+      .query_one(
+        "SELECT COUNT(*) FROM events WHERE source_user_id = $1 AND post_id = $2 AND event_type = $3",
+        &[&user_id, &post_id, &EventType::Boost.to_string()],
+      )
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return false,
+    };
+
+    let count: i64 = row.get(0);
+    count > 0
+  }
 }
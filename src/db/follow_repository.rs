@@ -1,4 +1,8 @@
-use crate::{helpers::api::map_db_err, logic::LogicErr, model::follow::Follow};
+use crate::{
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::{follow::Follow, user::User},
+};
 
 use async_trait::async_trait;
 use deadpool_postgres::Pool;
@@ -17,6 +21,9 @@ pub trait FollowRepo {
   async fn user_follows_poster(&self, post_id: &Uuid, user_id: &Uuid) -> bool;
   async fn user_follows_user(&self, following_user_id: &Uuid, followed_user_id: &Uuid) -> bool;
   async fn fetch_user_followers(&self, user_id: &Uuid) -> Option<Vec<Follow>>;
+  async fn fetch_user_local_followers(&self, user_id: &Uuid) -> Option<Vec<Follow>>;
+  /// Fetches the remote followers that can be delivered to, oldest follow first
+  async fn fetch_user_remote_followers(&self, user_id: &Uuid) -> Option<Vec<User>>;
 }
 
 pub type FollowPool = Arc<dyn FollowRepo + Send + Sync>;
@@ -124,4 +131,50 @@ impl FollowRepo for DbFollowRepo {
 
     Some(rows.into_iter().flat_map(Follow::from_row).collect())
   }
+
+  async fn fetch_user_local_followers(&self, user_id: &Uuid) -> Option<Vec<Follow>> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let rows = match db
+      .query(
+        r#"SELECT f.* FROM followers f INNER JOIN users u ON u.user_id = f.user_id
+        WHERE f.following_user_id = $1 AND f.user_id != f.following_user_id AND u.is_external = FALSE"#,
+        &[&user_id],
+      )
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(rows) => rows,
+      Err(_) => return None,
+    };
+
+    Some(rows.into_iter().flat_map(Follow::from_row).collect())
+  }
+
+  async fn fetch_user_remote_followers(&self, user_id: &Uuid) -> Option<Vec<User>> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let rows = match db
+      .query(
+        r#"SELECT u.* FROM followers f INNER JOIN users u ON u.user_id = f.user_id
+        WHERE f.following_user_id = $1 AND f.user_id != f.following_user_id AND u.is_external = TRUE
+        AND u.ext_apub_inbox_uri IS NOT NULL
+        ORDER BY f.created_at"#,
+        &[&user_id],
+      )
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(rows) => rows,
+      Err(_) => return None,
+    };
+
+    Some(rows.into_iter().flat_map(User::from_row).collect())
+  }
 }
@@ -0,0 +1,54 @@
+use crate::{helpers::api::map_db_err, logic::LogicErr};
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait HashtagRepo {
+  /// Replaces the hashtags a post is tagged with, creating any hashtags we haven't seen before. Names are expected to
+  /// have already been normalized.
+  async fn set_post_hashtags(&self, post_id: &Uuid, names: &[String]) -> Result<(), LogicErr>;
+}
+
+pub type HashtagPool = Arc<dyn HashtagRepo + Send + Sync>;
+
+pub struct DbHashtagRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl HashtagRepo for DbHashtagRepo {
+  async fn set_post_hashtags(&self, post_id: &Uuid, names: &[String]) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+
+    db.execute("DELETE FROM post_hashtags WHERE post_id = $1", &[&post_id])
+      .await
+      .map_err(map_db_err)?;
+
+    for name in names {
+      let hashtag_id = Uuid::new_v4();
+
+      db.execute(
+        "INSERT INTO hashtags (hashtag_id, name) VALUES ($1, $2) ON CONFLICT (name) DO NOTHING",
+        &[&hashtag_id, &name],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+      db.execute(
+        r#"INSERT INTO post_hashtags (post_id, hashtag_id) SELECT $1, hashtag_id FROM hashtags WHERE name = $2
+        ON CONFLICT DO NOTHING"#,
+        &[&post_id, &name],
+      )
+      .await
+      .map_err(map_db_err)?;
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,62 @@
+use super::FromRow;
+use crate::{helpers::api::map_db_err, logic::LogicErr, model::instance_actor::InstanceActor};
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait InstanceActorRepo {
+  async fn fetch_instance_actor(&self) -> Option<InstanceActor>;
+  async fn create_instance_actor(&self, private_key: &str, public_key: &str) -> Result<InstanceActor, LogicErr>;
+}
+
+pub type InstanceActorPool = Arc<dyn InstanceActorRepo + Send + Sync>;
+
+pub struct DbInstanceActorRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl InstanceActorRepo for DbInstanceActorRepo {
+  async fn fetch_instance_actor(&self) -> Option<InstanceActor> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+    // NOTE: The API server and workers may race to create the actor on first boot, so we always settle on the
+    //       oldest record to keep the signing key stable.
+    let row = match db
+      .query_opt("SELECT * FROM instance_actors ORDER BY created_at ASC LIMIT 1", &[])
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return None,
+    };
+
+    row.and_then(InstanceActor::from_row)
+  }
+
+  async fn create_instance_actor(&self, private_key: &str, public_key: &str) -> Result<InstanceActor, LogicErr> {
+    let instance_actor_id = Uuid::new_v4();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_one(
+        "INSERT INTO instance_actors (instance_actor_id, private_key, public_key) VALUES ($1, $2, $3) RETURNING *",
+        &[&instance_actor_id, &private_key, &public_key],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    match InstanceActor::from_row(row) {
+      Some(actor) => Ok(actor),
+      None => Err(LogicErr::MissingRecord),
+    }
+  }
+}
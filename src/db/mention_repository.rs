@@ -0,0 +1,107 @@
+use super::FromRow;
+use crate::{
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::{mention::Mention, user::User},
+};
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait MentionRepo {
+  async fn create_mention(
+    &self,
+    user_id: &Uuid,
+    author_user_id: &Uuid,
+    post_id: &Option<Uuid>,
+    comment_id: &Option<Uuid>,
+  ) -> Result<(), LogicErr>;
+  async fn fetch_post_mentioned_users(&self, post_id: &Uuid) -> Result<Vec<User>, LogicErr>;
+  async fn fetch_comment_mentioned_users(&self, comment_id: &Uuid) -> Result<Vec<User>, LogicErr>;
+  async fn fetch_user_mentions(&self, user_id: &Uuid, limit: i64, skip: i64) -> Result<Vec<Mention>, LogicErr>;
+  async fn count_user_mentions(&self, user_id: &Uuid) -> Result<i64, LogicErr>;
+}
+
+pub type MentionPool = Arc<dyn MentionRepo + Send + Sync>;
+
+pub struct DbMentionRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl MentionRepo for DbMentionRepo {
+  async fn create_mention(
+    &self,
+    user_id: &Uuid,
+    author_user_id: &Uuid,
+    post_id: &Option<Uuid>,
+    comment_id: &Option<Uuid>,
+  ) -> Result<(), LogicErr> {
+    let mention_id = Uuid::new_v4();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      r#"INSERT INTO mentions (mention_id, user_id, author_user_id, post_id, comment_id) VALUES ($1, $2, $3, $4, $5)"#,
+      &[&mention_id, &user_id, &author_user_id, &post_id, &comment_id],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn fetch_post_mentioned_users(&self, post_id: &Uuid) -> Result<Vec<User>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        "SELECT u.* FROM users u INNER JOIN mentions m ON m.user_id = u.user_id WHERE m.post_id = $1",
+        &[&post_id],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(User::from_row).collect())
+  }
+
+  async fn fetch_comment_mentioned_users(&self, comment_id: &Uuid) -> Result<Vec<User>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        "SELECT u.* FROM users u INNER JOIN mentions m ON m.user_id = u.user_id WHERE m.comment_id = $1",
+        &[&comment_id],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(User::from_row).collect())
+  }
+
+  async fn fetch_user_mentions(&self, user_id: &Uuid, limit: i64, skip: i64) -> Result<Vec<Mention>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        "SELECT * FROM mentions WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+        &[&user_id, &limit, &skip],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(Mention::from_row).collect())
+  }
+
+  async fn count_user_mentions(&self, user_id: &Uuid) -> Result<i64, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_one("SELECT COUNT(*) FROM mentions WHERE user_id = $1", &[&user_id])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(row.get(0))
+  }
+}
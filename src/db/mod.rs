@@ -1,18 +1,28 @@
 pub mod app_repository;
 pub mod comment_repository;
+pub mod domain_block_repository;
 pub mod event_repository;
 pub mod follow_repository;
+pub mod hashtag_repository;
+pub mod instance_actor_repository;
 pub mod job_repository;
 pub mod like_repository;
+pub mod mention_repository;
 pub mod orbit_moderator_repository;
 pub mod orbit_repository;
+pub mod poll_repository;
 pub mod post_attachment_repository;
 pub mod post_repository;
+pub mod post_revision_repository;
+pub mod relay_repository;
+pub mod report_repository;
 pub mod repositories;
 pub mod repository;
 pub mod session_repository;
+pub mod signature_repository;
 pub mod tombstone_repository;
 pub mod traits;
+pub mod user_block_repository;
 pub mod user_orbit_repository;
 pub mod user_repository;
 pub mod user_stats_repository;
@@ -55,6 +55,8 @@ pub trait OrbitRepo {
   async fn delete_orbit(&self, orbit_id: &Uuid) -> Result<(), LogicErr>;
   async fn delete_external_orbit(&self, orbit_id: &Uuid) -> Result<(), LogicErr>;
   async fn fetch_outdated_external_orbits(&self) -> Result<Vec<Uuid>, LogicErr>;
+  /// Fetches the external orbits that at least one of our users is a member of
+  async fn fetch_joined_external_orbits(&self) -> Result<Vec<Uuid>, LogicErr>;
 }
 
 pub type OrbitPool = Arc<dyn OrbitRepo + Send + Sync>;
@@ -355,4 +357,18 @@ impl OrbitRepo for DbOrbitRepo {
 
     Ok(rows.into_iter().map(|r| r.get::<&str, Uuid>("orbit_id")).collect())
   }
+
+  async fn fetch_joined_external_orbits(&self) -> Result<Vec<Uuid>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        r#"SELECT DISTINCT o.orbit_id FROM orbits o INNER JOIN user_orbits uo ON uo.orbit_id = o.orbit_id
+        WHERE o.is_external = TRUE"#,
+        &[],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().map(|r| r.get::<&str, Uuid>("orbit_id")).collect())
+  }
 }
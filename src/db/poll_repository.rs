@@ -0,0 +1,223 @@
+use super::FromRow;
+use crate::{
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::poll::{Poll, PollOption, PollVote},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait PollRepo {
+  /// Creates a poll for a post, with its options kept in the order they're given
+  async fn create_poll(
+    &self,
+    post_id: &Uuid,
+    multiple_choice: bool,
+    expires_at: &Option<DateTime<Utc>>,
+    options: &[String],
+  ) -> Result<Uuid, LogicErr>;
+  async fn fetch_poll_by_post(&self, post_id: &Uuid) -> Result<Option<Poll>, LogicErr>;
+  async fn fetch_poll_options(&self, poll_id: &Uuid) -> Result<Vec<PollOption>, LogicErr>;
+  async fn fetch_user_votes(&self, poll_id: &Uuid, user_id: &Uuid) -> Result<Vec<PollVote>, LogicErr>;
+  /// Records a user's votes in a poll, returning the options that hadn't already been voted for, or `None` if the user
+  /// can't cast these votes. The poll is locked while this happens, so that a user voting twice at once can't get more
+  /// votes than the poll allows. Our own users cast all their votes at once, whereas other servers send votes in
+  /// multiple choice polls one option at a time, which `additional` allows for.
+  async fn create_votes(
+    &self,
+    poll_id: &Uuid,
+    option_ids: &[Uuid],
+    user_id: &Uuid,
+    uri: &Option<String>,
+    additional: bool,
+  ) -> Result<Option<Vec<Uuid>>, LogicErr>;
+  async fn update_poll_expiry(&self, poll_id: &Uuid, expires_at: &Option<DateTime<Utc>>) -> Result<(), LogicErr>;
+  /// Replaces the vote count the server a remote poll came from last reported for one of its options
+  async fn update_option_remote_votes(&self, option_id: &Uuid, votes: i32) -> Result<(), LogicErr>;
+  /// Counts one of our users' votes against a remote poll's option until its server reports the new total
+  async fn increment_option_remote_votes(&self, option_id: &Uuid) -> Result<(), LogicErr>;
+}
+
+pub type PollPool = Arc<dyn PollRepo + Send + Sync>;
+
+pub struct DbPollRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl PollRepo for DbPollRepo {
+  async fn create_poll(
+    &self,
+    post_id: &Uuid,
+    multiple_choice: bool,
+    expires_at: &Option<DateTime<Utc>>,
+    options: &[String],
+  ) -> Result<Uuid, LogicErr> {
+    let poll_id = Uuid::new_v4();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      "INSERT INTO polls (poll_id, post_id, multiple_choice, expires_at) VALUES ($1, $2, $3, $4)",
+      &[&poll_id, &post_id, &multiple_choice, &expires_at],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    for (position, name) in options.iter().enumerate() {
+      let option_id = Uuid::new_v4();
+      let position = position as i32;
+
+      db.execute(
+        "INSERT INTO poll_options (option_id, poll_id, position, name) VALUES ($1, $2, $3, $4)",
+        &[&option_id, &poll_id, &position, &name],
+      )
+      .await
+      .map_err(map_db_err)?;
+    }
+
+    Ok(poll_id)
+  }
+
+  async fn fetch_poll_by_post(&self, post_id: &Uuid) -> Result<Option<Poll>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_opt("SELECT * FROM polls WHERE post_id = $1", &[&post_id])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(row.and_then(Poll::from_row))
+  }
+
+  async fn fetch_poll_options(&self, poll_id: &Uuid) -> Result<Vec<PollOption>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        "SELECT * FROM poll_options WHERE poll_id = $1 ORDER BY position",
+        &[&poll_id],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(PollOption::from_row).collect())
+  }
+
+  async fn fetch_user_votes(&self, poll_id: &Uuid, user_id: &Uuid) -> Result<Vec<PollVote>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        "SELECT * FROM poll_votes WHERE poll_id = $1 AND user_id = $2 ORDER BY created_at",
+        &[&poll_id, &user_id],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(PollVote::from_row).collect())
+  }
+
+  async fn create_votes(
+    &self,
+    poll_id: &Uuid,
+    option_ids: &[Uuid],
+    user_id: &Uuid,
+    uri: &Option<String>,
+    additional: bool,
+  ) -> Result<Option<Vec<Uuid>>, LogicErr> {
+    let mut db = self.db.get().await.map_err(map_db_err)?;
+    let tx = db.transaction().await.map_err(map_db_err)?;
+
+    let multiple_choice: bool = match tx
+      .query_opt(
+        "SELECT multiple_choice FROM polls WHERE poll_id = $1 FOR UPDATE",
+        &[&poll_id],
+      )
+      .await
+      .map_err(map_db_err)?
+    {
+      Some(row) => row.get("multiple_choice"),
+      None => return Err(LogicErr::MissingRecord),
+    };
+
+    let voted: Vec<Uuid> = tx
+      .query(
+        "SELECT option_id FROM poll_votes WHERE poll_id = $1 AND user_id = $2",
+        &[&poll_id, &user_id],
+      )
+      .await
+      .map_err(map_db_err)?
+      .into_iter()
+      .map(|row| row.get("option_id"))
+      .collect();
+
+    // The same vote may be delivered to us more than once, so votes the user has already cast don't count against them
+    let allowed = (voted.is_empty() || additional)
+      && (multiple_choice || (option_ids.len() == 1 && voted.iter().all(|id| id == &option_ids[0])));
+
+    if !allowed {
+      return Ok(None);
+    }
+
+    let mut created = vec![];
+
+    for option_id in option_ids.iter().filter(|id| !voted.contains(id)) {
+      let vote_id = Uuid::new_v4();
+
+      tx.execute(
+        r#"INSERT INTO poll_votes (vote_id, poll_id, option_id, user_id, uri) VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT DO NOTHING"#,
+        &[&vote_id, &poll_id, &option_id, &user_id, &uri],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+      created.push(*option_id);
+    }
+
+    tx.commit().await.map_err(map_db_err)?;
+
+    Ok(Some(created))
+  }
+
+  async fn update_poll_expiry(&self, poll_id: &Uuid, expires_at: &Option<DateTime<Utc>>) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      "UPDATE polls SET expires_at = $2, updated_at = now() WHERE poll_id = $1",
+      &[&poll_id, &expires_at],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn update_option_remote_votes(&self, option_id: &Uuid, votes: i32) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      "UPDATE poll_options SET remote_votes = $2 WHERE option_id = $1",
+      &[&option_id, &votes],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn increment_option_remote_votes(&self, option_id: &Uuid) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      "UPDATE poll_options SET remote_votes = remote_votes + 1 WHERE option_id = $1",
+      &[&option_id],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+}
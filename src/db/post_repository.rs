@@ -1,11 +1,11 @@
 use crate::{
   helpers::api::map_db_err,
   logic::LogicErr,
-  model::{access_type::AccessType, post::Post, post_event::PostEvent},
+  model::{access_type::AccessType, poll::PostPoll, post::Post, post_event::PostEvent},
 };
 
 use async_trait::async_trait;
-use deadpool_postgres::Pool;
+use deadpool_postgres::{Client, Pool};
 use std::{str::FromStr, sync::Arc};
 use uuid::Uuid;
 
@@ -39,18 +39,38 @@ pub trait PostRepo {
   /// user can see, or alternatively all the user's public posts
   async fn count_user_public_feed(&self, target_user_id: &Uuid, own_user_id: &Option<Uuid>) -> Result<i64, LogicErr>;
   /// Fetches the global federated feed, i.e. what users not signed into this instance can see
-  async fn fetch_global_federated_feed(&self, limit: i64, skip: i64) -> Result<Vec<PostEvent>, LogicErr>;
+  async fn fetch_global_federated_feed(
+    &self,
+    own_user_id: &Option<Uuid>,
+    limit: i64,
+    skip: i64,
+  ) -> Result<Vec<PostEvent>, LogicErr>;
   /// Fetches the post count for the global federated feed, i.e. what users not signed into this instance can see
-  async fn count_global_federated_feed(&self) -> Result<i64, LogicErr>;
+  async fn count_global_federated_feed(&self, own_user_id: &Option<Uuid>) -> Result<i64, LogicErr>;
   /// Fetches the global federated orbit feed
   async fn fetch_global_federated_orbit_feed(
     &self,
     orbit_id: &Uuid,
+    own_user_id: &Option<Uuid>,
     limit: i64,
     skip: i64,
   ) -> Result<Vec<PostEvent>, LogicErr>;
   /// Fetches the post count for the global federated orbit feed
-  async fn count_global_federated_orbit_feed(&self, orbit_id: &Uuid) -> Result<i64, LogicErr>;
+  async fn count_global_federated_orbit_feed(
+    &self,
+    orbit_id: &Uuid,
+    own_user_id: &Option<Uuid>,
+  ) -> Result<i64, LogicErr>;
+  /// Fetches the public posts tagged with the given hashtag
+  async fn fetch_hashtag_feed(
+    &self,
+    hashtag: &str,
+    own_user_id: &Option<Uuid>,
+    limit: i64,
+    skip: i64,
+  ) -> Result<Vec<PostEvent>, LogicErr>;
+  /// Fetches the count of public posts tagged with the given hashtag
+  async fn count_hashtag_feed(&self, hashtag: &str, own_user_id: &Option<Uuid>) -> Result<i64, LogicErr>;
   async fn fetch_by_id(&self, id: &Uuid) -> Result<Post, LogicErr>;
   /// Fetches the specified post from a user's own perspective
   async fn fetch_post(&self, post_id: &Uuid, user_id: &Option<Uuid>) -> Result<Option<PostEvent>, LogicErr>;
@@ -73,6 +93,7 @@ pub trait PostRepo {
   async fn fetch_owner_by_id(&self, post_id: &Uuid) -> Option<Uuid>;
   async fn fetch_owner_handle_by_id(&self, post_id: &Uuid) -> Option<String>;
   async fn fetch_post_count(&self) -> i64;
+  async fn fetch_user_posts(&self, user_id: &Uuid) -> Result<Vec<Post>, LogicErr>;
   /// Fetches the user's public feed, i.e. what users that follow this user
   /// can see, or alternatively all the user's public posts
   async fn fetch_user_public_likes_feed(
@@ -99,6 +120,35 @@ pub struct DbPostRepo {
   pub db: Pool,
 }
 
+impl DbPostRepo {
+  /// Fills in the polls of any of the posts that have one, along with which options the viewing user voted for
+  async fn attach_polls(
+    &self,
+    db: &Client,
+    mut posts: Vec<PostEvent>,
+    own_user_id: &Option<Uuid>,
+  ) -> Result<Vec<PostEvent>, LogicErr> {
+    if posts.is_empty() {
+      return Ok(posts);
+    }
+
+    let post_ids: Vec<Uuid> = posts.iter().map(|post| post.post_id).collect();
+
+    let rows = db
+      .query(include_str!("./sql/fetch_post_polls.sql"), &[&post_ids, &own_user_id])
+      .await
+      .map_err(map_db_err)?;
+
+    for poll in PostPoll::from_rows(rows)? {
+      if let Some(post) = posts.iter_mut().find(|post| post.post_id == poll.post_id) {
+        post.poll = Some(poll);
+      }
+    }
+
+    Ok(posts)
+  }
+}
+
 #[async_trait]
 impl PostRepo for DbPostRepo {
   async fn fetch_user_own_feed(&self, user_id: &Uuid, limit: i64, skip: i64) -> Result<Vec<PostEvent>, LogicErr> {
@@ -111,7 +161,9 @@ impl PostRepo for DbPostRepo {
       .await
       .map_err(map_db_err)?;
 
-    PostEvent::from_rows(rows)
+    self
+      .attach_polls(&db, PostEvent::from_rows(rows)?, &Some(*user_id))
+      .await
   }
 
   async fn count_user_own_feed(&self, user_id: &Uuid) -> Result<i64, LogicErr> {
@@ -134,7 +186,9 @@ impl PostRepo for DbPostRepo {
       .await
       .map_err(map_db_err)?;
 
-    PostEvent::from_rows(rows)
+    self
+      .attach_polls(&db, PostEvent::from_rows(rows)?, &Some(*user_id))
+      .await
   }
 
   async fn count_user_friends_feed(&self, user_id: &Uuid) -> Result<i64, LogicErr> {
@@ -157,7 +211,9 @@ impl PostRepo for DbPostRepo {
       .await
       .map_err(map_db_err)?;
 
-    PostEvent::from_rows(rows)
+    self
+      .attach_polls(&db, PostEvent::from_rows(rows)?, &Some(*user_id))
+      .await
   }
 
   async fn count_user_federated_feed(&self, user_id: &Uuid) -> Result<i64, LogicErr> {
@@ -186,7 +242,7 @@ impl PostRepo for DbPostRepo {
       .await
       .map_err(map_db_err)?;
 
-    PostEvent::from_rows(rows)
+    self.attach_polls(&db, PostEvent::from_rows(rows)?, own_user_id).await
   }
 
   async fn count_user_public_feed(&self, target_user_id: &Uuid, own_user_id: &Option<Uuid>) -> Result<i64, LogicErr> {
@@ -202,20 +258,28 @@ impl PostRepo for DbPostRepo {
     Ok(row.get(0))
   }
 
-  async fn fetch_global_federated_feed(&self, limit: i64, skip: i64) -> Result<Vec<PostEvent>, LogicErr> {
+  async fn fetch_global_federated_feed(
+    &self,
+    own_user_id: &Option<Uuid>,
+    limit: i64,
+    skip: i64,
+  ) -> Result<Vec<PostEvent>, LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     let rows = db
-      .query(include_str!("./sql/fetch_global_federated_feed.sql"), &[&limit, &skip])
+      .query(
+        include_str!("./sql/fetch_global_federated_feed.sql"),
+        &[&own_user_id, &limit, &skip],
+      )
       .await
       .map_err(map_db_err)?;
 
-    PostEvent::from_rows(rows)
+    self.attach_polls(&db, PostEvent::from_rows(rows)?, own_user_id).await
   }
 
-  async fn count_global_federated_feed(&self) -> Result<i64, LogicErr> {
+  async fn count_global_federated_feed(&self, own_user_id: &Option<Uuid>) -> Result<i64, LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     let row = db
-      .query_one(include_str!("./sql/count_global_federated_feed.sql"), &[])
+      .query_one(include_str!("./sql/count_global_federated_feed.sql"), &[&own_user_id])
       .await
       .map_err(map_db_err)?;
 
@@ -225,22 +289,59 @@ impl PostRepo for DbPostRepo {
   async fn fetch_global_federated_orbit_feed(
     &self,
     orbit_id: &Uuid,
+    own_user_id: &Option<Uuid>,
+    limit: i64,
+    skip: i64,
+  ) -> Result<Vec<PostEvent>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        include_str!("./sql/fetch_orbit_feed.sql"),
+        &[&orbit_id, &own_user_id, &limit, &skip],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    self.attach_polls(&db, PostEvent::from_rows(rows)?, own_user_id).await
+  }
+
+  async fn count_global_federated_orbit_feed(
+    &self,
+    orbit_id: &Uuid,
+    own_user_id: &Option<Uuid>,
+  ) -> Result<i64, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_one(include_str!("./sql/count_orbit_feed.sql"), &[&orbit_id, &own_user_id])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(row.get(0))
+  }
+
+  async fn fetch_hashtag_feed(
+    &self,
+    hashtag: &str,
+    own_user_id: &Option<Uuid>,
     limit: i64,
     skip: i64,
   ) -> Result<Vec<PostEvent>, LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     let rows = db
-      .query(include_str!("./sql/fetch_orbit_feed.sql"), &[&orbit_id, &limit, &skip])
+      .query(
+        include_str!("./sql/fetch_hashtag_feed.sql"),
+        &[&hashtag, &own_user_id, &limit, &skip],
+      )
       .await
       .map_err(map_db_err)?;
 
-    PostEvent::from_rows(rows)
+    self.attach_polls(&db, PostEvent::from_rows(rows)?, own_user_id).await
   }
 
-  async fn count_global_federated_orbit_feed(&self, orbit_id: &Uuid) -> Result<i64, LogicErr> {
+  async fn count_hashtag_feed(&self, hashtag: &str, own_user_id: &Option<Uuid>) -> Result<i64, LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     let row = db
-      .query_one(include_str!("./sql/count_orbit_feed.sql"), &[&orbit_id])
+      .query_one(include_str!("./sql/count_hashtag_feed.sql"), &[&hashtag, &own_user_id])
       .await
       .map_err(map_db_err)?;
 
@@ -269,7 +370,7 @@ impl PostRepo for DbPostRepo {
       .await
       .map_err(map_db_err)?;
 
-    let mut posts = PostEvent::from_rows(rows)?;
+    let mut posts = self.attach_polls(&db, PostEvent::from_rows(rows)?, user_id).await?;
 
     match posts.len() {
       1 => Ok(Some(posts.remove(0))),
@@ -285,7 +386,7 @@ impl PostRepo for DbPostRepo {
       .await
       .map_err(map_db_err)?;
 
-    let mut posts = PostEvent::from_rows(rows)?;
+    let mut posts = self.attach_polls(&db, PostEvent::from_rows(rows)?, user_id).await?;
 
     match posts.len() {
       1 => Ok(Some(posts.remove(0))),
@@ -401,7 +502,7 @@ impl PostRepo for DbPostRepo {
   async fn update_post_content(&self, post: &Post) -> Result<(), LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     db.execute(
-      "UPDATE posts SET content_html = $2, content_md = $3, visibility = $4, created_at = $5, updated_at = $6 WHERE post_id = $1",
+      "UPDATE posts SET content_html = $2, content_md = $3, visibility = $4, created_at = $5, updated_at = $6, title = $7 WHERE post_id = $1",
       &[
         &post.post_id,
         &post.content_html,
@@ -409,6 +510,7 @@ impl PostRepo for DbPostRepo {
         &post.visibility.to_string(),
         &post.created_at,
         &post.updated_at,
+        &post.title,
       ],
     )
     .await
@@ -497,6 +599,16 @@ impl PostRepo for DbPostRepo {
     row.get(0)
   }
 
+  async fn fetch_user_posts(&self, user_id: &Uuid) -> Result<Vec<Post>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query("SELECT * FROM posts WHERE user_id = $1", &[&user_id])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(Post::from_row).collect())
+  }
+
   async fn fetch_user_public_likes_feed(
     &self,
     target_user_id: &Uuid,
@@ -513,7 +625,7 @@ impl PostRepo for DbPostRepo {
       .await
       .map_err(map_db_err)?;
 
-    PostEvent::from_rows(rows)
+    self.attach_polls(&db, PostEvent::from_rows(rows)?, own_user_id).await
   }
 
   async fn count_user_public_likes_feed(
@@ -0,0 +1,76 @@
+use super::FromRow;
+use crate::{
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::{post::Post, post_revision::PostRevision},
+};
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait PostRevisionRepo {
+  /// Keeps the post's current title and content as a revision, which should be done before the post is edited
+  async fn create_revision(&self, post: &Post) -> Result<(), LogicErr>;
+  async fn fetch_post_revisions(&self, post_id: &Uuid, limit: i64, skip: i64) -> Result<Vec<PostRevision>, LogicErr>;
+  async fn count_post_revisions(&self, post_id: &Uuid) -> Result<i64, LogicErr>;
+}
+
+pub type PostRevisionPool = Arc<dyn PostRevisionRepo + Send + Sync>;
+
+pub struct DbPostRevisionRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl PostRevisionRepo for DbPostRevisionRepo {
+  async fn create_revision(&self, post: &Post) -> Result<(), LogicErr> {
+    let revision_id = Uuid::new_v4();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      r#"INSERT INTO post_revisions (revision_id, post_id, title, content_md, content_html, created_at)
+      VALUES ($1, $2, $3, $4, $5, $6)"#,
+      &[
+        &revision_id,
+        &post.post_id,
+        &post.title,
+        &post.content_md,
+        &post.content_html,
+        &post.updated_at,
+      ],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn fetch_post_revisions(&self, post_id: &Uuid, limit: i64, skip: i64) -> Result<Vec<PostRevision>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        "SELECT * FROM post_revisions WHERE post_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+        &[&post_id, &limit, &skip],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(PostRevision::from_row).collect())
+  }
+
+  async fn count_post_revisions(&self, post_id: &Uuid) -> Result<i64, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_one("SELECT COUNT(*) FROM post_revisions WHERE post_id = $1", &[&post_id])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(row.get(0))
+  }
+}
@@ -0,0 +1,135 @@
+use super::FromRow;
+use crate::{
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::{relay::Relay, relay_status::RelayStatus},
+};
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait RelayRepo {
+  async fn fetch_relays(&self) -> Result<Vec<Relay>, LogicErr>;
+  /// Fetches the relays that have accepted our subscription, i.e. those we should deliver public posts to
+  async fn fetch_accepted_relays(&self) -> Result<Vec<Relay>, LogicErr>;
+  async fn fetch_relay(&self, relay_id: &Uuid) -> Option<Relay>;
+  async fn fetch_relay_by_actor_uri(&self, actor_uri: &str) -> Option<Relay>;
+  async fn create_relay(&self, relay: &Relay) -> Result<(), LogicErr>;
+  async fn update_relay_status(&self, relay_id: &Uuid, status: &RelayStatus) -> Result<(), LogicErr>;
+  async fn delete_relay(&self, relay_id: &Uuid) -> Result<(), LogicErr>;
+}
+
+pub type RelayPool = Arc<dyn RelayRepo + Send + Sync>;
+
+pub struct DbRelayRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl RelayRepo for DbRelayRepo {
+  async fn fetch_relays(&self) -> Result<Vec<Relay>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query("SELECT * FROM relays ORDER BY created_at", &[])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(Relay::from_row).collect())
+  }
+
+  async fn fetch_accepted_relays(&self) -> Result<Vec<Relay>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        "SELECT * FROM relays WHERE status = $1",
+        &[&RelayStatus::Accepted.to_string()],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(Relay::from_row).collect())
+  }
+
+  async fn fetch_relay(&self, relay_id: &Uuid) -> Option<Relay> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let row = match db
+      .query_opt("SELECT * FROM relays WHERE relay_id = $1", &[&relay_id])
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return None,
+    };
+
+    row.and_then(Relay::from_row)
+  }
+
+  async fn fetch_relay_by_actor_uri(&self, actor_uri: &str) -> Option<Relay> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let row = match db
+      .query_opt("SELECT * FROM relays WHERE actor_uri = $1", &[&actor_uri])
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return None,
+    };
+
+    row.and_then(Relay::from_row)
+  }
+
+  async fn create_relay(&self, relay: &Relay) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      r#"INSERT INTO relays (relay_id, actor_uri, inbox_uri, status, created_at, updated_at)
+      VALUES ($1, $2, $3, $4, $5, $6)"#,
+      &[
+        &relay.relay_id,
+        &relay.actor_uri,
+        &relay.inbox_uri,
+        &relay.status.to_string(),
+        &relay.created_at,
+        &relay.updated_at,
+      ],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn update_relay_status(&self, relay_id: &Uuid, status: &RelayStatus) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      "UPDATE relays SET status = $2, updated_at = now() WHERE relay_id = $1",
+      &[&relay_id, &status.to_string()],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn delete_relay(&self, relay_id: &Uuid) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute("DELETE FROM relays WHERE relay_id = $1", &[&relay_id])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,112 @@
+use super::FromRow;
+use crate::{helpers::api::map_db_err, logic::LogicErr, model::report::Report};
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ReportRepo {
+  async fn create_report(&self, report: &Report) -> Result<(), LogicErr>;
+  async fn fetch_report(&self, report_id: &Uuid) -> Option<Report>;
+  async fn find_optional_by_uri(&self, uri: &str) -> Option<Report>;
+  async fn fetch_reports(&self, limit: i64, skip: i64) -> Result<Vec<Report>, LogicErr>;
+  async fn count_reports(&self) -> Result<i64, LogicErr>;
+}
+
+pub type ReportPool = Arc<dyn ReportRepo + Send + Sync>;
+
+pub struct DbReportRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl ReportRepo for DbReportRepo {
+  async fn create_report(&self, report: &Report) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      r#"INSERT INTO reports (report_id, reporter_user_id, reporter_uri, target_user_id, post_ids, comment, uri,
+      is_external, forwarded, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"#,
+      &[
+        &report.report_id,
+        &report.reporter_user_id,
+        &report.reporter_uri,
+        &report.target_user_id,
+        &report.post_ids,
+        &report.comment,
+        &report.uri,
+        &report.is_external,
+        &report.forwarded,
+        &report.created_at,
+        &report.updated_at,
+      ],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
+  async fn fetch_report(&self, report_id: &Uuid) -> Option<Report> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let row = match db
+      .query_opt("SELECT * FROM reports WHERE report_id = $1", &[&report_id])
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return None,
+    };
+
+    row.and_then(Report::from_row)
+  }
+
+  async fn find_optional_by_uri(&self, uri: &str) -> Option<Report> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let row = match db
+      .query_opt("SELECT * FROM reports WHERE uri = $1", &[&uri])
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return None,
+    };
+
+    row.and_then(Report::from_row)
+  }
+
+  async fn fetch_reports(&self, limit: i64, skip: i64) -> Result<Vec<Report>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        "SELECT * FROM reports ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+        &[&limit, &skip],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().flat_map(Report::from_row).collect())
+  }
+
+  async fn count_reports(&self) -> Result<i64, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_one("SELECT COUNT(*) FROM reports", &[])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(row.get(0))
+  }
+}
@@ -1,11 +1,15 @@
 use deadpool_postgres::Pool;
 
 use super::{
-  app_repository::AppPool, comment_repository::CommentPool, event_repository::EventPool, follow_repository::FollowPool,
-  job_repository::JobPool, like_repository::LikePool, orbit_moderator_repository::OrbitModeratorPool,
-  orbit_repository::OrbitPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
-  repository::Repository, session_repository::SessionPool, tombstone_repository::TombstonePool,
-  user_orbit_repository::UserOrbitPool, user_repository::UserPool, user_stats_repository::UserStatsPool,
+  app_repository::AppPool, comment_repository::CommentPool, domain_block_repository::DomainBlockPool,
+  event_repository::EventPool, follow_repository::FollowPool, hashtag_repository::HashtagPool,
+  instance_actor_repository::InstanceActorPool, job_repository::JobPool, like_repository::LikePool,
+  mention_repository::MentionPool, orbit_moderator_repository::OrbitModeratorPool, orbit_repository::OrbitPool,
+  poll_repository::PollPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+  post_revision_repository::PostRevisionPool, relay_repository::RelayPool, report_repository::ReportPool,
+  repository::Repository, session_repository::SessionPool, signature_repository::SignaturePool,
+  tombstone_repository::TombstonePool, user_block_repository::UserBlockPool, user_orbit_repository::UserOrbitPool,
+  user_repository::UserPool, user_stats_repository::UserStatsPool,
 };
 
 #[derive(Clone)]
@@ -26,6 +30,16 @@ pub struct Repositories {
   pub orbit_moderators: OrbitModeratorPool,
   pub user_orbits: UserOrbitPool,
   pub tombstones: TombstonePool,
+  pub instance_actors: InstanceActorPool,
+  pub signatures: SignaturePool,
+  pub user_blocks: UserBlockPool,
+  pub domain_blocks: DomainBlockPool,
+  pub reports: ReportPool,
+  pub relays: RelayPool,
+  pub mentions: MentionPool,
+  pub hashtags: HashtagPool,
+  pub polls: PollPool,
+  pub post_revisions: PostRevisionPool,
 }
 
 impl Repositories {
@@ -46,6 +60,16 @@ impl Repositories {
       orbit_moderators: Repository::new_orbit_moderator_pool(&db),
       user_orbits: Repository::new_user_orbit_pool(&db),
       tombstones: Repository::new_tombstone_pool(&db),
+      instance_actors: Repository::new_instance_actor_pool(&db),
+      signatures: Repository::new_signature_pool(&db),
+      user_blocks: Repository::new_user_block_pool(&db),
+      domain_blocks: Repository::new_domain_block_pool(&db),
+      reports: Repository::new_report_pool(&db),
+      relays: Repository::new_relay_pool(&db),
+      mentions: Repository::new_mention_pool(&db),
+      hashtags: Repository::new_hashtag_pool(&db),
+      polls: Repository::new_poll_pool(&db),
+      post_revisions: Repository::new_post_revision_pool(&db),
       pool: db,
     }
   }
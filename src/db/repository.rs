@@ -5,16 +5,26 @@ use deadpool_postgres::Pool;
 use super::{
   app_repository::{AppPool, DbAppRepo},
   comment_repository::{CommentPool, DbCommentRepo},
+  domain_block_repository::{DbDomainBlockRepo, DomainBlockPool},
   event_repository::{DbEventRepo, EventPool},
   follow_repository::{DbFollowRepo, FollowPool},
+  hashtag_repository::{DbHashtagRepo, HashtagPool},
+  instance_actor_repository::{DbInstanceActorRepo, InstanceActorPool},
   job_repository::{DbJobRepo, JobPool},
   like_repository::{DbLikeRepo, LikePool},
+  mention_repository::{DbMentionRepo, MentionPool},
   orbit_moderator_repository::{DbOrbitModeratorRepo, OrbitModeratorPool},
   orbit_repository::{DbOrbitRepo, OrbitPool},
+  poll_repository::{DbPollRepo, PollPool},
   post_attachment_repository::{DbPostAttachmentRepo, PostAttachmentPool},
   post_repository::{DbPostRepo, PostPool},
+  post_revision_repository::{DbPostRevisionRepo, PostRevisionPool},
+  relay_repository::{DbRelayRepo, RelayPool},
+  report_repository::{DbReportRepo, ReportPool},
   session_repository::{DbSessionRepo, SessionPool},
+  signature_repository::{DbSignatureRepo, SignaturePool},
   tombstone_repository::{DbTombstoneRepo, TombstonePool},
+  user_block_repository::{DbUserBlockRepo, UserBlockPool},
   user_orbit_repository::{DbUserOrbitRepo, UserOrbitPool},
   user_repository::{DbUserRepo, UserPool},
   user_stats_repository::{DbUserStatsRepo, UserStatsPool},
@@ -82,4 +92,44 @@ impl Repository {
   pub fn new_tombstone_pool(db: &Pool) -> TombstonePool {
     Arc::new(DbTombstoneRepo { db: db.clone() })
   }
+
+  pub fn new_instance_actor_pool(db: &Pool) -> InstanceActorPool {
+    Arc::new(DbInstanceActorRepo { db: db.clone() })
+  }
+
+  pub fn new_signature_pool(db: &Pool) -> SignaturePool {
+    Arc::new(DbSignatureRepo { db: db.clone() })
+  }
+
+  pub fn new_user_block_pool(db: &Pool) -> UserBlockPool {
+    Arc::new(DbUserBlockRepo { db: db.clone() })
+  }
+
+  pub fn new_domain_block_pool(db: &Pool) -> DomainBlockPool {
+    Arc::new(DbDomainBlockRepo { db: db.clone() })
+  }
+
+  pub fn new_report_pool(db: &Pool) -> ReportPool {
+    Arc::new(DbReportRepo { db: db.clone() })
+  }
+
+  pub fn new_relay_pool(db: &Pool) -> RelayPool {
+    Arc::new(DbRelayRepo { db: db.clone() })
+  }
+
+  pub fn new_mention_pool(db: &Pool) -> MentionPool {
+    Arc::new(DbMentionRepo { db: db.clone() })
+  }
+
+  pub fn new_hashtag_pool(db: &Pool) -> HashtagPool {
+    Arc::new(DbHashtagRepo { db: db.clone() })
+  }
+
+  pub fn new_poll_pool(db: &Pool) -> PollPool {
+    Arc::new(DbPollRepo { db: db.clone() })
+  }
+
+  pub fn new_post_revision_pool(db: &Pool) -> PostRevisionPool {
+    Arc::new(DbPostRevisionRepo { db: db.clone() })
+  }
 }
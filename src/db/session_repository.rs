@@ -24,6 +24,7 @@ pub trait SessionRepo {
     refresh_expires_at: &DateTime<Utc>,
   ) -> Result<(), LogicErr>;
   async fn delete_session(&self, user_id: &Uuid, app_id: &Uuid, refresh_token: &str) -> Result<(), LogicErr>;
+  async fn delete_user_sessions(&self, user_id: &Uuid) -> Result<(), LogicErr>;
   async fn query_session_exists(&self, session_id: &Uuid) -> bool;
 }
 
@@ -96,6 +97,15 @@ impl SessionRepo for DbSessionRepo {
     Ok(())
   }
 
+  async fn delete_user_sessions(&self, user_id: &Uuid) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute("DELETE FROM sessions WHERE user_id = $1", &[&user_id])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
   async fn query_session_exists(&self, session_id: &Uuid) -> bool {
     let db = match self.db.get().await.map_err(map_db_err) {
       Ok(db) => db,
@@ -0,0 +1,72 @@
+use crate::{helpers::api::map_db_err, logic::LogicErr};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait SignatureRepo {
+  /// Records that a verified signature has been used by the given job. Returns false if the signature has already been
+  /// used by another job and hasn't expired yet, i.e. the request is being replayed, or if the given number of
+  /// unexpired signatures are already recorded.
+  async fn record_signature_use(
+    &self,
+    key_id: &str,
+    signature: &str,
+    job_id: &Uuid,
+    expires_at: &DateTime<Utc>,
+    max_entries: i64,
+  ) -> Result<bool, LogicErr>;
+  /// Forgets expired signatures
+  async fn purge_signature_uses(&self) -> Result<(), LogicErr>;
+}
+
+pub type SignaturePool = Arc<dyn SignatureRepo + Send + Sync>;
+
+pub struct DbSignatureRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl SignatureRepo for DbSignatureRepo {
+  async fn record_signature_use(
+    &self,
+    key_id: &str,
+    signature: &str,
+    job_id: &Uuid,
+    expires_at: &DateTime<Utc>,
+    max_entries: i64,
+  ) -> Result<bool, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    // An expired record of the same signature may not have been purged yet, in which case it's reused. Retries of the
+    // job that first saw the signature aren't replays. Unexpired signatures are never forgotten to make room, as they
+    // could then be replayed, so nothing is recorded once we're remembering as many as we can.
+    let rows = db
+      .execute(
+        r#"INSERT INTO signature_uses (key_id, signature, job_id, expires_at)
+        SELECT $1, $2, $3, $4
+        WHERE (SELECT COUNT(*) FROM signature_uses WHERE expires_at > now()) < $5
+        ON CONFLICT (key_id, signature) DO UPDATE SET job_id = EXCLUDED.job_id, expires_at = EXCLUDED.expires_at
+        WHERE signature_uses.expires_at <= now() OR signature_uses.job_id = EXCLUDED.job_id"#,
+        &[&key_id, &signature, &job_id, &expires_at, &max_entries],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows > 0)
+  }
+
+  async fn purge_signature_uses(&self) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute("DELETE FROM signature_uses WHERE expires_at <= now()", &[])
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(())
+  }
+}
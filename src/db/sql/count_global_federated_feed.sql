@@ -1,3 +1,20 @@
 SELECT COUNT(DISTINCT e.post_id) FROM events e
+INNER JOIN posts p
+ON p.post_id = e.post_id
+INNER JOIN users u
+ON u.user_id = p.user_id
+INNER JOIN users u2
+ON u2.user_id = e.source_user_id
 WHERE e.target_user_id IS NULL
+-- a booster's own boost event only records that they've boosted the post
+AND e.event_type <> 'boost'
 AND e.visibility IN ('public_federated', 'public_local')
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($1, p.user_id, e.source_user_id)
+AND NOT EXISTS (
+  -- hides anything from servers that have been silenced or suspended, including their subdomains
+  SELECT 1 FROM domain_blocks d
+  WHERE d.severity IN ('silence', 'suspend')
+  AND (right('.' || substring(u.fediverse_uri from '^https?://([^/:]+)'), length(d.domain) + 1) = '.' || d.domain
+  OR right('.' || substring(u2.fediverse_uri from '^https?://([^/:]+)'), length(d.domain) + 1) = '.' || d.domain)
+)
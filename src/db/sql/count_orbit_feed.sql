@@ -1,2 +1,4 @@
-SELECT COUNT(*) FROM posts WHERE orbit_id = $1
-AND visibility IN ('public_federated', 'public_local')
+SELECT COUNT(*) FROM posts p WHERE p.orbit_id = $1
+AND p.visibility IN ('public_federated', 'public_local')
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($2, p.user_id)
@@ -1,4 +1,10 @@
 SELECT COUNT(DISTINCT e.post_id) FROM events e
+INNER JOIN posts p
+ON p.post_id = e.post_id
 WHERE e.source_user_id = $1
 AND e.target_user_id IS NULL
+-- a booster's own boost event only records that they've boosted the post
+AND e.event_type <> 'boost'
 AND e.visibility IN ('public_federated', 'public_local')
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($1, p.user_id, e.source_user_id)
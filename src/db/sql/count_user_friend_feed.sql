@@ -5,3 +5,5 @@ WHERE e.target_user_id = $1
 AND e.source_user_id != $1
 AND e.visibility IN ('public_federated', 'public_local', 'followers_only')
 AND p.orbit_id IS NULL
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($1, p.user_id, e.source_user_id)
\ No newline at end of file
@@ -1,3 +1,7 @@
 SELECT COUNT(DISTINCT e.post_id) FROM events e
-WHERE (e.source_user_id = $1 AND e.visibility IN ('public_federated', 'public_local', 'followers_only', 'private', 'unlisted'))
-OR (e.target_user_id = $1 AND e.visibility IN ('public_federated', 'public_local', 'followers_only'))
+INNER JOIN posts p
+ON p.post_id = e.post_id
+WHERE ((e.source_user_id = $1 AND e.visibility IN ('public_federated', 'public_local', 'followers_only', 'private', 'unlisted'))
+OR (e.target_user_id = $1 AND e.visibility IN ('public_federated', 'public_local', 'followers_only')))
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($1, p.user_id, e.source_user_id)
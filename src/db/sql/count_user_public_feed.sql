@@ -4,3 +4,5 @@ AND (
   (e.target_user_id = $2 AND e.visibility IN ('public_federated', 'public_local', 'followers_only') OR 
   (e.visibility IN ('public_federated', 'public_local'))
 ))
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($2, e.source_user_id)
\ No newline at end of file
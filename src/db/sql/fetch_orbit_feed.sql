@@ -3,6 +3,7 @@ u.fediverse_uri AS user_fediverse_uri, u.avatar_url AS user_avatar_url, COUNT(DI
 COUNT(DISTINCT c.comment_id) AS comments, u2.handle AS event_user_handle, u2.fediverse_id AS event_user_fediverse_id, 
 u2.fediverse_uri AS event_user_fediverse_uri, u2.avatar_url AS event_user_avatar_url, 
 ob.name as orbit_name, ob.shortcode as orbit_shortcode, ob.uri as orbit_uri, ob.fediverse_uri as orbit_fediverse_uri, ob.avatar_uri as orbit_avatar_uri, 
+ARRAY(SELECT h.name FROM post_hashtags ph INNER JOIN hashtags h ON h.hashtag_id = ph.hashtag_id WHERE ph.post_id = p.post_id ORDER BY h.name) AS hashtags,
 pa.attachment_id, pa.user_id as attachment_user_id,  pa.post_id as attachment_post_id, pa.uri as attachment_uri, pa.width as attachment_width, 
 pa.height as attachment_height, pa.content_type as attachment_content_type, pa.storage_ref as attachment_storage_ref, 
 pa.blurhash as attachment_blurhash, pa.created_at as attachment_created_at
@@ -22,9 +23,13 @@ ON pa.post_id = p.post_id
 LEFT OUTER JOIN orbits ob
 ON ob.orbit_id = p.orbit_id
 WHERE e.target_user_id IS NULL
+-- a booster's own boost event only records that they've boosted the post
+AND e.event_type <> 'boost'
 AND e.visibility IN ('public_federated', 'public_local')
 AND ob.orbit_id = $1
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($2, p.user_id, e.source_user_id)
 GROUP BY e.event_type, p.post_id, u.user_id, u2.user_id, pa.attachment_id, ob.orbit_id
 ORDER BY p.created_at DESC
-LIMIT $2
-OFFSET $3
+LIMIT $3
+OFFSET $4
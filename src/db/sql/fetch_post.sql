@@ -4,6 +4,7 @@ u.fediverse_id as event_user_fediverse_id, u.fediverse_uri AS event_user_fediver
 u.avatar_url as event_user_avatar_url, COUNT(DISTINCT l.like_id) AS likes, count(l2.like_id) >= 1 AS liked, 
 count(distinct c.comment_id) as comments, 
 ob.name as orbit_name, ob.shortcode as orbit_shortcode, ob.uri as orbit_uri, ob.fediverse_uri as orbit_fediverse_uri, ob.avatar_uri as orbit_avatar_uri,
+ARRAY(SELECT h.name FROM post_hashtags ph INNER JOIN hashtags h ON h.hashtag_id = ph.hashtag_id WHERE ph.post_id = p.post_id ORDER BY h.name) AS hashtags,
 pa.attachment_id, pa.user_id as attachment_user_id, 
 pa.post_id as attachment_post_id, pa.uri as attachment_uri, pa.width as attachment_width, 
 pa.height as attachment_height, pa.content_type as attachment_content_type, pa.storage_ref as attachment_storage_ref, 
@@ -24,4 +25,6 @@ ON pa.post_id = p.post_id
 LEFT OUTER JOIN orbits ob
 ON ob.orbit_id = p.orbit_id
 WHERE p.post_id = $1
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($2, p.user_id, e.source_user_id)
 GROUP BY e.event_type, p.post_id, u.user_id, pa.attachment_id, ob.orbit_id
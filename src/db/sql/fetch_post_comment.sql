@@ -1,8 +1,13 @@
-SELECT DISTINCT c.*, count(ul.comment_like_id) >= 1 AS liked, count(DISTINCT ul2.comment_like_id) as likes, u.handle AS user_handle, u.fediverse_id AS user_fediverse_id, u.fediverse_uri AS user_fediverse_uri, u.avatar_url AS user_avatar_url, p.visibility as visibility FROM comments c
+SELECT DISTINCT c.*, count(ul.comment_like_id) >= 1 AS liked, count(DISTINCT ul2.comment_like_id) as likes, u.handle AS user_handle, u.fediverse_id AS user_fediverse_id, u.fediverse_uri AS user_fediverse_uri, u.avatar_url AS user_avatar_url, p.visibility as visibility, p.uri AS post_uri, pc.uri AS parent_comment_uri,
+(SELECT COUNT(*) FROM comments r WHERE r.parent_comment_id = c.comment_id
+  -- replies share their post's visibility, so only the ones hidden by blocks need leaving out
+  AND NOT blocked_between($1, r.user_id)) AS reply_count FROM comments c
 INNER JOIN posts p
 ON p.post_id = c.post_id
 INNER JOIN users u
 ON u.user_id = c.user_id
+LEFT OUTER JOIN comments pc
+ON pc.comment_id = c.parent_comment_id
 LEFT OUTER JOIN (
   SELECT f.user_id, f.following_user_id, COUNT(DISTINCT f.follower_id) >= 1 AS following
   FROM followers f
@@ -23,6 +28,8 @@ AND c.comment_id = $3
 AND (
   (p.visibility IN ('public_local', 'public_federated'))
     OR (following IS TRUE AND p.visibility = 'followers_only'))
-GROUP BY c.comment_id, u.user_id, p.post_id
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($1, c.user_id)
+GROUP BY c.comment_id, u.user_id, p.post_id, pc.comment_id
 ORDER BY c.created_at DESC
 LIMIT 1
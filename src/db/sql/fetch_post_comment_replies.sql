@@ -0,0 +1,49 @@
+SELECT * FROM (
+SELECT c.*, count(ul.comment_like_id) >= 1 AS liked, count(DISTINCT ul2.comment_like_id) as likes, u.handle AS user_handle, u.fediverse_id AS user_fediverse_id, u.fediverse_uri AS user_fediverse_uri, u.avatar_url AS user_avatar_url, p.visibility as visibility, p.uri AS post_uri, pc.uri AS parent_comment_uri,
+(SELECT COUNT(*) FROM comments r WHERE r.parent_comment_id = c.comment_id
+  -- replies share their post's visibility, so only the ones hidden by blocks need leaving out
+  AND NOT blocked_between($1, r.user_id)) AS reply_count,
+-- replies are ranked below each of the comments they're replying to, in the same order the thread is sorted in
+ROW_NUMBER() OVER (
+  PARTITION BY c.parent_comment_id
+  ORDER BY
+    CASE WHEN $4 = 'top' THEN count(DISTINCT ul2.comment_like_id) END DESC NULLS LAST,
+    CASE WHEN $4 = 'controversial' THEN comment_controversy(
+      count(DISTINCT ul2.comment_like_id),
+      (SELECT COUNT(*) FROM comments r WHERE r.parent_comment_id = c.comment_id AND NOT blocked_between($1, r.user_id))
+    ) END DESC NULLS LAST,
+    c.created_at DESC
+) AS reply_rank
+FROM comments c
+INNER JOIN posts p
+ON p.post_id = c.post_id
+INNER JOIN users u
+ON u.user_id = c.user_id
+LEFT OUTER JOIN comments pc
+ON pc.comment_id = c.parent_comment_id
+LEFT OUTER JOIN (
+  SELECT f.user_id, f.following_user_id, COUNT(DISTINCT f.follower_id) >= 1 AS following
+  FROM followers f
+  INNER JOIN users u1
+  ON u1.user_id = f.user_id
+  INNER JOIN users u2
+  ON u2.user_id = f.following_user_id
+  WHERE u1.user_id = $1 -- the user viewing the post's comments
+  GROUP BY f.user_id, f.following_user_id
+) AS ff
+ON ff.following_user_id = p.user_id
+LEFT OUTER JOIN (SELECT DISTINCT comment_id, comment_like_id FROM comment_likes WHERE user_id = $1) AS ul
+ON ul.comment_id = c.comment_id
+LEFT OUTER JOIN (SELECT DISTINCT comment_id, comment_like_id FROM comment_likes) AS ul2
+ON ul2.comment_id = c.comment_id
+WHERE c.post_id = $2 -- the post id of this comment collection
+AND c.parent_comment_id = ANY($3) -- the comments whose replies are being fetched
+AND (
+  (p.visibility IN ('public_local', 'public_federated'))
+    OR (following IS TRUE AND p.visibility = 'followers_only'))
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($1, c.user_id)
+GROUP BY c.comment_id, u.user_id, p.post_id, pc.comment_id
+) AS replies
+WHERE reply_rank <= $5 -- the most replies included below each comment
+ORDER BY parent_comment_id, reply_rank
@@ -16,3 +16,5 @@ WHERE c.post_id = $2 -- the post id of this comment collection
 AND (
   (p.visibility IN ('public_local', 'public_federated'))
     OR (following IS TRUE AND p.visibility = 'followers_only'))
+-- hides anything from users that the viewer has blocked, or that have blocked the viewer
+AND NOT blocked_between($1, c.user_id)
\ No newline at end of file
@@ -0,0 +1,15 @@
+SELECT pl.poll_id, pl.post_id, pl.multiple_choice, pl.expires_at, po.option_id, po.name,
+CASE WHEN p.is_external THEN po.remote_votes::BIGINT ELSE COUNT(DISTINCT pv.vote_id) END AS votes,
+COUNT(DISTINCT pv2.vote_id) >= 1 AS voted FROM polls pl
+INNER JOIN posts p
+ON p.post_id = pl.post_id
+INNER JOIN poll_options po
+ON po.poll_id = pl.poll_id
+LEFT OUTER JOIN poll_votes pv
+ON pv.option_id = po.option_id
+LEFT OUTER JOIN poll_votes pv2
+ON pv2.option_id = po.option_id
+AND pv2.user_id = $2
+WHERE pl.post_id = ANY($1)
+GROUP BY pl.poll_id, p.post_id, po.option_id
+ORDER BY pl.poll_id, po.position
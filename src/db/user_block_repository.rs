@@ -0,0 +1,82 @@
+use crate::{helpers::api::map_db_err, logic::LogicErr};
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[cfg(test)]
+use mockall::automock;
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait UserBlockRepo {
+  /// Creates a block, returning its ID. Blocking an already blocked user returns the ID of the existing block.
+  async fn create_block(&self, user_id: &Uuid, blocked_user_id: &Uuid) -> Result<Uuid, LogicErr>;
+  /// Deletes a block, returning the ID of the block that was deleted, if any
+  async fn delete_block(&self, user_id: &Uuid, blocked_user_id: &Uuid) -> Result<Option<Uuid>, LogicErr>;
+  async fn user_blocks_user(&self, user_id: &Uuid, blocked_user_id: &Uuid) -> bool;
+}
+
+pub type UserBlockPool = Arc<dyn UserBlockRepo + Send + Sync>;
+
+pub struct DbUserBlockRepo {
+  pub db: Pool,
+}
+
+#[async_trait]
+impl UserBlockRepo for DbUserBlockRepo {
+  async fn create_block(&self, user_id: &Uuid, blocked_user_id: &Uuid) -> Result<Uuid, LogicErr> {
+    let user_block_id = Uuid::new_v4();
+
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_one(
+        r#"INSERT INTO user_blocks (user_block_id, user_id, blocked_user_id) VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, blocked_user_id) DO UPDATE SET user_id = EXCLUDED.user_id
+        RETURNING user_block_id"#,
+        &[&user_block_id, &user_id, &blocked_user_id],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(row.get("user_block_id"))
+  }
+
+  async fn delete_block(&self, user_id: &Uuid, blocked_user_id: &Uuid) -> Result<Option<Uuid>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let row = db
+      .query_opt(
+        "DELETE FROM user_blocks WHERE user_id = $1 AND blocked_user_id = $2 RETURNING user_block_id",
+        &[&user_id, &blocked_user_id],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(row.map(|row| row.get("user_block_id")))
+  }
+
+  /// Fetches a boolean indicator of if the source user has blocked the target user
+  async fn user_blocks_user(&self, user_id: &Uuid, blocked_user_id: &Uuid) -> bool {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return false,
+    };
+
+    let row = match db
+      .query_one(
+        r#"SELECT count(*) >= 1 AS blocked FROM user_blocks
+        WHERE user_id = $1
+        AND blocked_user_id = $2"#,
+        &[&user_id, &blocked_user_id],
+      )
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(row) => row,
+      Err(_) => return false,
+    };
+
+    row.get(0)
+  }
+}
@@ -19,6 +19,7 @@ pub trait UserOrbitRepo {
   async fn create_user_orbit(&self, orbit_id: &Uuid, user_id: &Uuid) -> Result<Uuid, LogicErr>;
   async fn delete_user_orbit(&self, orbit_id: &Uuid, user_id: &Uuid) -> Result<(), LogicErr>;
   async fn user_is_member(&self, user_id: &Uuid, orbit_id: &Uuid) -> Result<bool, LogicErr>;
+  async fn fetch_orbit_member_delivery_targets(&self, orbit_id: &Uuid) -> Option<Vec<Uuid>>;
 }
 
 pub type UserOrbitPool = Arc<dyn UserOrbitRepo + Send + Sync>;
@@ -119,4 +120,28 @@ impl UserOrbitRepo for DbUserOrbitRepo {
 
     Ok(row.get(0))
   }
+
+  async fn fetch_orbit_member_delivery_targets(&self, orbit_id: &Uuid) -> Option<Vec<Uuid>> {
+    let db = match self.db.get().await.map_err(map_db_err) {
+      Ok(db) => db,
+      Err(_) => return None,
+    };
+
+    let rows = match db
+      .query(
+        r#"SELECT DISTINCT ON (COALESCE(u.ext_apub_shared_inbox_uri, u.ext_apub_inbox_uri)) u.user_id
+        FROM user_orbits o INNER JOIN users u ON u.user_id = o.user_id
+        WHERE o.orbit_id = $1 AND u.is_external = TRUE AND u.ext_apub_inbox_uri IS NOT NULL
+        ORDER BY COALESCE(u.ext_apub_shared_inbox_uri, u.ext_apub_inbox_uri), o.created_at"#,
+        &[&orbit_id],
+      )
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(rows) => rows,
+      Err(_) => return None,
+    };
+
+    Some(rows.into_iter().map(|row| row.get("user_id")).collect())
+  }
 }
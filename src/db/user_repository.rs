@@ -26,6 +26,8 @@ pub trait UserRepo {
   async fn fetch_following_count(&self, user_id: &Uuid) -> i64;
   async fn fetch_by_fediverse_uri(&self, fediverse_uri: &str) -> Option<User>;
   async fn fetch_outdated_external_users(&self) -> Result<Vec<Uuid>, LogicErr>;
+  /// Fetches one remote user for each inbox we know of, with users sharing a server's shared inbox counted once
+  async fn fetch_known_inbox_targets(&self) -> Result<Vec<Uuid>, LogicErr>;
   async fn create(
     &self,
     handle: &str,
@@ -41,6 +43,8 @@ pub trait UserRepo {
   async fn update_from(&self, user: &User) -> Result<User, LogicErr>;
   async fn delete_user_from_uri(&self, uri: &str) -> Result<(), LogicErr>;
   async fn delete_user(&self, id: &Uuid) -> Result<(), LogicErr>;
+  /// Removes the local users whose deletion has been requested, once the jobs telling other servers about it are done
+  async fn purge_deleted_users(&self) -> Result<(), LogicErr>;
   async fn delete_external_user(&self, id: &Uuid) -> Result<(), LogicErr>;
   async fn user_is_external(&self, user_id: &Uuid) -> bool;
 }
@@ -238,7 +242,7 @@ impl UserRepo for DbUserRepo {
       Err(_) => return None,
     };
     let row = match db
-      .query_opt("SELECT * FROM users WHERE handle = $1", &[&fediverse_uri])
+      .query_opt("SELECT * FROM users WHERE fediverse_uri = $1", &[&fediverse_uri])
       .await
       .map_err(map_db_err)
     {
@@ -289,8 +293,8 @@ impl UserRepo for DbUserRepo {
     let db = self.db.get().await.map_err(map_db_err)?;
     db.execute(r#"INSERT INTO users (user_id, handle, fediverse_id, fediverse_uri, avatar_url, email, password_hash, is_external, 
       url_1, url_2, url_3, url_4, url_5, url_1_title, url_2_title, url_3_title, url_4_title, url_5_title, intro_md, intro_html, private_key, public_key, 
-      ext_apub_followers_uri, ext_apub_following_uri, ext_apub_inbox_uri, ext_apub_outbox_uri) 
-    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26) RETURNING user_id"#,
+      ext_apub_followers_uri, ext_apub_following_uri, ext_apub_inbox_uri, ext_apub_outbox_uri, ext_apub_shared_inbox_uri, also_known_as, moved_to_uri) 
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29) RETURNING user_id"#,
       &[
         &user.user_id,
         &user.handle,
@@ -318,6 +322,9 @@ impl UserRepo for DbUserRepo {
         &user.ext_apub_following_uri,
         &user.ext_apub_inbox_uri,
         &user.ext_apub_outbox_uri,
+        &user.ext_apub_shared_inbox_uri,
+        &user.also_known_as,
+        &user.moved_to_uri,
       ],
     )
     .await
@@ -330,7 +337,7 @@ impl UserRepo for DbUserRepo {
     let db = self.db.get().await.map_err(map_db_err)?;
     db.execute(r#"UPDATE users SET handle = $2, fediverse_id = $3, fediverse_uri = $4, avatar_url = $5, email = $6, password_hash = $7, is_external = $8, 
     url_1 = $9, url_2 = $10, url_3 = $11, url_4 = $12, url_5 = $13, url_1_title = $14, url_2_title = $15, url_3_title = $16, url_4_title = $17, url_5_title = $18, intro_md = $19, intro_html = $20, private_key = $21, public_key = $22, 
-    ext_apub_followers_uri = $23, ext_apub_following_uri = $24, ext_apub_inbox_uri = $25, ext_apub_outbox_uri = $26, updated_at = NOW() WHERE user_id = $1"#,
+    ext_apub_followers_uri = $23, ext_apub_following_uri = $24, ext_apub_inbox_uri = $25, ext_apub_outbox_uri = $26, ext_apub_shared_inbox_uri = $27, also_known_as = $28, moved_to_uri = $29, updated_at = NOW() WHERE user_id = $1"#,
       &[
         &user.user_id,
         &user.handle,
@@ -358,6 +365,9 @@ impl UserRepo for DbUserRepo {
         &user.ext_apub_following_uri,
         &user.ext_apub_inbox_uri,
         &user.ext_apub_outbox_uri,
+        &user.ext_apub_shared_inbox_uri,
+        &user.also_known_as,
+        &user.moved_to_uri,
       ],
     )
     .await
@@ -384,6 +394,21 @@ impl UserRepo for DbUserRepo {
     Ok(())
   }
 
+  async fn purge_deleted_users(&self) -> Result<(), LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    db.execute(
+      r#"DELETE FROM users u WHERE u.is_external = FALSE
+      AND EXISTS (SELECT 1 FROM tombstones t WHERE t.fediverse_uri = u.fediverse_uri)
+      AND NOT EXISTS (SELECT 1 FROM jobs j WHERE j.created_by_id = u.user_id
+        AND (j.status = 'not_started' OR j.status = 'in_progress'))"#,
+      &[],
+    )
+    .await
+    .map_err(map_db_err)?;
+
+    Ok(())
+  }
+
   async fn delete_external_user(&self, id: &Uuid) -> Result<(), LogicErr> {
     let db = self.db.get().await.map_err(map_db_err)?;
     db.execute("DELETE FROM users WHERE user_id = $1 AND is_external = TRUE", &[&id])
@@ -400,7 +425,7 @@ impl UserRepo for DbUserRepo {
     };
     let row = match db
       .query_one(
-        "SELECT COUNT(*) >= 1 FROM users WHERE user_id = $1 is_external = TRUE",
+        "SELECT COUNT(*) >= 1 FROM users WHERE user_id = $1 AND is_external = TRUE",
         &[&user_id],
       )
       .await
@@ -425,4 +450,19 @@ impl UserRepo for DbUserRepo {
 
     Ok(rows.into_iter().map(|r| r.get::<&str, Uuid>("user_id")).collect())
   }
+
+  async fn fetch_known_inbox_targets(&self) -> Result<Vec<Uuid>, LogicErr> {
+    let db = self.db.get().await.map_err(map_db_err)?;
+    let rows = db
+      .query(
+        r#"SELECT DISTINCT ON (COALESCE(ext_apub_shared_inbox_uri, ext_apub_inbox_uri)) user_id FROM users
+        WHERE is_external = TRUE AND ext_apub_inbox_uri IS NOT NULL
+        ORDER BY COALESCE(ext_apub_shared_inbox_uri, ext_apub_inbox_uri), created_at"#,
+        &[],
+      )
+      .await
+      .map_err(map_db_err)?;
+
+    Ok(rows.into_iter().map(|r| r.get("user_id")).collect())
+  }
 }
@@ -6,18 +6,23 @@ use uuid::Uuid;
 
 use crate::{
   activitypub::{
+    actor::ActorProps,
     object::{Object, ObjectType},
     orbit::OrbitProps,
     rdf_string::RdfString,
     reference::Reference,
   },
-  db::{orbit_repository::OrbitPool, user_repository::UserPool},
+  db::{domain_block_repository::DomainBlockPool, orbit_repository::OrbitPool, user_repository::UserPool},
   logic::LogicErr,
   model::{orbit::Orbit, user::User},
   settings::SETTINGS,
 };
 
-use super::util::{activitypub_ref_to_uri_opt, deref_activitypub_ref};
+use super::util::{
+  activitypub_ref_to_id, activitypub_ref_to_ids, activitypub_ref_to_uri_opt, activitypub_uri_is_suspended,
+  activitypub_uris_share_host, deref_activitypub_ref, fetch_activitypub_json, fetch_activitypub_object,
+  fetch_webfinger_actor_uri,
+};
 
 async fn query_activitypub_user_ref(obj_ref: &Option<Reference<Object>>, users: &UserPool) -> Option<User> {
   let uri = match obj_ref {
@@ -67,7 +72,33 @@ async fn query_activitypub_orbit_ref(obj_ref: &Option<Reference<Object>>, orbits
   orbits.fetch_by_fediverse_uri(&uri).await
 }
 
-pub async fn federate_user_actor(actor_ref: &Option<Reference<Object>>, users: &UserPool) -> Result<User, LogicErr> {
+/// Extracts the server's shared inbox for an actor, which Mastodon and most other servers advertise via the actor's
+/// `endpoints`.
+pub(super) fn activitypub_actor_shared_inbox(actor: &ActorProps) -> Option<String> {
+  let endpoints_shared_inbox = match &actor.endpoints {
+    Some(Reference::Embedded(obj)) => match &obj.actors {
+      Some(actors) => activitypub_ref_to_uri_opt(&actors.shared_inbox),
+      None => None,
+    },
+    Some(Reference::Map(data)) => data.get("sharedInbox").and_then(|v| v.as_str()).map(|v| v.to_owned()),
+    _ => None,
+  };
+
+  endpoints_shared_inbox.or_else(|| activitypub_ref_to_uri_opt(&actor.shared_inbox))
+}
+
+pub async fn federate_user_actor(
+  actor_ref: &Option<Reference<Object>>,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<User, LogicErr> {
+  // Actors from suspended servers are refused outright, whether or not we already know about them
+  if let Some(uri) = activitypub_ref_to_id(actor_ref) {
+    if activitypub_uri_is_suspended(&uri, domain_blocks).await {
+      return Err(LogicErr::UnauthorizedError);
+    }
+  }
+
   if let Some(user) = query_activitypub_user_ref(actor_ref, users).await {
     return Ok(user);
   }
@@ -107,6 +138,10 @@ pub async fn federate_user_actor(actor_ref: &Option<Reference<Object>>, users: &
     None => return Err(LogicErr::InvalidData),
   };
 
+  let shared_inbox_uri = activitypub_actor_shared_inbox(&actor);
+  let also_known_as = activitypub_ref_to_ids(&actor.also_known_as);
+  let moved_to_uri = activitypub_ref_to_id(&actor.moved_to);
+
   let public_key = match actor_obj.key {
     Some(k) => match k.public_key_pem {
       Some(k) => k,
@@ -161,6 +196,9 @@ pub async fn federate_user_actor(actor_ref: &Option<Reference<Object>>, users: &
     ext_apub_following_uri: Some(following_uri),
     ext_apub_inbox_uri: Some(inbox_uri),
     ext_apub_outbox_uri: Some(outbox_uri),
+    ext_apub_shared_inbox_uri: shared_inbox_uri,
+    also_known_as,
+    moved_to_uri,
     created_at: Utc::now(),
     updated_at: Utc::now(),
   };
@@ -168,15 +206,116 @@ pub async fn federate_user_actor(actor_ref: &Option<Reference<Object>>, users: &
   users.create_from(&user).await
 }
 
+/// Resolves a remote user from their handle and the host of their server, looking them up with WebFinger and
+/// fetching their actor if we haven't come across them before
+pub async fn federate_webfinger_user(
+  handle: &str,
+  host: &str,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<User, LogicErr> {
+  if let Some(user) = users.fetch_by_fediverse_id(&format!("@{}@{}", handle, host)).await? {
+    return Ok(user);
+  }
+
+  if activitypub_uri_is_suspended(&format!("https://{}", host), domain_blocks).await {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  let actor_uri = match fetch_webfinger_actor_uri(handle, host).await {
+    Some(uri) => uri,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  federate_user_actor(&Some(Reference::Remote(actor_uri)), users, domain_blocks).await
+}
+
+/// Resolves the public key referenced by an HTTP signature's keyId. Signers we already know about are looked up
+/// locally, otherwise the key's owner is fetched, as it may be an actor we don't track such as an instance actor.
+pub async fn federate_signing_key(key_id: &str, users: &UserPool) -> Option<String> {
+  let owner_uri = match key_id.split('#').next() {
+    Some(uri) => uri.to_owned(),
+    None => return None,
+  };
+
+  let owner_uri = match owner_uri.starts_with(&SETTINGS.server.api_fqdn) {
+    true => owner_uri.replace(&SETTINGS.server.api_fqdn, ""),
+    false => owner_uri,
+  };
+
+  if let Some(user) = users.fetch_by_fediverse_uri(&owner_uri).await {
+    return Some(user.public_key);
+  }
+
+  match fetch_activitypub_object(key_id).await {
+    Some(obj) => obj.key.and_then(|k| k.public_key_pem),
+    None => None,
+  }
+}
+
+/// Resolves the public key an actor lists as their own, provided it's the key with the given ID. Keys are only ever taken
+/// from the actor's own document, as anyone can host a key that claims to be owned by someone else.
+pub async fn federate_actor_public_key(key_id: &str, actor_uri: &str) -> Option<String> {
+  if !activitypub_uris_share_host(key_id, actor_uri) {
+    return None;
+  }
+
+  let actor = fetch_activitypub_object(actor_uri).await?;
+
+  if actor.id.as_deref() != Some(actor_uri) {
+    return None;
+  }
+
+  match actor.key {
+    Some(key) if key.id.as_deref() == Some(key_id) => key.public_key_pem,
+    _ => None,
+  }
+}
+
+/// Resolves the `publicKeyMultibase` of the key an actor signs integrity proofs with. Actors list these keys under
+/// `assertionMethod`, and we only accept keys hosted alongside the actor that name the actor as their controller.
+pub async fn federate_assertion_key(verification_method: &str, actor_uri: &str) -> Option<String> {
+  if !activitypub_uris_share_host(verification_method, actor_uri) {
+    return None;
+  }
+
+  let doc = fetch_activitypub_json(verification_method).await?;
+
+  let methods = match doc.get("id").and_then(|v| v.as_str()) {
+    Some(id) if id == verification_method => vec![doc.clone()],
+    Some(id) if id == actor_uri => match doc.get("assertionMethod") {
+      Some(serde_json::Value::Array(methods)) => methods.clone(),
+      Some(method) => vec![method.clone()],
+      None => return None,
+    },
+    _ => return None,
+  };
+
+  methods
+    .iter()
+    .find(|method| {
+      method.get("id").and_then(|v| v.as_str()) == Some(verification_method)
+        && method.get("controller").and_then(|v| v.as_str()) == Some(actor_uri)
+    })
+    .and_then(|method| method.get("publicKeyMultibase"))
+    .and_then(|v| v.as_str())
+    .map(|v| v.to_owned())
+}
+
 pub async fn federate_update_user_actor(
   actor_ref: &Option<Reference<Object>>,
   users: &UserPool,
+  domain_blocks: &DomainBlockPool,
 ) -> Result<User, LogicErr> {
   let mut user = match query_activitypub_user_ref(actor_ref, users).await {
     Some(user) => user,
-    None => return federate_user_actor(actor_ref, users).await,
+    None => return federate_user_actor(actor_ref, users, domain_blocks).await,
   };
 
+  if user.is_external && activitypub_uri_is_suspended(&user.fediverse_uri, domain_blocks).await {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
   let actor_obj = match deref_activitypub_ref(actor_ref).await {
     Some(obj) => obj,
     None => return Ok(user),
@@ -217,6 +356,10 @@ pub async fn federate_update_user_actor(
     None => return Ok(user),
   };
 
+  let shared_inbox_uri = activitypub_actor_shared_inbox(&actor);
+  let also_known_as = activitypub_ref_to_ids(&actor.also_known_as);
+  let moved_to_uri = activitypub_ref_to_id(&actor.moved_to);
+
   let public_key = match actor_obj.key {
     Some(k) => match k.public_key_pem {
       Some(k) => k,
@@ -250,6 +393,9 @@ pub async fn federate_update_user_actor(
   user.ext_apub_following_uri = Some(following_uri);
   user.ext_apub_inbox_uri = Some(inbox_uri);
   user.ext_apub_outbox_uri = Some(outbox_uri);
+  user.ext_apub_shared_inbox_uri = shared_inbox_uri;
+  user.also_known_as = also_known_as;
+  user.moved_to_uri = moved_to_uri;
 
   users.update_from(&user).await
 }
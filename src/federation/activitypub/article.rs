@@ -1,3 +1,4 @@
+use chrono::Utc;
 use uuid::Uuid;
 
 use crate::{
@@ -11,8 +12,9 @@ use crate::{
     reference::Reference,
   },
   db::{
-    job_repository::JobPool, orbit_repository::OrbitPool, post_attachment_repository::PostAttachmentPool,
-    post_repository::PostPool, user_orbit_repository::UserOrbitPool,
+    hashtag_repository::HashtagPool, job_repository::JobPool, mention_repository::MentionPool,
+    orbit_repository::OrbitPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    post_revision_repository::PostRevisionPool, user_orbit_repository::UserOrbitPool,
   },
   helpers::api::map_db_err,
   logic::LogicErr,
@@ -31,6 +33,8 @@ use crate::{
 
 use super::{
   actor::federate_orbit_group,
+  hashtag::activitypub_object_hashtags,
+  mention::activitypub_tag_mentions,
   util::{activitypub_ref_to_uri_opt, deref_activitypub_ref_list, send_activitypub_object, FederateResult},
 };
 
@@ -42,6 +46,7 @@ pub async fn federate_create_article(
   post_attachments: &PostAttachmentPool,
   orbits: &OrbitPool,
   user_orbits: &UserOrbitPool,
+  hashtags: &HashtagPool,
   queue: &Queue,
 ) -> Result<FederateResult, LogicErr> {
   let orbit = federate_orbit_group(&activity_object.audience, orbits).await?;
@@ -58,6 +63,8 @@ pub async fn federate_create_article(
     None => return Err(LogicErr::InvalidData),
   };
 
+  let post_hashtags = activitypub_object_hashtags(&activity_object);
+
   let attachments: Vec<Object> = match deref_activitypub_ref_list(&activity_object.attachment).await {
     Some(obj) => obj
       .into_iter()
@@ -152,6 +159,10 @@ pub async fn federate_create_article(
 
   posts.create_post_from(post).await?;
 
+  if !post_hashtags.is_empty() {
+    hashtags.set_post_hashtags(&post_id, &post_hashtags).await?;
+  }
+
   for attachment_obj in attachments {
     let image_content_type = match attachment_obj.media_type {
       Some(val) => val,
@@ -217,12 +228,16 @@ pub async fn federate_update_article(
   actor: &User,
   access: AccessType,
   posts: &PostPool,
+  hashtags: &HashtagPool,
+  post_revisions: &PostRevisionPool,
 ) -> Result<FederateResult, LogicErr> {
   let uri = match activity_object.id {
     Some(uri) => uri,
     None => return Err(LogicErr::InvalidData),
   };
 
+  let post_hashtags = activitypub_object_hashtags(&activity_object);
+
   let mut post = match posts.find_optional_by_uri(&uri).await {
     Some(post) => post,
     None => return Err(LogicErr::MissingRecord),
@@ -279,13 +294,19 @@ pub async fn federate_update_article(
     None => return Err(LogicErr::InvalidData),
   };
 
+  // Updates can also just refresh things like a poll's vote counts, which we don't keep a revision for
+  if post.content_html != content_html || post.content_md != content_md {
+    post_revisions.create_revision(&post).await?;
+  }
+
   post.content_html = content_html;
   post.content_md = content_md;
   post.visibility = access;
   post.created_at = created_at;
-  post.updated_at = created_at;
+  post.updated_at = activity_object.updated.unwrap_or_else(Utc::now);
 
   posts.update_post_content(&post).await?;
+  hashtags.set_post_hashtags(&post.post_id, &post_hashtags).await?;
 
   Ok(FederateResult::None)
 }
@@ -295,6 +316,7 @@ pub async fn federate_ext_create_article(
   actor: &User,
   dest_actor: &Orbit,
   posts: &PostPool,
+  mentions: &MentionPool,
 ) -> Result<(), LogicErr> {
   let post = match posts.fetch_post(post_id, &Some(actor.user_id)).await {
     Ok(post) => match post {
@@ -307,11 +329,13 @@ pub async fn federate_ext_create_article(
     }
   };
 
-  let obj = match post.to_object(&actor.fediverse_uri) {
+  let mut obj = match post.to_object(&actor.fediverse_uri) {
     Some(obj) => obj,
     None => return Err(LogicErr::MissingRecord),
   };
 
+  activitypub_tag_mentions(&mut obj, &mentions.fetch_post_mentioned_users(post_id).await?);
+
   let response_object = Object::builder()
     .kind(Some(ActivityType::Create.to_string()))
     .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
@@ -341,6 +365,7 @@ pub async fn federate_ext_update_article(
   actor: &User,
   dest_actor: &Orbit,
   posts: &PostPool,
+  mentions: &MentionPool,
 ) -> Result<(), LogicErr> {
   let post = match posts.fetch_post(post_id, &Some(actor.user_id)).await {
     Ok(post) => match post {
@@ -353,11 +378,13 @@ pub async fn federate_ext_update_article(
     }
   };
 
-  let obj = match post.to_object(&actor.fediverse_uri) {
+  let mut obj = match post.to_object(&actor.fediverse_uri) {
     Some(obj) => obj,
     None => return Err(LogicErr::MissingRecord),
   };
 
+  activitypub_tag_mentions(&mut obj, &mentions.fetch_post_mentioned_users(post_id).await?);
+
   let response_object = Object::builder()
     .kind(Some(ActivityType::Update.to_string()))
     .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
@@ -0,0 +1,269 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::{
+  actor::federate_user_actor,
+  mention::activitypub_tag_mentions,
+  util::{activitypub_ref_to_id, send_activitypub_object, FederateResult},
+};
+use crate::{
+  activitypub::{
+    activity::ActivityProps,
+    activity_convertible::ActivityConvertible,
+    activity_type::ActivityType,
+    document::ActivityPubDocument,
+    object::{Object, ObjectType},
+    rdf_string::RdfString,
+    reference::Reference,
+  },
+  db::{
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, mention_repository::MentionPool,
+    post_repository::PostPool, user_repository::UserPool,
+  },
+  logic::LogicErr,
+  model::{access_type::AccessType, comment::Comment, post::Post, user::User},
+  settings::SETTINGS,
+};
+
+fn to_local_uri(uri: String) -> String {
+  match uri.starts_with(&SETTINGS.server.api_fqdn) {
+    true => uri.replace(&SETTINGS.server.api_fqdn, ""),
+    false => uri,
+  }
+}
+
+fn federate_comment_content(activity_object: &Object) -> (String, String) {
+  let content_html: Option<RdfString> = match &activity_object.content_map {
+    Some(content) => {
+      // TODO: Once we support multiple content languages for a comment we should
+      //       do a mapping here instead of pulling out english values
+      if content.contains_key("en") {
+        content.get("en").cloned()
+      } else if content.contains_key("en-US") {
+        content.get("en-US").cloned()
+      } else if content.contains_key("en-GB") {
+        content.get("en-GB").cloned()
+      } else {
+        None
+      }
+    }
+    None => None,
+  };
+
+  let content_html = match content_html.or_else(|| activity_object.content.clone()) {
+    Some(content) => match content {
+      RdfString::Raw(content) => content,
+      RdfString::Props(props) => props.string,
+    },
+    None => "".to_string(),
+  };
+
+  let content_md = match &activity_object.source {
+    Some(source) => {
+      if source.media_type == "text/markdown" {
+        source.content.clone()
+      } else {
+        "".to_string()
+      }
+    }
+    None => "".to_string(),
+  };
+
+  (content_md, content_html)
+}
+
+/// Resolves the post a Note is replying to, if it's replying to a post or comment that we know about. Replies to
+/// comments are attached to the comment's post.
+pub async fn federate_find_reply_post(
+  activity_object: &Object,
+  posts: &PostPool,
+  comments: &CommentPool,
+) -> Option<Post> {
+  let uri = match activitypub_ref_to_id(&activity_object.in_reply_to) {
+    Some(uri) => to_local_uri(uri),
+    None => return None,
+  };
+
+  if let Some(post) = posts.find_optional_by_uri(&uri).await {
+    return Some(post);
+  }
+
+  match comments.find_optional_by_uri(&uri).await {
+    Some(comment) => posts.find_optional_by_id(&comment.post_id).await,
+    None => None,
+  }
+}
+
+pub async fn federate_find_comment(activity_object: &Object, comments: &CommentPool) -> Option<Comment> {
+  match &activity_object.id {
+    Some(uri) => comments.find_optional_by_uri(&to_local_uri(uri.to_owned())).await,
+    None => None,
+  }
+}
+
+pub async fn federate_create_comment(
+  activity_object: Object,
+  actor: &User,
+  post: Post,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  comments: &CommentPool,
+) -> Result<FederateResult, LogicErr> {
+  let author = federate_user_actor(&activity_object.attributed_to, users, domain_blocks).await?;
+
+  if author.user_id != actor.user_id {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  if post.visibility == AccessType::Private || post.visibility == AccessType::Shadow {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  let uri = match &activity_object.id {
+    Some(uri) => uri.to_owned(),
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  // We may receive the same reply more than once, e.g. via both a personal and a shared inbox
+  if comments.find_optional_by_uri(&uri).await.is_some() {
+    return Ok(FederateResult::None);
+  }
+
+  let created_at = match activity_object.published {
+    Some(date) => date,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  let (content_md, content_html) = federate_comment_content(&activity_object);
+
+  // Replies to comments are threaded below the comment they're replying to
+  let parent_comment_id = match activitypub_ref_to_id(&activity_object.in_reply_to) {
+    Some(uri) => comments
+      .find_optional_by_uri(&to_local_uri(uri))
+      .await
+      .filter(|parent| parent.post_id == post.post_id)
+      .map(|parent| parent.comment_id),
+    None => None,
+  };
+
+  let comment = Comment {
+    comment_id: Uuid::new_v4(),
+    user_id: actor.user_id,
+    post_id: post.post_id,
+    parent_comment_id,
+    content_md,
+    content_html,
+    uri,
+    is_external: true,
+    created_at,
+    updated_at: created_at,
+  };
+
+  comments.create_comment_from(comment).await?;
+
+  Ok(FederateResult::None)
+}
+
+pub async fn federate_update_comment(
+  activity_object: Object,
+  actor: &User,
+  mut comment: Comment,
+  comments: &CommentPool,
+) -> Result<FederateResult, LogicErr> {
+  if !comment.is_external || comment.user_id != actor.user_id {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  let (content_md, content_html) = federate_comment_content(&activity_object);
+
+  comment.content_md = content_md;
+  comment.content_html = content_html;
+  comment.updated_at = activity_object.updated.unwrap_or_else(Utc::now);
+
+  comments.update_comment_content(&comment).await?;
+
+  Ok(FederateResult::None)
+}
+
+pub async fn federate_ext_create_comment(
+  post_id: &Uuid,
+  comment_id: &Uuid,
+  actor: &User,
+  dest_actor: &User,
+  comments: &CommentPool,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
+  let comment = match comments.fetch_comment(post_id, comment_id, &Some(actor.user_id)).await {
+    Some(comment) => comment,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let actor_uri = format!("{}{}", SETTINGS.server.api_fqdn, actor.fediverse_uri);
+
+  let mut obj = match comment.to_object(&actor_uri) {
+    Some(obj) => obj,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  activitypub_tag_mentions(&mut obj, &mentions.fetch_comment_mentioned_users(comment_id).await?);
+
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Create.to_string()))
+    .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+    .actor(Some(Reference::Remote(actor_uri)))
+    .to(obj.to.clone())
+    .cc(obj.cc.clone())
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Embedded(Box::new(obj))))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
+pub async fn federate_ext_delete_comment(
+  post_id: &Uuid,
+  comment_id: &Uuid,
+  actor: &User,
+  dest_actor: &User,
+) -> Result<(), LogicErr> {
+  // NOTE: By this point, the comment is deleted in our DB, so we have to build the URI from scratch here
+  let uri = format!("{}/feed/{}/comments/{}", SETTINGS.server.api_fqdn, post_id, comment_id);
+  let obj = Object::builder()
+    .kind(Some(ObjectType::Tombstone.to_string()))
+    .id(Some(uri.clone()))
+    .url(Some(Reference::Remote(uri)))
+    .build();
+
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Delete.to_string()))
+    .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+    .actor(Some(Reference::Remote(format!(
+      "{}{}",
+      SETTINGS.server.api_fqdn, actor.fediverse_uri
+    ))))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Embedded(Box::new(obj))))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
@@ -1,23 +1,45 @@
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 
 use super::{
-  actor::{federate_update_user_actor, federate_user_actor},
+  actor::{
+    federate_actor_public_key, federate_assertion_key, federate_signing_key, federate_update_user_actor,
+    federate_user_actor,
+  },
   article::{
-    federate_create_article, federate_ext_create_article, federate_ext_delete_article, federate_update_article,
+    federate_create_article, federate_ext_create_article, federate_ext_delete_article, federate_ext_update_article,
+    federate_update_article,
+  },
+  comment::{
+    federate_create_comment, federate_ext_create_comment, federate_ext_delete_comment, federate_find_comment,
+    federate_find_reply_post, federate_update_comment,
   },
-  group::{federate_create_member, federate_remove_member},
+  flag::federate_flag,
+  group::{
+    federate_activity_handled_in_orbit, federate_activity_local_orbit, federate_activity_object_uri,
+    federate_create_member, federate_group_announced_activity, federate_queue_group_announce, federate_remove_member,
+  },
+  mention::{federate_new_object_mentions, federate_record_mentions},
   note::{
-    federate_create_note, federate_ext_create_note, federate_ext_delete_note, federate_ext_update_note,
-    federate_like_note, federate_unlike_note, federate_update_note,
+    federate_boost_note, federate_create_note, federate_ext_boost_note, federate_ext_create_note,
+    federate_ext_delete_note, federate_ext_unboost_note, federate_ext_update_note, federate_like_note,
+    federate_store_note, federate_unlike_note, federate_update_note,
   },
   object::federate_delete_remote_object,
   person::{
-    federate_create_follow, federate_ext_create_follow, federate_ext_join_group, federate_ext_leave_group,
-    federate_ext_remove_follow, federate_remove_follow,
+    federate_create_block, federate_create_follow, federate_ext_block, federate_ext_create_follow,
+    federate_ext_delete_person, federate_ext_join_group, federate_ext_leave_group, federate_ext_move_profile,
+    federate_ext_remove_follow, federate_ext_unblock, federate_ext_update_profile, federate_move,
+    federate_remove_follow,
   },
+  question::{activitypub_object_poll, federate_create_poll_vote, federate_find_vote_poll, federate_store_remote_poll},
+  relay::{federate_relay_activity, federate_relayed_post, federate_signing_relay},
+  undo::federate_undo,
   util::{
-    activitypub_ref_to_uri_opt, deref_activitypub_ref, determine_activity_target, determine_activity_visibility,
+    activitypub_ref_to_id, activitypub_ref_to_uri_opt, activitypub_uri_domain_block, activitypub_uris_share_host,
+    deref_activitypub_ref, determine_activity_target, determine_activity_visibility, fetch_activitypub_object,
     send_activitypub_object, ActivityTarget, FederateResult,
   },
 };
@@ -30,42 +52,253 @@ use crate::{
     reference::Reference,
   },
   db::{
-    follow_repository::FollowPool, job_repository::JobPool, like_repository::LikePool, orbit_repository::OrbitPool,
-    post_attachment_repository::PostAttachmentPool, post_repository::PostPool, user_orbit_repository::UserOrbitPool,
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, follow_repository::FollowPool,
+    hashtag_repository::HashtagPool, job_repository::JobPool, like_repository::LikePool,
+    mention_repository::MentionPool, orbit_repository::OrbitPool, poll_repository::PollPool,
+    post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    post_revision_repository::PostRevisionPool, relay_repository::RelayPool, report_repository::ReportPool,
+    signature_repository::SignaturePool, user_block_repository::UserBlockPool, user_orbit_repository::UserOrbitPool,
     user_repository::UserPool,
   },
   helpers::core::unwrap_or_fail,
   logic::LogicErr,
-  model::{orbit::Orbit, queue_job::OriginDataEntry, user::User},
-  net::http_sig::verify_http_signature,
+  model::{
+    access_type::AccessType, domain_block_severity::DomainBlockSeverity, orbit::Orbit, queue_job::OriginDataEntry,
+    user::User,
+  },
+  net::{
+    http_sig::{
+      extract_http_signature_key_id, extract_http_signature_value, signature_use_expiry, verify_http_signature,
+      MAX_RECORDED_SIGNATURES,
+    },
+    integrity_proof::{extract_integrity_proof_verification_method, verify_integrity_proof},
+  },
   settings::SETTINGS,
   work_queue::queue::Queue,
 };
 
 use std::{collections::HashMap, str::FromStr};
 
+/// Determines whether the key that signed a request belongs to the given actor. Most servers use a fragment of the
+/// actor's URI as the key ID, otherwise the actor needs to list the key as their own.
+pub(super) async fn federate_key_belongs_to_actor(key_id: &str, actor_uri: &str) -> bool {
+  if key_id.split('#').next() == Some(actor_uri) {
+    return true;
+  }
+
+  federate_actor_public_key(key_id, actor_uri).await.is_some()
+}
+
+/// Checks that a request was signed by the given actor, without resolving the actor to a user. This is used for actors
+/// that aren't people, such as instance actors and relays.
+pub(super) async fn federate_verify_actor_signature(
+  actor_uri: &str,
+  origin_data: &Option<HashMap<String, OriginDataEntry>>,
+) -> Result<(), LogicErr> {
+  if !SETTINGS.app.secure {
+    return Ok(());
+  }
+
+  let key_id = match extract_http_signature_key_id(origin_data) {
+    Some(key_id) => key_id,
+    None => return Err(LogicErr::UnauthorizedError),
+  };
+
+  match federate_actor_public_key(&key_id, actor_uri).await {
+    Some(public_key) if verify_http_signature(origin_data, &public_key) => Ok(()),
+    _ => Err(LogicErr::UnauthorizedError),
+  }
+}
+
+/// Records a verified signature as used by the given job, rejecting it if another job has already used it
+pub(super) async fn federate_record_signature_use(
+  key_id: &str,
+  signature: &str,
+  job_id: &Uuid,
+  signatures: &SignaturePool,
+) -> Result<(), LogicErr> {
+  match signatures
+    .record_signature_use(
+      key_id,
+      signature,
+      job_id,
+      &signature_use_expiry(Utc::now()),
+      MAX_RECORDED_SIGNATURES,
+    )
+    .await?
+  {
+    true => Ok(()),
+    false => Err(LogicErr::UnauthorizedError),
+  }
+}
+
+/// Verifies the signature of an inbound request and makes sure it hasn't been replayed. Signatures are only recorded
+/// once they've been verified, so that unverified requests can't fill up the record, and they're recorded in the
+/// database so that replays are caught by every instance of the server.
+pub async fn federate_check_signature_replay(
+  job_id: &Uuid,
+  origin_data: &Option<HashMap<String, OriginDataEntry>>,
+  users: &UserPool,
+  signatures: &SignaturePool,
+) -> Result<(), LogicErr> {
+  if !SETTINGS.app.secure {
+    return Ok(());
+  }
+
+  let (key_id, signature) = match (
+    extract_http_signature_key_id(origin_data),
+    extract_http_signature_value(origin_data),
+  ) {
+    (Some(key_id), Some(signature)) => (key_id, signature),
+    _ => return Err(LogicErr::UnauthorizedError),
+  };
+
+  let verified = match federate_signing_key(&key_id, users).await {
+    Some(public_key) if verify_http_signature(origin_data, &public_key) => true,
+    // The signer may have rotated their key since we last saw them
+    _ => match fetch_activitypub_object(&key_id).await.and_then(|obj| obj.key) {
+      Some(key) => key
+        .public_key_pem
+        .map(|public_key| verify_http_signature(origin_data, &public_key))
+        .unwrap_or(false),
+      None => false,
+    },
+  };
+
+  if !verified {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  federate_record_signature_use(&key_id, &signature, job_id, signatures).await
+}
+
+/// Resolves the actor performing the activity and checks it against the request's signature. Returns the actor along
+/// with whether the activity was relayed, i.e. legitimately signed by one of our relays rather than the actor.
 async fn federate_get_actor_user(
   doc: &ActivityPubDocument,
   users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  relays: &RelayPool,
   origin_data: &Option<HashMap<String, OriginDataEntry>>,
-) -> Result<User, LogicErr> {
-  let mut actor_user = match federate_user_actor(&doc.object.actor, users).await {
+) -> Result<(User, bool), LogicErr> {
+  let mut actor_user = match federate_user_actor(&doc.object.actor, users, domain_blocks).await {
     Ok(user) => user,
     Err(err) => return Err(err),
   };
 
-  if SETTINGS.app.secure && !verify_http_signature(origin_data, &actor_user.public_key) {
-    actor_user = federate_update_user_actor(&doc.object.actor, users).await?;
+  if !SETTINGS.app.secure {
+    return Ok((actor_user, false));
+  }
+
+  let key_id = match extract_http_signature_key_id(origin_data) {
+    Some(key_id) => key_id,
+    None => return Err(LogicErr::UnauthorizedError),
+  };
+
+  if federate_key_belongs_to_actor(&key_id, &actor_user.fediverse_uri).await {
     if !verify_http_signature(origin_data, &actor_user.public_key) {
-      return Err(LogicErr::UnauthorizedError);
+      actor_user = federate_update_user_actor(&doc.object.actor, users, domain_blocks).await?;
+      if !verify_http_signature(origin_data, &actor_user.public_key) {
+        return Err(LogicErr::UnauthorizedError);
+      }
     }
+
+    return Ok((actor_user, false));
+  }
+
+  // We only accept activities forwarded on someone else's behalf from relays we're subscribed to
+  let relay = match federate_signing_relay(origin_data, relays).await {
+    Some(relay) => relay,
+    None => return Err(LogicErr::UnauthorizedError),
+  };
+
+  let signer_public_key = match federate_actor_public_key(&key_id, &relay.actor_uri).await {
+    Some(key) => key,
+    None => return Err(LogicErr::UnauthorizedError),
+  };
+
+  if !verify_http_signature(origin_data, &signer_public_key) {
+    return Err(LogicErr::UnauthorizedError);
   }
 
-  Ok(actor_user)
+  Ok((actor_user, true))
+}
+
+/// Checks that an object claiming to come from the given actor is hosted on the actor's server and attributed to them
+fn federate_verify_object_attribution(object: &Object, actor: &User) -> Result<(), LogicErr> {
+  let id = match &object.id {
+    Some(id) => id,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  if !activitypub_uris_share_host(&actor.fediverse_uri, id)
+    || activitypub_ref_to_id(&object.attributed_to).as_deref() != Some(actor.fediverse_uri.as_str())
+  {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  Ok(())
+}
+
+/// Objects that reach us from someone other than their actor can't be trusted as-is, so we fetch the authoritative
+/// copy of the object from the actor's server and check it's attributed to the actor.
+pub(super) async fn federate_verify_relayed_object(object: Object, actor: &User) -> Result<Object, LogicErr> {
+  federate_verify_object_attribution(&object, actor)?;
+
+  let id = object.id.unwrap_or_default();
+
+  let object = match fetch_activitypub_object(&id).await {
+    Some(obj) => obj,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  if object.id.as_deref() != Some(id.as_str()) {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  federate_verify_object_attribution(&object, actor)?;
+
+  Ok(object)
+}
+
+/// Relayed activities aren't signed by their actor over HTTP. If the actor attached an integrity proof to the activity
+/// covering its embedded object we can trust the object as delivered, otherwise we fall back to fetching it from the
+/// actor's server. A proof that's present but doesn't verify is treated as a forgery.
+async fn federate_verify_relayed_activity(raw_doc: &Value, object: Object, actor: &User) -> Result<Object, LogicErr> {
+  let verification_method = match extract_integrity_proof_verification_method(raw_doc) {
+    Some(verification_method) => verification_method,
+    None => return federate_verify_relayed_object(object, actor).await,
+  };
+
+  if !raw_doc.get("object").map(|v| v.is_object()).unwrap_or(false) {
+    return federate_verify_relayed_object(object, actor).await;
+  }
+
+  federate_verify_object_attribution(&object, actor)?;
+
+  let public_key = match federate_assertion_key(&verification_method, &actor.fediverse_uri).await {
+    Some(public_key) => public_key,
+    None => return Err(LogicErr::UnauthorizedError),
+  };
+
+  match verify_integrity_proof(raw_doc, &public_key) {
+    true => Ok(object),
+    false => Err(LogicErr::UnauthorizedError),
+  }
+}
+
+/// Determines who an activity is visible to. Relayed activities take their audience from the verified object, as the
+/// activity wrapping it is under the relay's control rather than the actor's.
+fn federate_activity_visibility(activity: &Object, object: &Object, actor: &User, relayed: bool) -> Option<AccessType> {
+  match relayed {
+    true => determine_activity_visibility(&object.to, actor),
+    false => determine_activity_visibility(&activity.to, actor),
+  }
 }
 
 pub async fn federate(
   doc: ActivityPubDocument,
+  raw_doc: &Value,
   origin_data: &Option<HashMap<String, OriginDataEntry>>,
   users: &UserPool,
   follows: &FollowPool,
@@ -75,6 +308,15 @@ pub async fn federate(
   post_attachments: &PostAttachmentPool,
   orbits: &OrbitPool,
   user_orbits: &UserOrbitPool,
+  comments: &CommentPool,
+  user_blocks: &UserBlockPool,
+  domain_blocks: &DomainBlockPool,
+  reports: &ReportPool,
+  relays: &RelayPool,
+  mentions: &MentionPool,
+  hashtags: &HashtagPool,
+  polls: &PollPool,
+  post_revisions: &PostRevisionPool,
   queue: &Queue,
 ) -> Result<(), LogicErr> {
   let kind = match unwrap_or_fail(doc.object.kind.as_ref().map(|v| ActivityType::from_str(v))) {
@@ -82,9 +324,39 @@ pub async fn federate(
     Err(err) => return Err(err),
   };
 
-  let actor_user = federate_get_actor_user(&doc, users, origin_data).await?;
+  // Reports are generally sent by a server's instance actor, which isn't a user we can resolve
+  if kind == ActivityType::Flag {
+    return federate_flag(&doc.object, origin_data, users, posts, reports, domain_blocks).await;
+  }
+
+  // Relays aren't users either, so anything a relay sends us on its own behalf is handled separately
+  if let Some(actor_uri) = activitypub_ref_to_id(&doc.object.actor) {
+    if let Some(relay) = relays.fetch_relay_by_actor_uri(&actor_uri).await {
+      return federate_relay_activity(
+        kind,
+        &doc.object,
+        relay,
+        origin_data,
+        users,
+        posts,
+        jobs,
+        post_attachments,
+        relays,
+        domain_blocks,
+        hashtags,
+        queue,
+      )
+      .await;
+    }
+  }
 
-  let activity_visibility = determine_activity_visibility(&doc.object.to, &actor_user);
+  let (actor_user, relayed) = federate_get_actor_user(&doc, users, domain_blocks, relays, origin_data).await?;
+
+  // We can only vouch for the content of relayed activities, not whether the actor actually performed them, so we
+  // only accept those that deliver content
+  if relayed && kind != ActivityType::Create && kind != ActivityType::Update {
+    return Err(LogicErr::UnauthorizedError);
+  }
 
   let activity = match &doc.object.activity {
     Some(ac) => ac,
@@ -96,8 +368,88 @@ pub async fn federate(
     None => return Err(LogicErr::InvalidData),
   };
 
+  let mut object = match relayed {
+    true => federate_verify_relayed_activity(raw_doc, object, &actor_user).await?,
+    false => object,
+  };
+
+  let activity_visibility = federate_activity_visibility(&doc.object, &object, &actor_user, relayed);
+
+  // Servers we reject media from can still post, we just don't keep anything they attach
+  if let Some(block) = activitypub_uri_domain_block(&actor_user.fediverse_uri, domain_blocks).await {
+    if block.severity == DomainBlockSeverity::RejectMedia {
+      object.attachment = None;
+    }
+  }
+
+  // Our users are told when they're mentioned, and are sent posts mentioning them even if they don't follow the author
+  let mentioned_users = federate_new_object_mentions(&kind, &object, users, posts, comments).await;
+
+  // Posts forwarded by a relay we're subscribed to are kept even if nobody here follows their author. Replies still
+  // go through the usual path so that any replying to our posts become comments, as do posts mentioning our users so
+  // that they're kept and the mentions recorded whatever the post's audience.
+  if relayed && kind == ActivityType::Create && object.in_reply_to.is_none() && mentioned_users.is_empty() {
+    return federate_relayed_post(object, &actor_user, posts, jobs, post_attachments, hashtags, queue)
+      .await
+      .map(|_| ());
+  }
+
   let target = activitypub_ref_to_uri_opt(&activity.target);
 
+  // Undo wraps another activity rather than an object, so it needs to be handled before we inspect the object type
+  if kind == ActivityType::Undo {
+    return federate_undo(
+      object,
+      &actor_user,
+      users,
+      follows,
+      posts,
+      likes,
+      jobs,
+      orbits,
+      user_orbits,
+      user_blocks,
+      queue,
+    )
+    .await
+    .map(|_| ());
+  }
+
+  // Votes in our polls arrive as replies to the poll, but they're counted rather than kept as comments
+  if !relayed && kind == ActivityType::Create {
+    if let Some(poll) = federate_find_vote_poll(&object, posts, polls).await {
+      return federate_create_poll_vote(&object, &actor_user, &poll, polls)
+        .await
+        .map(|_| ());
+    }
+  }
+
+  // Posts, comments, edits and deletions in our orbits are announced to the orbit's members once we've handled them,
+  // so that remote members see activity from outside their own server. The orbit passes on the object as we've kept
+  // it, rather than as it was sent to us.
+  let announce = match relayed {
+    true => None,
+    false => match federate_activity_local_orbit(
+      &kind,
+      &object,
+      &target,
+      &actor_user,
+      posts,
+      comments,
+      orbits,
+      user_orbits,
+    )
+    .await
+    {
+      Some(orbit) => federate_activity_object_uri(&kind, &object, &target)
+        .map(|uri| (orbit, uri, federate_group_announced_activity(&doc.object, &object))),
+      None => None,
+    },
+  };
+
+  let object_uri = object.id.clone();
+  let remote_poll = activitypub_object_poll(&object);
+
   let object_type = match &object.kind {
     Some(v) => match ObjectType::from_str(v) {
       Ok(t) => t,
@@ -107,45 +459,98 @@ pub async fn federate(
   };
 
   let result = match object_type {
-    ObjectType::Note => match kind {
-      ActivityType::Create => {
-        let activity_visibility = match activity_visibility {
-          Some(v) => v,
-          None => return Err(LogicErr::InvalidData),
-        };
+    ObjectType::Note | ObjectType::Question => match kind {
+      ActivityType::Create => match federate_find_reply_post(&object, posts, comments).await {
+        Some(post) => federate_create_comment(object, &actor_user, post, users, domain_blocks, comments).await,
+        None => {
+          let activity_visibility = match activity_visibility {
+            Some(v) => v,
+            None => return Err(LogicErr::InvalidData),
+          };
 
-        federate_create_note(
-          object,
-          &actor_user,
-          activity_visibility,
-          follows,
-          posts,
-          jobs,
-          post_attachments,
-          queue,
-        )
-        .await
-      }
-      ActivityType::Update => {
-        let activity_visibility = match activity_visibility {
-          Some(v) => v,
-          None => return Err(LogicErr::InvalidData),
-        };
+          match mentioned_users.is_empty() {
+            true => {
+              federate_create_note(
+                object,
+                &actor_user,
+                activity_visibility,
+                follows,
+                posts,
+                jobs,
+                post_attachments,
+                hashtags,
+                queue,
+              )
+              .await
+            }
+            false => {
+              federate_store_note(
+                object,
+                &actor_user,
+                activity_visibility,
+                posts,
+                jobs,
+                post_attachments,
+                hashtags,
+                queue,
+              )
+              .await
+            }
+          }
+        }
+      },
+      ActivityType::Update => match federate_find_comment(&object, comments).await {
+        Some(comment) => federate_update_comment(object, &actor_user, comment, comments).await,
+        None => {
+          let activity_visibility = match activity_visibility {
+            Some(v) => v,
+            None => return Err(LogicErr::InvalidData),
+          };
 
-        federate_update_note(object, &actor_user, activity_visibility, posts).await
-      }
+          federate_update_note(
+            object,
+            &actor_user,
+            activity_visibility,
+            posts,
+            hashtags,
+            post_revisions,
+          )
+          .await
+        }
+      },
       ActivityType::Like => federate_like_note(object, &actor_user, posts, likes).await,
+      ActivityType::Announce => federate_boost_note(object, &actor_user, posts, jobs, queue).await,
       ActivityType::Remove => match determine_activity_target(target) {
         ActivityTarget::PostLikes(target) => federate_unlike_note(target, &actor_user, posts, likes).await,
         ActivityTarget::Unknown(target) => {
-          federate_delete_remote_object(target, &actor_user, object_type, origin_data, posts, users, likes).await
+          federate_delete_remote_object(
+            target,
+            &actor_user,
+            object_type,
+            origin_data,
+            posts,
+            comments,
+            users,
+            likes,
+          )
+          .await
         }
         _ => Err(LogicErr::InvalidData),
       },
       ActivityType::Delete => match determine_activity_target(target) {
         ActivityTarget::PostLikes(target) => federate_unlike_note(target, &actor_user, posts, likes).await,
         ActivityTarget::Unknown(target) => {
-          federate_delete_remote_object(target, &actor_user, object_type, origin_data, posts, users, likes).await
+          federate_delete_remote_object(
+            target,
+            &actor_user,
+            object_type,
+            origin_data,
+            posts,
+            comments,
+            users,
+            likes,
+          )
+          .await
         }
         _ => Err(LogicErr::InvalidData),
       },
@@ -161,6 +566,7 @@ pub async fn federate(
           post_attachments,
           orbits,
           user_orbits,
+          hashtags,
           queue,
         )
         .await
@@ -171,37 +577,90 @@ pub async fn federate(
           None => return Err(LogicErr::InvalidData),
         };
 
-        federate_update_article(object, &actor_user, activity_visibility, posts).await
+        federate_update_article(
+          object,
+          &actor_user,
+          activity_visibility,
+          posts,
+          hashtags,
+          post_revisions,
+        )
+        .await
       }
+      ActivityType::Announce => federate_boost_note(object, &actor_user, posts, jobs, queue).await,
       ActivityType::Remove => match determine_activity_target(target) {
         ActivityTarget::OrbitMembers(target) => federate_remove_member(target, &actor_user, user_orbits, orbits).await,
         ActivityTarget::Unknown(target) => {
-          federate_delete_remote_object(target, &actor_user, object_type, origin_data, posts, users, likes).await
+          federate_delete_remote_object(
+            target,
+            &actor_user,
+            object_type,
+            origin_data,
+            posts,
+            comments,
+            users,
+            likes,
+          )
+          .await
         }
         _ => Err(LogicErr::InvalidData),
       },
       ActivityType::Delete => match determine_activity_target(target) {
         ActivityTarget::OrbitMembers(target) => federate_remove_member(target, &actor_user, user_orbits, orbits).await,
         ActivityTarget::Unknown(target) => {
-          federate_delete_remote_object(target, &actor_user, object_type, origin_data, posts, users, likes).await
+          federate_delete_remote_object(
+            target,
+            &actor_user,
+            object_type,
+            origin_data,
+            posts,
+            comments,
+            users,
+            likes,
+          )
+          .await
         }
         _ => Err(LogicErr::InvalidData),
       },
       _ => Err(LogicErr::InternalError("Unimplemented".to_string())),
     },
     ObjectType::Person => match kind {
-      ActivityType::Follow => federate_create_follow(object, &actor_user, follows, users).await,
+      ActivityType::Follow => federate_create_follow(object, &actor_user, follows, users, user_blocks).await,
+      ActivityType::Block => federate_create_block(object, &actor_user, follows, users, user_blocks).await,
+      ActivityType::Move => {
+        federate_move(object, target, &actor_user, follows, users, domain_blocks, jobs, queue).await
+      }
       ActivityType::Remove => match determine_activity_target(target) {
         ActivityTarget::UserFollowers(target) => federate_remove_follow(target, &actor_user, follows, users).await,
         ActivityTarget::Unknown(target) => {
-          federate_delete_remote_object(target, &actor_user, object_type, origin_data, posts, users, likes).await
+          federate_delete_remote_object(
+            target,
+            &actor_user,
+            object_type,
+            origin_data,
+            posts,
+            comments,
+            users,
+            likes,
+          )
+          .await
         }
         _ => Err(LogicErr::InvalidData),
       },
       ActivityType::Delete => match determine_activity_target(target) {
         ActivityTarget::UserFollowers(target) => federate_remove_follow(target, &actor_user, follows, users).await,
         ActivityTarget::Unknown(target) => {
-          federate_delete_remote_object(target, &actor_user, object_type, origin_data, posts, users, likes).await
+          federate_delete_remote_object(
+            target,
+            &actor_user,
+            object_type,
+            origin_data,
+            posts,
+            comments,
+            users,
+            likes,
+          )
+          .await
         }
         _ => Err(LogicErr::InvalidData),
       },
@@ -217,7 +676,9 @@ pub async fn federate(
           ActivityTarget::PostLikes(target) => federate_unlike_note(target, &actor_user, posts, likes).await,
           _ => Err(LogicErr::InternalError("Unimplemented".to_string())),
         },
-        false => federate_delete_remote_object(id, &actor_user, object_type, origin_data, posts, users, likes).await,
+        false => {
+          federate_delete_remote_object(id, &actor_user, object_type, origin_data, posts, comments, users, likes).await
+        }
       },
       None => Err(LogicErr::InvalidData),
     },
@@ -230,6 +691,20 @@ pub async fn federate(
 
   match result {
     Ok(result) => {
+      if let Some((orbit, uri, announced)) = &announce {
+        if federate_activity_handled_in_orbit(&kind, uri, orbit, posts, comments).await {
+          federate_queue_group_announce(orbit, announced, &actor_user.user_id, jobs, user_orbits, queue).await?;
+        }
+      }
+
+      if let Some(uri) = &object_uri {
+        federate_record_mentions(uri, &mentioned_users, &actor_user, posts, comments, mentions).await?;
+      }
+
+      if let (Some(uri), Some(remote_poll)) = (&object_uri, &remote_poll) {
+        federate_store_remote_poll(uri, remote_poll, &actor_user, posts, polls).await?;
+      }
+
       let (activity_type, actor_private_key, actor_fediverse_uri) = match result {
         FederateResult::None => return Ok(()),
         FederateResult::Accept(actor) => (ActivityType::Accept, actor.0, actor.1),
@@ -271,10 +746,19 @@ pub enum FederateExtAction {
   CreatePost(Uuid),
   UpdatePost(Uuid),
   DeletePost(Uuid),
+  BoostPost(Uuid),
+  UnboostPost(Uuid),
+  CreateComment(Uuid, Uuid),
+  DeleteComment(Uuid, Uuid),
   FollowProfile,
   UnfollowProfile,
   FollowGroup(Uuid),
   UnfollowGroup(Uuid),
+  UpdateProfile,
+  MoveProfile,
+  DeleteProfile,
+  BlockProfile(Uuid),
+  UnblockProfile(Uuid),
 }
 
 #[derive(Serialize, Deserialize)]
@@ -288,6 +772,9 @@ pub enum FederateExtActor {
 pub enum FederateExtActorRef {
   None,
   Person(Uuid),
+  /// Delivers to the shared inbox of the given person's server on behalf of all of its recipients, falling back to
+  /// the person's own inbox if their server doesn't have one
+  PersonSharedInbox(Uuid),
   Group(Uuid),
 }
 
@@ -297,16 +784,26 @@ pub async fn federate_ext(
   dest_actor: &FederateExtActor,
   posts: &PostPool,
   orbits: &OrbitPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
 ) -> Result<(), LogicErr> {
   match action {
     FederateExtAction::CreatePost(post_id) => match dest_actor {
-      FederateExtActor::Person(dest_actor) => federate_ext_create_note(&post_id, actor, dest_actor, posts).await,
-      FederateExtActor::Group(dest_actor) => federate_ext_create_article(&post_id, actor, dest_actor, posts).await,
+      FederateExtActor::Person(dest_actor) => {
+        federate_ext_create_note(&post_id, actor, dest_actor, posts, mentions).await
+      }
+      FederateExtActor::Group(dest_actor) => {
+        federate_ext_create_article(&post_id, actor, dest_actor, posts, mentions).await
+      }
       FederateExtActor::None => Ok(()),
     },
     FederateExtAction::UpdatePost(post_id) => match dest_actor {
-      FederateExtActor::Person(dest_actor) => federate_ext_update_note(&post_id, actor, dest_actor, posts).await,
-      FederateExtActor::Group(dest_actor) => federate_ext_create_article(&post_id, actor, dest_actor, posts).await,
+      FederateExtActor::Person(dest_actor) => {
+        federate_ext_update_note(&post_id, actor, dest_actor, posts, mentions).await
+      }
+      FederateExtActor::Group(dest_actor) => {
+        federate_ext_update_article(&post_id, actor, dest_actor, posts, mentions).await
+      }
       FederateExtActor::None => Ok(()),
     },
     FederateExtAction::DeletePost(post_id) => match dest_actor {
@@ -314,9 +811,249 @@ pub async fn federate_ext(
       FederateExtActor::Group(dest_actor) => federate_ext_delete_article(&post_id, actor, dest_actor).await,
       FederateExtActor::None => Ok(()),
     },
+    FederateExtAction::BoostPost(post_id) => match dest_actor {
+      FederateExtActor::Person(dest_actor) => federate_ext_boost_note(&post_id, actor, dest_actor, posts).await,
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
+    FederateExtAction::UnboostPost(post_id) => match dest_actor {
+      FederateExtActor::Person(dest_actor) => federate_ext_unboost_note(&post_id, actor, dest_actor, posts).await,
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
+    FederateExtAction::CreateComment(post_id, comment_id) => match dest_actor {
+      FederateExtActor::Person(dest_actor) => {
+        federate_ext_create_comment(&post_id, &comment_id, actor, dest_actor, comments, mentions).await
+      }
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
+    FederateExtAction::DeleteComment(post_id, comment_id) => match dest_actor {
+      FederateExtActor::Person(dest_actor) => {
+        federate_ext_delete_comment(&post_id, &comment_id, actor, dest_actor).await
+      }
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
     FederateExtAction::FollowProfile => federate_ext_create_follow(actor, dest_actor).await,
     FederateExtAction::UnfollowProfile => federate_ext_remove_follow(actor, dest_actor).await,
     FederateExtAction::FollowGroup(group_id) => federate_ext_join_group(actor, &group_id, orbits).await,
     FederateExtAction::UnfollowGroup(group_id) => federate_ext_leave_group(actor, &group_id, orbits).await,
+    FederateExtAction::UpdateProfile => match dest_actor {
+      FederateExtActor::Person(dest_actor) => federate_ext_update_profile(actor, dest_actor).await,
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
+    FederateExtAction::MoveProfile => match dest_actor {
+      FederateExtActor::Person(dest_actor) => federate_ext_move_profile(actor, dest_actor).await,
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
+    FederateExtAction::DeleteProfile => match dest_actor {
+      FederateExtActor::Person(dest_actor) => federate_ext_delete_person(actor, dest_actor).await,
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
+    FederateExtAction::BlockProfile(user_block_id) => match dest_actor {
+      FederateExtActor::Person(dest_actor) => federate_ext_block(actor, dest_actor, &user_block_id).await,
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
+    FederateExtAction::UnblockProfile(user_block_id) => match dest_actor {
+      FederateExtActor::Person(dest_actor) => federate_ext_unblock(actor, dest_actor, &user_block_id).await,
+      FederateExtActor::Group(_) => Ok(()),
+      FederateExtActor::None => Ok(()),
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use serde_json::{json, Value};
+  use uuid::Uuid;
+
+  use crate::{
+    activitypub::object::Object,
+    db::signature_repository::{MockSignatureRepo, SignaturePool},
+    federation::activitypub::federate::{
+      federate_activity_visibility, federate_key_belongs_to_actor, federate_record_signature_use,
+      federate_verify_relayed_activity,
+    },
+    logic::LogicErr,
+    model::{access_type::AccessType, user::User},
+    net::http_sig::MAX_RECORDED_SIGNATURES,
+  };
+
+  fn build_remote_user() -> User {
+    User {
+      user_id: Uuid::new_v4(),
+      fediverse_id: "@a@a.test".to_string(),
+      handle: "a".to_string(),
+      fediverse_uri: "https://a.test/users/a".to_string(),
+      avatar_url: None,
+      email: None,
+      password_hash: None,
+      is_external: true,
+      url_1: None,
+      url_2: None,
+      url_3: None,
+      url_4: None,
+      url_5: None,
+      url_1_title: None,
+      url_2_title: None,
+      url_3_title: None,
+      url_4_title: None,
+      url_5_title: None,
+      intro_md: None,
+      intro_html: None,
+      private_key: "private".to_string(),
+      public_key: "public".to_string(),
+      ext_apub_followers_uri: Some("https://a.test/users/a/followers".to_string()),
+      ext_apub_following_uri: None,
+      ext_apub_inbox_uri: None,
+      ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+    }
+  }
+
+  fn build_relayed_activity(object: Value, verification_method: &str) -> Value {
+    json!({
+      "@context": ["https://www.w3.org/ns/activitystreams", "https://w3id.org/security/data-integrity/v1"],
+      "id": "https://a.test/activities/1",
+      "type": "Create",
+      "actor": "https://a.test/users/a",
+      "to": ["https://www.w3.org/ns/activitystreams#Public"],
+      "object": object,
+      "proof": {
+        "type": "DataIntegrityProof",
+        "cryptosuite": "eddsa-jcs-2022",
+        "verificationMethod": verification_method,
+        "proofPurpose": "assertionMethod",
+        "proofValue": "z1111",
+      },
+    })
+  }
+
+  #[async_std::test]
+  async fn test_verify_relayed_activity_rejects_object_from_other_actor() {
+    let object = json!({
+      "id": "https://a.test/notes/1",
+      "type": "Note",
+      "attributedTo": "https://b.test/users/b",
+    });
+    let raw_doc = build_relayed_activity(object.clone(), "https://a.test/users/a#ed25519-key");
+
+    assert_eq!(
+      federate_verify_relayed_activity(&raw_doc, serde_json::from_value(object).unwrap(), &build_remote_user()).await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_verify_relayed_activity_rejects_object_from_other_host() {
+    let object = json!({
+      "id": "https://b.test/notes/1",
+      "type": "Note",
+      "attributedTo": "https://a.test/users/a",
+    });
+    let raw_doc = build_relayed_activity(object.clone(), "https://a.test/users/a#ed25519-key");
+
+    assert_eq!(
+      federate_verify_relayed_activity(&raw_doc, serde_json::from_value(object).unwrap(), &build_remote_user()).await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_verify_relayed_activity_rejects_proof_from_other_host() {
+    let object = json!({
+      "id": "https://a.test/notes/1",
+      "type": "Note",
+      "attributedTo": "https://a.test/users/a",
+    });
+    let raw_doc = build_relayed_activity(object.clone(), "https://b.test/users/b#ed25519-key");
+
+    assert_eq!(
+      federate_verify_relayed_activity(&raw_doc, serde_json::from_value(object).unwrap(), &build_remote_user()).await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+
+  #[test]
+  fn test_activity_visibility_uses_relayed_object_audience() {
+    let activity: Object = serde_json::from_value(json!({
+      "type": "Create",
+      "to": ["https://www.w3.org/ns/activitystreams#Public"],
+    }))
+    .unwrap();
+    let object: Object = serde_json::from_value(json!({
+      "id": "https://a.test/notes/1",
+      "type": "Note",
+      "to": ["https://a.test/users/a/followers"],
+    }))
+    .unwrap();
+    let actor = build_remote_user();
+
+    assert_eq!(
+      federate_activity_visibility(&activity, &object, &actor, true),
+      Some(AccessType::FollowersOnly)
+    );
+    assert_eq!(
+      federate_activity_visibility(&activity, &object, &actor, false),
+      Some(AccessType::PublicFederated)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_key_belongs_to_actor_accepts_actor_fragment() {
+    assert!(federate_key_belongs_to_actor("https://relay.test/actor#main-key", "https://relay.test/actor").await);
+  }
+
+  #[async_std::test]
+  async fn test_key_belongs_to_actor_rejects_foreign_host_key() {
+    // Anyone can host a key that names another server's actor as its owner
+    assert!(!federate_key_belongs_to_actor("https://evil.test/keys/relay", "https://relay.test/actor").await);
+  }
+
+  #[async_std::test]
+  async fn test_record_signature_use_rejects_replays() {
+    let job_id = Uuid::new_v4();
+
+    let mut signature_repo = MockSignatureRepo::new();
+    signature_repo
+      .expect_record_signature_use()
+      .with(
+        eq("https://a.test/users/a#main-key"),
+        eq("sig"),
+        eq(job_id),
+        always(),
+        eq(MAX_RECORDED_SIGNATURES),
+      )
+      .times(1)
+      .returning(|_, _, _, _, _| Ok(true));
+    signature_repo
+      .expect_record_signature_use()
+      .withf(move |_, _, id, _, _| id != &job_id)
+      .times(1)
+      .returning(|_, _, _, _, _| Ok(false));
+
+    let signatures: SignaturePool = Arc::new(signature_repo);
+
+    assert_eq!(
+      federate_record_signature_use("https://a.test/users/a#main-key", "sig", &job_id, &signatures).await,
+      Ok(())
+    );
+    assert_eq!(
+      federate_record_signature_use("https://a.test/users/a#main-key", "sig", &Uuid::new_v4(), &signatures).await,
+      Err(LogicErr::UnauthorizedError)
+    );
   }
 }
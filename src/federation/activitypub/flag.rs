@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::{
+  federate::federate_verify_actor_signature,
+  util::{
+    activitypub_ref_to_id, activitypub_ref_to_ids, activitypub_uri_is_suspended, send_instance_activitypub_object,
+  },
+};
+use crate::{
+  activitypub::{
+    activity::ActivityProps, activity_type::ActivityType, document::ActivityPubDocument, object::Object,
+    rdf_string::RdfString, reference::Reference,
+  },
+  db::{
+    domain_block_repository::DomainBlockPool, post_repository::PostPool, report_repository::ReportPool,
+    user_repository::UserPool,
+  },
+  helpers::api::relative_to_absolute_uri,
+  logic::LogicErr,
+  model::{instance_actor::InstanceActor, queue_job::OriginDataEntry, report::Report, user::User},
+  settings::SETTINGS,
+};
+
+fn to_local_uri(uri: String) -> String {
+  match uri.starts_with(&SETTINGS.server.api_fqdn) {
+    true => uri.replace(&SETTINGS.server.api_fqdn, ""),
+    false => uri,
+  }
+}
+
+/// Invoked when a remote server reports one of our users, and optionally some of their posts, to us. Anything in the
+/// report that isn't ours is ignored.
+pub async fn federate_flag(
+  activity_object: &Object,
+  origin_data: &Option<HashMap<String, OriginDataEntry>>,
+  users: &UserPool,
+  posts: &PostPool,
+  reports: &ReportPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<(), LogicErr> {
+  let actor_uri = match activitypub_ref_to_id(&activity_object.actor) {
+    Some(uri) => uri,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  if activitypub_uri_is_suspended(&actor_uri, domain_blocks).await {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  // Reports are usually sent by a server's instance actor rather than by a person, so we don't resolve it to a user
+  federate_verify_actor_signature(&actor_uri, origin_data).await?;
+
+  let uri = match &activity_object.id {
+    Some(uri) => uri.to_owned(),
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  // We may receive the same report more than once, e.g. via both a personal and a shared inbox
+  if reports.find_optional_by_uri(&uri).await.is_some() {
+    return Ok(());
+  }
+
+  let objects = match &activity_object.activity {
+    Some(activity) => activitypub_ref_to_ids(&activity.object),
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  let mut target_user_id: Option<Uuid> = None;
+  let mut post_ids = vec![];
+
+  for object_uri in objects {
+    let object_uri = to_local_uri(object_uri);
+
+    if let Some(user) = users.fetch_by_fediverse_uri(&object_uri).await {
+      if !user.is_external {
+        target_user_id = Some(user.user_id);
+      }
+
+      continue;
+    }
+
+    if let Some(post) = posts.find_optional_by_uri(&object_uri).await {
+      if !post.is_external {
+        target_user_id = target_user_id.or(Some(post.user_id));
+        post_ids.push(post.post_id);
+      }
+    }
+  }
+
+  let target_user_id = match target_user_id {
+    Some(id) => id,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let comment = match &activity_object.content {
+    Some(RdfString::Raw(content)) => Some(content.to_owned()),
+    Some(RdfString::Props(props)) => Some(props.string.to_owned()),
+    None => None,
+  };
+
+  let report = Report {
+    report_id: Uuid::new_v4(),
+    reporter_user_id: None,
+    reporter_uri: Some(actor_uri),
+    target_user_id,
+    post_ids,
+    comment,
+    uri: Some(uri),
+    is_external: true,
+    forwarded: false,
+    created_at: Utc::now(),
+    updated_at: Utc::now(),
+  };
+
+  reports.create_report(&report).await
+}
+
+/// Forwards one of our users' reports on a remote user to the remote user's server. The report is sent from the
+/// instance actor so that the reporter stays anonymous to the remote server.
+pub async fn federate_ext_report(report: &Report, dest_actor: &User, post_uris: Vec<String>) -> Result<(), LogicErr> {
+  let mut objects = vec![Reference::Remote(dest_actor.fediverse_uri.clone())];
+  objects.extend(post_uris.into_iter().map(Reference::Remote));
+
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Flag.to_string()))
+    .id(Some(format!(
+      "{}/reports/{}",
+      SETTINGS.server.api_fqdn, report.report_id
+    )))
+    .actor(Some(Reference::Remote(relative_to_absolute_uri(
+      &InstanceActor::fediverse_uri(),
+    ))))
+    .content(report.comment.clone().map(RdfString::Raw))
+    .activity(Some(
+      ActivityProps::builder().object(Some(Reference::Mixed(objects))).build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match dest_actor
+    .ext_apub_shared_inbox_uri
+    .as_ref()
+    .or(dest_actor.ext_apub_inbox_uri.as_ref())
+  {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_instance_activitypub_object(response_uri, doc).await
+}
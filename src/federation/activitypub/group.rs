@@ -1,12 +1,37 @@
+use uuid::Uuid;
+
 use crate::{
-  activitypub::object::Object,
-  db::{orbit_repository::OrbitPool, user_orbit_repository::UserOrbitPool},
+  activitypub::{
+    activity::ActivityProps,
+    activity_convertible::ActivityConvertible,
+    activity_type::ActivityType,
+    document::ActivityPubDocument,
+    object::{Object, ObjectType},
+    reference::Reference,
+  },
+  db::{
+    comment_repository::CommentPool, job_repository::JobPool, mention_repository::MentionPool,
+    orbit_repository::OrbitPool, post_repository::PostPool, user_orbit_repository::UserOrbitPool,
+  },
+  helpers::api::{map_db_err, relative_to_absolute_uri},
   logic::LogicErr,
-  model::user::User,
+  model::{
+    job::{JobStatus, NewJob},
+    orbit::Orbit,
+    post::Post,
+    queue_job::{QueueJob, QueueJobType},
+    user::User,
+  },
   settings::SETTINGS,
+  work_queue::queue::Queue,
 };
 
-use super::util::FederateResult;
+use super::{
+  comment::federate_find_reply_post,
+  federate::FederateExtAction,
+  mention::activitypub_tag_mentions,
+  util::{activitypub_ref_to_id, send_activitypub_object, FederateResult},
+};
 
 pub async fn federate_create_member(
   activity_object: Object,
@@ -75,3 +100,712 @@ pub async fn federate_remove_member(
     target_orbit.private_key.to_string(),
   )))
 }
+
+async fn federate_local_orbit(orbit_id: &Option<Uuid>, orbits: &OrbitPool) -> Option<Orbit> {
+  let orbit_id = match orbit_id {
+    Some(id) => id,
+    None => return None,
+  };
+
+  match orbits.fetch_orbit(orbit_id).await {
+    Ok(Some(orbit)) if !orbit.is_external => Some(orbit),
+    _ => None,
+  }
+}
+
+/// Finds the post behind a stored post or comment
+async fn federate_stored_object_post(uri: &str, posts: &PostPool, comments: &CommentPool) -> Option<Post> {
+  match posts.find_optional_by_uri(uri).await {
+    Some(post) => Some(post),
+    None => {
+      let comment = comments.find_optional_by_uri(uri).await?;
+      posts.find_optional_by_id(&comment.post_id).await
+    }
+  }
+}
+
+/// Determines the URI of the post or comment that an incoming post, comment, edit or deletion is about
+pub(super) fn federate_activity_object_uri(
+  kind: &ActivityType,
+  object: &Object,
+  target: &Option<String>,
+) -> Option<String> {
+  match kind {
+    ActivityType::Create => object.id.to_owned(),
+    _ => target.to_owned().or_else(|| object.id.to_owned()),
+  }
+}
+
+async fn federate_activity_orbit(
+  kind: &ActivityType,
+  object: &Object,
+  target: &Option<String>,
+  posts: &PostPool,
+  comments: &CommentPool,
+  orbits: &OrbitPool,
+) -> Option<Orbit> {
+  match kind {
+    ActivityType::Create => {
+      // New posts name the orbit they're made in, whereas comments belong to the orbit of the post they reply to
+      if let Some(audience) = activitypub_ref_to_id(&object.audience) {
+        let uri = match audience.starts_with(&SETTINGS.server.api_fqdn) {
+          true => audience.replace(&SETTINGS.server.api_fqdn, ""),
+          false => audience,
+        };
+
+        return match orbits.fetch_by_fediverse_uri(&uri).await {
+          Some(orbit) if !orbit.is_external => Some(orbit),
+          _ => None,
+        };
+      }
+
+      let post = federate_find_reply_post(object, posts, comments).await?;
+      federate_local_orbit(&post.orbit_id, orbits).await
+    }
+    ActivityType::Update | ActivityType::Delete => {
+      let uri = federate_activity_object_uri(kind, object, target)?;
+      let post = federate_stored_object_post(&uri, posts, comments).await?;
+      federate_local_orbit(&post.orbit_id, orbits).await
+    }
+    _ => None,
+  }
+}
+
+/// Finds the local orbit that an incoming post, comment, edit or deletion belongs to, if any. This needs to happen
+/// before the activity is handled, as a deletion removes the records we'd otherwise find the orbit from. Only the
+/// orbit's members can have the orbit announce what they do in it.
+pub(super) async fn federate_activity_local_orbit(
+  kind: &ActivityType,
+  object: &Object,
+  target: &Option<String>,
+  actor: &User,
+  posts: &PostPool,
+  comments: &CommentPool,
+  orbits: &OrbitPool,
+  user_orbits: &UserOrbitPool,
+) -> Option<Orbit> {
+  let orbit = federate_activity_orbit(kind, object, target, posts, comments, orbits).await?;
+
+  match user_orbits.user_is_member(&actor.user_id, &orbit.orbit_id).await {
+    Ok(true) => Some(orbit),
+    _ => None,
+  }
+}
+
+/// Checks that handling an activity stored, updated or deleted a post or comment in the given orbit, as otherwise
+/// there's nothing for the orbit to announce
+pub(super) async fn federate_activity_handled_in_orbit(
+  kind: &ActivityType,
+  uri: &str,
+  orbit: &Orbit,
+  posts: &PostPool,
+  comments: &CommentPool,
+) -> bool {
+  let post = federate_stored_object_post(uri, posts, comments).await;
+
+  match kind {
+    ActivityType::Delete => post.is_none(),
+    _ => post.is_some_and(|post| post.orbit_id == Some(orbit.orbit_id)),
+  }
+}
+
+/// Rebuilds an incoming activity around its object as we've stored it, so that an orbit doesn't announce anything we've
+/// stripped from the object, such as attachments from servers we reject media from
+pub(super) fn federate_group_announced_activity(activity: &Object, object: &Object) -> Object {
+  let mut announced = activity.clone();
+
+  if let Some(props) = announced.activity.as_mut() {
+    props.object = Some(Reference::Embedded(Box::new(object.clone())));
+  }
+
+  announced
+}
+
+/// Builds the activity for something one of our users did in an orbit, so that the orbit can announce it to its
+/// members. Only posts, comments, edits and deletions are announced.
+pub async fn federate_ext_group_activity(
+  action: &FederateExtAction,
+  actor: &User,
+  posts: &PostPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
+) -> Result<Option<Object>, LogicErr> {
+  let actor_uri = relative_to_absolute_uri(&actor.fediverse_uri);
+
+  let (kind, obj) = match action {
+    FederateExtAction::CreatePost(post_id) | FederateExtAction::UpdatePost(post_id) => {
+      let post = match posts.fetch_post(post_id, &Some(actor.user_id)).await? {
+        Some(post) => post,
+        None => return Err(LogicErr::MissingRecord),
+      };
+
+      let mut obj = match post.to_object(&actor.fediverse_uri) {
+        Some(obj) => obj,
+        None => return Err(LogicErr::MissingRecord),
+      };
+
+      activitypub_tag_mentions(&mut obj, &mentions.fetch_post_mentioned_users(post_id).await?);
+
+      let kind = match action {
+        FederateExtAction::CreatePost(_) => ActivityType::Create,
+        _ => ActivityType::Update,
+      };
+
+      (kind, obj)
+    }
+    FederateExtAction::CreateComment(post_id, comment_id) => {
+      let comment = match comments.fetch_comment(post_id, comment_id, &Some(actor.user_id)).await {
+        Some(comment) => comment,
+        None => return Err(LogicErr::MissingRecord),
+      };
+
+      let mut obj = match comment.to_object(&actor_uri) {
+        Some(obj) => obj,
+        None => return Err(LogicErr::MissingRecord),
+      };
+
+      activitypub_tag_mentions(&mut obj, &mentions.fetch_comment_mentioned_users(comment_id).await?);
+
+      (ActivityType::Create, obj)
+    }
+    FederateExtAction::DeletePost(post_id) | FederateExtAction::DeleteComment(post_id, _) => {
+      // NOTE: By this point, the record is deleted in our DB, so we have to build the URI from scratch here
+      let uri = match action {
+        FederateExtAction::DeleteComment(_, comment_id) => {
+          format!("{}/feed/{}/comments/{}", SETTINGS.server.api_fqdn, post_id, comment_id)
+        }
+        _ => format!("{}/feed/{}", SETTINGS.server.api_fqdn, post_id),
+      };
+
+      let obj = Object::builder()
+        .kind(Some(ObjectType::Tombstone.to_string()))
+        .id(Some(uri.clone()))
+        .url(Some(Reference::Remote(uri)))
+        .build();
+
+      (ActivityType::Delete, obj)
+    }
+    _ => return Ok(None),
+  };
+
+  Ok(Some(
+    Object::builder()
+      .kind(Some(kind.to_string()))
+      .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+      .actor(Some(Reference::Remote(actor_uri)))
+      .to(obj.to.clone())
+      .cc(obj.cc.clone())
+      .activity(Some(
+        ActivityProps::builder()
+          .object(Some(Reference::Embedded(Box::new(obj))))
+          .build(),
+      ))
+      .build(),
+  ))
+}
+
+/// Queues an orbit's announcement of an activity to each of its remote members, in the way Lemmy communities share
+/// their activity with their followers (FEP-1b12). Members on the same server are delivered to with a single request
+/// to that server's shared inbox.
+pub async fn federate_queue_group_announce(
+  orbit: &Orbit,
+  activity: &Object,
+  created_by_id: &Uuid,
+  jobs: &JobPool,
+  user_orbits: &UserOrbitPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let targets = user_orbits
+    .fetch_orbit_member_delivery_targets(&orbit.orbit_id)
+    .await
+    .unwrap_or_default();
+
+  if targets.is_empty() {
+    return Ok(());
+  }
+
+  let data = serde_json::to_value(activity).map_err(|err| LogicErr::InternalError(err.to_string()))?;
+
+  for target in targets {
+    let job_id = jobs
+      .create(NewJob {
+        created_by_id: Some(*created_by_id),
+        status: JobStatus::NotStarted,
+        record_id: Some(orbit.orbit_id),
+        associated_record_id: Some(target),
+      })
+      .await
+      .map_err(map_db_err)?;
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::FederateGroupAnnounce)
+      .data(data.clone())
+      .build();
+
+    queue.send_job(job).await?;
+  }
+
+  Ok(())
+}
+
+/// Sends an orbit's Announce of an activity that happened in it to one of its members, signed as the orbit
+pub async fn federate_ext_group_announce(orbit: &Orbit, activity: Object, dest_actor: &User) -> Result<(), LogicErr> {
+  let orbit_uri = relative_to_absolute_uri(&orbit.fediverse_uri);
+
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Announce.to_string()))
+    .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+    .actor(Some(Reference::Remote(orbit_uri)))
+    .to(Some(Reference::Remote(
+      "https://www.w3.org/ns/activitystreams#Public".to_string(),
+    )))
+    .cc(Some(Reference::Remote(format!(
+      "{}/orbit/{}/members",
+      SETTINGS.server.api_fqdn, orbit.orbit_id
+    ))))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Embedded(Box::new(activity))))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &orbit.fediverse_uri, &orbit.private_key).await
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use serde_json::json;
+  use uuid::Uuid;
+
+  use crate::{
+    activitypub::{activity_type::ActivityType, object::Object, reference::Reference},
+    db::{
+      comment_repository::{CommentPool, MockCommentRepo},
+      job_repository::{JobPool, MockJobRepo},
+      orbit_repository::{MockOrbitRepo, OrbitPool},
+      post_repository::{MockPostRepo, PostPool},
+      user_orbit_repository::{MockUserOrbitRepo, UserOrbitPool},
+    },
+    federation::activitypub::group::{
+      federate_activity_handled_in_orbit, federate_activity_local_orbit, federate_group_announced_activity,
+      federate_queue_group_announce,
+    },
+    model::{access_type::AccessType, comment::Comment, orbit::Orbit, post::Post, queue_job::QueueJobType, user::User},
+    settings::SETTINGS,
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  fn build_orbit(is_external: bool) -> Orbit {
+    let orbit_id = Uuid::new_v4();
+
+    Orbit {
+      orbit_id,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      shortcode: "orbit".to_string(),
+      name: "Orbit".to_string(),
+      description_md: "".to_string(),
+      description_html: "".to_string(),
+      avatar_uri: None,
+      banner_uri: None,
+      uri: format!("/orbits/{}", orbit_id),
+      fediverse_uri: format!("/orbit/{}", orbit_id),
+      private_key: "private".to_string(),
+      public_key: "public".to_string(),
+      is_external,
+      ext_apub_inbox_uri: None,
+      ext_apub_outbox_uri: None,
+      ext_apub_followers_uri: None,
+    }
+  }
+
+  fn build_post(orbit_id: Option<Uuid>) -> Post {
+    let post_id = Uuid::new_v4();
+
+    Post {
+      post_id,
+      user_id: Uuid::new_v4(),
+      orbit_id,
+      uri: format!("/feed/{}", post_id),
+      is_external: false,
+      title: None,
+      content_md: "hello".to_string(),
+      content_html: "<p>hello</p>".to_string(),
+      visibility: AccessType::PublicFederated,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      deletion_scheduled_at: None,
+    }
+  }
+
+  fn build_note(audience: Option<String>) -> Object {
+    serde_json::from_value(json!({
+      "id": "https://a.test/notes/1",
+      "type": "Note",
+      "audience": audience,
+      "attachment": [{ "type": "Image", "url": "https://a.test/media/1.png" }],
+    }))
+    .unwrap()
+  }
+
+  fn build_user_orbit_repo(actor: &User, orbit: &Orbit, is_member: bool) -> MockUserOrbitRepo {
+    let mut user_orbit_repo = MockUserOrbitRepo::new();
+    user_orbit_repo
+      .expect_user_is_member()
+      .with(eq(actor.user_id), eq(orbit.orbit_id))
+      .times(1)
+      .return_const(Ok(is_member));
+
+    user_orbit_repo
+  }
+
+  #[async_std::test]
+  async fn test_activity_local_orbit_finds_audience_of_new_post() {
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+    let orbit = build_orbit(false);
+    let audience = format!("{}{}", SETTINGS.server.api_fqdn, orbit.fediverse_uri);
+
+    let mut orbit_repo = MockOrbitRepo::new();
+    orbit_repo
+      .expect_fetch_by_fediverse_uri()
+      .with(eq(orbit.fediverse_uri.clone()))
+      .times(1)
+      .return_const(Some(orbit.clone()));
+
+    let posts: PostPool = Arc::new(MockPostRepo::new());
+    let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let orbits: OrbitPool = Arc::new(orbit_repo);
+    let user_orbits: UserOrbitPool = Arc::new(build_user_orbit_repo(&actor, &orbit, true));
+
+    assert_eq!(
+      federate_activity_local_orbit(
+        &ActivityType::Create,
+        &build_note(Some(audience)),
+        &None,
+        &actor,
+        &posts,
+        &comments,
+        &orbits,
+        &user_orbits
+      )
+      .await,
+      Some(orbit)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_activity_local_orbit_skips_non_member() {
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+    let orbit = build_orbit(false);
+    let audience = format!("{}{}", SETTINGS.server.api_fqdn, orbit.fediverse_uri);
+
+    let mut orbit_repo = MockOrbitRepo::new();
+    orbit_repo
+      .expect_fetch_by_fediverse_uri()
+      .times(1)
+      .return_const(Some(orbit.clone()));
+
+    let posts: PostPool = Arc::new(MockPostRepo::new());
+    let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let orbits: OrbitPool = Arc::new(orbit_repo);
+    let user_orbits: UserOrbitPool = Arc::new(build_user_orbit_repo(&actor, &orbit, false));
+
+    assert_eq!(
+      federate_activity_local_orbit(
+        &ActivityType::Create,
+        &build_note(Some(audience)),
+        &None,
+        &actor,
+        &posts,
+        &comments,
+        &orbits,
+        &user_orbits
+      )
+      .await,
+      None
+    );
+  }
+
+  #[async_std::test]
+  async fn test_activity_local_orbit_skips_remote_orbit() {
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+    let orbit = build_orbit(true);
+
+    let mut orbit_repo = MockOrbitRepo::new();
+    orbit_repo
+      .expect_fetch_by_fediverse_uri()
+      .times(1)
+      .return_const(Some(orbit));
+
+    let posts: PostPool = Arc::new(MockPostRepo::new());
+    let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let orbits: OrbitPool = Arc::new(orbit_repo);
+    let user_orbits: UserOrbitPool = Arc::new(MockUserOrbitRepo::new());
+
+    assert_eq!(
+      federate_activity_local_orbit(
+        &ActivityType::Create,
+        &build_note(Some("https://b.test/c/orbit".to_string())),
+        &None,
+        &actor,
+        &posts,
+        &comments,
+        &orbits,
+        &user_orbits
+      )
+      .await,
+      None
+    );
+  }
+
+  #[async_std::test]
+  async fn test_activity_local_orbit_finds_orbit_of_deleted_comment() {
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+    let orbit = build_orbit(false);
+    let orbit_id = orbit.orbit_id;
+    let post_id = Uuid::new_v4();
+    let comment_uri = "https://a.test/comments/1".to_string();
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_uri()
+      .with(eq(comment_uri.clone()))
+      .times(1)
+      .returning(|_| None);
+    post_repo
+      .expect_find_optional_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .returning(move |_| Some(build_post(Some(orbit_id))));
+
+    let mut comment_repo = MockCommentRepo::new();
+    comment_repo
+      .expect_find_optional_by_uri()
+      .with(eq(comment_uri.clone()))
+      .times(1)
+      .return_const(Some(Comment {
+        comment_id: Uuid::new_v4(),
+        user_id: Uuid::new_v4(),
+        post_id,
+        parent_comment_id: None,
+        content_md: "hello".to_string(),
+        content_html: "<p>hello</p>".to_string(),
+        uri: comment_uri.clone(),
+        is_external: true,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+      }));
+
+    let mut orbit_repo = MockOrbitRepo::new();
+    orbit_repo
+      .expect_fetch_orbit()
+      .with(eq(orbit_id))
+      .times(1)
+      .return_const(Ok(Some(orbit.clone())));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let comments: CommentPool = Arc::new(comment_repo);
+    let orbits: OrbitPool = Arc::new(orbit_repo);
+    let user_orbits: UserOrbitPool = Arc::new(build_user_orbit_repo(&actor, &orbit, true));
+
+    assert_eq!(
+      federate_activity_local_orbit(
+        &ActivityType::Delete,
+        &Object::builder().build(),
+        &Some(comment_uri),
+        &actor,
+        &posts,
+        &comments,
+        &orbits,
+        &user_orbits
+      )
+      .await,
+      Some(orbit)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_activity_local_orbit_ignores_other_activities() {
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+    let posts: PostPool = Arc::new(MockPostRepo::new());
+    let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let orbits: OrbitPool = Arc::new(MockOrbitRepo::new());
+    let user_orbits: UserOrbitPool = Arc::new(MockUserOrbitRepo::new());
+
+    assert_eq!(
+      federate_activity_local_orbit(
+        &ActivityType::Like,
+        &build_note(None),
+        &None,
+        &actor,
+        &posts,
+        &comments,
+        &orbits,
+        &user_orbits
+      )
+      .await,
+      None
+    );
+  }
+
+  #[async_std::test]
+  async fn test_activity_handled_in_orbit_finds_stored_post() {
+    let orbit = build_orbit(false);
+    let orbit_id = orbit.orbit_id;
+    let uri = "https://a.test/notes/1".to_string();
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_uri()
+      .with(eq(uri.clone()))
+      .times(1)
+      .returning(move |_| Some(build_post(Some(orbit_id))));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let comments: CommentPool = Arc::new(MockCommentRepo::new());
+
+    assert!(federate_activity_handled_in_orbit(&ActivityType::Create, &uri, &orbit, &posts, &comments).await);
+  }
+
+  #[async_std::test]
+  async fn test_activity_handled_in_orbit_skips_post_outside_orbit() {
+    let orbit = build_orbit(false);
+    let uri = "https://a.test/notes/1".to_string();
+
+    // Notes naming the orbit as their audience aren't necessarily stored in it
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_uri()
+      .with(eq(uri.clone()))
+      .times(1)
+      .returning(|_| Some(build_post(None)));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let comments: CommentPool = Arc::new(MockCommentRepo::new());
+
+    assert!(!federate_activity_handled_in_orbit(&ActivityType::Create, &uri, &orbit, &posts, &comments).await);
+  }
+
+  #[async_std::test]
+  async fn test_activity_handled_in_orbit_skips_noop_delete() {
+    let orbit = build_orbit(false);
+    let orbit_id = orbit.orbit_id;
+    let uri = "https://a.test/notes/1".to_string();
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_uri()
+      .with(eq(uri.clone()))
+      .times(1)
+      .returning(move |_| Some(build_post(Some(orbit_id))));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let comments: CommentPool = Arc::new(MockCommentRepo::new());
+
+    assert!(!federate_activity_handled_in_orbit(&ActivityType::Delete, &uri, &orbit, &posts, &comments).await);
+  }
+
+  #[async_std::test]
+  async fn test_activity_handled_in_orbit_finds_deleted_comment() {
+    let orbit = build_orbit(false);
+    let uri = "https://a.test/comments/1".to_string();
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_uri()
+      .with(eq(uri.clone()))
+      .times(1)
+      .returning(|_| None);
+
+    let mut comment_repo = MockCommentRepo::new();
+    comment_repo
+      .expect_find_optional_by_uri()
+      .with(eq(uri.clone()))
+      .times(1)
+      .return_const(None);
+
+    let posts: PostPool = Arc::new(post_repo);
+    let comments: CommentPool = Arc::new(comment_repo);
+
+    assert!(federate_activity_handled_in_orbit(&ActivityType::Delete, &uri, &orbit, &posts, &comments).await);
+  }
+
+  #[test]
+  fn test_group_announced_activity_uses_stored_object() {
+    let activity: Object = serde_json::from_value(json!({
+      "id": "https://a.test/activities/1",
+      "type": "Create",
+      "actor": "https://a.test/users/a",
+      "object": build_note(None),
+    }))
+    .unwrap();
+
+    let mut object = build_note(None);
+    object.attachment = None;
+
+    let announced = federate_group_announced_activity(&activity, &object);
+
+    assert_eq!(announced.id, activity.id);
+    assert_eq!(
+      announced.activity.and_then(|a| a.object),
+      Some(Reference::Embedded(Box::new(object)))
+    );
+  }
+
+  #[async_std::test]
+  async fn test_queue_group_announce_queues_job_per_target() {
+    let orbit = build_orbit(false);
+    let orbit_id = orbit.orbit_id;
+    let user_id = Uuid::new_v4();
+    let targets = vec![Uuid::new_v4(), Uuid::new_v4()];
+    let activity = build_note(None);
+    let data = serde_json::to_value(&activity).unwrap();
+
+    let mut user_orbit_repo = MockUserOrbitRepo::new();
+    user_orbit_repo
+      .expect_fetch_orbit_member_delivery_targets()
+      .with(eq(orbit_id))
+      .times(1)
+      .return_const(Some(targets.clone()));
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_create()
+      .withf(move |job| {
+        job.created_by_id == Some(user_id)
+          && job.record_id == Some(orbit_id)
+          && job.associated_record_id.is_some_and(|target| targets.contains(&target))
+      })
+      .times(2)
+      .returning(|_| Ok(Uuid::new_v4()));
+
+    let mut queue_be = MockQueueBackend::new();
+    queue_be
+      .expect_send_job()
+      .withf(move |job| job.job_type == QueueJobType::FederateGroupAnnounce && job.data == Some(data.clone()))
+      .times(2)
+      .return_const(Ok(()));
+
+    let jobs: JobPool = Arc::new(job_repo);
+    let user_orbits: UserOrbitPool = Arc::new(user_orbit_repo);
+    let queue = Queue::new_inner(Box::new(queue_be));
+
+    assert_eq!(
+      federate_queue_group_announce(&orbit, &activity, &user_id, &jobs, &user_orbits, &queue).await,
+      Ok(())
+    );
+  }
+}
@@ -0,0 +1,33 @@
+use crate::{
+  activitypub::{
+    object::{Object, ObjectType},
+    reference::Reference,
+  },
+  logic::hashtag::normalize_hashtag,
+};
+
+/// Finds the hashtags a post from another server has been tagged with, normalized the same way as our own
+pub(super) fn activitypub_object_hashtags(obj: &Object) -> Vec<String> {
+  let tags = match &obj.tag {
+    Some(Reference::Mixed(tags)) => tags.iter().collect(),
+    Some(tag) => vec![tag],
+    None => vec![],
+  };
+
+  let mut hashtags: Vec<String> = vec![];
+
+  for tag in tags {
+    let name = match tag {
+      Reference::Embedded(tag) if ObjectType::from_str_opt(&tag.kind) == Some(ObjectType::Hashtag) => &tag.name,
+      _ => continue,
+    };
+
+    if let Some(name) = name.as_deref().and_then(normalize_hashtag) {
+      if !hashtags.contains(&name) {
+        hashtags.push(name);
+      }
+    }
+  }
+
+  hashtags
+}
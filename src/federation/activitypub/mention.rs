@@ -0,0 +1,164 @@
+use crate::{
+  activitypub::{
+    activity_type::ActivityType,
+    link::LinkProps,
+    object::{Object, ObjectType},
+    reference::Reference,
+  },
+  db::{
+    comment_repository::CommentPool, mention_repository::MentionPool, post_repository::PostPool,
+    user_repository::UserPool,
+  },
+  helpers::{api::relative_to_absolute_uri, types::RELATIVE_API_ROOT_FQDN},
+  logic::LogicErr,
+  model::user::User,
+  settings::SETTINGS,
+};
+
+use super::util::activitypub_ref_to_id;
+
+fn activitypub_ref_to_list(obj_ref: Option<Reference<Object>>) -> Vec<Reference<Object>> {
+  match obj_ref {
+    Some(Reference::Mixed(items)) => items,
+    Some(item) => vec![item],
+    None => vec![],
+  }
+}
+
+/// Tags an outgoing post or comment with the users it mentions, and copies those users in so that their servers know
+/// to show it to them
+pub(super) fn activitypub_tag_mentions(obj: &mut Object, mentioned: &[User]) {
+  if mentioned.is_empty() {
+    return;
+  }
+
+  let mut tags = activitypub_ref_to_list(obj.tag.take());
+  let mut cc = activitypub_ref_to_list(obj.cc.take());
+
+  for user in mentioned {
+    let actor_uri = relative_to_absolute_uri(&user.fediverse_uri);
+
+    let name = match user.is_external {
+      true => user.fediverse_id.to_owned(),
+      false => format!("@{}@{}", user.handle, *RELATIVE_API_ROOT_FQDN),
+    };
+
+    tags.push(Reference::Embedded(Box::new(
+      Object::builder()
+        .kind(Some(ObjectType::Mention.to_string()))
+        .name(Some(name))
+        .link(Some(
+          LinkProps::builder()
+            .href(Some(Reference::Remote(actor_uri.clone())))
+            .build(),
+        ))
+        .build(),
+    )));
+
+    if !cc.contains(&Reference::Remote(actor_uri.clone())) {
+      cc.push(Reference::Remote(actor_uri));
+    }
+  }
+
+  obj.tag = Some(Reference::Mixed(tags));
+  obj.cc = Some(Reference::Mixed(cc));
+}
+
+/// Finds which of our users are mentioned by a post or comment we're about to store from another server. Nothing is
+/// returned for objects we already have, so that redelivered posts don't notify anyone twice.
+pub(super) async fn federate_new_object_mentions(
+  kind: &ActivityType,
+  object: &Object,
+  users: &UserPool,
+  posts: &PostPool,
+  comments: &CommentPool,
+) -> Vec<User> {
+  if kind != &ActivityType::Create {
+    return vec![];
+  }
+
+  match ObjectType::from_str_opt(&object.kind) {
+    Some(ObjectType::Note) | Some(ObjectType::Article) | Some(ObjectType::Page) | Some(ObjectType::Question) => {}
+    _ => return vec![],
+  }
+
+  let uri = match &object.id {
+    Some(uri) => uri,
+    None => return vec![],
+  };
+
+  if posts.find_optional_by_uri(uri).await.is_some() || comments.find_optional_by_uri(uri).await.is_some() {
+    return vec![];
+  }
+
+  let tags = match &object.tag {
+    Some(Reference::Mixed(tags)) => tags.iter().collect(),
+    Some(tag) => vec![tag],
+    None => vec![],
+  };
+
+  let mut mentioned: Vec<User> = vec![];
+
+  for tag in tags {
+    let tag = match tag {
+      Reference::Embedded(tag) if ObjectType::from_str_opt(&tag.kind) == Some(ObjectType::Mention) => tag,
+      _ => continue,
+    };
+
+    let href = match tag.link.as_ref().and_then(|link| activitypub_ref_to_id(&link.href)) {
+      Some(href) => href,
+      None => continue,
+    };
+
+    // Only our own users need to know they've been mentioned
+    if !href.starts_with(&SETTINGS.server.api_fqdn) {
+      continue;
+    }
+
+    if let Some(user) = users
+      .fetch_by_fediverse_uri(&href.replace(&SETTINGS.server.api_fqdn, ""))
+      .await
+    {
+      if !user.is_external && !mentioned.iter().any(|u| u.user_id == user.user_id) {
+        mentioned.push(user);
+      }
+    }
+  }
+
+  mentioned
+}
+
+/// Records our users being mentioned in a post or comment we've just stored from another server
+pub(super) async fn federate_record_mentions(
+  object_uri: &str,
+  mentioned: &[User],
+  author: &User,
+  posts: &PostPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
+  if mentioned.is_empty() {
+    return Ok(());
+  }
+
+  let (post_id, comment_id) = match posts.find_optional_by_uri(object_uri).await {
+    Some(post) => (Some(post.post_id), None),
+    None => match comments.find_optional_by_uri(object_uri).await {
+      Some(comment) => (None, Some(comment.comment_id)),
+      // Posts mentioning our users are always kept, so this only happens when the object was turned away, e.g. as it's
+      // a reply to a post our users can't see
+      None => {
+        log::warn!("Mentions in {} couldn't be recorded as it wasn't stored", object_uri);
+        return Ok(());
+      }
+    },
+  };
+
+  for user in mentioned {
+    mentions
+      .create_mention(&user.user_id, &author.user_id, &post_id, &comment_id)
+      .await?;
+  }
+
+  Ok(())
+}
@@ -1,9 +1,27 @@
 pub mod actor;
 mod article;
+mod comment;
 pub mod federate;
+mod flag;
 mod group;
+mod hashtag;
+mod mention;
 mod note;
 mod object;
+mod outbox;
 mod person;
+mod question;
+mod relay;
+mod undo;
 mod util;
 pub use federate::*;
+pub use flag::federate_ext_report;
+pub use group::{federate_ext_group_activity, federate_ext_group_announce, federate_queue_group_announce};
+pub use note::build_ext_boost_activity;
+pub use outbox::{federate_backfill_group_outbox, federate_backfill_outbox};
+pub use person::federate_move_local_followers;
+pub use question::federate_ext_poll_votes;
+pub use relay::{
+  federate_ext_follow_relay, federate_ext_relay_post, federate_ext_unfollow_relay, federate_relay_inbox,
+};
+pub use util::{activitypub_uri_is_suspended, set_instance_private_key};
@@ -1,6 +1,11 @@
+use chrono::Utc;
 use uuid::Uuid;
 
-use super::util::{activitypub_ref_to_uri_opt, deref_activitypub_ref_list, send_activitypub_object, FederateResult};
+use super::{
+  hashtag::activitypub_object_hashtags,
+  mention::activitypub_tag_mentions,
+  util::{activitypub_ref_to_uri_opt, deref_activitypub_ref_list, send_activitypub_object, FederateResult},
+};
 use crate::{
   activitypub::{
     activity::ActivityProps,
@@ -12,10 +17,11 @@ use crate::{
     reference::Reference,
   },
   db::{
-    follow_repository::FollowPool, job_repository::JobPool, like_repository::LikePool,
-    post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    follow_repository::FollowPool, hashtag_repository::HashtagPool, job_repository::JobPool, like_repository::LikePool,
+    mention_repository::MentionPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    post_revision_repository::PostRevisionPool,
   },
-  helpers::api::map_db_err,
+  helpers::api::{map_db_err, relative_to_absolute_uri},
   logic::LogicErr,
   model::{
     access_type::AccessType,
@@ -37,6 +43,7 @@ pub async fn federate_create_note(
   posts: &PostPool,
   jobs: &JobPool,
   post_attachments: &PostAttachmentPool,
+  hashtags: &HashtagPool,
   queue: &Queue,
 ) -> Result<FederateResult, LogicErr> {
   let followers = follows.fetch_user_followers(&actor.user_id).await.unwrap_or_default();
@@ -45,11 +52,37 @@ pub async fn federate_create_note(
     return Ok(FederateResult::None);
   }
 
+  federate_store_note(
+    activity_object,
+    actor,
+    access,
+    posts,
+    jobs,
+    post_attachments,
+    hashtags,
+    queue,
+  )
+  .await
+}
+
+/// Stores a remote note as a post and queues the creation of its feed events, regardless of who follows its author.
+pub(super) async fn federate_store_note(
+  activity_object: Object,
+  actor: &User,
+  access: AccessType,
+  posts: &PostPool,
+  jobs: &JobPool,
+  post_attachments: &PostAttachmentPool,
+  hashtags: &HashtagPool,
+  queue: &Queue,
+) -> Result<FederateResult, LogicErr> {
   let uri = match activity_object.id {
     Some(uri) => uri,
     None => return Err(LogicErr::InvalidData),
   };
 
+  let post_hashtags = activitypub_object_hashtags(&activity_object);
+
   let attachments: Vec<Object> = match deref_activitypub_ref_list(&activity_object.attachment).await {
     Some(obj) => obj
       .into_iter()
@@ -135,6 +168,10 @@ pub async fn federate_create_note(
 
   posts.create_post_from(post).await?;
 
+  if !post_hashtags.is_empty() {
+    hashtags.set_post_hashtags(&post_id, &post_hashtags).await?;
+  }
+
   for attachment_obj in attachments {
     let image_content_type = match attachment_obj.media_type {
       Some(val) => val,
@@ -200,12 +237,16 @@ pub async fn federate_update_note(
   actor: &User,
   access: AccessType,
   posts: &PostPool,
+  hashtags: &HashtagPool,
+  post_revisions: &PostRevisionPool,
 ) -> Result<FederateResult, LogicErr> {
   let uri = match activity_object.id {
     Some(uri) => uri,
     None => return Err(LogicErr::InvalidData),
   };
 
+  let post_hashtags = activitypub_object_hashtags(&activity_object);
+
   let mut post = match posts.find_optional_by_uri(&uri).await {
     Some(post) => post,
     None => return Err(LogicErr::MissingRecord),
@@ -262,13 +303,19 @@ pub async fn federate_update_note(
     None => return Err(LogicErr::InvalidData),
   };
 
+  // Updates can also just refresh things like a poll's vote counts, which we don't keep a revision for
+  if post.content_html != content_html || post.content_md != content_md {
+    post_revisions.create_revision(&post).await?;
+  }
+
   post.content_html = content_html;
   post.content_md = content_md;
   post.visibility = access;
   post.created_at = created_at;
-  post.updated_at = created_at;
+  post.updated_at = activity_object.updated.unwrap_or_else(Utc::now);
 
   posts.update_post_content(&post).await?;
+  hashtags.set_post_hashtags(&post.post_id, &post_hashtags).await?;
 
   Ok(FederateResult::None)
 }
@@ -278,6 +325,23 @@ pub async fn federate_ext_create_note(
   actor: &User,
   dest_actor: &User,
   posts: &PostPool,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  federate_ext_create_note_to_inbox(post_id, actor, response_uri, posts, mentions).await
+}
+
+/// Sends a Create for one of our posts to the given inbox, which may belong to an actor we don't track as a user
+pub(super) async fn federate_ext_create_note_to_inbox(
+  post_id: &Uuid,
+  actor: &User,
+  inbox_uri: &str,
+  posts: &PostPool,
+  mentions: &MentionPool,
 ) -> Result<(), LogicErr> {
   let post = match posts.fetch_post(post_id, &Some(actor.user_id)).await {
     Ok(post) => match post {
@@ -290,11 +354,13 @@ pub async fn federate_ext_create_note(
     }
   };
 
-  let obj = match post.to_object(&actor.fediverse_uri) {
+  let mut obj = match post.to_object(&actor.fediverse_uri) {
     Some(obj) => obj,
     None => return Err(LogicErr::MissingRecord),
   };
 
+  activitypub_tag_mentions(&mut obj, &mentions.fetch_post_mentioned_users(post_id).await?);
+
   let response_object = Object::builder()
     .kind(Some(ActivityType::Create.to_string()))
     .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
@@ -311,19 +377,31 @@ pub async fn federate_ext_create_note(
 
   let doc = ActivityPubDocument::new(response_object);
 
+  send_activitypub_object(inbox_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
+pub async fn federate_ext_update_note(
+  post_id: &Uuid,
+  actor: &User,
+  dest_actor: &User,
+  posts: &PostPool,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
   let response_uri = match &dest_actor.ext_apub_inbox_uri {
     Some(uri) => uri,
     None => return Ok(()),
   };
 
-  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+  federate_ext_update_note_to_inbox(post_id, actor, response_uri, posts, mentions).await
 }
 
-pub async fn federate_ext_update_note(
+/// Sends an Update for one of our posts to the given inbox, which may belong to an actor we don't track as a user
+pub(super) async fn federate_ext_update_note_to_inbox(
   post_id: &Uuid,
   actor: &User,
-  dest_actor: &User,
+  inbox_uri: &str,
   posts: &PostPool,
+  mentions: &MentionPool,
 ) -> Result<(), LogicErr> {
   let post = match posts.fetch_post(post_id, &Some(actor.user_id)).await {
     Ok(post) => match post {
@@ -336,11 +414,13 @@ pub async fn federate_ext_update_note(
     }
   };
 
-  let obj = match post.to_object(&actor.fediverse_uri) {
+  let mut obj = match post.to_object(&actor.fediverse_uri) {
     Some(obj) => obj,
     None => return Err(LogicErr::MissingRecord),
   };
 
+  activitypub_tag_mentions(&mut obj, &mentions.fetch_post_mentioned_users(post_id).await?);
+
   let response_object = Object::builder()
     .kind(Some(ActivityType::Update.to_string()))
     .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
@@ -357,12 +437,7 @@ pub async fn federate_ext_update_note(
 
   let doc = ActivityPubDocument::new(response_object);
 
-  let response_uri = match &dest_actor.ext_apub_inbox_uri {
-    Some(uri) => uri,
-    None => return Ok(()),
-  };
-
-  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+  send_activitypub_object(inbox_uri, doc, &actor.fediverse_uri, &actor.private_key).await
 }
 
 pub async fn federate_ext_delete_note(post_id: &Uuid, actor: &User, dest_actor: &User) -> Result<(), LogicErr> {
@@ -397,6 +472,113 @@ pub async fn federate_ext_delete_note(post_id: &Uuid, actor: &User, dest_actor:
   send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
 }
 
+/// Builds the Announce for one of our users' boosts. Its ID is served by the post's boosts route, so that servers can
+/// fetch the Announce to check it.
+pub fn build_ext_boost_activity(post: &Post, actor: &User) -> Option<Object> {
+  let actor_uri = format!("{}{}", SETTINGS.server.api_fqdn, actor.fediverse_uri);
+  let followers_uri = format!("{}/user/{}/followers", SETTINGS.server.api_fqdn, actor.user_id);
+
+  let (to, cc) = match post.visibility {
+    AccessType::PublicFederated => (
+      Reference::Remote::<Object>("https://www.w3.org/ns/activitystreams#Public".to_string()),
+      Reference::Remote::<Object>(followers_uri),
+    ),
+    AccessType::Unlisted => (
+      Reference::Remote::<Object>(followers_uri),
+      Reference::Remote::<Object>("https://www.w3.org/ns/activitystreams#Public".to_string()),
+    ),
+    // Boosts of anything that isn't publicly visible stay on this instance
+    _ => return None,
+  };
+
+  Some(
+    Object::builder()
+      .kind(Some(ActivityType::Announce.to_string()))
+      // NOTE: The ID needs to be stable so that a later Undo can reference the same Announce
+      .id(Some(format!(
+        "{}/feed/{}/boosts/{}",
+        SETTINGS.server.api_fqdn, post.post_id, actor.user_id
+      )))
+      .actor(Some(Reference::Remote(actor_uri)))
+      .to(Some(to))
+      .cc(Some(cc))
+      .activity(Some(
+        ActivityProps::builder()
+          .object(Some(Reference::Remote(relative_to_absolute_uri(&post.uri))))
+          .build(),
+      ))
+      .build(),
+  )
+}
+
+pub async fn federate_ext_boost_note(
+  post_id: &Uuid,
+  actor: &User,
+  dest_actor: &User,
+  posts: &PostPool,
+) -> Result<(), LogicErr> {
+  let post = match posts.find_optional_by_id(post_id).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let response_object = match build_ext_boost_activity(&post, actor) {
+    Some(obj) => obj,
+    None => return Ok(()),
+  };
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
+pub async fn federate_ext_unboost_note(
+  post_id: &Uuid,
+  actor: &User,
+  dest_actor: &User,
+  posts: &PostPool,
+) -> Result<(), LogicErr> {
+  let post = match posts.find_optional_by_id(post_id).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let obj = match build_ext_boost_activity(&post, actor) {
+    Some(obj) => obj,
+    None => return Ok(()),
+  };
+
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Undo.to_string()))
+    .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+    .actor(Some(Reference::Remote(format!(
+      "{}{}",
+      SETTINGS.server.api_fqdn, actor.fediverse_uri
+    ))))
+    .to(obj.to.clone())
+    .cc(obj.cc.clone())
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Embedded(Box::new(obj))))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
 pub async fn federate_delete_note(target: String, actor: &User, posts: &PostPool) -> Result<FederateResult, LogicErr> {
   posts.delete_post_from_uri(&target, &actor.user_id).await?;
 
@@ -410,7 +592,10 @@ pub async fn federate_like_note(
   likes: &LikePool,
 ) -> Result<FederateResult, LogicErr> {
   let uri = match activitypub_ref_to_uri_opt(&activity_object.url) {
-    Some(uri) => uri,
+    Some(uri) => match uri.starts_with(&SETTINGS.server.api_fqdn) {
+      true => uri.replace(&SETTINGS.server.api_fqdn, ""),
+      false => uri,
+    },
     None => return Err(LogicErr::InvalidData),
   };
 
@@ -433,7 +618,12 @@ pub async fn federate_unlike_note(
   posts: &PostPool,
   likes: &LikePool,
 ) -> Result<FederateResult, LogicErr> {
-  let post = match posts.fetch_post_from_uri(&target, &Some(actor.user_id)).await {
+  let uri = match target.starts_with(&SETTINGS.server.api_fqdn) {
+    true => target.replace(&SETTINGS.server.api_fqdn, ""),
+    false => target,
+  };
+
+  let post = match posts.fetch_post_from_uri(&uri, &Some(actor.user_id)).await {
     Ok(post) => match post {
       Some(post) => post,
       None => return Err(LogicErr::MissingRecord),
@@ -445,3 +635,85 @@ pub async fn federate_unlike_note(
 
   Ok(FederateResult::None)
 }
+
+pub async fn federate_boost_note(
+  activity_object: Object,
+  actor: &User,
+  posts: &PostPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<FederateResult, LogicErr> {
+  let uri = match activity_object.id {
+    Some(uri) => match uri.starts_with(&SETTINGS.server.api_fqdn) {
+      true => uri.replace(&SETTINGS.server.api_fqdn, ""),
+      false => uri,
+    },
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  let post = match posts.find_optional_by_uri(&uri).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  // Only posts that were federated publicly in the first place can be boosted by remote users
+  if post.visibility != AccessType::PublicFederated && post.visibility != AccessType::Unlisted {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(actor.user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(post.post_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::CreateBoostEvents)
+    .build();
+
+  queue.send_job(job).await?;
+
+  Ok(FederateResult::None)
+}
+
+pub async fn federate_unboost_note(
+  target: String,
+  actor: &User,
+  posts: &PostPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<FederateResult, LogicErr> {
+  let uri = match target.starts_with(&SETTINGS.server.api_fqdn) {
+    true => target.replace(&SETTINGS.server.api_fqdn, ""),
+    false => target,
+  };
+
+  let post = match posts.find_optional_by_uri(&uri).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(actor.user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(post.post_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::DeleteBoostEvents)
+    .build();
+
+  queue.send_job(job).await?;
+
+  Ok(FederateResult::None)
+}
@@ -2,7 +2,9 @@ use std::collections::HashMap;
 
 use crate::{
   activitypub::object::ObjectType,
-  db::{like_repository::LikePool, post_repository::PostPool, user_repository::UserPool},
+  db::{
+    comment_repository::CommentPool, like_repository::LikePool, post_repository::PostPool, user_repository::UserPool,
+  },
   logic::LogicErr,
   model::{queue_job::OriginDataEntry, user::User},
   net::http_sig::extract_http_signature_origin,
@@ -18,6 +20,7 @@ pub async fn federate_delete_remote_object(
   object_type: ObjectType,
   origin_data: &Option<HashMap<String, OriginDataEntry>>,
   posts: &PostPool,
+  comments: &CommentPool,
   users: &UserPool,
   _likes: &LikePool,
 ) -> Result<FederateResult, LogicErr> {
@@ -39,6 +42,7 @@ pub async fn federate_delete_remote_object(
   match object_type {
     ObjectType::Note => {
       posts.delete_post_from_uri(&target, &actor.user_id).await?;
+      comments.delete_comment_from_uri(&target, &actor.user_id).await?;
       return Ok(FederateResult::None);
     }
     ObjectType::Profile => {
@@ -60,6 +64,7 @@ pub async fn federate_delete_remote_object(
   // a Tombstone, it's deleting an object, so we're forced to assume that any notification of Tombstone
   // means that an object is going to be deleted somewhere somehow with whatever URI it sends us.
   posts.delete_post_from_uri(&target, &actor.user_id).await?;
+  comments.delete_comment_from_uri(&target, &actor.user_id).await?;
   users.delete_user_from_uri(&target).await?;
 
   Ok(FederateResult::None)
@@ -0,0 +1,596 @@
+use std::str::FromStr;
+
+use super::{
+  actor::federate_user_actor,
+  article::federate_create_article,
+  federate::federate_verify_relayed_object,
+  note::federate_create_note,
+  util::{
+    activitypub_ref_to_id, activitypub_ref_to_uri_opt, activitypub_uri_domain_block, activitypub_uris_share_host,
+    deref_activitypub_ref, determine_activity_visibility, fetch_activitypub_object,
+  },
+};
+use crate::{
+  activitypub::{
+    activity_type::ActivityType,
+    object::{Object, ObjectType},
+    reference::Reference,
+  },
+  db::{
+    domain_block_repository::DomainBlockPool, follow_repository::FollowPool, hashtag_repository::HashtagPool,
+    job_repository::JobPool, orbit_repository::OrbitPool, post_attachment_repository::PostAttachmentPool,
+    post_repository::PostPool, user_orbit_repository::UserOrbitPool, user_repository::UserPool,
+  },
+  logic::LogicErr,
+  model::{access_type::AccessType, domain_block_severity::DomainBlockSeverity, orbit::Orbit, user::User},
+  work_queue::queue::Queue,
+};
+
+/// Lists the items of a collection or collection page, which may either be embedded or referenced by URI
+fn activitypub_collection_items(collection: &Object) -> Vec<Reference<Object>> {
+  let items = match &collection.collection {
+    Some(props) => props.ordered_items.as_ref().or(props.items.as_ref()),
+    None => None,
+  };
+
+  match items {
+    Some(Reference::Mixed(items)) => items.clone(),
+    Some(item) => vec![item.clone()],
+    None => vec![],
+  }
+}
+
+/// Determines whether a page or item of a collection can be attributed to the collection's owner. Anything referenced
+/// by URI must be hosted on the owner's server, while embedded objects were served to us by that server and only need
+/// checking if they claim to be from elsewhere.
+fn activitypub_collection_ref_is_owned(obj_ref: &Reference<Object>, owner_uri: &str) -> bool {
+  match obj_ref {
+    Reference::Remote(uri) => activitypub_uris_share_host(owner_uri, uri),
+    Reference::Embedded(obj) => match &obj.id {
+      Some(id) => activitypub_uris_share_host(owner_uri, id),
+      None => true,
+    },
+    _ => false,
+  }
+}
+
+/// Takes the items of a collection page that fit within the `remaining` number of items still to be looked at, keeping
+/// only those owned by the collection's owner. Skipped items still count towards the limit, so that a collection can't
+/// keep us paging through it by padding its pages with someone else's objects.
+fn activitypub_collection_page_refs(page: &Object, owner_uri: &str, remaining: &mut usize) -> Vec<Reference<Object>> {
+  let refs: Vec<Reference<Object>> = activitypub_collection_items(page)
+    .into_iter()
+    .take(*remaining)
+    .collect();
+  *remaining -= refs.len();
+
+  refs
+    .into_iter()
+    .filter(|item| activitypub_collection_ref_is_owned(item, owner_uri))
+    .collect()
+}
+
+/// Fetches up to `limit` of the first items in a collection owned by the given actor, paging through the collection as
+/// needed. Most servers only list a collection's items in its pages, though some embed them in the collection itself.
+/// Pages and items hosted anywhere but the owner's server are skipped, as a collection could otherwise pass off
+/// someone else's objects as the owner's.
+async fn fetch_activitypub_collection_items(uri: &str, owner_uri: &str, limit: usize) -> Vec<Object> {
+  if !activitypub_uris_share_host(owner_uri, uri) {
+    return vec![];
+  }
+
+  let collection = match fetch_activitypub_object(uri).await {
+    Some(collection) => collection,
+    None => return vec![],
+  };
+
+  let mut page = match collection.collection.as_ref().and_then(|c| c.first.clone()) {
+    Some(first) if activitypub_collection_ref_is_owned(&first, owner_uri) => deref_activitypub_ref(&Some(first)).await,
+    Some(_) => None,
+    None => Some(collection),
+  };
+
+  let mut items = vec![];
+  let mut remaining = limit;
+
+  while let Some(current) = page {
+    let previously_remaining = remaining;
+    let refs = activitypub_collection_page_refs(&current, owner_uri, &mut remaining);
+
+    if remaining == previously_remaining {
+      break;
+    }
+
+    for item in refs {
+      if let Some(item) = deref_activitypub_ref(&Some(item)).await {
+        items.push(item);
+      }
+    }
+
+    if remaining == 0 {
+      break;
+    }
+
+    page = match current.collection_page.and_then(|p| p.next) {
+      Some(next) if activitypub_collection_ref_is_owned(&next, owner_uri) => deref_activitypub_ref(&Some(next)).await,
+      _ => None,
+    };
+  }
+
+  items
+}
+
+fn activity_type(obj: &Object) -> Option<ActivityType> {
+  obj.kind.as_deref().and_then(|kind| ActivityType::from_str(kind).ok())
+}
+
+/// Extracts the public post created by an outbox item, if it is one. Anything else in the outbox, such as boosts,
+/// replies and posts attributed to someone else, is ignored.
+async fn federate_outbox_item_post(item: Object, actor: &User) -> Option<Object> {
+  if activity_type(&item) != Some(ActivityType::Create)
+    || activitypub_ref_to_id(&item.actor).as_deref() != Some(actor.fediverse_uri.as_str())
+  {
+    return None;
+  }
+
+  let object = match &item.activity {
+    Some(activity) => deref_activitypub_ref(&activity.object).await?,
+    None => return None,
+  };
+
+  match ObjectType::from_str_opt(&object.kind) {
+    Some(ObjectType::Note) | Some(ObjectType::Article) => {}
+    _ => return None,
+  }
+
+  let id = object.id.as_deref()?;
+
+  if !activitypub_uris_share_host(&actor.fediverse_uri, id)
+    || activitypub_ref_to_id(&object.attributed_to).as_deref() != Some(actor.fediverse_uri.as_str())
+    || object.in_reply_to.is_some()
+    || determine_activity_visibility(&item.to, actor) != Some(AccessType::PublicFederated)
+  {
+    return None;
+  }
+
+  Some(object)
+}
+
+/// Imports a remote user's recent public posts from their outbox, so that they don't appear empty to our users until
+/// they next post. Up to `depth` of the most recent outbox items are looked at, and posts we already have are skipped.
+pub async fn federate_backfill_outbox(
+  actor: &User,
+  depth: usize,
+  follows: &FollowPool,
+  posts: &PostPool,
+  jobs: &JobPool,
+  post_attachments: &PostAttachmentPool,
+  domain_blocks: &DomainBlockPool,
+  hashtags: &HashtagPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let outbox_uri = match &actor.ext_apub_outbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  if depth == 0 {
+    return Ok(());
+  }
+
+  let domain_block = activitypub_uri_domain_block(&actor.fediverse_uri, domain_blocks).await;
+
+  if let Some(block) = &domain_block {
+    if block.severity == DomainBlockSeverity::Suspend {
+      return Ok(());
+    }
+  }
+
+  for item in fetch_activitypub_collection_items(outbox_uri, &actor.fediverse_uri, depth).await {
+    let mut object = match federate_outbox_item_post(item, actor).await {
+      Some(object) => object,
+      None => continue,
+    };
+
+    if let Some(uri) = &object.id {
+      if posts.find_optional_by_uri(uri).await.is_some() {
+        continue;
+      }
+    }
+
+    if let Some(block) = &domain_block {
+      if block.severity == DomainBlockSeverity::RejectMedia {
+        object.attachment = None;
+      }
+    }
+
+    if let Err(err) = federate_create_note(
+      object,
+      actor,
+      AccessType::PublicFederated,
+      follows,
+      posts,
+      jobs,
+      post_attachments,
+      hashtags,
+      queue,
+    )
+    .await
+    {
+      log::warn!("Failed to import post from outbox of {}: {}", actor.fediverse_uri, err);
+    }
+  }
+
+  Ok(())
+}
+
+/// Extracts a post and a reference to its author from an item in a group's outbox or featured collection. Groups
+/// list their posts either as the author's Create, as the group's Announce of that Create like Lemmy does, or as the
+/// bare post in the case of featured collections.
+async fn federate_group_item_post(item: Object, orbit: &Orbit) -> Option<(Object, Reference<Object>)> {
+  let item = match activity_type(&item) {
+    Some(ActivityType::Announce) => {
+      if activitypub_ref_to_id(&item.actor).as_deref() != Some(orbit.fediverse_uri.as_str()) {
+        return None;
+      }
+
+      match &item.activity {
+        Some(activity) => deref_activitypub_ref(&activity.object).await?,
+        None => return None,
+      }
+    }
+    _ => item,
+  };
+
+  let (object, author) = match activity_type(&item) {
+    Some(ActivityType::Create) => {
+      let author = item.actor.clone()?;
+
+      match &item.activity {
+        Some(activity) => (deref_activitypub_ref(&activity.object).await?, author),
+        None => return None,
+      }
+    }
+    _ => {
+      let author = item.attributed_to.clone()?;
+      (item, author)
+    }
+  };
+
+  match ObjectType::from_str_opt(&object.kind) {
+    Some(ObjectType::Note) | Some(ObjectType::Article) | Some(ObjectType::Page) => {}
+    _ => return None,
+  }
+
+  if object.in_reply_to.is_some() {
+    return None;
+  }
+
+  Some((object, author))
+}
+
+/// Imports a remote orbit's recent posts from its outbox and featured collection, so that members joining it don't
+/// see an empty feed until someone next posts. Up to `depth` items are looked at from each collection, and posts we
+/// already have are skipped.
+pub async fn federate_backfill_group_outbox(
+  orbit: &Orbit,
+  depth: usize,
+  users: &UserPool,
+  posts: &PostPool,
+  jobs: &JobPool,
+  post_attachments: &PostAttachmentPool,
+  orbits: &OrbitPool,
+  user_orbits: &UserOrbitPool,
+  domain_blocks: &DomainBlockPool,
+  hashtags: &HashtagPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  if !orbit.is_external || depth == 0 {
+    return Ok(());
+  }
+
+  if let Some(block) = activitypub_uri_domain_block(&orbit.fediverse_uri, domain_blocks).await {
+    if block.severity == DomainBlockSeverity::Suspend {
+      return Ok(());
+    }
+  }
+
+  let mut items = match &orbit.ext_apub_outbox_uri {
+    Some(uri) => fetch_activitypub_collection_items(uri, &orbit.fediverse_uri, depth).await,
+    None => vec![],
+  };
+
+  // Only some servers advertise a featured collection, so we look it up from the group rather than storing it
+  let featured_uri = match fetch_activitypub_object(&orbit.fediverse_uri).await {
+    Some(group) => group.actors.and_then(|a| activitypub_ref_to_uri_opt(&a.featured)),
+    None => None,
+  };
+
+  if let Some(uri) = featured_uri {
+    items.extend(fetch_activitypub_collection_items(&uri, &orbit.fediverse_uri, depth).await);
+  }
+
+  for item in items {
+    let (mut object, author_ref) = match federate_group_item_post(item, orbit).await {
+      Some(post) => post,
+      None => continue,
+    };
+
+    let id = match &object.id {
+      Some(id) => id.to_owned(),
+      None => continue,
+    };
+
+    if posts.find_optional_by_uri(&id).await.is_some() {
+      continue;
+    }
+
+    // Posts shared to other groups as well as this one may name another group as their audience, but as the group
+    // listed the post we know it belongs here
+    if let Some(audience) = activitypub_ref_to_id(&object.audience) {
+      if audience != orbit.fediverse_uri {
+        continue;
+      }
+    }
+
+    object.audience = Some(Reference::Remote(orbit.fediverse_uri.clone()));
+
+    let author = match federate_user_actor(&Some(author_ref), users, domain_blocks).await {
+      Ok(author) => author,
+      Err(_) => continue,
+    };
+
+    // We can only take the group's word for posts hosted on its own server, anything else is fetched from its origin
+    let mut object = match activitypub_uris_share_host(&orbit.fediverse_uri, &id)
+      && activitypub_uris_share_host(&author.fediverse_uri, &id)
+      && activitypub_ref_to_id(&object.attributed_to).as_deref() == Some(author.fediverse_uri.as_str())
+    {
+      true => object,
+      false => match federate_verify_relayed_object(object, &author).await {
+        Ok(mut verified) => {
+          verified.audience = Some(Reference::Remote(orbit.fediverse_uri.clone()));
+          verified
+        }
+        Err(_) => continue,
+      },
+    };
+
+    if let Some(block) = activitypub_uri_domain_block(&author.fediverse_uri, domain_blocks).await {
+      if block.severity == DomainBlockSeverity::RejectMedia {
+        object.attachment = None;
+      }
+    }
+
+    if let Err(err) = federate_create_article(
+      object,
+      &author,
+      posts,
+      jobs,
+      post_attachments,
+      orbits,
+      user_orbits,
+      hashtags,
+      queue,
+    )
+    .await
+    {
+      log::warn!("Failed to import post from outbox of {}: {}", orbit.fediverse_uri, err);
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use serde_json::{json, Value};
+  use uuid::Uuid;
+
+  use crate::{
+    activitypub::{object::Object, reference::Reference},
+    db::{
+      domain_block_repository::{DomainBlockPool, MockDomainBlockRepo},
+      follow_repository::{FollowPool, MockFollowRepo},
+      hashtag_repository::{HashtagPool, MockHashtagRepo},
+      job_repository::{JobPool, MockJobRepo},
+      post_attachment_repository::{MockPostAttachmentRepo, PostAttachmentPool},
+      post_repository::{MockPostRepo, PostPool},
+    },
+    federation::activitypub::outbox::{
+      activitypub_collection_page_refs, activitypub_collection_ref_is_owned, federate_backfill_outbox,
+      federate_outbox_item_post, fetch_activitypub_collection_items,
+    },
+    model::user::User,
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  fn build_object(id: Option<&str>) -> Reference<Object> {
+    let object: Object = serde_json::from_value(json!({ "id": id, "type": "Create" })).unwrap();
+    Reference::Embedded(Box::new(object))
+  }
+
+  #[test]
+  fn test_collection_ref_is_owned_rejects_off_host_pages() {
+    let owner_uri = "https://a.test/users/a";
+
+    assert!(activitypub_collection_ref_is_owned(
+      &Reference::Remote("https://a.test/users/a/outbox?page=2".to_string()),
+      owner_uri
+    ));
+    assert!(!activitypub_collection_ref_is_owned(
+      &Reference::Remote("https://b.test/users/a/outbox?page=2".to_string()),
+      owner_uri
+    ));
+  }
+
+  #[test]
+  fn test_collection_ref_is_owned_rejects_off_host_items() {
+    let owner_uri = "https://a.test/users/a";
+
+    assert!(activitypub_collection_ref_is_owned(
+      &build_object(Some("https://a.test/activities/1")),
+      owner_uri
+    ));
+    assert!(activitypub_collection_ref_is_owned(&build_object(None), owner_uri));
+    assert!(!activitypub_collection_ref_is_owned(
+      &build_object(Some("https://b.test/activities/1")),
+      owner_uri
+    ));
+    assert!(!activitypub_collection_ref_is_owned(
+      &Reference::Mixed(vec![Reference::Remote("https://a.test/activities/1".to_string())]),
+      owner_uri
+    ));
+  }
+
+  #[async_std::test]
+  async fn test_fetch_collection_items_skips_off_host_collection() {
+    assert!(
+      fetch_activitypub_collection_items("https://b.test/users/a/outbox", "https://a.test/users/a", 10)
+        .await
+        .is_empty()
+    );
+  }
+
+  fn build_outbox_actor() -> User {
+    User {
+      ext_apub_outbox_uri: Some("https://a.test/users/user/outbox".to_string()),
+      ..User::test_remote(Uuid::new_v4(), "a.test")
+    }
+  }
+
+  fn build_create(object: Value) -> Object {
+    serde_json::from_value(json!({
+      "id": "https://a.test/activities/1",
+      "type": "Create",
+      "actor": "https://a.test/users/user",
+      "to": "https://www.w3.org/ns/activitystreams#Public",
+      "object": object,
+    }))
+    .unwrap()
+  }
+
+  fn build_note() -> Value {
+    json!({
+      "id": "https://a.test/notes/1",
+      "type": "Note",
+      "attributedTo": "https://a.test/users/user",
+      "content": "hello",
+    })
+  }
+
+  #[test]
+  fn test_collection_page_refs_stops_at_depth() {
+    let page: Object = serde_json::from_value(json!({
+      "type": "OrderedCollectionPage",
+      "orderedItems": [
+        "https://a.test/activities/1",
+        "https://a.test/activities/2",
+        "https://a.test/activities/3",
+      ],
+    }))
+    .unwrap();
+    let mut remaining = 2;
+
+    assert_eq!(
+      activitypub_collection_page_refs(&page, "https://a.test/users/user", &mut remaining),
+      vec![
+        Reference::Remote("https://a.test/activities/1".to_string()),
+        Reference::Remote("https://a.test/activities/2".to_string()),
+      ]
+    );
+    assert_eq!(remaining, 0);
+  }
+
+  #[test]
+  fn test_collection_page_refs_counts_skipped_items_towards_depth() {
+    let page: Object = serde_json::from_value(json!({
+      "type": "OrderedCollectionPage",
+      "orderedItems": [
+        "https://b.test/activities/1",
+        "https://a.test/activities/2",
+        "https://a.test/activities/3",
+      ],
+    }))
+    .unwrap();
+    let mut remaining = 2;
+
+    assert_eq!(
+      activitypub_collection_page_refs(&page, "https://a.test/users/user", &mut remaining),
+      vec![Reference::Remote("https://a.test/activities/2".to_string())]
+    );
+    assert_eq!(remaining, 0);
+  }
+
+  #[async_std::test]
+  async fn test_outbox_item_post_accepts_public_post() {
+    let actor = build_outbox_actor();
+    let post = federate_outbox_item_post(build_create(build_note()), &actor).await;
+
+    assert_eq!(post.and_then(|p| p.id), Some("https://a.test/notes/1".to_string()));
+  }
+
+  #[async_std::test]
+  async fn test_outbox_item_post_skips_other_items() {
+    let actor = build_outbox_actor();
+
+    let mut announce = build_create(build_note());
+    announce.kind = Some("Announce".to_string());
+
+    let mut reply = build_note();
+    reply["inReplyTo"] = json!("https://b.test/notes/1");
+
+    let mut other_author = build_note();
+    other_author["attributedTo"] = json!("https://a.test/users/other");
+
+    let mut other_host = build_note();
+    other_host["id"] = json!("https://b.test/notes/1");
+
+    let mut followers_only = build_create(build_note());
+    followers_only.to = Some(Reference::Remote("https://a.test/users/user/followers".to_string()));
+
+    for item in [
+      announce,
+      build_create(reply),
+      build_create(other_author),
+      build_create(other_host),
+      followers_only,
+    ] {
+      assert!(federate_outbox_item_post(item, &actor).await.is_none());
+    }
+  }
+
+  #[async_std::test]
+  async fn test_backfill_outbox_skips_zero_depth() {
+    let mut domain_block_repo = MockDomainBlockRepo::new();
+    domain_block_repo.expect_fetch_domain_block().times(0);
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo.expect_find_optional_by_uri().times(0);
+
+    let follows: FollowPool = Arc::new(MockFollowRepo::new());
+    let posts: PostPool = Arc::new(post_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let post_attachments: PostAttachmentPool = Arc::new(MockPostAttachmentRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(domain_block_repo);
+    let hashtags: HashtagPool = Arc::new(MockHashtagRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      federate_backfill_outbox(
+        &build_outbox_actor(),
+        0,
+        &follows,
+        &posts,
+        &jobs,
+        &post_attachments,
+        &domain_blocks,
+        &hashtags,
+        &queue
+      )
+      .await,
+      Ok(())
+    );
+  }
+}
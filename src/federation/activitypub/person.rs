@@ -11,15 +11,25 @@ use crate::{
     reference::Reference,
     tombstone::TombstoneProps,
   },
-  db::{follow_repository::FollowPool, orbit_repository::OrbitPool, user_repository::UserPool},
+  db::{
+    domain_block_repository::DomainBlockPool, follow_repository::FollowPool, job_repository::JobPool,
+    orbit_repository::OrbitPool, user_block_repository::UserBlockPool, user_repository::UserPool,
+  },
+  helpers::api::{map_db_err, relative_to_absolute_uri},
   logic::LogicErr,
-  model::user::User,
+  model::{
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+    user::User,
+  },
   settings::SETTINGS,
+  work_queue::queue::Queue,
 };
 
 use super::{
+  actor::federate_update_user_actor,
   util::{send_activitypub_object, FederateResult},
-  FederateExtActor,
+  FederateExtAction, FederateExtActor, FederateExtActorRef,
 };
 
 pub async fn federate_create_follow(
@@ -27,6 +37,7 @@ pub async fn federate_create_follow(
   actor: &User,
   follows: &FollowPool,
   users: &UserPool,
+  user_blocks: &UserBlockPool,
 ) -> Result<FederateResult, LogicErr> {
   let uri = match activity_object.id {
     Some(uri) => match uri.starts_with(&SETTINGS.server.api_fqdn) {
@@ -45,6 +56,20 @@ pub async fn federate_create_follow(
     return Err(LogicErr::MissingRecord);
   }
 
+  // Blocks sever follows in both directions, so new follows across a block are turned away
+  if user_blocks
+    .user_blocks_user(&followed_user.user_id, &actor.user_id)
+    .await
+    || user_blocks
+      .user_blocks_user(&actor.user_id, &followed_user.user_id)
+      .await
+  {
+    return Ok(FederateResult::Reject((
+      followed_user.fediverse_uri,
+      followed_user.private_key,
+    )));
+  }
+
   if !follows.user_follows_user(&actor.user_id, &followed_user.user_id).await {
     follows.create_follow(&actor.user_id, &followed_user.user_id).await?;
   }
@@ -83,6 +108,180 @@ pub async fn federate_remove_follow(
   )))
 }
 
+async fn federate_find_local_user(target: String, users: &UserPool) -> Result<User, LogicErr> {
+  let uri = match target.starts_with(&SETTINGS.server.api_fqdn) {
+    true => target.replace(&SETTINGS.server.api_fqdn, ""),
+    false => target,
+  };
+
+  match users.fetch_by_fediverse_uri(&uri).await {
+    Some(user) if !user.is_external => Ok(user),
+    _ => Err(LogicErr::MissingRecord),
+  }
+}
+
+/// Invoked when a remote actor blocks one of our users. Any follows between the two are severed, and the block is
+/// recorded so that our user's content is no longer delivered to them.
+pub async fn federate_create_block(
+  activity_object: Object,
+  actor: &User,
+  follows: &FollowPool,
+  users: &UserPool,
+  user_blocks: &UserBlockPool,
+) -> Result<FederateResult, LogicErr> {
+  let target = match activity_object.id {
+    Some(uri) => uri,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let blocked_user = federate_find_local_user(target, users).await?;
+
+  user_blocks.create_block(&actor.user_id, &blocked_user.user_id).await?;
+  follows.delete_follow(&actor.user_id, &blocked_user.user_id).await?;
+  follows.delete_follow(&blocked_user.user_id, &actor.user_id).await?;
+
+  Ok(FederateResult::None)
+}
+
+pub async fn federate_remove_block(
+  target: String,
+  actor: &User,
+  users: &UserPool,
+  user_blocks: &UserBlockPool,
+) -> Result<FederateResult, LogicErr> {
+  let blocked_user = federate_find_local_user(target, users).await?;
+
+  user_blocks.delete_block(&actor.user_id, &blocked_user.user_id).await?;
+
+  Ok(FederateResult::None)
+}
+
+/// Re-points the follows our users have on an actor that has moved to the actor it moved to, following the new actor
+/// on the remote server where needed.
+pub async fn federate_move_local_followers(
+  from_user_id: &Uuid,
+  to_user: &User,
+  follows: &FollowPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let followers = follows
+    .fetch_user_local_followers(from_user_id)
+    .await
+    .unwrap_or_default();
+
+  for follow in followers {
+    if follow.user_id != to_user.user_id && !follows.user_follows_user(&follow.user_id, &to_user.user_id).await {
+      follows.create_follow(&follow.user_id, &to_user.user_id).await?;
+
+      if to_user.is_external {
+        let job_id = jobs
+          .create(NewJob {
+            created_by_id: Some(follow.user_id),
+            status: JobStatus::NotStarted,
+            record_id: Some(to_user.user_id),
+            associated_record_id: None,
+          })
+          .await
+          .map_err(map_db_err)?;
+
+        let job = QueueJob::builder()
+          .job_id(job_id)
+          .job_type(QueueJobType::FederateActivityPubExt)
+          .context(vec![follow.user_id.to_string()])
+          .activitypub_federate_ext_action(FederateExtAction::FollowProfile)
+          .activitypub_federate_ext_dest_actor(FederateExtActorRef::Person(to_user.user_id))
+          .build();
+
+        queue.send_job(job).await?;
+      }
+    }
+
+    follows.delete_follow(&follow.user_id, from_user_id).await?;
+  }
+
+  Ok(())
+}
+
+/// Invoked when a remote actor tells us it has moved to another actor. The move is only honoured if the new actor
+/// lists the old one in its alsoKnownAs, otherwise anyone could steal another actor's followers.
+pub async fn federate_move(
+  activity_object: Object,
+  target: Option<String>,
+  actor: &User,
+  follows: &FollowPool,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<FederateResult, LogicErr> {
+  // Actors can only move themselves
+  if activity_object.id.as_ref() != Some(&actor.fediverse_uri) {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  let target = match target {
+    Some(target) => target,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  let target_user = match target.starts_with(&SETTINGS.server.api_fqdn) {
+    true => match users
+      .fetch_by_fediverse_uri(&target.replace(&SETTINGS.server.api_fqdn, ""))
+      .await
+    {
+      Some(user) => user,
+      None => return Err(LogicErr::MissingRecord),
+    },
+    // We always want the latest copy of the new actor here, as its aliases have most likely only just been changed
+    false => federate_update_user_actor(&Some(Reference::Remote(target)), users, domain_blocks).await?,
+  };
+
+  if target_user.user_id == actor.user_id || !target_user.also_known_as.contains(&actor.fediverse_uri) {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  let mut moved_user = actor.clone();
+  moved_user.moved_to_uri = Some(relative_to_absolute_uri(&target_user.fediverse_uri));
+  users.update_from(&moved_user).await?;
+
+  federate_move_local_followers(&actor.user_id, &target_user, follows, jobs, queue).await?;
+
+  Ok(FederateResult::None)
+}
+
+pub async fn federate_ext_move_profile(actor: &User, dest_actor: &User) -> Result<(), LogicErr> {
+  let target_uri = match &actor.moved_to_uri {
+    Some(uri) => uri.to_owned(),
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  let actor_uri = format!("{}{}", SETTINGS.server.api_fqdn, actor.fediverse_uri);
+  let followers_uri = format!("{}/user/{}/followers", SETTINGS.server.api_fqdn, actor.user_id);
+
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Move.to_string()))
+    .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+    .actor(Some(Reference::Remote(actor_uri.clone())))
+    .to(Some(Reference::Remote(followers_uri)))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Remote(actor_uri)))
+        .target(Some(Reference::Remote(target_uri)))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
 pub async fn federate_ext_create_follow(actor: &User, following_actor: &FederateExtActor) -> Result<(), LogicErr> {
   let following_actor = match following_actor {
     FederateExtActor::Person(actor) => actor,
@@ -118,6 +317,71 @@ pub async fn federate_ext_create_follow(actor: &User, following_actor: &Federate
   send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
 }
 
+pub async fn federate_ext_update_profile(actor: &User, dest_actor: &User) -> Result<(), LogicErr> {
+  let obj = match actor.to_object(&actor.fediverse_uri) {
+    Some(obj) => obj,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let followers_uri = format!("{}/user/{}/followers", SETTINGS.server.api_fqdn, actor.user_id);
+
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Update.to_string()))
+    .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+    .actor(Some(Reference::Remote(format!(
+      "{}{}",
+      SETTINGS.server.api_fqdn, actor.fediverse_uri
+    ))))
+    .to(Some(Reference::Remote(
+      "https://www.w3.org/ns/activitystreams#Public".to_string(),
+    )))
+    .cc(Some(Reference::Remote(followers_uri)))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Embedded(Box::new(obj))))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
+/// Tells a remote server that one of our users has deleted their account. This is sent to the shared inbox of each
+/// server we know of, so that their copies of the user's profile and posts are removed too.
+pub async fn federate_ext_delete_person(actor: &User, dest_actor: &User) -> Result<(), LogicErr> {
+  let actor_uri = format!("{}{}", SETTINGS.server.api_fqdn, actor.fediverse_uri);
+
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Delete.to_string()))
+    .id(Some(format!("{}#delete", actor_uri)))
+    .actor(Some(Reference::Remote(actor_uri.clone())))
+    .to(Some(Reference::Remote(
+      "https://www.w3.org/ns/activitystreams#Public".to_string(),
+    )))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Remote(actor_uri)))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let inbox_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(inbox_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
 pub async fn federate_ext_remove_follow(actor: &User, unfollowing_actor: &FederateExtActor) -> Result<(), LogicErr> {
   let unfollowing_actor = match unfollowing_actor {
     FederateExtActor::Person(actor) => actor,
@@ -164,6 +428,68 @@ pub async fn federate_ext_remove_follow(actor: &User, unfollowing_actor: &Federa
   send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
 }
 
+/// The URI of the Block activity sent for one of our users' blocks, which is derived from the block so that the Undo
+/// sent when unblocking references the same activity
+pub fn block_activity_uri(user_block_id: &Uuid) -> String {
+  format!("{}/blocks/{}", SETTINGS.server.api_fqdn, user_block_id)
+}
+
+fn build_block_object(actor: &User, dest_actor: &User, user_block_id: &Uuid) -> Object {
+  Object::builder()
+    .kind(Some(ActivityType::Block.to_string()))
+    .id(Some(block_activity_uri(user_block_id)))
+    .actor(Some(Reference::Remote(format!(
+      "{}{}",
+      SETTINGS.server.api_fqdn, actor.fediverse_uri
+    ))))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Remote(dest_actor.fediverse_uri.clone())))
+        .build(),
+    ))
+    .build()
+}
+
+pub async fn federate_ext_block(actor: &User, dest_actor: &User, user_block_id: &Uuid) -> Result<(), LogicErr> {
+  let doc = ActivityPubDocument::new(build_block_object(actor, dest_actor, user_block_id));
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
+pub async fn federate_ext_unblock(actor: &User, dest_actor: &User, user_block_id: &Uuid) -> Result<(), LogicErr> {
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Undo.to_string()))
+    .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+    .actor(Some(Reference::Remote(format!(
+      "{}{}",
+      SETTINGS.server.api_fqdn, actor.fediverse_uri
+    ))))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Embedded(Box::new(build_block_object(
+          actor,
+          dest_actor,
+          user_block_id,
+        )))))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  let response_uri = match &dest_actor.ext_apub_inbox_uri {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await
+}
+
 pub async fn federate_ext_join_group(actor: &User, joining_orbit: &Uuid, orbits: &OrbitPool) -> Result<(), LogicErr> {
   let orbit = match orbits.fetch_orbit(joining_orbit).await? {
     Some(orbit) => orbit,
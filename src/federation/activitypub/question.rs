@@ -0,0 +1,365 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::util::{activitypub_ref_to_id, activitypub_uris_share_host, send_activitypub_object, FederateResult};
+use crate::{
+  activitypub::{
+    activity::ActivityProps,
+    activity_type::ActivityType,
+    document::ActivityPubDocument,
+    object::{Object, ObjectType},
+    question::QuestionClosed,
+    rdf_string::RdfString,
+    reference::Reference,
+  },
+  db::{poll_repository::PollPool, post_repository::PostPool},
+  helpers::api::relative_to_absolute_uri,
+  logic::LogicErr,
+  model::{
+    poll::{Poll, PollOption, PollVote},
+    post::Post,
+    user::User,
+  },
+  settings::SETTINGS,
+};
+
+/// A poll carried by a Question from another server, along with the vote counts its server reported
+pub(super) struct RemotePoll {
+  multiple_choice: bool,
+  expires_at: Option<DateTime<Utc>>,
+  options: Vec<(String, i32)>,
+}
+
+fn activitypub_poll_options(obj_ref: &Option<Reference<Object>>) -> Vec<(String, i32)> {
+  let items = match obj_ref {
+    Some(Reference::Mixed(items)) => items.iter().collect(),
+    Some(item) => vec![item],
+    None => vec![],
+  };
+
+  items
+    .into_iter()
+    .filter_map(|item| match item {
+      Reference::Embedded(option) => option.name.as_ref().map(|name| {
+        let votes: i32 = option
+          .replies
+          .as_ref()
+          .and_then(|replies| replies.collection.as_ref())
+          .and_then(|collection| collection.total_items)
+          .map(|total_items| total_items.try_into().unwrap_or(i32::MAX))
+          .unwrap_or_default();
+
+        (name.to_owned(), votes)
+      }),
+      _ => None,
+    })
+    .collect()
+}
+
+/// Reads the poll out of a Question from another server. Questions list their options in `oneOf` when only one can
+/// be chosen, or in `anyOf` when several can.
+pub(super) fn activitypub_object_poll(obj: &Object) -> Option<RemotePoll> {
+  if ObjectType::from_str_opt(&obj.kind) != Some(ObjectType::Question) {
+    return None;
+  }
+
+  let question = obj.question.as_ref()?;
+
+  let (multiple_choice, options) = match &question.any_of {
+    Some(_) => (true, activitypub_poll_options(&question.any_of)),
+    None => (false, activitypub_poll_options(&question.one_of)),
+  };
+
+  if options.is_empty() {
+    return None;
+  }
+
+  let expires_at = match &question.closed {
+    Some(QuestionClosed::Date(closed_at)) => Some(*closed_at),
+    Some(QuestionClosed::Bool(true)) => Some(obj.end_time.unwrap_or_else(Utc::now)),
+    _ => obj.end_time,
+  };
+
+  Some(RemotePoll {
+    multiple_choice,
+    expires_at,
+    options,
+  })
+}
+
+/// Stores the poll of a Question we've just stored or updated as a post, or refreshes its vote counts if we already
+/// have it. Only the poll's author can tell us about its options and votes.
+pub(super) async fn federate_store_remote_poll(
+  object_uri: &str,
+  remote_poll: &RemotePoll,
+  actor: &User,
+  posts: &PostPool,
+  polls: &PollPool,
+) -> Result<(), LogicErr> {
+  let post = match posts.find_optional_by_uri(object_uri).await {
+    Some(post) => post,
+    // The post wasn't kept, e.g. because nobody here follows its author
+    None => return Ok(()),
+  };
+
+  if !post.is_external
+    || post.user_id != actor.user_id
+    || !activitypub_uris_share_host(&actor.fediverse_uri, object_uri)
+  {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  let poll_id = match polls.fetch_poll_by_post(&post.post_id).await? {
+    Some(poll) => {
+      polls.update_poll_expiry(&poll.poll_id, &remote_poll.expires_at).await?;
+      poll.poll_id
+    }
+    None => {
+      let names: Vec<String> = remote_poll.options.iter().map(|(name, _)| name.to_owned()).collect();
+      polls
+        .create_poll(
+          &post.post_id,
+          remote_poll.multiple_choice,
+          &remote_poll.expires_at,
+          &names,
+        )
+        .await?
+    }
+  };
+
+  for option in polls.fetch_poll_options(&poll_id).await? {
+    if let Some((_, votes)) = remote_poll.options.iter().find(|(name, _)| name == &option.name) {
+      polls.update_option_remote_votes(&option.option_id, *votes).await?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Finds the poll a note from another server is voting in. Votes are notes with a name but no content, sent in reply
+/// to the Question they're voting in.
+pub(super) async fn federate_find_vote_poll(object: &Object, posts: &PostPool, polls: &PollPool) -> Option<Poll> {
+  if ObjectType::from_str_opt(&object.kind) != Some(ObjectType::Note) || object.name.is_none() {
+    return None;
+  }
+
+  let has_content = match &object.content {
+    Some(RdfString::Raw(content)) => !content.is_empty(),
+    Some(RdfString::Props(props)) => !props.string.is_empty(),
+    None => false,
+  };
+
+  if has_content {
+    return None;
+  }
+
+  let in_reply_to = activitypub_ref_to_id(&object.in_reply_to)?;
+
+  // Only votes in our own polls are sent to us, remote polls are counted by their own servers
+  if !in_reply_to.starts_with(&SETTINGS.server.api_fqdn) {
+    return None;
+  }
+
+  let post = posts
+    .find_optional_by_uri(&in_reply_to.replace(&SETTINGS.server.api_fqdn, ""))
+    .await?;
+
+  if post.is_external {
+    return None;
+  }
+
+  polls.fetch_poll_by_post(&post.post_id).await.ok().flatten()
+}
+
+/// Records a remote user's vote in one of our polls
+pub(super) async fn federate_create_poll_vote(
+  object: &Object,
+  actor: &User,
+  poll: &Poll,
+  polls: &PollPool,
+) -> Result<FederateResult, LogicErr> {
+  if poll.is_closed() {
+    return Err(LogicErr::InvalidOperation("Poll has closed".to_string()));
+  }
+
+  let uri = match &object.id {
+    Some(uri) => uri.to_owned(),
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  let option = match polls
+    .fetch_poll_options(&poll.poll_id)
+    .await?
+    .into_iter()
+    .find(|option| Some(&option.name) == object.name.as_ref())
+  {
+    Some(option) => option,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  // Votes in multiple choice polls arrive one option at a time, and votes we already have are left as they are
+  if polls
+    .create_votes(&poll.poll_id, &[option.option_id], &actor.user_id, &Some(uri), true)
+    .await?
+    .is_none()
+  {
+    return Err(LogicErr::InvalidOperation("User has already voted".to_string()));
+  }
+
+  Ok(FederateResult::None)
+}
+
+/// Sends one of our users' votes in a remote poll to the poll's author. Each option voted for is sent as its own note,
+/// which is how other servers expect to receive votes.
+pub async fn federate_ext_poll_votes(
+  post: &Post,
+  votes: &[PollVote],
+  options: &[PollOption],
+  actor: &User,
+  dest_actor: &User,
+) -> Result<(), LogicErr> {
+  let response_uri = match dest_actor
+    .ext_apub_inbox_uri
+    .as_ref()
+    .or(dest_actor.ext_apub_shared_inbox_uri.as_ref())
+  {
+    Some(uri) => uri,
+    None => return Ok(()),
+  };
+
+  let actor_uri = relative_to_absolute_uri(&actor.fediverse_uri);
+
+  for vote in votes {
+    let option = match options.iter().find(|option| option.option_id == vote.option_id) {
+      Some(option) => option,
+      None => continue,
+    };
+
+    let obj = Object::builder()
+      .kind(Some(ObjectType::Note.to_string()))
+      .id(Some(format!("{}#votes/{}", actor_uri, vote.vote_id)))
+      .name(Some(option.name.clone()))
+      .attributed_to(Some(Reference::Remote(actor_uri.clone())))
+      .in_reply_to(Some(Reference::Remote(post.uri.clone())))
+      .to(Some(Reference::Remote(dest_actor.fediverse_uri.clone())))
+      .build();
+
+    let response_object = Object::builder()
+      .kind(Some(ActivityType::Create.to_string()))
+      .id(Some(format!("{}/{}", SETTINGS.server.api_fqdn, Uuid::new_v4())))
+      .actor(Some(Reference::Remote(actor_uri.clone())))
+      .to(Some(Reference::Remote(dest_actor.fediverse_uri.clone())))
+      .activity(Some(
+        ActivityProps::builder()
+          .object(Some(Reference::Embedded(Box::new(obj))))
+          .build(),
+      ))
+      .build();
+
+    let doc = ActivityPubDocument::new(response_object);
+
+    send_activitypub_object(response_uri, doc, &actor.fediverse_uri, &actor.private_key).await?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      poll_repository::{MockPollRepo, PollPool},
+      post_repository::{MockPostRepo, PostPool},
+    },
+    logic::LogicErr,
+    model::{access_type::AccessType, post::Post, user::User},
+  };
+
+  use super::{federate_store_remote_poll, RemotePoll};
+
+  fn build_remote_post(user_id: Uuid, uri: &str) -> Post {
+    Post {
+      post_id: Uuid::new_v4(),
+      user_id,
+      orbit_id: None,
+      uri: uri.to_string(),
+      is_external: true,
+      title: None,
+      content_md: "Which?".to_string(),
+      content_html: "<p>Which?</p>".to_string(),
+      visibility: AccessType::PublicFederated,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      deletion_scheduled_at: None,
+    }
+  }
+
+  fn build_remote_poll() -> RemotePoll {
+    RemotePoll {
+      multiple_choice: false,
+      expires_at: None,
+      options: vec![("Yes".to_string(), 100), ("No".to_string(), 0)],
+    }
+  }
+
+  #[async_std::test]
+  async fn test_store_remote_poll_rejects_other_authors_poll() {
+    let uri = "https://a.test/notes/1";
+    let author_id = Uuid::new_v4();
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_uri()
+      .with(eq(uri.to_string()))
+      .times(1)
+      .returning(move |uri| Some(build_remote_post(author_id, uri)));
+
+    let mut poll_repo = MockPollRepo::new();
+    poll_repo.expect_fetch_poll_by_post().times(0);
+    poll_repo.expect_update_option_remote_votes().times(0);
+
+    let posts: PostPool = Arc::new(post_repo);
+    let polls: PollPool = Arc::new(poll_repo);
+
+    assert_eq!(
+      federate_store_remote_poll(uri, &build_remote_poll(), &actor, &posts, &polls).await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_store_remote_poll_rejects_local_poll() {
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+    let actor_id = actor.user_id;
+    let uri = "https://a.test/notes/1";
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_uri()
+      .with(eq(uri.to_string()))
+      .times(1)
+      .returning(move |uri| {
+        let mut post = build_remote_post(actor_id, uri);
+        post.is_external = false;
+        Some(post)
+      });
+
+    let mut poll_repo = MockPollRepo::new();
+    poll_repo.expect_fetch_poll_by_post().times(0);
+
+    let posts: PostPool = Arc::new(post_repo);
+    let polls: PollPool = Arc::new(poll_repo);
+
+    assert_eq!(
+      federate_store_remote_poll(uri, &build_remote_poll(), &actor, &posts, &polls).await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use super::{
+  actor::{activitypub_actor_shared_inbox, federate_user_actor},
+  federate::{federate_verify_actor_signature, federate_verify_relayed_object},
+  note::{federate_ext_create_note_to_inbox, federate_ext_update_note_to_inbox, federate_store_note},
+  util::{
+    activitypub_ref_to_id, activitypub_ref_to_uri_opt, activitypub_uri_domain_block, deref_activitypub_ref,
+    determine_activity_visibility, fetch_activitypub_object, send_instance_activitypub_object, FederateResult,
+  },
+};
+use crate::{
+  activitypub::{
+    activity::ActivityProps,
+    activity_type::ActivityType,
+    document::ActivityPubDocument,
+    object::{Object, ObjectType},
+    reference::Reference,
+  },
+  db::{
+    domain_block_repository::DomainBlockPool, hashtag_repository::HashtagPool, job_repository::JobPool,
+    mention_repository::MentionPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    relay_repository::RelayPool, user_repository::UserPool,
+  },
+  helpers::api::relative_to_absolute_uri,
+  logic::LogicErr,
+  model::{
+    access_type::AccessType, domain_block_severity::DomainBlockSeverity, instance_actor::InstanceActor,
+    queue_job::OriginDataEntry, relay::Relay, relay_status::RelayStatus, user::User,
+  },
+  net::http_sig::extract_http_signature_key_id,
+  work_queue::queue::Queue,
+};
+
+const ACTIVITYSTREAMS_PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// Resolves the inbox we should subscribe to a relay with. Relays generally only expose a shared inbox, but we fall
+/// back to the actor's own inbox for those that don't.
+pub async fn federate_relay_inbox(actor_uri: &str) -> Result<String, LogicErr> {
+  let actor_obj = match fetch_activitypub_object(actor_uri).await {
+    Some(obj) => obj,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  if actor_obj.id.as_deref() != Some(actor_uri) {
+    return Err(LogicErr::InvalidData);
+  }
+
+  let actor = match &actor_obj.actors {
+    Some(actor) => actor,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  match activitypub_actor_shared_inbox(actor).or_else(|| activitypub_ref_to_uri_opt(&actor.inbox)) {
+    Some(uri) => Ok(uri),
+    None => Err(LogicErr::InvalidData),
+  }
+}
+
+fn build_relay_follow(relay: &Relay) -> Object {
+  Object::builder()
+    .kind(Some(ActivityType::Follow.to_string()))
+    .id(Some(relay.follow_activity_uri()))
+    .actor(Some(Reference::Remote(relative_to_absolute_uri(
+      &InstanceActor::fediverse_uri(),
+    ))))
+    .to(Some(Reference::Remote(relay.actor_uri.clone())))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Remote(ACTIVITYSTREAMS_PUBLIC.to_string())))
+        .build(),
+    ))
+    .build()
+}
+
+/// Subscribes the instance to a relay. Both LitePub and Mastodon-style relays accept a Follow of the public
+/// collection from the subscribing server's instance actor.
+pub async fn federate_ext_follow_relay(relay: &Relay) -> Result<(), LogicErr> {
+  let doc = ActivityPubDocument::new(build_relay_follow(relay));
+
+  send_instance_activitypub_object(&relay.inbox_uri, doc).await
+}
+
+pub async fn federate_ext_unfollow_relay(relay: &Relay) -> Result<(), LogicErr> {
+  let response_object = Object::builder()
+    .kind(Some(ActivityType::Undo.to_string()))
+    .id(Some(format!("{}#undo", relay.follow_activity_uri())))
+    .actor(Some(Reference::Remote(relative_to_absolute_uri(
+      &InstanceActor::fediverse_uri(),
+    ))))
+    .to(Some(Reference::Remote(relay.actor_uri.clone())))
+    .activity(Some(
+      ActivityProps::builder()
+        .object(Some(Reference::Embedded(Box::new(build_relay_follow(relay)))))
+        .build(),
+    ))
+    .build();
+
+  let doc = ActivityPubDocument::new(response_object);
+
+  send_instance_activitypub_object(&relay.inbox_uri, doc).await
+}
+
+/// Forwards one of our users' public posts to a relay, which shares it with the relay's other subscribers. Edits are
+/// forwarded the same way, so that subscribers don't keep showing the old version.
+pub async fn federate_ext_relay_post(
+  post_id: &Uuid,
+  actor: &User,
+  relay: &Relay,
+  updated: bool,
+  posts: &PostPool,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
+  match updated {
+    true => federate_ext_update_note_to_inbox(post_id, actor, &relay.inbox_uri, posts, mentions).await,
+    false => federate_ext_create_note_to_inbox(post_id, actor, &relay.inbox_uri, posts, mentions).await,
+  }
+}
+
+/// Determines whether a request was signed by one of the relays we're subscribed to, which is how Mastodon-style
+/// relays forward posts on behalf of their subscribers.
+pub(super) async fn federate_signing_relay(
+  origin_data: &Option<HashMap<String, OriginDataEntry>>,
+  relays: &RelayPool,
+) -> Option<Relay> {
+  let key_id = match extract_http_signature_key_id(origin_data) {
+    Some(key_id) => key_id,
+    None => return None,
+  };
+
+  let owner_uri = match key_id.split('#').next() {
+    Some(uri) => uri.to_owned(),
+    None => return None,
+  };
+
+  match relays.fetch_relay_by_actor_uri(&owner_uri).await {
+    Some(relay) if relay.status == RelayStatus::Accepted => Some(relay),
+    _ => None,
+  }
+}
+
+/// Stores a public post that reached us via a relay. Unlike posts delivered to us directly, we keep these whether or
+/// not anyone here follows their author, as populating the global feed is the point of subscribing to a relay.
+pub(super) async fn federate_relayed_post(
+  object: Object,
+  author: &User,
+  posts: &PostPool,
+  jobs: &JobPool,
+  post_attachments: &PostAttachmentPool,
+  hashtags: &HashtagPool,
+  queue: &Queue,
+) -> Result<FederateResult, LogicErr> {
+  match ObjectType::from_str_opt(&object.kind) {
+    Some(ObjectType::Note) | Some(ObjectType::Article) => {}
+    _ => return Ok(FederateResult::None),
+  }
+
+  // Replies belong with the post they reply to rather than in the feed
+  if object.in_reply_to.is_some() {
+    return Ok(FederateResult::None);
+  }
+
+  if determine_activity_visibility(&object.to, author) != Some(AccessType::PublicFederated) {
+    return Ok(FederateResult::None);
+  }
+
+  let uri = match &object.id {
+    Some(uri) => uri,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  // Relays deliver the same post to us for each relay we're subscribed to that carries it
+  if posts.find_optional_by_uri(uri).await.is_some() {
+    return Ok(FederateResult::None);
+  }
+
+  federate_store_note(
+    object,
+    author,
+    AccessType::PublicFederated,
+    posts,
+    jobs,
+    post_attachments,
+    hashtags,
+    queue,
+  )
+  .await
+}
+
+/// Handles an activity a relay sent us on its own behalf, i.e. a response to our subscription or a relayed post.
+pub(super) async fn federate_relay_activity(
+  kind: ActivityType,
+  activity_object: &Object,
+  relay: Relay,
+  origin_data: &Option<HashMap<String, OriginDataEntry>>,
+  users: &UserPool,
+  posts: &PostPool,
+  jobs: &JobPool,
+  post_attachments: &PostAttachmentPool,
+  relays: &RelayPool,
+  domain_blocks: &DomainBlockPool,
+  hashtags: &HashtagPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  federate_verify_actor_signature(&relay.actor_uri, origin_data).await?;
+
+  let activity = match &activity_object.activity {
+    Some(activity) => activity,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  match kind {
+    ActivityType::Accept | ActivityType::Reject => {
+      // Responses that don't reference a Follow are assumed to be for our subscription, as not all relays embed it
+      if let Some(follow_uri) = activitypub_ref_to_id(&activity.object) {
+        if follow_uri != relay.follow_activity_uri() {
+          return Ok(());
+        }
+      }
+
+      let status = match kind {
+        ActivityType::Accept => RelayStatus::Accepted,
+        _ => RelayStatus::Rejected,
+      };
+
+      relays.update_relay_status(&relay.relay_id, &status).await
+    }
+    ActivityType::Announce => {
+      if relay.status != RelayStatus::Accepted {
+        return Err(LogicErr::UnauthorizedError);
+      }
+
+      let object = match deref_activitypub_ref(&activity.object).await {
+        Some(obj) => obj,
+        None => return Err(LogicErr::InvalidData),
+      };
+
+      let author = federate_user_actor(&object.attributed_to, users, domain_blocks).await?;
+      let mut object = federate_verify_relayed_object(object, &author).await?;
+
+      if let Some(block) = activitypub_uri_domain_block(&author.fediverse_uri, domain_blocks).await {
+        if block.severity == DomainBlockSeverity::RejectMedia {
+          object.attachment = None;
+        }
+      }
+
+      federate_relayed_post(object, &author, posts, jobs, post_attachments, hashtags, queue)
+        .await
+        .map(|_| ())
+    }
+    _ => Ok(()),
+  }
+}
@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use crate::{
+  activitypub::{activity_type::ActivityType, object::Object},
+  db::{
+    follow_repository::FollowPool, job_repository::JobPool, like_repository::LikePool, orbit_repository::OrbitPool,
+    post_repository::PostPool, user_block_repository::UserBlockPool, user_orbit_repository::UserOrbitPool,
+    user_repository::UserPool,
+  },
+  logic::LogicErr,
+  model::user::User,
+  settings::SETTINGS,
+  work_queue::queue::Queue,
+};
+
+use super::{
+  group::federate_remove_member,
+  note::{federate_unboost_note, federate_unlike_note},
+  person::{federate_remove_block, federate_remove_follow},
+  util::{activitypub_ref_to_id, FederateResult},
+};
+
+async fn federate_undo_follow(
+  target: String,
+  actor: &User,
+  follows: &FollowPool,
+  users: &UserPool,
+  user_orbits: &UserOrbitPool,
+  orbits: &OrbitPool,
+) -> Result<FederateResult, LogicErr> {
+  let uri = match target.starts_with(&SETTINGS.server.api_fqdn) {
+    true => target.replace(&SETTINGS.server.api_fqdn, ""),
+    false => target.clone(),
+  };
+
+  // Follows can target either one of our users or one of our orbits, in which case it's a membership. Unlike
+  // Orbit's own Remove-based unfollows, Undo doesn't expect an Accept in response.
+  match users.fetch_by_fediverse_uri(&uri).await {
+    Some(_) => federate_remove_follow(target, actor, follows, users)
+      .await
+      .map(|_| FederateResult::None),
+    None => federate_remove_member(target, actor, user_orbits, orbits)
+      .await
+      .map(|_| FederateResult::None),
+  }
+}
+
+pub async fn federate_undo(
+  activity_object: Object,
+  actor: &User,
+  users: &UserPool,
+  follows: &FollowPool,
+  posts: &PostPool,
+  likes: &LikePool,
+  jobs: &JobPool,
+  orbits: &OrbitPool,
+  user_orbits: &UserOrbitPool,
+  user_blocks: &UserBlockPool,
+  queue: &Queue,
+) -> Result<FederateResult, LogicErr> {
+  let kind = match &activity_object.kind {
+    Some(v) => match ActivityType::from_str(v) {
+      Ok(kind) => kind,
+      Err(_) => return Err(LogicErr::InvalidData),
+    },
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  // Only the actor that performed the original activity is allowed to undo it, so an activity that doesn't say who
+  // performed it can't be undone
+  match activitypub_ref_to_id(&activity_object.actor) {
+    Some(inner_actor) if inner_actor == actor.fediverse_uri => {}
+    _ => return Err(LogicErr::UnauthorizedError),
+  }
+
+  let activity = match &activity_object.activity {
+    Some(ac) => ac,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  let target = match activitypub_ref_to_id(&activity.object) {
+    Some(target) => target,
+    None => return Err(LogicErr::InvalidData),
+  };
+
+  match kind {
+    ActivityType::Follow => federate_undo_follow(target, actor, follows, users, user_orbits, orbits).await,
+    ActivityType::Join => federate_remove_member(target, actor, user_orbits, orbits)
+      .await
+      .map(|_| FederateResult::None),
+    ActivityType::Block => federate_remove_block(target, actor, users, user_blocks).await,
+    ActivityType::Like => federate_unlike_note(target, actor, posts, likes).await,
+    ActivityType::Announce => federate_unboost_note(target, actor, posts, jobs, queue).await,
+    _ => Err(LogicErr::InternalError("Unimplemented".to_string())),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use serde_json::json;
+  use uuid::Uuid;
+
+  use crate::{
+    activitypub::object::Object,
+    db::{
+      follow_repository::{FollowPool, MockFollowRepo},
+      job_repository::{JobPool, MockJobRepo},
+      like_repository::{LikePool, MockLikeRepo},
+      orbit_repository::{MockOrbitRepo, OrbitPool},
+      post_repository::{MockPostRepo, PostPool},
+      user_block_repository::{MockUserBlockRepo, UserBlockPool},
+      user_orbit_repository::{MockUserOrbitRepo, UserOrbitPool},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    logic::LogicErr,
+    model::user::User,
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  use super::federate_undo;
+
+  async fn undo(activity: Object, actor: &User) -> Result<(), LogicErr> {
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let follows: FollowPool = Arc::new(MockFollowRepo::new());
+    let posts: PostPool = Arc::new(MockPostRepo::new());
+    let likes: LikePool = Arc::new(MockLikeRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let orbits: OrbitPool = Arc::new(MockOrbitRepo::new());
+    let user_orbits: UserOrbitPool = Arc::new(MockUserOrbitRepo::new());
+    let user_blocks: UserBlockPool = Arc::new(MockUserBlockRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    federate_undo(
+      activity,
+      actor,
+      &users,
+      &follows,
+      &posts,
+      &likes,
+      &jobs,
+      &orbits,
+      &user_orbits,
+      &user_blocks,
+      &queue,
+    )
+    .await
+    .map(|_| ())
+  }
+
+  #[async_std::test]
+  async fn test_federate_undo_rejects_another_actors_activity() {
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+    let activity = serde_json::from_value(json!({
+      "id": "https://b.test/likes/1",
+      "type": "Like",
+      "actor": "https://b.test/users/user",
+      "object": "https://c.test/notes/1",
+    }))
+    .unwrap();
+
+    assert_eq!(undo(activity, &actor).await, Err(LogicErr::UnauthorizedError));
+  }
+
+  #[async_std::test]
+  async fn test_federate_undo_rejects_activity_without_actor() {
+    let actor = User::test_remote(Uuid::new_v4(), "a.test");
+    let activity = serde_json::from_value(json!({
+      "id": "https://a.test/likes/1",
+      "type": "Like",
+      "object": "https://c.test/notes/1",
+    }))
+    .unwrap();
+
+    assert_eq!(undo(activity, &actor).await, Err(LogicErr::UnauthorizedError));
+  }
+}
@@ -2,15 +2,20 @@ use std::time::Duration;
 
 use crate::{
   activitypub::{document::ActivityPubDocument, object::Object, reference::Reference},
+  db::domain_block_repository::DomainBlockPool,
   helpers::api::map_ext_err,
   logic::LogicErr,
-  model::{access_type::AccessType, user::User},
+  model::{
+    access_type::AccessType, domain_block::DomainBlock, domain_block_severity::DomainBlockSeverity,
+    instance_actor::InstanceActor, user::User,
+  },
   settings::SETTINGS,
 };
 
 use backoff::{future::retry, ExponentialBackoff};
 use http_signing::{alg::RsaSha256, Key, PrivateKey, SigningConfig};
 use lazy_static::lazy_static;
+use once_cell::sync::OnceCell;
 use sha2::{Digest, Sha256};
 use tokio_stream::{self as stream, StreamExt};
 use url::Url;
@@ -28,6 +33,7 @@ lazy_static! {
       .build()
       .unwrap()
   };
+  pub static ref INSTANCE_PRIVATE_KEY: OnceCell<String> = OnceCell::new();
 }
 
 pub enum FederateResult {
@@ -74,6 +80,35 @@ pub fn activitypub_ref_to_uri_opt(obj_ref: &Option<Reference<Object>>) -> Option
   }
 }
 
+pub fn activitypub_ref_to_id(obj_ref: &Option<Reference<Object>>) -> Option<String> {
+  match obj_ref {
+    Some(a) => match a {
+      Reference::Embedded(obj) => obj.id.clone(),
+      Reference::Remote(uri) => Some(uri.to_owned()),
+      Reference::Mixed(_) => None,
+      Reference::Map(_) => None,
+    },
+    None => None,
+  }
+}
+
+/// Collects the ids of every object in a reference, which may either be a single object or a list of them
+pub fn activitypub_ref_to_ids(obj_ref: &Option<Reference<Object>>) -> Vec<String> {
+  match obj_ref {
+    Some(Reference::Embedded(obj)) => obj.id.iter().cloned().collect(),
+    Some(Reference::Remote(uri)) => vec![uri.to_owned()],
+    Some(Reference::Mixed(vals)) => vals
+      .iter()
+      .filter_map(|v| match v {
+        Reference::Embedded(obj) => obj.id.clone(),
+        Reference::Remote(uri) => Some(uri.to_owned()),
+        _ => None,
+      })
+      .collect(),
+    _ => vec![],
+  }
+}
+
 pub fn determine_activity_visibility(to: &Option<Reference<Object>>, author: &User) -> Option<AccessType> {
   let objs = match to {
     Some(obj_ref) => match obj_ref {
@@ -98,24 +133,125 @@ pub fn determine_activity_visibility(to: &Option<Reference<Object>>, author: &Us
   Some(AccessType::Unlisted)
 }
 
+/// Registers the instance actor's private key, which is used to sign any ActivityPub objects we fetch so that servers
+/// requiring authorized fetches will serve them to us.
+pub fn set_instance_private_key(private_key: String) {
+  let _ = INSTANCE_PRIVATE_KEY.set(private_key);
+}
+
+/// Fetches the domain block that applies to the server hosting the given URI, if any. Local URIs are never blocked.
+pub async fn activitypub_uri_domain_block(uri: &str, domain_blocks: &DomainBlockPool) -> Option<DomainBlock> {
+  let host = match Url::parse(uri) {
+    Ok(url) => match url.host_str() {
+      Some(host) => host.to_lowercase(),
+      None => return None,
+    },
+    Err(_) => return None,
+  };
+
+  domain_blocks.fetch_domain_block(&host).await
+}
+
+pub async fn activitypub_uri_is_suspended(uri: &str, domain_blocks: &DomainBlockPool) -> bool {
+  match activitypub_uri_domain_block(uri, domain_blocks).await {
+    Some(block) => block.severity == DomainBlockSeverity::Suspend,
+    None => false,
+  }
+}
+
+/// Determines whether two URIs are served by the same host, e.g. to check that an object really comes from the server
+/// of the actor it's attributed to
+pub fn activitypub_uris_share_host(a: &str, b: &str) -> bool {
+  let host = |uri: &str| {
+    Url::parse(uri)
+      .ok()
+      .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+  };
+
+  match host(a) {
+    Some(a) => Some(a) == host(b),
+    None => false,
+  }
+}
+
+/// Looks up the actor behind a `user@host` handle using WebFinger, as people are mentioned by handle rather than by
+/// the URI of their actor
+pub async fn fetch_webfinger_actor_uri(handle: &str, host: &str) -> Option<String> {
+  let resource = format!("acct:{}@{}", handle, host);
+
+  let record: serde_json::Value = HTTP_CLIENT
+    .get(format!("https://{}/.well-known/webfinger", host))
+    .query(&[("resource", resource)])
+    .header("accept", "application/jrd+json")
+    .send()
+    .await
+    .ok()?
+    .error_for_status()
+    .ok()?
+    .json()
+    .await
+    .ok()?;
+
+  record.get("links")?.as_array()?.iter().find_map(|link| {
+    let link_type = link.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+
+    if link.get("rel").and_then(|r| r.as_str()) != Some("self")
+      || !(link_type.starts_with("application/activity+json") || link_type.starts_with("application/ld+json"))
+    {
+      return None;
+    }
+
+    link.get("href").and_then(|h| h.as_str()).map(|h| h.to_owned())
+  })
+}
+
 pub async fn fetch_activitypub_object(obj_ref: &str) -> Option<Object> {
-  let result: Result<Option<ActivityPubDocument>, reqwest::Error> = retry(BACKOFF_POLICY.clone(), || async {
+  let value = fetch_activitypub_json(obj_ref).await?;
+
+  match serde_json::from_value::<ActivityPubDocument>(value) {
+    Ok(doc) => Some(doc.object),
+    Err(_) => None,
+  }
+}
+
+/// Fetches an ActivityPub document without deserializing it, for when we need properties our object model doesn't
+/// cover or the document exactly as its server sent it
+pub async fn fetch_activitypub_json(obj_ref: &str) -> Option<serde_json::Value> {
+  let result: Result<Option<serde_json::Value>, LogicErr> = retry(BACKOFF_POLICY.clone(), || async {
+    let host = match Url::parse(obj_ref).map_err(map_ext_err)?.host() {
+      Some(host) => host.to_string(),
+      None => return Err(backoff::Error::Permanent(LogicErr::InvalidData)),
+    };
+
+    let mut req = HTTP_CLIENT
+      .get(obj_ref)
+      .header("accept", "application/activity+json")
+      .header("host", host)
+      .build()
+      .map_err(map_ext_err)?;
+
+    if SETTINGS.app.secure {
+      if let Some(private_key) = INSTANCE_PRIVATE_KEY.get() {
+        let private_key = PrivateKey::from_pem(private_key.as_bytes()).map_err(map_ext_err)?;
+        SigningConfig::new(RsaSha256, &private_key, InstanceActor::key_id())
+          .sign(&mut req)
+          .map_err(map_ext_err)?;
+      }
+    }
+
     Ok(
       HTTP_CLIENT
-        .get(obj_ref)
-        .header("accept", "application/activity+json")
-        .send()
-        .await?
+        .execute(req)
+        .await
+        .map_err(map_ext_err)?
         .json()
-        .await?,
+        .await
+        .map_err(map_ext_err)?,
     )
   })
   .await;
 
-  match result {
-    Ok(v) => v.map(|v| v.object),
-    Err(_) => None,
-  }
+  result.ok().flatten()
 }
 
 pub async fn send_activitypub_object(
@@ -177,6 +313,21 @@ pub async fn send_activitypub_object(
   }
 }
 
+/// Sends an activity on behalf of the instance itself rather than one of its users or orbits, e.g. relay
+/// subscriptions and instance-level moderation activities.
+pub async fn send_instance_activitypub_object(uri: &str, doc: ActivityPubDocument) -> Result<(), LogicErr> {
+  let private_key = match INSTANCE_PRIVATE_KEY.get() {
+    Some(key) => key,
+    None => {
+      return Err(LogicErr::InternalError(
+        "Instance actor has not been loaded".to_string(),
+      ))
+    }
+  };
+
+  send_activitypub_object(uri, doc, &InstanceActor::fediverse_uri(), private_key).await
+}
+
 pub async fn deref_activitypub_ref(obj_ref: &Option<Reference<Object>>) -> Option<Object> {
   match obj_ref {
     Some(a) => match a {
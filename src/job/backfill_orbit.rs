@@ -0,0 +1,56 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    domain_block_repository::DomainBlockPool, hashtag_repository::HashtagPool, job_repository::JobPool,
+    orbit_repository::OrbitPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    user_orbit_repository::UserOrbitPool, user_repository::UserPool,
+  },
+  federation::activitypub::federate_backfill_group_outbox,
+  logic::LogicErr,
+  settings::SETTINGS,
+  work_queue::queue::Queue,
+};
+
+pub async fn backfill_orbit(
+  job_id: Uuid,
+  jobs: &JobPool,
+  users: &UserPool,
+  posts: &PostPool,
+  post_attachments: &PostAttachmentPool,
+  orbits: &OrbitPool,
+  user_orbits: &UserOrbitPool,
+  domain_blocks: &DomainBlockPool,
+  hashtags: &HashtagPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let orbit_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Orbit ID not found for job".to_string())),
+  };
+
+  let orbit = match orbits.fetch_orbit(&orbit_id).await? {
+    Some(orbit) => orbit,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  federate_backfill_group_outbox(
+    &orbit,
+    SETTINGS.app.outbox_backfill_depth,
+    users,
+    posts,
+    jobs,
+    post_attachments,
+    orbits,
+    user_orbits,
+    domain_blocks,
+    hashtags,
+    queue,
+  )
+  .await
+}
@@ -0,0 +1,54 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    domain_block_repository::DomainBlockPool, follow_repository::FollowPool, hashtag_repository::HashtagPool,
+    job_repository::JobPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    user_repository::UserPool,
+  },
+  federation::activitypub::federate_backfill_outbox,
+  logic::LogicErr,
+  settings::SETTINGS,
+  work_queue::queue::Queue,
+};
+
+pub async fn backfill_outbox(
+  job_id: Uuid,
+  jobs: &JobPool,
+  users: &UserPool,
+  follows: &FollowPool,
+  posts: &PostPool,
+  post_attachments: &PostAttachmentPool,
+  domain_blocks: &DomainBlockPool,
+  hashtags: &HashtagPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let user_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
+  };
+
+  let user = users.fetch_by_id(&user_id).await?;
+
+  if !user.is_external {
+    return Ok(());
+  }
+
+  federate_backfill_outbox(
+    &user,
+    SETTINGS.app.outbox_backfill_depth,
+    follows,
+    posts,
+    jobs,
+    post_attachments,
+    domain_blocks,
+    hashtags,
+    queue,
+  )
+  .await
+}
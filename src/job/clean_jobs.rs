@@ -1,5 +1,11 @@
-use crate::{db::job_repository::JobPool, logic::LogicErr};
+use crate::{
+  db::{job_repository::JobPool, signature_repository::SignaturePool, user_repository::UserPool},
+  logic::LogicErr,
+};
 
-pub async fn clean_jobs(jobs: &JobPool) -> Result<(), LogicErr> {
-  jobs.purge_completed_jobs().await
+pub async fn clean_jobs(jobs: &JobPool, users: &UserPool, signatures: &SignaturePool) -> Result<(), LogicErr> {
+  // Deleted users are only removed once they've finished telling other servers, which needs the jobs that did so
+  users.purge_deleted_users().await?;
+  jobs.purge_completed_jobs().await?;
+  signatures.purge_signature_uses().await
 }
@@ -1,19 +1,30 @@
 use uuid::Uuid;
 
 use crate::{
-  db::{follow_repository::FollowPool, job_repository::JobPool},
+  db::{
+    event_repository::EventPool, follow_repository::FollowPool, job_repository::JobPool, post_repository::PostPool,
+    user_repository::UserPool,
+  },
+  federation::activitypub::FederateExtAction,
   helpers::api::map_db_err,
   logic::LogicErr,
   model::{
+    event::NewEvent,
+    event_type::EventType,
     job::{JobStatus, NewJob},
     queue_job::{QueueJob, QueueJobType},
   },
   work_queue::queue::Queue,
 };
 
+use super::follower_deliveries::queue_follower_deliveries;
+
 pub async fn create_boost_events(
   jobs: &JobPool,
   follows: &FollowPool,
+  users: &UserPool,
+  posts: &PostPool,
+  events: &EventPool,
   job_id: Uuid,
   queue: &Queue,
 ) -> Result<(), LogicErr> {
@@ -32,6 +43,29 @@ pub async fn create_boost_events(
     None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
   };
 
+  let visibility = match posts.fetch_visibility_by_id(&post_id).await {
+    Some(v) => v,
+    None => return Err(LogicErr::InternalError("Visibility not found for post".to_string())),
+  };
+
+  // The booster's own event records that they've boosted the post, so a boost we've already handled, such as one
+  // from a redelivered Announce, isn't passed on to their followers again
+  let own_event = NewEvent {
+    source_user_id: user_id,
+    target_user_id: None,
+    visibility,
+    post_id: Some(post_id),
+    like_id: None,
+    comment_id: None,
+    event_type: EventType::Boost,
+  };
+
+  if !events.try_create_event(own_event).await? {
+    return Ok(());
+  }
+
+  let user = users.fetch_by_id(&user_id).await?;
+
   let followers = follows.fetch_user_followers(&user_id).await.unwrap_or_default();
 
   for follower in followers {
@@ -53,5 +87,246 @@ pub async fn create_boost_events(
     queue.send_job(job).await?;
   }
 
-  Ok(())
+  // Boosts received from remote users have already been federated by their own instance
+  if user.is_external {
+    return Ok(());
+  }
+
+  queue_follower_deliveries(
+    &user_id,
+    &post_id,
+    FederateExtAction::BoostPost(post_id),
+    jobs,
+    follows,
+    queue,
+  )
+  .await
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      event_repository::{EventPool, MockEventRepo},
+      follow_repository::{FollowPool, MockFollowRepo},
+      job_repository::{JobPool, MockJobRepo},
+      post_repository::{MockPostRepo, PostPool},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    model::{
+      access_type::AccessType,
+      event_type::EventType,
+      follow::Follow,
+      job::{Job, JobStatus},
+      queue_job::QueueJobType,
+      user::User,
+    },
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  use super::create_boost_events;
+
+  fn build_job(job_id: Uuid, post_id: Uuid, user_id: Uuid) -> Job {
+    Job {
+      job_id,
+      record_id: Some(post_id),
+      associated_record_id: None,
+      created_by_id: Some(user_id),
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      status: JobStatus::InProgress,
+      failed_count: 0,
+    }
+  }
+
+  #[async_std::test]
+  async fn test_create_boost_events_queues_follower_events() {
+    let job_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+    let user = User::test_remote(Uuid::new_v4(), "a.test");
+    let user_id = user.user_id;
+    let follower_id = Uuid::new_v4();
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_fetch_optional_by_id()
+      .with(eq(job_id))
+      .times(1)
+      .return_const(Some(build_job(job_id, post_id, user_id)));
+    job_repo
+      .expect_create()
+      .withf(move |job| job.record_id == Some(post_id) && job.associated_record_id == Some(follower_id))
+      .times(1)
+      .returning(|_| Ok(Uuid::new_v4()));
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_fetch_visibility_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .return_const(Some(AccessType::PublicFederated));
+
+    let mut event_repo = MockEventRepo::new();
+    event_repo
+      .expect_try_create_event()
+      .withf(move |event| {
+        event.source_user_id == user_id
+          && event.target_user_id.is_none()
+          && event.post_id == Some(post_id)
+          && event.event_type == EventType::Boost
+      })
+      .times(1)
+      .return_const(Ok(true));
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .with(eq(user_id))
+      .times(1)
+      .return_const(Ok(user));
+
+    let mut follow_repo = MockFollowRepo::new();
+    follow_repo
+      .expect_fetch_user_followers()
+      .with(eq(user_id))
+      .times(1)
+      .returning(move |_| {
+        Some(vec![Follow {
+          follower_id: Uuid::new_v4(),
+          user_id: follower_id,
+          following_user_id: user_id,
+          created_at: Utc::now(),
+        }])
+      });
+
+    // Boosts from remote users have already been delivered by their own server
+    let mut queue_be = MockQueueBackend::new();
+    queue_be
+      .expect_send_job()
+      .withf(|job| job.job_type == QueueJobType::CreateBoostEvent)
+      .times(1)
+      .return_const(Ok(()));
+
+    let jobs: JobPool = Arc::new(job_repo);
+    let follows: FollowPool = Arc::new(follow_repo);
+    let users: UserPool = Arc::new(user_repo);
+    let posts: PostPool = Arc::new(post_repo);
+    let events: EventPool = Arc::new(event_repo);
+    let queue = Queue::new_inner(Box::new(queue_be));
+
+    assert_eq!(
+      create_boost_events(&jobs, &follows, &users, &posts, &events, job_id, &queue).await,
+      Ok(())
+    );
+  }
+
+  #[async_std::test]
+  async fn test_create_boost_events_skips_repeated_boost() {
+    let job_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_fetch_optional_by_id()
+      .with(eq(job_id))
+      .times(1)
+      .return_const(Some(build_job(job_id, post_id, user_id)));
+    job_repo.expect_create().times(0);
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_fetch_visibility_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .return_const(Some(AccessType::PublicFederated));
+
+    let mut event_repo = MockEventRepo::new();
+    event_repo.expect_try_create_event().times(1).return_const(Ok(false));
+
+    let mut follow_repo = MockFollowRepo::new();
+    follow_repo.expect_fetch_user_followers().times(0);
+
+    let jobs: JobPool = Arc::new(job_repo);
+    let follows: FollowPool = Arc::new(follow_repo);
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let posts: PostPool = Arc::new(post_repo);
+    let events: EventPool = Arc::new(event_repo);
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      create_boost_events(&jobs, &follows, &users, &posts, &events, job_id, &queue).await,
+      Ok(())
+    );
+  }
+
+  #[async_std::test]
+  async fn test_create_boost_events_handles_self_boost() {
+    let job_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+    let user = User::test_local(Uuid::new_v4());
+    let user_id = user.user_id;
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_fetch_optional_by_id()
+      .with(eq(job_id))
+      .times(1)
+      .return_const(Some(build_job(job_id, post_id, user_id)));
+    job_repo.expect_create().times(0);
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_fetch_visibility_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .return_const(Some(AccessType::PublicFederated));
+
+    // The author's own Post event for the post has the same source, target and post, but a different event type
+    let mut event_repo = MockEventRepo::new();
+    event_repo
+      .expect_try_create_event()
+      .withf(move |event| {
+        event.source_user_id == user_id && event.post_id == Some(post_id) && event.event_type == EventType::Boost
+      })
+      .times(1)
+      .return_const(Ok(true));
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .with(eq(user_id))
+      .times(1)
+      .return_const(Ok(user));
+
+    let mut follow_repo = MockFollowRepo::new();
+    follow_repo
+      .expect_fetch_user_followers()
+      .with(eq(user_id))
+      .times(1)
+      .returning(|_| Some(vec![]));
+    follow_repo
+      .expect_fetch_user_remote_followers()
+      .with(eq(user_id))
+      .times(1)
+      .returning(|_| Some(vec![]));
+
+    let jobs: JobPool = Arc::new(job_repo);
+    let follows: FollowPool = Arc::new(follow_repo);
+    let users: UserPool = Arc::new(user_repo);
+    let posts: PostPool = Arc::new(post_repo);
+    let events: EventPool = Arc::new(event_repo);
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      create_boost_events(&jobs, &follows, &users, &posts, &events, job_id, &queue).await,
+      Ok(())
+    );
+  }
 }
@@ -3,13 +3,18 @@ use uuid::Uuid;
 
 use crate::{
   db::{
-    event_repository::EventPool, follow_repository::FollowPool, job_repository::JobPool, orbit_repository::OrbitPool,
-    post_repository::PostPool, user_orbit_repository::UserOrbitPool, user_repository::UserPool,
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, event_repository::EventPool,
+    follow_repository::FollowPool, job_repository::JobPool, mention_repository::MentionPool,
+    orbit_repository::OrbitPool, post_repository::PostPool, relay_repository::RelayPool,
+    user_orbit_repository::UserOrbitPool, user_repository::UserPool,
+  },
+  federation::activitypub::{
+    federate_ext, federate_ext_group_activity, federate_queue_group_announce, FederateExtAction, FederateExtActor,
   },
-  federation::activitypub::{federate_ext, FederateExtAction, FederateExtActor},
   helpers::api::{map_db_err, map_ext_err},
-  logic::LogicErr,
+  logic::{mention::resolve_post_mentions, LogicErr},
   model::{
+    access_type::AccessType,
     event::NewEvent,
     event_type::EventType,
     job::{JobStatus, NewJob},
@@ -18,6 +23,8 @@ use crate::{
   work_queue::queue::Queue,
 };
 
+use super::follower_deliveries::{queue_follower_deliveries, queue_mention_deliveries, queue_relay_deliveries};
+
 pub async fn create_post_events(
   jobs: &JobPool,
   posts: &PostPool,
@@ -26,6 +33,10 @@ pub async fn create_post_events(
   user_orbits: &UserOrbitPool,
   orbits: &OrbitPool,
   users: &UserPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
+  relays: &RelayPool,
+  domain_blocks: &DomainBlockPool,
   job_id: Uuid,
   queue: &Queue,
 ) -> Result<(), LogicErr> {
@@ -44,6 +55,9 @@ pub async fn create_post_events(
     None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
   };
 
+  // Remote users mentioned in the post are looked up before it's delivered, so that they're sent it too
+  resolve_post_mentions(&post_id, posts, users, domain_blocks, mentions).await?;
+
   let post = posts.fetch_by_id(&post_id).await?;
 
   let own_event = NewEvent {
@@ -72,8 +86,23 @@ pub async fn create_post_events(
             &FederateExtActor::Group(orbit),
             posts,
             orbits,
+            comments,
+            mentions,
           )
           .await?;
+        } else if !user.is_external {
+          // Posts by remote members are announced when we receive them, so we only need to announce our own users'
+          if let Some(activity) = federate_ext_group_activity(
+            &FederateExtAction::CreatePost(post_id),
+            &user,
+            posts,
+            comments,
+            mentions,
+          )
+          .await?
+          {
+            federate_queue_group_announce(&orbit, &activity, &user_id, jobs, user_orbits, queue).await?;
+          }
         }
       }
       _ => {
@@ -106,7 +135,7 @@ pub async fn create_post_events(
       queue.send_job(job).await?;
     }
   } else {
-    let followers = follows.fetch_user_followers(&user_id).await.unwrap_or_default();
+    let followers = follows.fetch_user_local_followers(&user_id).await.unwrap_or_default();
 
     for follower in followers {
       let job_id = jobs
@@ -126,6 +155,41 @@ pub async fn create_post_events(
 
       queue.send_job(job).await?;
     }
+
+    if !users.user_is_external(&user_id).await {
+      queue_follower_deliveries(
+        &user_id,
+        &post_id,
+        FederateExtAction::CreatePost(post_id),
+        jobs,
+        follows,
+        queue,
+      )
+      .await?;
+
+      let mentioned = mentions.fetch_post_mentioned_users(&post_id).await?;
+      queue_mention_deliveries(
+        &user_id,
+        &post_id,
+        FederateExtAction::CreatePost(post_id),
+        &mentioned,
+        jobs,
+        queue,
+      )
+      .await?;
+
+      if post.visibility == AccessType::PublicFederated {
+        queue_relay_deliveries(
+          &user_id,
+          &post_id,
+          FederateExtAction::CreatePost(post_id),
+          jobs,
+          relays,
+          queue,
+        )
+        .await?;
+      }
+    }
   }
 
   Ok(())
@@ -1,13 +1,26 @@
 use uuid::Uuid;
 
 use crate::{
-  db::{event_repository::EventPool, job_repository::JobPool},
+  db::{
+    event_repository::EventPool, follow_repository::FollowPool, job_repository::JobPool, user_repository::UserPool,
+  },
+  federation::activitypub::FederateExtAction,
   helpers::api::map_db_err,
   logic::LogicErr,
   model::event_type::EventType,
+  work_queue::queue::Queue,
 };
 
-pub async fn delete_boost_events(job_id: Uuid, jobs: &JobPool, events: &EventPool) -> Result<(), LogicErr> {
+use super::follower_deliveries::queue_follower_deliveries;
+
+pub async fn delete_boost_events(
+  job_id: Uuid,
+  jobs: &JobPool,
+  events: &EventPool,
+  follows: &FollowPool,
+  users: &UserPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
   let job = match jobs.fetch_optional_by_id(&job_id).await {
     Some(job) => job,
     None => return Err(LogicErr::InternalError("Job not found".to_string())),
@@ -26,5 +39,22 @@ pub async fn delete_boost_events(job_id: Uuid, jobs: &JobPool, events: &EventPoo
   events
     .delete_post_events(&post_id, &user_id, EventType::Boost)
     .await
-    .map_err(map_db_err)
+    .map_err(map_db_err)?;
+
+  let user = users.fetch_by_id(&user_id).await?;
+
+  // Unboosts received from remote users have already been federated by their own instance
+  if user.is_external {
+    return Ok(());
+  }
+
+  queue_follower_deliveries(
+    &user_id,
+    &post_id,
+    FederateExtAction::UnboostPost(post_id),
+    jobs,
+    follows,
+    queue,
+  )
+  .await
 }
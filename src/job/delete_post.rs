@@ -2,19 +2,19 @@ use uuid::Uuid;
 
 use crate::{
   db::{
-    follow_repository::FollowPool, job_repository::JobPool, orbit_repository::OrbitPool, post_repository::PostPool,
+    comment_repository::CommentPool, follow_repository::FollowPool, job_repository::JobPool,
+    mention_repository::MentionPool, orbit_repository::OrbitPool, post_repository::PostPool,
     user_orbit_repository::UserOrbitPool, user_repository::UserPool,
   },
-  federation::activitypub::{federate_ext, FederateExtAction, FederateExtActor, FederateExtActorRef},
-  helpers::api::map_db_err,
-  logic::LogicErr,
-  model::{
-    job::{JobStatus, NewJob},
-    queue_job::{QueueJob, QueueJobType},
+  federation::activitypub::{
+    federate_ext, federate_ext_group_activity, federate_queue_group_announce, FederateExtAction, FederateExtActor,
   },
+  logic::LogicErr,
   work_queue::queue::Queue,
 };
 
+use super::follower_deliveries::queue_follower_deliveries;
+
 pub async fn delete_post(
   job_id: Uuid,
   jobs: &JobPool,
@@ -23,6 +23,8 @@ pub async fn delete_post(
   users: &UserPool,
   posts: &PostPool,
   follows: &FollowPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
   queue: &Queue,
 ) -> Result<(), LogicErr> {
   let job = match jobs.fetch_optional_by_id(&job_id).await {
@@ -52,10 +54,25 @@ pub async fn delete_post(
             &FederateExtActor::Group(orbit),
             posts,
             orbits,
+            comments,
+            mentions,
           )
           .await?;
           return Ok(());
         }
+
+        // Remote members learn of the deletion through the orbit's announcement of it
+        if let Some(activity) = federate_ext_group_activity(
+          &FederateExtAction::DeletePost(post_id),
+          &user,
+          posts,
+          comments,
+          mentions,
+        )
+        .await?
+        {
+          federate_queue_group_announce(&orbit, &activity, &user_id, jobs, user_orbits, queue).await?;
+        }
       }
       _ => {
         log::warn!(
@@ -65,54 +82,16 @@ pub async fn delete_post(
         );
       }
     };
-
-    let users = user_orbits.fetch_orbit_external_user_ids(&orbit_id).await?;
-
-    for user in users {
-      let job_id = jobs
-        .create(NewJob {
-          created_by_id: Some(user_id),
-          status: JobStatus::NotStarted,
-          record_id: Some(post_id),
-          associated_record_id: Some(user),
-        })
-        .await
-        .map_err(map_db_err)?;
-
-      let job = QueueJob::builder()
-        .job_id(job_id)
-        .job_type(QueueJobType::FederateActivityPubExt)
-        .context(vec![user_id.to_string()])
-        .activitypub_federate_ext_action(FederateExtAction::DeletePost(post_id))
-        .activitypub_federate_ext_dest_actor(FederateExtActorRef::Person(user))
-        .build();
-
-      queue.send_job(job).await?;
-    }
   } else {
-    let followers = follows.fetch_user_followers(&user_id).await.unwrap_or_default();
-
-    for follower in followers {
-      let job_id = jobs
-        .create(NewJob {
-          created_by_id: Some(user_id),
-          status: JobStatus::NotStarted,
-          record_id: Some(post_id),
-          associated_record_id: Some(follower.user_id),
-        })
-        .await
-        .map_err(map_db_err)?;
-
-      let job = QueueJob::builder()
-        .job_id(job_id)
-        .job_type(QueueJobType::FederateActivityPubExt)
-        .context(vec![user_id.to_string()])
-        .activitypub_federate_ext_action(FederateExtAction::DeletePost(post_id))
-        .activitypub_federate_ext_dest_actor(FederateExtActorRef::Person(follower.user_id))
-        .build();
-
-      queue.send_job(job).await?;
-    }
+    queue_follower_deliveries(
+      &user_id,
+      &post_id,
+      FederateExtAction::DeletePost(post_id),
+      jobs,
+      follows,
+      queue,
+    )
+    .await?;
   }
 
   Ok(())
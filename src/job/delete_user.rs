@@ -0,0 +1,229 @@
+use uuid::Uuid;
+
+use crate::{
+  activitypub::object::ObjectType,
+  db::{
+    job_repository::JobPool, post_repository::PostPool, tombstone_repository::TombstonePool, user_repository::UserPool,
+  },
+  federation::activitypub::{FederateExtAction, FederateExtActorRef},
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::{
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+  },
+  work_queue::queue::Queue,
+};
+
+pub async fn delete_user(
+  job_id: Uuid,
+  jobs: &JobPool,
+  users: &UserPool,
+  posts: &PostPool,
+  tombstones: &TombstonePool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let user_id = match job.created_by_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
+  };
+
+  let mut user = users.fetch_by_id(&user_id).await?;
+
+  if user.is_external {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  for post in posts.fetch_user_posts(&user_id).await? {
+    if tombstones.fetch_for_fediverse_uri(&post.uri).await.is_some() {
+      continue;
+    }
+
+    let former_type = match post.orbit_id {
+      Some(_) => ObjectType::Article,
+      None => ObjectType::Note,
+    };
+
+    tombstones.create_tombstone(&post.uri, &former_type.to_string()).await?;
+  }
+
+  // The user can't sign back in while the deletion is being delivered, which still needs their key to sign it. The
+  // user is removed for good once those deliveries are done, when jobs are next cleaned up.
+  user.password_hash = None;
+  users.update_from(&user).await?;
+
+  for target in users.fetch_known_inbox_targets().await? {
+    let job_id = jobs
+      .create(NewJob {
+        created_by_id: Some(user_id),
+        status: JobStatus::NotStarted,
+        record_id: Some(user_id),
+        associated_record_id: Some(target),
+      })
+      .await
+      .map_err(map_db_err)?;
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::FederateActivityPubExt)
+      .context(vec![user_id.to_string()])
+      .activitypub_federate_ext_action(FederateExtAction::DeleteProfile)
+      .activitypub_federate_ext_dest_actor(FederateExtActorRef::PersonSharedInbox(target))
+      .build();
+
+    queue.send_job(job).await?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      job_repository::{JobPool, MockJobRepo},
+      post_repository::{MockPostRepo, PostPool},
+      tombstone_repository::{MockTombstoneRepo, TombstonePool},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    federation::activitypub::{FederateExtAction, FederateExtActorRef},
+    model::{
+      job::{Job, JobStatus},
+      queue_job::QueueJobType,
+      user::User,
+    },
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  use super::delete_user;
+
+  fn build_local_user() -> User {
+    User {
+      user_id: Uuid::new_v4(),
+      fediverse_id: "@user@127.0.0.1:8000".to_string(),
+      handle: "user".to_string(),
+      fediverse_uri: "/user/user".to_string(),
+      avatar_url: None,
+      email: None,
+      password_hash: Some("hash".to_string()),
+      is_external: false,
+      url_1: None,
+      url_2: None,
+      url_3: None,
+      url_4: None,
+      url_5: None,
+      url_1_title: None,
+      url_2_title: None,
+      url_3_title: None,
+      url_4_title: None,
+      url_5_title: None,
+      intro_md: None,
+      intro_html: None,
+      private_key: "private".to_string(),
+      public_key: "public".to_string(),
+      ext_apub_followers_uri: None,
+      ext_apub_following_uri: None,
+      ext_apub_inbox_uri: None,
+      ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+    }
+  }
+
+  #[async_std::test]
+  async fn test_delete_user_queues_delete_per_inbox() {
+    let user = build_local_user();
+    let user_id = user.user_id;
+    let job_id = Uuid::new_v4();
+    let targets = vec![Uuid::new_v4(), Uuid::new_v4()];
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_fetch_optional_by_id()
+      .with(eq(job_id))
+      .times(1)
+      .return_const(Some(Job {
+        job_id,
+        record_id: Some(user_id),
+        associated_record_id: None,
+        created_by_id: Some(user_id),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        status: JobStatus::InProgress,
+        failed_count: 0,
+      }));
+    job_repo
+      .expect_create()
+      .withf(move |job| job.created_by_id == Some(user_id) && job.record_id == Some(user_id))
+      .times(2)
+      .returning(|_| Ok(Uuid::new_v4()));
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .with(eq(user_id))
+      .times(1)
+      .return_const(Ok(user));
+    user_repo
+      .expect_update_from()
+      .withf(|user| user.password_hash.is_none())
+      .times(1)
+      .returning(|user| Ok(user.clone()));
+    user_repo
+      .expect_fetch_known_inbox_targets()
+      .times(1)
+      .return_const(Ok(targets.clone()));
+    // The user is only removed once the deliveries are done, as they're signed with the user's key
+    user_repo.expect_delete_user().times(0);
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_fetch_user_posts()
+      .with(eq(user_id))
+      .times(1)
+      .returning(|_| Ok(vec![]));
+
+    let mut queue_be = MockQueueBackend::new();
+    queue_be
+      .expect_send_job()
+      .withf(move |job| {
+        job.job_type == QueueJobType::FederateActivityPubExt
+          && job.context == Some(vec![user_id.to_string()])
+          && matches!(
+            job.activitypub_federate_ext_action,
+            Some(FederateExtAction::DeleteProfile)
+          )
+          && matches!(
+            job.activitypub_federate_ext_dest_actor,
+            Some(FederateExtActorRef::PersonSharedInbox(target)) if targets.contains(&target)
+          )
+      })
+      .times(2)
+      .return_const(Ok(()));
+
+    let jobs: JobPool = Arc::new(job_repo);
+    let users: UserPool = Arc::new(user_repo);
+    let posts: PostPool = Arc::new(post_repo);
+    let tombstones: TombstonePool = Arc::new(MockTombstoneRepo::new());
+    let queue = Queue::new_inner(Box::new(queue_be));
+
+    assert_eq!(
+      delete_user(job_id, &jobs, &users, &posts, &tombstones, &queue).await,
+      Ok(())
+    );
+  }
+}
@@ -1,17 +1,19 @@
 use log::debug;
 use serde_json::Value;
 use std::collections::HashMap;
+use uuid::Uuid;
 
 use crate::{
   activitypub::document::{ActivityPubDocument, RawActivityPubDocument},
   db::repositories::Repositories,
-  federation::activitypub::federate,
+  federation::activitypub::{federate, federate_check_signature_replay},
   logic::LogicErr,
   model::queue_job::OriginDataEntry,
   work_queue::queue::Queue,
 };
 
 pub async fn federate_activitypub(
+  job_id: Uuid,
   data: &Option<Value>,
   origin_data: &Option<HashMap<String, OriginDataEntry>>,
   repositories: &Repositories,
@@ -22,21 +24,26 @@ pub async fn federate_activitypub(
     data.clone().map(|v| serde_json::to_string_pretty(&v))
   );
 
-  let doc: RawActivityPubDocument = match data.to_owned() {
-    Some(value) => match serde_json::from_value(value) {
-      Ok(doc) => doc,
-      Err(err) => return Err(LogicErr::InvalidOperation(err.to_string())),
-    },
+  let data = match data {
+    Some(value) => value,
     None => return Err(LogicErr::MissingRecord),
   };
 
+  let doc: RawActivityPubDocument = match serde_json::from_value(data.to_owned()) {
+    Ok(doc) => doc,
+    Err(err) => return Err(LogicErr::InvalidOperation(err.to_string())),
+  };
+
   let doc = match ActivityPubDocument::from(doc) {
     Ok(doc) => doc,
     Err(err) => return Err(LogicErr::InvalidOperation(err.to_string())),
   };
 
+  federate_check_signature_replay(&job_id, origin_data, &repositories.users, &repositories.signatures).await?;
+
   federate(
     doc,
+    data,
     origin_data,
     &repositories.users,
     &repositories.follows,
@@ -46,6 +53,15 @@ pub async fn federate_activitypub(
     &repositories.post_attachments,
     &repositories.orbits,
     &repositories.user_orbits,
+    &repositories.comments,
+    &repositories.user_blocks,
+    &repositories.domain_blocks,
+    &repositories.reports,
+    &repositories.relays,
+    &repositories.mentions,
+    &repositories.hashtags,
+    &repositories.polls,
+    &repositories.post_revisions,
     queue,
   )
   .await
@@ -4,7 +4,9 @@ use uuid::Uuid;
 
 use crate::{
   db::repositories::Repositories,
-  federation::activitypub::{federate_ext, FederateExtAction, FederateExtActor, FederateExtActorRef},
+  federation::activitypub::{
+    activitypub_uri_is_suspended, federate_ext, FederateExtAction, FederateExtActor, FederateExtActorRef,
+  },
   helpers::api::map_ext_err,
   logic::LogicErr,
 };
@@ -35,18 +37,40 @@ pub async fn federate_activitypub(
   let dest_actor = match dest_actor {
     FederateExtActorRef::None => FederateExtActor::None,
     FederateExtActorRef::Person(id) => FederateExtActor::Person(repositories.users.fetch_by_id(id).await?),
+    FederateExtActorRef::PersonSharedInbox(id) => {
+      let mut user = repositories.users.fetch_by_id(id).await?;
+      if user.ext_apub_shared_inbox_uri.is_some() {
+        user.ext_apub_inbox_uri = user.ext_apub_shared_inbox_uri.clone();
+      }
+      FederateExtActor::Person(user)
+    }
     FederateExtActorRef::Group(id) => match repositories.orbits.fetch_orbit(id).await? {
       Some(orbit) => FederateExtActor::Group(orbit),
       None => return Err(LogicErr::MissingRecord),
     },
   };
 
+  let dest_inbox_uri = match &dest_actor {
+    FederateExtActor::Person(user) => user.ext_apub_inbox_uri.clone(),
+    FederateExtActor::Group(orbit) => orbit.ext_apub_inbox_uri.clone(),
+    FederateExtActor::None => None,
+  };
+
+  // Nothing is delivered to suspended servers, though we still let the job complete so that it isn't retried
+  if let Some(uri) = dest_inbox_uri {
+    if activitypub_uri_is_suspended(&uri, &repositories.domain_blocks).await {
+      return Ok(());
+    }
+  }
+
   federate_ext(
     action.clone(),
     &actor,
     &dest_actor,
     &repositories.posts,
     &repositories.orbits,
+    &repositories.comments,
+    &repositories.mentions,
   )
   .await
 }
@@ -0,0 +1,214 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, follow_repository::FollowPool,
+    job_repository::JobPool, mention_repository::MentionPool, orbit_repository::OrbitPool, post_repository::PostPool,
+    user_block_repository::UserBlockPool, user_orbit_repository::UserOrbitPool, user_repository::UserPool,
+  },
+  federation::activitypub::{
+    federate_ext_group_activity, federate_queue_group_announce, FederateExtAction, FederateExtActorRef,
+  },
+  helpers::api::map_db_err,
+  logic::{mention::resolve_comment_mentions, LogicErr},
+  model::{
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+  },
+  work_queue::queue::Queue,
+};
+
+use super::follower_deliveries::{queue_follower_deliveries, queue_mention_deliveries};
+
+async fn federate_comment(
+  job_id: Uuid,
+  jobs: &JobPool,
+  posts: &PostPool,
+  follows: &FollowPool,
+  users: &UserPool,
+  user_blocks: &UserBlockPool,
+  orbits: &OrbitPool,
+  user_orbits: &UserOrbitPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
+  queue: &Queue,
+  action: fn(Uuid, Uuid) -> FederateExtAction,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let user_id = match job.created_by_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
+  };
+
+  let comment_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Comment ID not found for job".to_string())),
+  };
+
+  let post_id = match job.associated_record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Post ID not found for job".to_string())),
+  };
+
+  if users.user_is_external(&user_id).await {
+    return Ok(());
+  }
+
+  // A comment is delivered to the commenter's followers as well as to the author of the post being replied to
+  queue_follower_deliveries(&user_id, &comment_id, action(post_id, comment_id), jobs, follows, queue).await?;
+
+  let mentioned = mentions.fetch_comment_mentioned_users(&comment_id).await?;
+  queue_mention_deliveries(
+    &user_id,
+    &comment_id,
+    action(post_id, comment_id),
+    &mentioned,
+    jobs,
+    queue,
+  )
+  .await?;
+
+  let orbit = match posts.find_optional_by_id(&post_id).await.and_then(|post| post.orbit_id) {
+    Some(orbit_id) => orbits.fetch_orbit(&orbit_id).await?,
+    None => None,
+  };
+
+  // Comments on posts in our orbits are also announced to the orbit's remote members
+  if let Some(orbit) = orbit.filter(|orbit| !orbit.is_external) {
+    let user = users.fetch_by_id(&user_id).await?;
+
+    if let Some(activity) =
+      federate_ext_group_activity(&action(post_id, comment_id), &user, posts, comments, mentions).await?
+    {
+      federate_queue_group_announce(&orbit, &activity, &user_id, jobs, user_orbits, queue).await?;
+    }
+  }
+
+  let owner_id = match posts.fetch_owner_by_id(&post_id).await {
+    Some(owner_id) => owner_id,
+    None => return Ok(()),
+  };
+
+  let mut recipient_ids = vec![owner_id];
+
+  // Replies to comments are also sent to the author of the comment being replied to. Deleted comments can't be looked
+  // up any more, so their deletion only reaches the post's author.
+  let parent_comment_id = comments
+    .fetch_comment(&post_id, &comment_id, &Some(user_id))
+    .await
+    .and_then(|comment| comment.parent_comment_id);
+
+  if let Some(parent_comment_id) = parent_comment_id {
+    if let Some(parent) = comments
+      .fetch_comment(&post_id, &parent_comment_id, &Some(user_id))
+      .await
+    {
+      if !recipient_ids.contains(&parent.user_id) {
+        recipient_ids.push(parent.user_id);
+      }
+    }
+  }
+
+  for recipient_id in recipient_ids {
+    if recipient_id == user_id || !users.user_is_external(&recipient_id).await {
+      continue;
+    }
+
+    // Remote users that have blocked the commenter shouldn't be sent their replies
+    if user_blocks.user_blocks_user(&recipient_id, &user_id).await {
+      continue;
+    }
+
+    let job_id = jobs
+      .create(NewJob {
+        created_by_id: Some(user_id),
+        status: JobStatus::NotStarted,
+        record_id: Some(comment_id),
+        associated_record_id: Some(recipient_id),
+      })
+      .await
+      .map_err(map_db_err)?;
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::FederateActivityPubExt)
+      .context(vec![user_id.to_string()])
+      .activitypub_federate_ext_action(action(post_id, comment_id))
+      .activitypub_federate_ext_dest_actor(FederateExtActorRef::Person(recipient_id))
+      .build();
+
+    queue.send_job(job).await?;
+  }
+
+  Ok(())
+}
+
+pub async fn federate_create_comment(
+  job_id: Uuid,
+  jobs: &JobPool,
+  posts: &PostPool,
+  follows: &FollowPool,
+  users: &UserPool,
+  user_blocks: &UserBlockPool,
+  orbits: &OrbitPool,
+  user_orbits: &UserOrbitPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
+  domain_blocks: &DomainBlockPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  // Remote users mentioned in the comment are looked up before it's delivered, so that they're sent it too
+  if let Some(comment_id) = jobs.fetch_optional_by_id(&job_id).await.and_then(|job| job.record_id) {
+    resolve_comment_mentions(&comment_id, comments, users, domain_blocks, mentions).await?;
+  }
+
+  federate_comment(
+    job_id,
+    jobs,
+    posts,
+    follows,
+    users,
+    user_blocks,
+    orbits,
+    user_orbits,
+    comments,
+    mentions,
+    queue,
+    FederateExtAction::CreateComment,
+  )
+  .await
+}
+
+pub async fn federate_delete_comment(
+  job_id: Uuid,
+  jobs: &JobPool,
+  posts: &PostPool,
+  follows: &FollowPool,
+  users: &UserPool,
+  user_blocks: &UserBlockPool,
+  orbits: &OrbitPool,
+  user_orbits: &UserOrbitPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  federate_comment(
+    job_id,
+    jobs,
+    posts,
+    follows,
+    users,
+    user_blocks,
+    orbits,
+    user_orbits,
+    comments,
+    mentions,
+    queue,
+    FederateExtAction::DeleteComment,
+  )
+  .await
+}
@@ -0,0 +1,214 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+  activitypub::object::Object,
+  db::{
+    domain_block_repository::DomainBlockPool, job_repository::JobPool, orbit_repository::OrbitPool,
+    user_repository::UserPool,
+  },
+  federation::activitypub::{activitypub_uri_is_suspended, federate_ext_group_announce},
+  logic::LogicErr,
+};
+
+/// Delivers an orbit's announcement of an activity to one of its members. The job carries the activity being
+/// announced, as it may have come from another server or refer to something that has since been deleted.
+pub async fn federate_group_announce(
+  job_id: Uuid,
+  data: &Option<Value>,
+  jobs: &JobPool,
+  orbits: &OrbitPool,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let orbit_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Orbit ID not found for job".to_string())),
+  };
+
+  let user_id = match job.associated_record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
+  };
+
+  let activity: Object = match data.to_owned() {
+    Some(value) => match serde_json::from_value(value) {
+      Ok(activity) => activity,
+      Err(err) => return Err(LogicErr::InvalidOperation(err.to_string())),
+    },
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let orbit = match orbits.fetch_orbit(&orbit_id).await? {
+    Some(orbit) => orbit,
+    None => return Ok(()),
+  };
+
+  let mut user = users.fetch_by_id(&user_id).await?;
+  if user.ext_apub_shared_inbox_uri.is_some() {
+    user.ext_apub_inbox_uri = user.ext_apub_shared_inbox_uri.clone();
+  }
+
+  // Nothing is delivered to suspended servers, though we still let the job complete so that it isn't retried
+  if let Some(uri) = &user.ext_apub_inbox_uri {
+    if activitypub_uri_is_suspended(uri, domain_blocks).await {
+      return Ok(());
+    }
+  }
+
+  federate_ext_group_announce(&orbit, activity, &user).await
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use serde_json::json;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      domain_block_repository::{DomainBlockPool, MockDomainBlockRepo},
+      job_repository::{JobPool, MockJobRepo},
+      orbit_repository::{MockOrbitRepo, OrbitPool},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    logic::LogicErr,
+    model::{
+      domain_block::DomainBlock,
+      domain_block_severity::DomainBlockSeverity,
+      job::{Job, JobStatus},
+      orbit::Orbit,
+      user::User,
+    },
+  };
+
+  use super::federate_group_announce;
+
+  fn build_job(job_id: Uuid, orbit_id: Uuid, user_id: Uuid) -> Job {
+    Job {
+      job_id,
+      record_id: Some(orbit_id),
+      associated_record_id: Some(user_id),
+      created_by_id: Some(Uuid::new_v4()),
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      status: JobStatus::InProgress,
+      failed_count: 0,
+    }
+  }
+
+  #[async_std::test]
+  async fn test_group_announce_rejects_missing_activity() {
+    let job_id = Uuid::new_v4();
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_fetch_optional_by_id()
+      .with(eq(job_id))
+      .times(1)
+      .return_const(Some(build_job(job_id, Uuid::new_v4(), Uuid::new_v4())));
+
+    let mut orbit_repo = MockOrbitRepo::new();
+    orbit_repo.expect_fetch_orbit().times(0);
+
+    let jobs: JobPool = Arc::new(job_repo);
+    let orbits: OrbitPool = Arc::new(orbit_repo);
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+
+    assert_eq!(
+      federate_group_announce(job_id, &None, &jobs, &orbits, &users, &domain_blocks).await,
+      Err(LogicErr::MissingRecord)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_group_announce_skips_suspended_shared_inbox() {
+    let job_id = Uuid::new_v4();
+    let orbit_id = Uuid::new_v4();
+    let user = User {
+      ext_apub_shared_inbox_uri: Some("https://b.test/inbox".to_string()),
+      ..User::test_remote(Uuid::new_v4(), "a.test")
+    };
+    let user_id = user.user_id;
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_fetch_optional_by_id()
+      .with(eq(job_id))
+      .times(1)
+      .return_const(Some(build_job(job_id, orbit_id, user_id)));
+
+    let mut orbit_repo = MockOrbitRepo::new();
+    orbit_repo
+      .expect_fetch_orbit()
+      .with(eq(orbit_id))
+      .times(1)
+      .return_const(Ok(Some(Orbit {
+        orbit_id,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        shortcode: "orbit".to_string(),
+        name: "Orbit".to_string(),
+        description_md: "".to_string(),
+        description_html: "".to_string(),
+        avatar_uri: None,
+        banner_uri: None,
+        uri: format!("/orbits/{}", orbit_id),
+        fediverse_uri: format!("/orbit/{}", orbit_id),
+        private_key: "private".to_string(),
+        public_key: "public".to_string(),
+        is_external: false,
+        ext_apub_inbox_uri: None,
+        ext_apub_outbox_uri: None,
+        ext_apub_followers_uri: None,
+      })));
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .with(eq(user_id))
+      .times(1)
+      .return_const(Ok(user));
+
+    // Deliveries go to the shared inbox, so that's the server whose suspension matters
+    let mut domain_block_repo = MockDomainBlockRepo::new();
+    domain_block_repo
+      .expect_fetch_domain_block()
+      .with(eq("b.test"))
+      .times(1)
+      .return_const(Some(DomainBlock {
+        domain_block_id: Uuid::new_v4(),
+        domain: "b.test".to_string(),
+        severity: DomainBlockSeverity::Suspend,
+        public_comment: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+      }));
+
+    let jobs: JobPool = Arc::new(job_repo);
+    let orbits: OrbitPool = Arc::new(orbit_repo);
+    let users: UserPool = Arc::new(user_repo);
+    let domain_blocks: DomainBlockPool = Arc::new(domain_block_repo);
+
+    let activity = json!({
+      "id": "https://a.test/activities/1",
+      "type": "Create",
+      "actor": "https://a.test/users/user",
+      "object": "https://a.test/notes/1",
+    });
+
+    assert_eq!(
+      federate_group_announce(job_id, &Some(activity), &jobs, &orbits, &users, &domain_blocks).await,
+      Ok(())
+    );
+  }
+}
@@ -0,0 +1,52 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{follow_repository::FollowPool, job_repository::JobPool, user_repository::UserPool},
+  federation::activitypub::{federate_move_local_followers, FederateExtAction},
+  logic::LogicErr,
+  work_queue::queue::Queue,
+};
+
+use super::follower_deliveries::queue_follower_deliveries;
+
+pub async fn federate_move_profile(
+  job_id: Uuid,
+  jobs: &JobPool,
+  follows: &FollowPool,
+  users: &UserPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let user_id = match job.created_by_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
+  };
+
+  let target_user_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Target user ID not found for job".to_string())),
+  };
+
+  if users.user_is_external(&user_id).await {
+    return Ok(());
+  }
+
+  // Remote followers are told about the move and re-follow on their own, whereas we move our own users' follows here
+  queue_follower_deliveries(
+    &user_id,
+    &target_user_id,
+    FederateExtAction::MoveProfile,
+    jobs,
+    follows,
+    queue,
+  )
+  .await?;
+
+  let target_user = users.fetch_by_id(&target_user_id).await?;
+
+  federate_move_local_followers(&user_id, &target_user, follows, jobs, queue).await
+}
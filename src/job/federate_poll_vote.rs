@@ -0,0 +1,62 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    domain_block_repository::DomainBlockPool, job_repository::JobPool, poll_repository::PollPool,
+    post_repository::PostPool, user_repository::UserPool,
+  },
+  federation::activitypub::{activitypub_uri_is_suspended, federate_ext_poll_votes},
+  logic::LogicErr,
+};
+
+/// Sends a user's votes in a poll from another server to the poll's author, whose server keeps count of them
+pub async fn federate_poll_vote(
+  job_id: Uuid,
+  jobs: &JobPool,
+  posts: &PostPool,
+  polls: &PollPool,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let post_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Post ID not found for job".to_string())),
+  };
+
+  let user_id = match job.created_by_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
+  };
+
+  let post = match posts.find_optional_by_id(&post_id).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  if !post.is_external {
+    return Ok(());
+  }
+
+  let poll = match polls.fetch_poll_by_post(&post_id).await? {
+    Some(poll) => poll,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let votes = polls.fetch_user_votes(&poll.poll_id, &user_id).await?;
+  let options = polls.fetch_poll_options(&poll.poll_id).await?;
+
+  let actor = users.fetch_by_id(&user_id).await?;
+  let dest_actor = users.fetch_by_id(&post.user_id).await?;
+
+  // Nothing is delivered to suspended servers, though we still let the job complete so that it isn't retried
+  if activitypub_uri_is_suspended(&dest_actor.fediverse_uri, domain_blocks).await {
+    return Ok(());
+  }
+
+  federate_ext_poll_votes(&post, &votes, &options, &actor, &dest_actor).await
+}
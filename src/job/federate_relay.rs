@@ -0,0 +1,89 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    job_repository::JobPool, mention_repository::MentionPool, post_repository::PostPool, relay_repository::RelayPool,
+    user_repository::UserPool,
+  },
+  federation::activitypub::{
+    federate_ext_follow_relay, federate_ext_relay_post, federate_ext_unfollow_relay, FederateExtAction,
+  },
+  logic::LogicErr,
+  model::{relay::Relay, relay_status::RelayStatus},
+};
+
+pub async fn federate_follow_relay(job_id: Uuid, jobs: &JobPool, relays: &RelayPool) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let relay_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Relay ID not found for job".to_string())),
+  };
+
+  // The subscription may have been cancelled before we got around to sending it
+  let relay = match relays.fetch_relay(&relay_id).await {
+    Some(relay) => relay,
+    None => return Ok(()),
+  };
+
+  federate_ext_follow_relay(&relay).await
+}
+
+/// Unsubscribes from a relay. The relay's record has already been deleted by the time this runs, so the job carries
+/// a copy of it.
+pub async fn federate_unfollow_relay(data: &Option<Value>) -> Result<(), LogicErr> {
+  let relay: Relay = match data.to_owned() {
+    Some(value) => match serde_json::from_value(value) {
+      Ok(relay) => relay,
+      Err(err) => return Err(LogicErr::InvalidOperation(err.to_string())),
+    },
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  federate_ext_unfollow_relay(&relay).await
+}
+
+pub async fn federate_relay_post(
+  job_id: Uuid,
+  action: &Option<FederateExtAction>,
+  jobs: &JobPool,
+  relays: &RelayPool,
+  users: &UserPool,
+  posts: &PostPool,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let post_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Post ID not found for job".to_string())),
+  };
+
+  let relay_id = match job.associated_record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Relay ID not found for job".to_string())),
+  };
+
+  let user_id = match job.created_by_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
+  };
+
+  let relay = match relays.fetch_relay(&relay_id).await {
+    Some(relay) if relay.status == RelayStatus::Accepted => relay,
+    _ => return Ok(()),
+  };
+
+  let user = users.fetch_by_id(&user_id).await?;
+
+  let updated = matches!(action, Some(FederateExtAction::UpdatePost(_)));
+
+  federate_ext_relay_post(&post_id, &user, &relay, updated, posts, mentions).await
+}
@@ -0,0 +1,48 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{job_repository::JobPool, post_repository::PostPool, report_repository::ReportPool, user_repository::UserPool},
+  federation::activitypub::federate_ext_report,
+  logic::LogicErr,
+};
+
+pub async fn federate_report(
+  job_id: Uuid,
+  jobs: &JobPool,
+  reports: &ReportPool,
+  users: &UserPool,
+  posts: &PostPool,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let report_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Report ID not found for job".to_string())),
+  };
+
+  let report = match reports.fetch_report(&report_id).await {
+    Some(report) => report,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let target_user = users.fetch_by_id(&report.target_user_id).await?;
+
+  if !target_user.is_external {
+    return Ok(());
+  }
+
+  let mut post_uris = vec![];
+
+  for post_id in &report.post_ids {
+    if let Some(post) = posts.find_optional_by_id(post_id).await {
+      if post.is_external {
+        post_uris.push(post.uri);
+      }
+    }
+  }
+
+  federate_ext_report(&report, &target_user, post_uris).await
+}
@@ -0,0 +1,138 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{follow_repository::FollowPool, job_repository::JobPool, user_repository::UserPool},
+  federation::activitypub::FederateExtAction,
+  logic::LogicErr,
+  work_queue::queue::Queue,
+};
+
+use super::follower_deliveries::queue_follower_deliveries;
+
+pub async fn federate_update_profile(
+  job_id: Uuid,
+  jobs: &JobPool,
+  follows: &FollowPool,
+  users: &UserPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let user_id = match job.created_by_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User ID not found for job".to_string())),
+  };
+
+  if users.user_is_external(&user_id).await {
+    return Ok(());
+  }
+
+  queue_follower_deliveries(
+    &user_id,
+    &user_id,
+    FederateExtAction::UpdateProfile,
+    jobs,
+    follows,
+    queue,
+  )
+  .await
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      follow_repository::{FollowPool, MockFollowRepo},
+      job_repository::{JobPool, MockJobRepo},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    federation::activitypub::{FederateExtAction, FederateExtActorRef},
+    model::{
+      job::{Job, JobStatus},
+      queue_job::QueueJobType,
+      user::User,
+    },
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  use super::federate_update_profile;
+
+  #[async_std::test]
+  async fn test_federate_update_profile_queues_update_to_followers() {
+    let user_id = Uuid::new_v4();
+    let job_id = Uuid::new_v4();
+    let follower = User::test_remote(Uuid::new_v4(), "example.com");
+    let follower_id = follower.user_id;
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_fetch_optional_by_id()
+      .with(eq(job_id))
+      .times(1)
+      .return_const(Some(Job {
+        job_id,
+        record_id: Some(user_id),
+        associated_record_id: None,
+        created_by_id: Some(user_id),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        status: JobStatus::InProgress,
+        failed_count: 0,
+      }));
+    job_repo
+      .expect_create()
+      .withf(move |job| job.created_by_id == Some(user_id) && job.associated_record_id == Some(follower_id))
+      .times(1)
+      .returning(|_| Ok(Uuid::new_v4()));
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_user_is_external()
+      .with(eq(user_id))
+      .times(1)
+      .return_const(false);
+
+    let mut follow_repo = MockFollowRepo::new();
+    follow_repo
+      .expect_fetch_user_remote_followers()
+      .with(eq(user_id))
+      .times(1)
+      .return_const(Some(vec![follower]));
+
+    let mut queue_be = MockQueueBackend::new();
+    queue_be
+      .expect_send_job()
+      .withf(move |job| {
+        job.job_type == QueueJobType::FederateActivityPubExt
+          && matches!(
+            job.activitypub_federate_ext_action,
+            Some(FederateExtAction::UpdateProfile)
+          )
+          && matches!(
+            job.activitypub_federate_ext_dest_actor,
+            Some(FederateExtActorRef::PersonSharedInbox(target)) if target == follower_id
+          )
+      })
+      .times(1)
+      .return_const(Ok(()));
+
+    let jobs: JobPool = Arc::new(job_repo);
+    let follows: FollowPool = Arc::new(follow_repo);
+    let users: UserPool = Arc::new(user_repo);
+    let queue = Queue::new_inner(Box::new(queue_be));
+
+    assert_eq!(
+      federate_update_profile(job_id, &jobs, &follows, &users, &queue).await,
+      Ok(())
+    );
+  }
+}
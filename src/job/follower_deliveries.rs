@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+  db::{follow_repository::FollowPool, job_repository::JobPool, relay_repository::RelayPool},
+  federation::activitypub::{FederateExtAction, FederateExtActorRef},
+  helpers::api::map_db_err,
+  logic::LogicErr,
+  model::{
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+    user::User,
+  },
+  work_queue::queue::Queue,
+};
+
+/// Queues delivery of an activity to a local user's remote followers. Followers on the same server are delivered to
+/// with a single request to that server's shared inbox, so only servers without one receive a request per follower.
+pub async fn queue_follower_deliveries(
+  user_id: &Uuid,
+  record_id: &Uuid,
+  action: FederateExtAction,
+  jobs: &JobPool,
+  follows: &FollowPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let followers = follows.fetch_user_remote_followers(user_id).await.unwrap_or_default();
+
+  for target in follower_delivery_targets(&followers) {
+    let job_id = jobs
+      .create(NewJob {
+        created_by_id: Some(*user_id),
+        status: JobStatus::NotStarted,
+        record_id: Some(*record_id),
+        associated_record_id: Some(target),
+      })
+      .await
+      .map_err(map_db_err)?;
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::FederateActivityPubExt)
+      .context(vec![user_id.to_string()])
+      .activitypub_federate_ext_action(action.clone())
+      .activitypub_federate_ext_dest_actor(FederateExtActorRef::PersonSharedInbox(target))
+      .build();
+
+    queue.send_job(job).await?;
+  }
+
+  Ok(())
+}
+
+/// Picks the followers to address deliveries to, so that each inbox only receives an activity once. A server's shared
+/// inbox hands activities to every one of its users following the author, so any followers on that server who only
+/// advertise a personal inbox are reached by delivering to one who advertises the shared inbox.
+fn follower_delivery_targets(followers: &[User]) -> Vec<Uuid> {
+  let host = |uri: &str| {
+    Url::parse(uri)
+      .ok()
+      .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+  };
+
+  let mut shared_inbox_hosts = HashSet::new();
+  let mut inboxes = HashSet::new();
+  let mut targets = vec![];
+
+  for user in followers {
+    if let Some(shared_inbox) = &user.ext_apub_shared_inbox_uri {
+      shared_inbox_hosts.extend(host(shared_inbox));
+      shared_inbox_hosts.extend(user.ext_apub_inbox_uri.as_deref().and_then(host));
+
+      if inboxes.insert(shared_inbox.clone()) {
+        targets.push(user.user_id);
+      }
+    }
+  }
+
+  for user in followers.iter().filter(|u| u.ext_apub_shared_inbox_uri.is_none()) {
+    let inbox = match &user.ext_apub_inbox_uri {
+      Some(inbox) => inbox,
+      None => continue,
+    };
+
+    if host(inbox).is_some_and(|h| shared_inbox_hosts.contains(&h)) {
+      continue;
+    }
+
+    if inboxes.insert(inbox.clone()) {
+      targets.push(user.user_id);
+    }
+  }
+
+  targets
+}
+
+/// Queues delivery of an activity to the remote users mentioned in it, as they won't otherwise receive it unless they
+/// follow its author
+pub async fn queue_mention_deliveries(
+  user_id: &Uuid,
+  record_id: &Uuid,
+  action: FederateExtAction,
+  mentioned: &[User],
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  for target in mentioned.iter().filter(|user| user.is_external) {
+    let job_id = jobs
+      .create(NewJob {
+        created_by_id: Some(*user_id),
+        status: JobStatus::NotStarted,
+        record_id: Some(*record_id),
+        associated_record_id: Some(target.user_id),
+      })
+      .await
+      .map_err(map_db_err)?;
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::FederateActivityPubExt)
+      .context(vec![user_id.to_string()])
+      .activitypub_federate_ext_action(action.clone())
+      .activitypub_federate_ext_dest_actor(FederateExtActorRef::Person(target.user_id))
+      .build();
+
+    queue.send_job(job).await?;
+  }
+
+  Ok(())
+}
+
+/// Queues delivery of one of a local user's public posts, or an edit of one, to every relay that has accepted our
+/// subscription
+pub async fn queue_relay_deliveries(
+  user_id: &Uuid,
+  post_id: &Uuid,
+  action: FederateExtAction,
+  jobs: &JobPool,
+  relays: &RelayPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let relays = relays.fetch_accepted_relays().await.unwrap_or_default();
+
+  for relay in relays {
+    let job_id = jobs
+      .create(NewJob {
+        created_by_id: Some(*user_id),
+        status: JobStatus::NotStarted,
+        record_id: Some(*post_id),
+        associated_record_id: Some(relay.relay_id),
+      })
+      .await
+      .map_err(map_db_err)?;
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::FederateRelayPost)
+      .activitypub_federate_ext_action(action.clone())
+      .build();
+
+    queue.send_job(job).await?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use uuid::Uuid;
+
+  use crate::model::user::User;
+
+  use super::follower_delivery_targets;
+
+  fn build_follower(host: &str, shared_inbox: bool) -> User {
+    let user_id = Uuid::new_v4();
+
+    User {
+      fediverse_uri: format!("https://{}/users/{}", host, user_id),
+      ext_apub_inbox_uri: Some(format!("https://{}/users/{}/inbox", host, user_id)),
+      ext_apub_shared_inbox_uri: shared_inbox.then(|| format!("https://{}/inbox", host)),
+      ..User::test_remote(user_id, host)
+    }
+  }
+
+  #[test]
+  fn test_follower_delivery_targets_groups_shared_inbox() {
+    let a = build_follower("a.test", true);
+    let b = build_follower("a.test", true);
+
+    assert_eq!(follower_delivery_targets(&[a.clone(), b]), vec![a.user_id]);
+  }
+
+  #[test]
+  fn test_follower_delivery_targets_covers_personal_inboxes_with_shared_inbox() {
+    let a = build_follower("a.test", false);
+    let b = build_follower("a.test", true);
+    let c = build_follower("A.test", false);
+
+    assert_eq!(follower_delivery_targets(&[a, b.clone(), c]), vec![b.user_id]);
+  }
+
+  #[test]
+  fn test_follower_delivery_targets_keeps_personal_inboxes_without_shared_inbox() {
+    let a = build_follower("a.test", false);
+    let b = build_follower("a.test", false);
+    let c = build_follower("b.test", true);
+
+    assert_eq!(
+      follower_delivery_targets(&[a.clone(), b.clone(), c.clone()]),
+      vec![c.user_id, a.user_id, b.user_id]
+    );
+  }
+
+  #[test]
+  fn test_follower_delivery_targets_dedupes_personal_inbox() {
+    let a = build_follower("a.test", false);
+    let b = User {
+      user_id: Uuid::new_v4(),
+      ..a.clone()
+    };
+
+    assert_eq!(follower_delivery_targets(&[a.clone(), b]), vec![a.user_id]);
+  }
+}
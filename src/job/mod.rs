@@ -6,6 +6,9 @@ use crate::{
   work_queue::queue::Queue,
 };
 
+mod backfill_external_orbits;
+mod backfill_orbit;
+mod backfill_outbox;
 mod clean_jobs;
 mod convert_new_post_images;
 mod create_boost_event;
@@ -14,12 +17,22 @@ mod create_post_event;
 mod create_post_events;
 mod delete_boost_events;
 mod delete_post;
+mod delete_user;
 mod federate_activitypub;
 mod federate_activitypub_ext;
+mod federate_comment;
+mod federate_group_announce;
+mod federate_move_profile;
+mod federate_poll_vote;
+mod federate_relay;
+mod federate_report;
+mod federate_update_profile;
+mod follower_deliveries;
 mod refresh_external_orbit;
 mod refresh_external_orbits;
 mod refresh_external_profile;
 mod refresh_external_profiles;
+mod update_post;
 
 pub async fn delegate_job(
   queue_job: &QueueJob,
@@ -46,6 +59,10 @@ pub async fn delegate_job(
         &repositories.user_orbits,
         &repositories.orbits,
         &repositories.users,
+        &repositories.comments,
+        &repositories.mentions,
+        &repositories.relays,
+        &repositories.domain_blocks,
         queue_job.job_id,
         queue,
       )
@@ -58,12 +75,23 @@ pub async fn delegate_job(
         &repositories.events,
         &repositories.users,
         &repositories.orbits,
+        &repositories.comments,
+        &repositories.mentions,
         queue_job.job_id,
       )
       .await
     }
     QueueJobType::CreateBoostEvents => {
-      create_boost_events::create_boost_events(&repositories.jobs, &repositories.follows, queue_job.job_id, queue).await
+      create_boost_events::create_boost_events(
+        &repositories.jobs,
+        &repositories.follows,
+        &repositories.users,
+        &repositories.posts,
+        &repositories.events,
+        queue_job.job_id,
+        queue,
+      )
+      .await
     }
     QueueJobType::CreateBoostEvent => {
       create_boost_event::create_boost_event(
@@ -75,7 +103,15 @@ pub async fn delegate_job(
       .await
     }
     QueueJobType::DeleteBoostEvents => {
-      delete_boost_events::delete_boost_events(queue_job.job_id, &repositories.jobs, &repositories.events).await
+      delete_boost_events::delete_boost_events(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.events,
+        &repositories.follows,
+        &repositories.users,
+        queue,
+      )
+      .await
     }
     QueueJobType::DeletePost => {
       delete_post::delete_post(
@@ -86,12 +122,21 @@ pub async fn delegate_job(
         &repositories.users,
         &repositories.posts,
         &repositories.follows,
+        &repositories.comments,
+        &repositories.mentions,
         queue,
       )
       .await
     }
     QueueJobType::FederateActivityPub => {
-      federate_activitypub::federate_activitypub(&queue_job.data, &queue_job.origin_data, repositories, queue).await
+      federate_activitypub::federate_activitypub(
+        queue_job.job_id,
+        &queue_job.data,
+        &queue_job.origin_data,
+        repositories,
+        queue,
+      )
+      .await
     }
     QueueJobType::FederateActivityPubExt => {
       federate_activitypub_ext::federate_activitypub(
@@ -102,7 +147,9 @@ pub async fn delegate_job(
       )
       .await
     }
-    QueueJobType::CleanJobs => clean_jobs::clean_jobs(&repositories.jobs).await,
+    QueueJobType::CleanJobs => {
+      clean_jobs::clean_jobs(&repositories.jobs, &repositories.users, &repositories.signatures).await
+    }
     QueueJobType::RefreshExternalOrbits => {
       refresh_external_orbits::refresh_external_orbits(&repositories.orbits, &repositories.jobs, queue).await
     }
@@ -113,8 +160,174 @@ pub async fn delegate_job(
       refresh_external_orbit::refresh_external_orbit(&repositories.orbits, &repositories.jobs, queue_job.job_id).await
     }
     QueueJobType::RefreshExternalProfile => {
-      refresh_external_profile::refresh_external_profile(&repositories.users, &repositories.jobs, queue_job.job_id)
-        .await
+      refresh_external_profile::refresh_external_profile(
+        &repositories.users,
+        &repositories.domain_blocks,
+        &repositories.jobs,
+        queue_job.job_id,
+      )
+      .await
+    }
+    QueueJobType::FederateCreateComment => {
+      federate_comment::federate_create_comment(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.posts,
+        &repositories.follows,
+        &repositories.users,
+        &repositories.user_blocks,
+        &repositories.orbits,
+        &repositories.user_orbits,
+        &repositories.comments,
+        &repositories.mentions,
+        &repositories.domain_blocks,
+        queue,
+      )
+      .await
+    }
+    QueueJobType::FederateDeleteComment => {
+      federate_comment::federate_delete_comment(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.posts,
+        &repositories.follows,
+        &repositories.users,
+        &repositories.user_blocks,
+        &repositories.orbits,
+        &repositories.user_orbits,
+        &repositories.comments,
+        &repositories.mentions,
+        queue,
+      )
+      .await
+    }
+    QueueJobType::FederateUpdateProfile => {
+      federate_update_profile::federate_update_profile(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.follows,
+        &repositories.users,
+        queue,
+      )
+      .await
+    }
+    QueueJobType::FederateMoveProfile => {
+      federate_move_profile::federate_move_profile(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.follows,
+        &repositories.users,
+        queue,
+      )
+      .await
+    }
+    QueueJobType::DeleteUser => {
+      delete_user::delete_user(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.users,
+        &repositories.posts,
+        &repositories.tombstones,
+        queue,
+      )
+      .await
+    }
+    QueueJobType::FederateReport => {
+      federate_report::federate_report(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.reports,
+        &repositories.users,
+        &repositories.posts,
+      )
+      .await
+    }
+    QueueJobType::FederateFollowRelay => {
+      federate_relay::federate_follow_relay(queue_job.job_id, &repositories.jobs, &repositories.relays).await
+    }
+    QueueJobType::FederateUnfollowRelay => federate_relay::federate_unfollow_relay(&queue_job.data).await,
+    QueueJobType::FederateRelayPost => {
+      federate_relay::federate_relay_post(
+        queue_job.job_id,
+        &queue_job.activitypub_federate_ext_action,
+        &repositories.jobs,
+        &repositories.relays,
+        &repositories.users,
+        &repositories.posts,
+        &repositories.mentions,
+      )
+      .await
+    }
+    QueueJobType::BackfillOutbox => {
+      backfill_outbox::backfill_outbox(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.users,
+        &repositories.follows,
+        &repositories.posts,
+        &repositories.post_attachments,
+        &repositories.domain_blocks,
+        &repositories.hashtags,
+        queue,
+      )
+      .await
+    }
+    QueueJobType::BackfillOrbit => {
+      backfill_orbit::backfill_orbit(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.users,
+        &repositories.posts,
+        &repositories.post_attachments,
+        &repositories.orbits,
+        &repositories.user_orbits,
+        &repositories.domain_blocks,
+        &repositories.hashtags,
+        queue,
+      )
+      .await
+    }
+    QueueJobType::BackfillExternalOrbits => {
+      backfill_external_orbits::backfill_external_orbits(&repositories.orbits, &repositories.jobs, queue).await
+    }
+    QueueJobType::FederateGroupAnnounce => {
+      federate_group_announce::federate_group_announce(
+        queue_job.job_id,
+        &queue_job.data,
+        &repositories.jobs,
+        &repositories.orbits,
+        &repositories.users,
+        &repositories.domain_blocks,
+      )
+      .await
+    }
+    QueueJobType::FederatePollVote => {
+      federate_poll_vote::federate_poll_vote(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.posts,
+        &repositories.polls,
+        &repositories.users,
+        &repositories.domain_blocks,
+      )
+      .await
+    }
+    QueueJobType::UpdatePost => {
+      update_post::update_post(
+        queue_job.job_id,
+        &repositories.jobs,
+        &repositories.orbits,
+        &repositories.user_orbits,
+        &repositories.users,
+        &repositories.posts,
+        &repositories.follows,
+        &repositories.comments,
+        &repositories.mentions,
+        &repositories.relays,
+        &repositories.domain_blocks,
+        queue,
+      )
+      .await
     }
     QueueJobType::Unknown => Err(LogicErr::Unimplemented),
   }
@@ -2,12 +2,17 @@ use uuid::Uuid;
 
 use crate::{
   activitypub::reference::Reference,
-  db::{job_repository::JobPool, user_repository::UserPool},
+  db::{domain_block_repository::DomainBlockPool, job_repository::JobPool, user_repository::UserPool},
   federation::activitypub::actor::federate_update_user_actor,
   logic::LogicErr,
 };
 
-pub async fn refresh_external_profile(users: &UserPool, jobs: &JobPool, job_id: Uuid) -> Result<(), LogicErr> {
+pub async fn refresh_external_profile(
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  jobs: &JobPool,
+  job_id: Uuid,
+) -> Result<(), LogicErr> {
   let job = match jobs.fetch_by_id(&job_id).await? {
     Some(job) => job,
     None => return Err(LogicErr::MissingRecord),
@@ -20,7 +25,7 @@ pub async fn refresh_external_profile(users: &UserPool, jobs: &JobPool, job_id:
 
   let user = users.fetch_by_id(&user_id).await?;
 
-  federate_update_user_actor(&Some(Reference::Remote(user.fediverse_uri)), users).await?;
+  federate_update_user_actor(&Some(Reference::Remote(user.fediverse_uri)), users, domain_blocks).await?;
 
   Ok(())
 }
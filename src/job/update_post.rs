@@ -0,0 +1,129 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, follow_repository::FollowPool,
+    job_repository::JobPool, mention_repository::MentionPool, orbit_repository::OrbitPool, post_repository::PostPool,
+    relay_repository::RelayPool, user_orbit_repository::UserOrbitPool, user_repository::UserPool,
+  },
+  federation::activitypub::{
+    federate_ext, federate_ext_group_activity, federate_queue_group_announce, FederateExtAction, FederateExtActor,
+  },
+  logic::{mention::resolve_post_mentions, LogicErr},
+  model::access_type::AccessType,
+  work_queue::queue::Queue,
+};
+
+use super::follower_deliveries::{queue_follower_deliveries, queue_mention_deliveries, queue_relay_deliveries};
+
+pub async fn update_post(
+  job_id: Uuid,
+  jobs: &JobPool,
+  orbits: &OrbitPool,
+  user_orbits: &UserOrbitPool,
+  users: &UserPool,
+  posts: &PostPool,
+  follows: &FollowPool,
+  comments: &CommentPool,
+  mentions: &MentionPool,
+  relays: &RelayPool,
+  domain_blocks: &DomainBlockPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let job = match jobs.fetch_optional_by_id(&job_id).await {
+    Some(job) => job,
+    None => return Err(LogicErr::InternalError("Job not found".to_string())),
+  };
+
+  let user_id = match job.created_by_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("User not found".to_string())),
+  };
+
+  let post_id = match job.record_id {
+    Some(id) => id,
+    None => return Err(LogicErr::InternalError("Post ID not found for job".to_string())),
+  };
+
+  // Remote users newly mentioned in the edit are looked up before it's delivered, so that they're sent it too
+  resolve_post_mentions(&post_id, posts, users, domain_blocks, mentions).await?;
+
+  if let Some(orbit_id) = job.associated_record_id {
+    match orbits.fetch_orbit(&orbit_id).await? {
+      Some(orbit) => {
+        let user = users.fetch_by_id(&user_id).await?;
+
+        if orbit.is_external {
+          federate_ext(
+            FederateExtAction::UpdatePost(post_id),
+            &user,
+            &FederateExtActor::Group(orbit),
+            posts,
+            orbits,
+            comments,
+            mentions,
+          )
+          .await?;
+          return Ok(());
+        }
+
+        // Remote members learn of the edit through the orbit's announcement of it
+        if let Some(activity) = federate_ext_group_activity(
+          &FederateExtAction::UpdatePost(post_id),
+          &user,
+          posts,
+          comments,
+          mentions,
+        )
+        .await?
+        {
+          federate_queue_group_announce(&orbit, &activity, &user_id, jobs, user_orbits, queue).await?;
+        }
+      }
+      _ => {
+        log::warn!(
+          "Failed to fetch remote orbit information with id {} to federate post {}. Federation will be permanently aborted for this post.",
+          orbit_id,
+          post_id
+        );
+      }
+    };
+  } else {
+    queue_follower_deliveries(
+      &user_id,
+      &post_id,
+      FederateExtAction::UpdatePost(post_id),
+      jobs,
+      follows,
+      queue,
+    )
+    .await?;
+
+    // Mentioned users were sent the post when it was created, or are newly mentioned in the edit
+    let mentioned = mentions.fetch_post_mentioned_users(&post_id).await?;
+    queue_mention_deliveries(
+      &user_id,
+      &post_id,
+      FederateExtAction::UpdatePost(post_id),
+      &mentioned,
+      jobs,
+      queue,
+    )
+    .await?;
+
+    // Relays were sent the post when it was created, so they're sent the edit too
+    if posts.fetch_visibility_by_id(&post_id).await == Some(AccessType::PublicFederated) {
+      queue_relay_deliveries(
+        &user_id,
+        &post_id,
+        FederateExtAction::UpdatePost(post_id),
+        jobs,
+        relays,
+        queue,
+      )
+      .await?;
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,336 @@
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    follow_repository::FollowPool, job_repository::JobPool, user_block_repository::UserBlockPool,
+    user_repository::UserPool,
+  },
+  federation::activitypub::{FederateExtAction, FederateExtActorRef},
+  helpers::api::map_db_err,
+  model::{
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+  },
+  work_queue::queue::Queue,
+};
+
+use super::LogicErr;
+
+async fn queue_block_federation(
+  user_id: &Uuid,
+  blocked_user_id: &Uuid,
+  action: FederateExtAction,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(*user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(*blocked_user_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::FederateActivityPubExt)
+    .context(vec![user_id.to_string()])
+    .activitypub_federate_ext_action(action)
+    .activitypub_federate_ext_dest_actor(FederateExtActorRef::Person(*blocked_user_id))
+    .build();
+
+  queue.send_job(job).await
+}
+
+/// Blocks the specified user, severing any follows between the two users. The blocked user's posts and comments are
+/// hidden from the blocking user's feeds from then on, and vice versa.
+pub async fn create_block(
+  users: &UserPool,
+  follows: &FollowPool,
+  user_blocks: &UserBlockPool,
+  jobs: &JobPool,
+  queue: &Queue,
+  blocked_user_handle: &str,
+  user_id: &Uuid,
+) -> Result<(), LogicErr> {
+  let blocked_user = match users.fetch_by_handle(blocked_user_handle).await? {
+    Some(user) => user,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let blocked_user_id = blocked_user.user_id;
+
+  if blocked_user_id == *user_id {
+    return Err(LogicErr::InvalidOperation("Users can't block themselves".to_string()));
+  }
+
+  let user_block_id = user_blocks.create_block(user_id, &blocked_user_id).await?;
+  follows.delete_follow(user_id, &blocked_user_id).await?;
+  follows.delete_follow(&blocked_user_id, user_id).await?;
+
+  // Remote servers sever the follows on their end when they receive the Block, so there's no need to Undo them first
+  if blocked_user.is_external {
+    queue_block_federation(
+      user_id,
+      &blocked_user_id,
+      FederateExtAction::BlockProfile(user_block_id),
+      jobs,
+      queue,
+    )
+    .await?;
+  }
+
+  Ok(())
+}
+
+pub async fn delete_block(
+  users: &UserPool,
+  user_blocks: &UserBlockPool,
+  jobs: &JobPool,
+  queue: &Queue,
+  blocked_user_handle: &str,
+  user_id: &Uuid,
+) -> Result<(), LogicErr> {
+  let blocked_user = match users.fetch_by_handle(blocked_user_handle).await? {
+    Some(user) => user,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let blocked_user_id = blocked_user.user_id;
+
+  if !user_blocks.user_blocks_user(user_id, &blocked_user_id).await {
+    return Ok(());
+  }
+
+  let user_block_id = user_blocks.delete_block(user_id, &blocked_user_id).await?;
+
+  // The Undo needs to reference the Block we originally sent, otherwise remote servers won't know which to undo
+  if let (true, Some(user_block_id)) = (blocked_user.is_external, user_block_id) {
+    queue_block_federation(
+      user_id,
+      &blocked_user_id,
+      FederateExtAction::UnblockProfile(user_block_id),
+      jobs,
+      queue,
+    )
+    .await?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      follow_repository::{FollowPool, MockFollowRepo},
+      job_repository::{JobPool, MockJobRepo},
+      user_block_repository::{MockUserBlockRepo, UserBlockPool},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    federation::activitypub::FederateExtAction,
+    logic::{
+      block::{create_block, delete_block},
+      LogicErr,
+    },
+    model::user::User,
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  #[async_std::test]
+  async fn test_create_block_rejects_for_missing_user() {
+    let user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+
+    user_repo
+      .expect_fetch_by_handle()
+      .times(1)
+      .with(eq("user_handle"))
+      .return_const(Ok(None));
+
+    let users: UserPool = Arc::new(user_repo);
+    let follows: FollowPool = Arc::new(MockFollowRepo::new());
+    let user_blocks: UserBlockPool = Arc::new(MockUserBlockRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      create_block(&users, &follows, &user_blocks, &jobs, &queue, "user_handle", &user_id).await,
+      Err(LogicErr::MissingRecord)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_create_block_rejects_blocking_self() {
+    let user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+
+    user_repo
+      .expect_fetch_by_handle()
+      .times(1)
+      .with(eq("user_handle"))
+      .return_const(Ok(Some(User::test_local(user_id))));
+
+    let mut user_block_repo = MockUserBlockRepo::new();
+    user_block_repo.expect_create_block().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let follows: FollowPool = Arc::new(MockFollowRepo::new());
+    let user_blocks: UserBlockPool = Arc::new(user_block_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      create_block(&users, &follows, &user_blocks, &jobs, &queue, "user_handle", &user_id).await,
+      Err(LogicErr::InvalidOperation("Users can't block themselves".to_string()))
+    );
+  }
+
+  #[async_std::test]
+  async fn test_create_block_severs_follows() {
+    let user_id = Uuid::new_v4();
+    let blocked_user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+
+    user_repo
+      .expect_fetch_by_handle()
+      .times(1)
+      .with(eq("user_handle"))
+      .return_const(Ok(Some(User::test_local(blocked_user_id))));
+
+    let mut user_block_repo = MockUserBlockRepo::new();
+
+    user_block_repo
+      .expect_create_block()
+      .times(1)
+      .with(eq(user_id), eq(blocked_user_id))
+      .returning(|_, _| Ok(Uuid::new_v4()));
+
+    let mut follow_repo = MockFollowRepo::new();
+
+    follow_repo
+      .expect_delete_follow()
+      .times(1)
+      .with(eq(user_id), eq(blocked_user_id))
+      .returning(|_, _| Ok(()));
+
+    follow_repo
+      .expect_delete_follow()
+      .times(1)
+      .with(eq(blocked_user_id), eq(user_id))
+      .returning(|_, _| Ok(()));
+
+    let users: UserPool = Arc::new(user_repo);
+    let follows: FollowPool = Arc::new(follow_repo);
+    let user_blocks: UserBlockPool = Arc::new(user_block_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      create_block(&users, &follows, &user_blocks, &jobs, &queue, "user_handle", &user_id).await,
+      Ok(())
+    );
+  }
+
+  #[async_std::test]
+  async fn test_delete_block_ignores_unblocked_user() {
+    let user_id = Uuid::new_v4();
+    let blocked_user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+
+    user_repo
+      .expect_fetch_by_handle()
+      .times(1)
+      .with(eq("user_handle"))
+      .return_const(Ok(Some(User::test_local(blocked_user_id))));
+
+    let mut user_block_repo = MockUserBlockRepo::new();
+
+    user_block_repo
+      .expect_user_blocks_user()
+      .times(1)
+      .with(eq(user_id), eq(blocked_user_id))
+      .return_const(false);
+
+    user_block_repo.expect_delete_block().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let user_blocks: UserBlockPool = Arc::new(user_block_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      delete_block(&users, &user_blocks, &jobs, &queue, "user_handle", &user_id).await,
+      Ok(())
+    );
+  }
+
+  #[async_std::test]
+  async fn test_delete_block_undoes_original_block() {
+    let user_id = Uuid::new_v4();
+    let blocked_user_id = Uuid::new_v4();
+    let user_block_id = Uuid::new_v4();
+    let job_id = Uuid::new_v4();
+
+    let mut blocked_user = User::test_local(blocked_user_id);
+    blocked_user.is_external = true;
+
+    let mut user_repo = MockUserRepo::new();
+
+    user_repo
+      .expect_fetch_by_handle()
+      .times(1)
+      .with(eq("user_handle"))
+      .return_const(Ok(Some(blocked_user)));
+
+    let mut user_block_repo = MockUserBlockRepo::new();
+
+    user_block_repo
+      .expect_user_blocks_user()
+      .times(1)
+      .with(eq(user_id), eq(blocked_user_id))
+      .return_const(true);
+
+    user_block_repo
+      .expect_delete_block()
+      .times(1)
+      .with(eq(user_id), eq(blocked_user_id))
+      .returning(move |_, _| Ok(Some(user_block_id)));
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo.expect_create().times(1).returning(move |_| Ok(job_id));
+
+    let mut queue_be = MockQueueBackend::new();
+    queue_be
+      .expect_send_job()
+      .withf(move |job| {
+        matches!(
+          job.activitypub_federate_ext_action,
+          Some(FederateExtAction::UnblockProfile(id)) if id == user_block_id
+        )
+      })
+      .times(1)
+      .return_const(Ok(()));
+
+    let users: UserPool = Arc::new(user_repo);
+    let user_blocks: UserBlockPool = Arc::new(user_block_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(queue_be));
+
+    assert_eq!(
+      delete_block(&users, &user_blocks, &jobs, &queue, "user_handle", &user_id).await,
+      Ok(())
+    );
+  }
+}
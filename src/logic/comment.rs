@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{
@@ -5,21 +6,73 @@ use crate::{
     activity_convertible::ActivityConvertible, document::ActivityPubDocument,
     helpers::create_activitypub_ordered_collection_page,
   },
-  db::{comment_repository::CommentPool, follow_repository::FollowPool, post_repository::PostPool},
-  helpers::math::div_up,
-  model::{access_type::AccessType, comment_pub::CommentPub, response::ListResponse},
+  db::{
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, follow_repository::FollowPool,
+    job_repository::JobPool, mention_repository::MentionPool, post_repository::PostPool, user_repository::UserPool,
+  },
+  helpers::{api::map_db_err, math::div_up},
+  model::{
+    access_type::AccessType,
+    comment_pub::CommentPub,
+    comment_sort::CommentSort,
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+    response::ListResponse,
+  },
   settings::SETTINGS,
+  work_queue::queue::Queue,
 };
 
-use super::LogicErr;
+use super::{
+  mention::{link_mentions, record_mentions},
+  LogicErr,
+};
+
+/// How many levels of replies are included below a thread's comments when no depth is asked for
+pub const DEFAULT_COMMENT_DEPTH: i64 = 3;
+/// The most levels of replies that can be included below a thread's comments in one request
+pub const MAX_COMMENT_DEPTH: i64 = 8;
+/// The most replies included below each comment. Further replies are loaded by fetching the comment's own thread.
+pub const MAX_NESTED_REPLIES: i64 = 10;
+/// The most comments that can be fetched in one page
+pub const MAX_COMMENT_PAGE_SIZE: i64 = 100;
+
+async fn queue_comment_federation(
+  jobs: &JobPool,
+  queue: &Queue,
+  job_type: QueueJobType,
+  post_id: &Uuid,
+  comment_id: &Uuid,
+  user_id: &Uuid,
+) -> Result<(), LogicErr> {
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(user_id.to_owned()),
+      status: JobStatus::NotStarted,
+      record_id: Some(comment_id.to_owned()),
+      associated_record_id: Some(post_id.to_owned()),
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder().job_id(job_id).job_type(job_type).build();
+
+  queue.send_job(job).await
+}
 
 pub async fn create_comment(
   posts: &PostPool,
   follows: &FollowPool,
   comments: &CommentPool,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  mentions: &MentionPool,
+  jobs: &JobPool,
+  queue: &Queue,
   post_id: &Uuid,
   user_id: &Uuid,
   content_md: &str,
+  parent_comment_id: &Option<Uuid>,
 ) -> Result<CommentPub, LogicErr> {
   let visibility = match posts.fetch_visibility_by_id(post_id).await {
     Some(visibility) => visibility,
@@ -42,19 +95,45 @@ pub async fn create_comment(
     return Err(LogicErr::MissingRecord);
   }
 
-  let content_html = markdown::to_html(content_md);
+  // Replies can only be made to comments on the same post that the user is able to see
+  if let Some(parent_comment_id) = parent_comment_id {
+    if comments
+      .fetch_comment(post_id, parent_comment_id, &Some(user_id.to_owned()))
+      .await
+      .is_none()
+    {
+      return Err(LogicErr::MissingRecord);
+    }
+  }
+
+  let linked = link_mentions(content_md, users, domain_blocks).await;
+  let content_html = markdown::to_html(&linked.content_md);
 
   let comment_id = comments
-    .create_comment(user_id, post_id, content_md, &content_html)
+    .create_comment(user_id, post_id, content_md, &content_html, parent_comment_id)
     .await?;
 
-  match comments
+  record_mentions(&linked.mentioned, user_id, &None, &Some(comment_id), mentions).await?;
+
+  let comment = match comments
     .fetch_comment(post_id, &comment_id, &Some(user_id.to_owned()))
     .await
   {
-    Some(comment) => Ok(comment),
-    None => Err(LogicErr::MissingRecord),
-  }
+    Some(comment) => comment,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  queue_comment_federation(
+    jobs,
+    queue,
+    QueueJobType::FederateCreateComment,
+    post_id,
+    &comment_id,
+    user_id,
+  )
+  .await?;
+
+  Ok(comment)
 }
 
 pub async fn create_comment_like(
@@ -91,11 +170,26 @@ pub async fn create_comment_like(
 
 pub async fn delete_comment(
   comments: &CommentPool,
+  jobs: &JobPool,
+  queue: &Queue,
   post_id: &Uuid,
   comment_id: &Uuid,
   user_id: &Uuid,
 ) -> Result<(), LogicErr> {
-  comments.delete_comment(user_id, post_id, comment_id).await
+  // Nothing is federated for comments that don't exist or belong to someone else
+  if comments.delete_comment(user_id, post_id, comment_id).await? == 0 {
+    return Err(LogicErr::MissingRecord);
+  }
+
+  queue_comment_federation(
+    jobs,
+    queue,
+    QueueJobType::FederateDeleteComment,
+    post_id,
+    comment_id,
+    user_id,
+  )
+  .await
 }
 
 pub async fn delete_comment_like(
@@ -107,36 +201,133 @@ pub async fn delete_comment_like(
   comments.delete_comment_like(user_id, comment_id, post_id).await
 }
 
+/// Mirrors the database's `comment_controversy`: comments that have drawn plenty of both likes and replies, split
+/// evenly between them, score highest, and ones that have only drawn one or the other don't score at all
+fn comment_controversy(likes: i64, replies: i64) -> f64 {
+  let (least, greatest) = (likes.min(replies), likes.max(replies));
+  if least <= 0 {
+    return 0.0;
+  }
+
+  ((likes + replies) as f64).powf(least as f64 / greatest as f64)
+}
+
+/// Orders the replies to a comment the same way its thread's comments are ordered by the database
+pub fn sort_comments(comments: &mut [CommentPub], sort: &CommentSort) {
+  match sort {
+    CommentSort::New => comments.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+    CommentSort::Top => comments.sort_by(|a, b| b.likes.cmp(&a.likes).then(b.created_at.cmp(&a.created_at))),
+    CommentSort::Controversial => comments.sort_by(|a, b| {
+      let a_score = comment_controversy(a.likes, a.reply_count);
+      let b_score = comment_controversy(b.likes, b.reply_count);
+
+      b_score.total_cmp(&a_score).then(b.created_at.cmp(&a.created_at))
+    }),
+  }
+}
+
+/// Fetches a page of a thread's comments, i.e. a post's top-level comments or the replies to one of its comments, with
+/// their replies nested below them up to the given depth. Comments with more replies than are included report them in
+/// `reply_count`, and the rest can be loaded by fetching the thread of the comment they're replying to.
 pub async fn get_comments(
   comments: &CommentPool,
   post_id: &Uuid,
   own_user_id: &Option<Uuid>,
+  parent_comment_id: &Option<Uuid>,
+  sort: &Option<CommentSort>,
+  depth: &Option<i64>,
   page: &Option<i64>,
   page_size: &Option<i64>,
 ) -> Result<ListResponse<CommentPub>, LogicErr> {
   let page = page.unwrap_or(0);
-  let page_size = page_size.unwrap_or(20);
-  let comments_count = match comments.fetch_comments_count(post_id, own_user_id).await {
-    Ok(count) => count,
-    Err(err) => return Err(err),
-  };
+  let page_size = page_size.unwrap_or(20).clamp(1, MAX_COMMENT_PAGE_SIZE);
+  let sort = sort.unwrap_or_default();
+  let depth = depth.unwrap_or(DEFAULT_COMMENT_DEPTH).clamp(0, MAX_COMMENT_DEPTH);
+
+  let comments_count = comments
+    .fetch_comment_thread_count(post_id, parent_comment_id, own_user_id)
+    .await?;
 
   if comments_count == 0 {
     return Err(LogicErr::MissingRecord);
   }
 
-  match comments
-    .fetch_comments(post_id, own_user_id, page_size, page * page_size)
-    .await
-  {
-    Ok(comments) => Ok(ListResponse {
-      data: comments,
-      page,
-      total_items: comments_count,
-      total_pages: div_up(comments_count, page_size) + 1,
-    }),
-    Err(err) => Err(err),
+  let thread = comments
+    .fetch_comment_thread(
+      post_id,
+      parent_comment_id,
+      own_user_id,
+      &sort,
+      page_size,
+      page * page_size,
+    )
+    .await?;
+
+  // Each level of replies is fetched in one go for all of the comments in the level above it, and then nested from the
+  // bottom up once every level has been fetched
+  let mut levels: Vec<Vec<CommentPub>> = vec![thread];
+
+  for _ in 0..depth {
+    let parent_ids: Vec<Uuid> = match levels.last() {
+      Some(level) => level
+        .iter()
+        .filter(|comment| comment.reply_count > 0)
+        .map(|comment| comment.comment_id)
+        .collect(),
+      None => break,
+    };
+
+    if parent_ids.is_empty() {
+      break;
+    }
+
+    let mut replies_by_parent: HashMap<Uuid, Vec<CommentPub>> = HashMap::new();
+    for reply in comments
+      .fetch_comment_replies(post_id, &parent_ids, own_user_id, &sort, MAX_NESTED_REPLIES)
+      .await?
+    {
+      if let Some(parent_id) = reply.parent_comment_id {
+        replies_by_parent.entry(parent_id).or_default().push(reply);
+      }
+    }
+
+    let mut level: Vec<CommentPub> = vec![];
+    for parent_id in parent_ids.iter() {
+      if let Some(mut replies) = replies_by_parent.remove(parent_id) {
+        sort_comments(&mut replies, &sort);
+        level.append(&mut replies);
+      }
+    }
+
+    levels.push(level);
+  }
+
+  while levels.len() > 1 {
+    let replies = levels.pop().unwrap_or_default();
+    let parents = match levels.last_mut() {
+      Some(parents) => parents,
+      None => break,
+    };
+
+    let lookup: HashMap<Uuid, usize> = parents
+      .iter()
+      .enumerate()
+      .map(|(idx, parent)| (parent.comment_id, idx))
+      .collect();
+
+    for reply in replies {
+      if let Some(idx) = reply.parent_comment_id.and_then(|parent_id| lookup.get(&parent_id)) {
+        parents[*idx].replies.push(reply);
+      }
+    }
   }
+
+  Ok(ListResponse {
+    data: levels.pop().unwrap_or_default(),
+    page,
+    total_items: comments_count,
+    total_pages: div_up(comments_count, page_size) + 1,
+  })
 }
 
 pub async fn activitypub_get_comments(
@@ -153,7 +344,7 @@ pub async fn activitypub_get_comments(
   };
 
   let page = page.unwrap_or(0);
-  let page_size = page_size.unwrap_or(20);
+  let page_size = page_size.unwrap_or(20).clamp(1, MAX_COMMENT_PAGE_SIZE);
   let comments_count = match comments.fetch_comments_count(post_id, own_user_id).await {
     Ok(count) => count,
     Err(err) => return Err(err),
@@ -224,14 +415,22 @@ mod tests {
   use crate::{
     db::{
       comment_repository::{CommentPool, MockCommentRepo},
+      domain_block_repository::{DomainBlockPool, MockDomainBlockRepo},
       follow_repository::{FollowPool, MockFollowRepo},
+      job_repository::{JobPool, MockJobRepo},
+      mention_repository::{MentionPool, MockMentionRepo},
       post_repository::{MockPostRepo, PostPool},
+      user_repository::{MockUserRepo, UserPool},
     },
     logic::{
-      comment::{create_comment, create_comment_like, delete_comment, delete_comment_like},
+      comment::{
+        create_comment, create_comment_like, delete_comment, delete_comment_like, get_comments, sort_comments,
+        MAX_COMMENT_PAGE_SIZE, MAX_NESTED_REPLIES,
+      },
       LogicErr,
     },
-    model::{access_type::AccessType, comment_pub::CommentPub},
+    model::{access_type::AccessType, comment_pub::CommentPub, comment_sort::CommentSort},
+    work_queue::queue::{MockQueueBackend, Queue},
   };
 
   #[async_std::test]
@@ -250,9 +449,29 @@ mod tests {
     let posts: PostPool = Arc::new(post_repo);
     let follows: FollowPool = Arc::new(MockFollowRepo::new());
     let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
 
     assert_eq!(
-      create_comment(&posts, &follows, &comments, &post_id, &user_id, "test").await,
+      create_comment(
+        &posts,
+        &follows,
+        &comments,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &jobs,
+        &queue,
+        &post_id,
+        &user_id,
+        "test",
+        &None
+      )
+      .await,
       Err(LogicErr::MissingRecord)
     );
   }
@@ -279,9 +498,29 @@ mod tests {
     let posts: PostPool = Arc::new(post_repo);
     let follows: FollowPool = Arc::new(MockFollowRepo::new());
     let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
 
     assert_eq!(
-      create_comment(&posts, &follows, &comments, &post_id, &user_id, "test").await,
+      create_comment(
+        &posts,
+        &follows,
+        &comments,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &jobs,
+        &queue,
+        &post_id,
+        &user_id,
+        "test",
+        &None
+      )
+      .await,
       Err(LogicErr::MissingRecord)
     );
   }
@@ -308,9 +547,29 @@ mod tests {
     let posts: PostPool = Arc::new(post_repo);
     let follows: FollowPool = Arc::new(MockFollowRepo::new());
     let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
 
     assert_eq!(
-      create_comment(&posts, &follows, &comments, &post_id, &user_id, "test").await,
+      create_comment(
+        &posts,
+        &follows,
+        &comments,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &jobs,
+        &queue,
+        &post_id,
+        &user_id,
+        "test",
+        &None
+      )
+      .await,
       Err(LogicErr::UnauthorizedError)
     );
   }
@@ -337,9 +596,29 @@ mod tests {
     let posts: PostPool = Arc::new(post_repo);
     let follows: FollowPool = Arc::new(MockFollowRepo::new());
     let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
 
     assert_eq!(
-      create_comment(&posts, &follows, &comments, &post_id, &user_id, "test").await,
+      create_comment(
+        &posts,
+        &follows,
+        &comments,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &jobs,
+        &queue,
+        &post_id,
+        &user_id,
+        "test",
+        &None
+      )
+      .await,
       Err(LogicErr::UnauthorizedError)
     );
   }
@@ -374,9 +653,29 @@ mod tests {
     let posts: PostPool = Arc::new(post_repo);
     let follows: FollowPool = Arc::new(follow_repo);
     let comments: CommentPool = Arc::new(MockCommentRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
 
     assert_eq!(
-      create_comment(&posts, &follows, &comments, &post_id, &user_id, "test").await,
+      create_comment(
+        &posts,
+        &follows,
+        &comments,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &jobs,
+        &queue,
+        &post_id,
+        &user_id,
+        "test",
+        &None
+      )
+      .await,
       Err(LogicErr::MissingRecord)
     );
   }
@@ -405,15 +704,35 @@ mod tests {
     comment_repo
       .expect_create_comment()
       .times(1)
-      .with(eq(user_id), eq(post_id), eq("test"), always())
-      .returning(|_, _, _, _| Err(LogicErr::DbError("Boop".to_string())));
+      .with(eq(user_id), eq(post_id), eq("test"), always(), eq(None))
+      .returning(|_, _, _, _, _| Err(LogicErr::DbError("Boop".to_string())));
 
     let posts: PostPool = Arc::new(post_repo);
     let follows: FollowPool = Arc::new(MockFollowRepo::new());
     let comments: CommentPool = Arc::new(comment_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
 
     assert_eq!(
-      create_comment(&posts, &follows, &comments, &post_id, &user_id, "test").await,
+      create_comment(
+        &posts,
+        &follows,
+        &comments,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &jobs,
+        &queue,
+        &post_id,
+        &user_id,
+        "test",
+        &None
+      )
+      .await,
       Err(LogicErr::DbError("Boop".to_string()))
     );
   }
@@ -428,16 +747,24 @@ mod tests {
       comment_id: exp_comment_id,
       user_id,
       post_id,
+      post_uri: format!("/feed/{}", post_id),
+      parent_comment_id: None,
+      parent_comment_uri: None,
       content_md: "test".to_string(),
       content_html: "<p>test</p>".to_string(),
+      uri: format!("/feed/{}/comments/{}", post_id, exp_comment_id),
+      is_external: false,
       created_at: Utc::now(),
       updated_at: Utc::now(),
       user_handle: "a".to_string(),
       user_fediverse_id: "a".to_string(),
+      user_fediverse_uri: format!("/user/{}", user_id),
       user_avatar_url: Some("a".to_string()),
       likes: 0,
       liked: Some(true),
       visibility: AccessType::PublicFederated,
+      reply_count: 0,
+      replies: vec![],
     });
 
     let mut post_repo = MockPostRepo::new();
@@ -459,8 +786,8 @@ mod tests {
     comment_repo
       .expect_create_comment()
       .times(1)
-      .with(eq(user_id), eq(post_id), eq("test"), always())
-      .returning(move |_, _, _, _| Ok(comment_id));
+      .with(eq(user_id), eq(post_id), eq("test"), always(), eq(None))
+      .returning(move |_, _, _, _, _| Ok(comment_id));
 
     comment_repo
       .expect_fetch_comment()
@@ -470,11 +797,42 @@ mod tests {
 
     let posts: PostPool = Arc::new(post_repo);
     let follows: FollowPool = Arc::new(MockFollowRepo::new());
-    let comments: CommentPool = Arc::new(comment_repo);
+    let mut job_repo = MockJobRepo::new();
 
-    assert!(create_comment(&posts, &follows, &comments, &post_id, &user_id, "test")
-      .await
-      .is_ok());
+    job_repo
+      .expect_create()
+      .with(always())
+      .times(1)
+      .return_const(Ok(Uuid::new_v4()));
+
+    let mut queue_be = MockQueueBackend::new();
+
+    queue_be.expect_send_job().with(always()).times(1).return_const(Ok(()));
+
+    let comments: CommentPool = Arc::new(comment_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(queue_be));
+
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
+
+    assert!(create_comment(
+      &posts,
+      &follows,
+      &comments,
+      &users,
+      &domain_blocks,
+      &mentions,
+      &jobs,
+      &queue,
+      &post_id,
+      &user_id,
+      "test",
+      &None
+    )
+    .await
+    .is_ok());
   }
 
   #[async_std::test]
@@ -720,13 +1078,43 @@ mod tests {
       .returning(|_, _, _| Err(LogicErr::DbError("Boop".to_string())));
 
     let comments: CommentPool = Arc::new(comment_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
 
     assert_eq!(
-      delete_comment(&comments, &post_id, &comment_id, &user_id).await,
+      delete_comment(&comments, &jobs, &queue, &post_id, &comment_id, &user_id).await,
       Err(LogicErr::DbError("Boop".to_string()))
     );
   }
 
+  #[async_std::test]
+  async fn test_delete_comment_skips_federation_when_nothing_deleted() {
+    let post_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let comment_id = Uuid::new_v4();
+
+    let mut comment_repo = MockCommentRepo::new();
+
+    comment_repo
+      .expect_delete_comment()
+      .times(1)
+      .with(eq(user_id), eq(post_id), eq(comment_id))
+      .returning(|_, _, _| Ok(0));
+
+    let mut job_repo = MockJobRepo::new();
+
+    job_repo.expect_create().times(0);
+
+    let comments: CommentPool = Arc::new(comment_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      delete_comment(&comments, &jobs, &queue, &post_id, &comment_id, &user_id).await,
+      Err(LogicErr::MissingRecord)
+    );
+  }
+
   #[async_std::test]
   async fn test_delete_comment_succeeds() {
     let post_id = Uuid::new_v4();
@@ -739,11 +1127,28 @@ mod tests {
       .expect_delete_comment()
       .times(1)
       .with(eq(user_id), eq(post_id), eq(comment_id))
-      .returning(|_, _, _| Ok(()));
+      .returning(|_, _, _| Ok(1));
+
+    let mut job_repo = MockJobRepo::new();
+
+    job_repo
+      .expect_create()
+      .with(always())
+      .times(1)
+      .return_const(Ok(Uuid::new_v4()));
+
+    let mut queue_be = MockQueueBackend::new();
+
+    queue_be.expect_send_job().with(always()).times(1).return_const(Ok(()));
 
     let comments: CommentPool = Arc::new(comment_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(queue_be));
 
-    assert_eq!(delete_comment(&comments, &post_id, &comment_id, &user_id).await, Ok(()));
+    assert_eq!(
+      delete_comment(&comments, &jobs, &queue, &post_id, &comment_id, &user_id).await,
+      Ok(())
+    );
   }
 
   #[async_std::test]
@@ -789,4 +1194,133 @@ mod tests {
       Ok(())
     );
   }
+
+  fn test_comment(post_id: Uuid, parent_comment_id: Option<Uuid>, likes: i64, reply_count: i64) -> CommentPub {
+    let comment_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+
+    CommentPub {
+      comment_id,
+      user_id,
+      post_id,
+      post_uri: format!("/feed/{}", post_id),
+      parent_comment_id,
+      parent_comment_uri: parent_comment_id.map(|id| format!("/feed/{}/comments/{}", post_id, id)),
+      content_md: "test".to_string(),
+      content_html: "<p>test</p>".to_string(),
+      uri: format!("/feed/{}/comments/{}", post_id, comment_id),
+      is_external: false,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      user_handle: "a".to_string(),
+      user_fediverse_id: "a".to_string(),
+      user_fediverse_uri: format!("/user/{}", user_id),
+      user_avatar_url: None,
+      likes,
+      liked: Some(false),
+      visibility: AccessType::PublicFederated,
+      reply_count,
+      replies: vec![],
+    }
+  }
+
+  #[test]
+  fn test_sort_comments_by_top_and_controversial() {
+    let post_id = Uuid::new_v4();
+    let liked = test_comment(post_id, None, 10, 1);
+    let argued = test_comment(post_id, None, 4, 5);
+    let chatty = test_comment(post_id, None, 0, 8);
+
+    let mut comments = vec![chatty.clone(), argued.clone(), liked.clone()];
+    sort_comments(&mut comments, &CommentSort::Top);
+    assert_eq!(
+      comments.iter().map(|c| c.comment_id).collect::<Vec<Uuid>>(),
+      vec![liked.comment_id, argued.comment_id, chatty.comment_id]
+    );
+
+    sort_comments(&mut comments, &CommentSort::Controversial);
+    assert_eq!(
+      comments.iter().map(|c| c.comment_id).collect::<Vec<Uuid>>(),
+      vec![argued.comment_id, liked.comment_id, chatty.comment_id]
+    );
+  }
+
+  #[async_std::test]
+  async fn test_get_comments_nests_replies() {
+    let post_id = Uuid::new_v4();
+    let top = test_comment(post_id, None, 0, 1);
+    let reply = test_comment(post_id, Some(top.comment_id), 0, 1);
+    let top_id = top.comment_id;
+    let reply_id = reply.comment_id;
+
+    let mut comment_repo = MockCommentRepo::new();
+
+    comment_repo
+      .expect_fetch_comment_thread_count()
+      .times(1)
+      .with(eq(post_id), eq(None), eq(None))
+      .return_const(Ok(1));
+
+    comment_repo
+      .expect_fetch_comment_thread()
+      .times(1)
+      .with(eq(post_id), eq(None), eq(None), eq(CommentSort::New), eq(20), eq(0))
+      .return_const(Ok(vec![top]));
+
+    comment_repo
+      .expect_fetch_comment_replies()
+      .times(1)
+      .withf(move |p, parents, _, sort, limit| {
+        p == &post_id && parents == [top_id] && sort == &CommentSort::New && limit == &MAX_NESTED_REPLIES
+      })
+      .return_const(Ok(vec![reply]));
+
+    let comments: CommentPool = Arc::new(comment_repo);
+
+    // With a depth of one, the reply's own reply is left to be loaded from the reply's thread
+    let response = get_comments(&comments, &post_id, &None, &None, &None, &Some(1), &None, &None)
+      .await
+      .unwrap();
+
+    assert_eq!(response.data.len(), 1);
+    assert_eq!(response.data[0].replies.len(), 1);
+    assert_eq!(response.data[0].replies[0].comment_id, reply_id);
+    assert_eq!(response.data[0].replies[0].reply_count, 1);
+    assert!(response.data[0].replies[0].replies.is_empty());
+  }
+
+  #[async_std::test]
+  async fn test_get_comments_clamps_page_size() {
+    let post_id = Uuid::new_v4();
+
+    let mut comment_repo = MockCommentRepo::new();
+
+    comment_repo
+      .expect_fetch_comment_thread_count()
+      .times(1)
+      .return_const(Ok(1));
+
+    comment_repo
+      .expect_fetch_comment_thread()
+      .times(1)
+      .with(
+        eq(post_id),
+        eq(None),
+        eq(None),
+        eq(CommentSort::New),
+        eq(MAX_COMMENT_PAGE_SIZE),
+        eq(MAX_COMMENT_PAGE_SIZE * 2),
+      )
+      .return_const(Ok(vec![test_comment(post_id, None, 0, 0)]));
+
+    comment_repo.expect_fetch_comment_replies().times(0);
+
+    let comments: CommentPool = Arc::new(comment_repo);
+
+    let response = get_comments(&comments, &post_id, &None, &None, &None, &None, &Some(2), &Some(10_000))
+      .await
+      .unwrap();
+
+    assert_eq!(response.data.len(), 1);
+  }
 }
@@ -0,0 +1,264 @@
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+  db::{domain_block_repository::DomainBlockPool, user_repository::UserPool},
+  model::{domain_block::DomainBlock, domain_block_severity::DomainBlockSeverity},
+  settings::SETTINGS,
+};
+
+use super::{user::require_admin, LogicErr};
+
+fn normalize_domain(domain: &str) -> Result<String, LogicErr> {
+  let domain = domain
+    .trim()
+    .trim_start_matches("*.")
+    .trim_end_matches('.')
+    .to_lowercase();
+
+  if domain.is_empty() || domain.contains(|c: char| c.is_whitespace() || c == '/' || c == '@' || c == ':') {
+    return Err(LogicErr::InvalidOperation("Invalid domain".to_string()));
+  }
+
+  let local_domain = Url::parse(&SETTINGS.server.api_fqdn)
+    .ok()
+    .and_then(|url| url.host_str().map(|host| host.to_lowercase()));
+
+  if local_domain.as_deref() == Some(domain.as_str()) {
+    return Err(LogicErr::InvalidOperation(
+      "The local domain can't be blocked".to_string(),
+    ));
+  }
+
+  Ok(domain)
+}
+
+/// Splits a CSV line into its fields, unquoting any quoted fields along the way
+fn split_csv_line(line: &str) -> Vec<String> {
+  let mut fields = vec![];
+  let mut field = String::new();
+  let mut quoted = false;
+  let mut chars = line.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        field.push('"');
+        chars.next();
+      }
+      '"' => quoted = !quoted,
+      ',' if !quoted => fields.push(std::mem::take(&mut field)),
+      _ => field.push(c),
+    }
+  }
+
+  fields.push(field);
+  fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Parses a domain blocklist in the CSV format exported by Mastodon, i.e. with the columns `#domain`, `#severity`,
+/// `#reject_media`, `#reject_reports`, `#public_comment` and `#obfuscate`. Lists without a header are read in that
+/// column order, and entries that don't specify a severity are treated as suspensions.
+fn parse_domain_block_csv(csv: &str) -> Vec<(String, DomainBlockSeverity, Option<String>)> {
+  let mut lines = csv.lines().filter(|line| !line.trim().is_empty()).peekable();
+
+  let mut columns: Vec<String> = ["domain", "severity", "reject_media", "reject_reports", "public_comment"]
+    .iter()
+    .map(|c| c.to_string())
+    .collect();
+
+  if let Some(line) = lines.peek() {
+    let header: Vec<String> = split_csv_line(line)
+      .into_iter()
+      .map(|c| c.trim_start_matches('#').to_lowercase())
+      .collect();
+
+    if header.contains(&"domain".to_string()) {
+      columns = header;
+      lines.next();
+    }
+  }
+
+  let column = |fields: &[String], name: &str| -> Option<String> {
+    columns
+      .iter()
+      .position(|c| c == name)
+      .and_then(|i| fields.get(i))
+      .filter(|v| !v.is_empty())
+      .cloned()
+  };
+
+  let mut entries = vec![];
+
+  for line in lines {
+    let fields = split_csv_line(line);
+
+    let domain = match column(&fields, "domain") {
+      Some(domain) => domain,
+      None => continue,
+    };
+
+    let reject_media = column(&fields, "reject_media").map(|v| v.to_lowercase()) == Some("true".to_string());
+
+    let severity = match column(&fields, "severity").map(|v| v.to_lowercase()).as_deref() {
+      Some("suspend") | None => DomainBlockSeverity::Suspend,
+      Some("silence") => DomainBlockSeverity::Silence,
+      Some("noop") if reject_media => DomainBlockSeverity::RejectMedia,
+      _ => continue,
+    };
+
+    entries.push((domain, severity, column(&fields, "public_comment")));
+  }
+
+  entries
+}
+
+pub async fn get_domain_blocks(
+  user_id: &Uuid,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<Vec<DomainBlock>, LogicErr> {
+  require_admin(user_id, users).await?;
+
+  domain_blocks.fetch_domain_blocks().await
+}
+
+pub async fn create_domain_block(
+  user_id: &Uuid,
+  domain: &str,
+  severity: &DomainBlockSeverity,
+  public_comment: &Option<String>,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<(), LogicErr> {
+  require_admin(user_id, users).await?;
+
+  let domain = normalize_domain(domain)?;
+
+  domain_blocks
+    .create_domain_block(&domain, severity, public_comment)
+    .await
+}
+
+pub async fn delete_domain_block(
+  user_id: &Uuid,
+  domain: &str,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<(), LogicErr> {
+  require_admin(user_id, users).await?;
+
+  let domain = normalize_domain(domain)?;
+
+  domain_blocks.delete_domain_block(&domain).await
+}
+
+/// Imports a Mastodon-format domain blocklist, replacing any existing blocks on the listed domains. Returns the
+/// number of domains that were blocked.
+pub async fn import_domain_blocks(
+  user_id: &Uuid,
+  csv: &str,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+) -> Result<usize, LogicErr> {
+  require_admin(user_id, users).await?;
+
+  let mut imported = 0;
+
+  for (domain, severity, public_comment) in parse_domain_block_csv(csv) {
+    // Shared blocklists often contain entries we can't use, such as obfuscated domains, which we skip over
+    let domain = match normalize_domain(&domain) {
+      Ok(domain) => domain,
+      Err(_) => continue,
+    };
+
+    domain_blocks
+      .create_domain_block(&domain, &severity, &public_comment)
+      .await?;
+
+    imported += 1;
+  }
+
+  Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      domain_block_repository::{DomainBlockPool, MockDomainBlockRepo},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    logic::{
+      domain_block::{import_domain_blocks, parse_domain_block_csv},
+      LogicErr,
+    },
+    model::{domain_block_severity::DomainBlockSeverity, user::User},
+  };
+
+  #[test]
+  fn test_parse_domain_block_csv_reads_mastodon_export() {
+    let csv = r#"#domain,#severity,#reject_media,#reject_reports,#public_comment,#obfuscate
+spam.example,suspend,false,false,"Spam, and lots of it",false
+loud.example,silence,false,false,,false
+pics.example,noop,true,false,,false
+fine.example,noop,false,false,,false
+"#;
+
+    assert_eq!(
+      parse_domain_block_csv(csv),
+      vec![
+        (
+          "spam.example".to_string(),
+          DomainBlockSeverity::Suspend,
+          Some("Spam, and lots of it".to_string())
+        ),
+        ("loud.example".to_string(), DomainBlockSeverity::Silence, None),
+        ("pics.example".to_string(), DomainBlockSeverity::RejectMedia, None),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_parse_domain_block_csv_reads_domain_list() {
+    let csv = "spam.example\nloud.example,silence\n";
+
+    assert_eq!(
+      parse_domain_block_csv(csv),
+      vec![
+        ("spam.example".to_string(), DomainBlockSeverity::Suspend, None),
+        ("loud.example".to_string(), DomainBlockSeverity::Silence, None),
+      ]
+    );
+  }
+
+  #[async_std::test]
+  async fn test_import_domain_blocks_rejects_non_admin() {
+    let user_id = Uuid::new_v4();
+
+    let user = User::test_local(user_id);
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .times(1)
+      .with(eq(user_id))
+      .return_const(Ok(user));
+
+    let mut domain_block_repo = MockDomainBlockRepo::new();
+    domain_block_repo.expect_create_domain_block().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let domain_blocks: DomainBlockPool = Arc::new(domain_block_repo);
+
+    assert_eq!(
+      import_domain_blocks(&user_id, "spam.example", &users, &domain_blocks).await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+}
@@ -1,13 +1,17 @@
 use uuid::Uuid;
 
 use crate::{
-  db::{follow_repository::FollowPool, job_repository::JobPool, user_repository::UserPool},
+  db::{
+    follow_repository::FollowPool, job_repository::JobPool, user_block_repository::UserBlockPool,
+    user_repository::UserPool,
+  },
   federation::activitypub::{FederateExtAction, FederateExtActorRef},
   helpers::api::map_db_err,
   model::{
     job::{JobStatus, NewJob},
     queue_job::{QueueJob, QueueJobType},
   },
+  settings::SETTINGS,
   work_queue::queue::Queue,
 };
 
@@ -16,6 +20,7 @@ use super::LogicErr;
 pub async fn create_follow(
   users: &UserPool,
   follows: &FollowPool,
+  user_blocks: &UserBlockPool,
   jobs: &JobPool,
   queue: &Queue,
   following_user_handle: &str,
@@ -28,6 +33,14 @@ pub async fn create_follow(
 
   let following_user_id = following_user.user_id;
 
+  if user_blocks.user_blocks_user(user_id, &following_user_id).await
+    || user_blocks.user_blocks_user(&following_user_id, user_id).await
+  {
+    return Err(LogicErr::InvalidOperation(
+      "Users can't follow blocked users".to_string(),
+    ));
+  }
+
   if following_user.is_external {
     let job_id = jobs
       .create(NewJob {
@@ -51,6 +64,28 @@ pub async fn create_follow(
   }
 
   follows.create_follow(user_id, &following_user_id).await?;
+
+  // Remote users' existing posts never reach us on their own, so we import some of the recent ones now that someone
+  // here will see them
+  if following_user.is_external && SETTINGS.app.outbox_backfill_depth > 0 {
+    let job_id = jobs
+      .create(NewJob {
+        created_by_id: Some(*user_id),
+        status: JobStatus::NotStarted,
+        record_id: Some(following_user_id),
+        associated_record_id: None,
+      })
+      .await
+      .map_err(map_db_err)?;
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::BackfillOutbox)
+      .build();
+
+    queue.send_job(job).await?;
+  }
+
   Ok(())
 }
 
@@ -106,6 +141,7 @@ mod tests {
     db::{
       follow_repository::{FollowPool, MockFollowRepo},
       job_repository::{JobPool, MockJobRepo},
+      user_block_repository::{MockUserBlockRepo, UserBlockPool},
       user_repository::{MockUserRepo, UserPool},
     },
     logic::{
@@ -131,11 +167,21 @@ mod tests {
 
     let users: UserPool = Arc::new(user_repo);
     let follows: FollowPool = Arc::new(MockFollowRepo::new());
+    let user_blocks: UserBlockPool = Arc::new(MockUserBlockRepo::new());
     let jobs: JobPool = Arc::new(MockJobRepo::new());
     let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
 
     assert_eq!(
-      create_follow(&users, &follows, &jobs, &queue, &following_user_handle, &user_id).await,
+      create_follow(
+        &users,
+        &follows,
+        &user_blocks,
+        &jobs,
+        &queue,
+        &following_user_handle,
+        &user_id
+      )
+      .await,
       Err(LogicErr::MissingRecord)
     );
   }
@@ -174,6 +220,9 @@ mod tests {
       ext_apub_following_uri: None,
       ext_apub_inbox_uri: None,
       ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
       created_at: Utc::now(),
       updated_at: Utc::now(),
     };
@@ -186,6 +235,10 @@ mod tests {
       .with(eq("user_handle"))
       .return_const(Ok(Some(following_user)));
 
+    let mut user_block_repo = MockUserBlockRepo::new();
+
+    user_block_repo.expect_user_blocks_user().times(2).return_const(false);
+
     let mut follow_repo = MockFollowRepo::new();
 
     follow_repo
@@ -196,11 +249,21 @@ mod tests {
 
     let users: UserPool = Arc::new(user_repo);
     let follows: FollowPool = Arc::new(follow_repo);
+    let user_blocks: UserBlockPool = Arc::new(user_block_repo);
     let jobs: JobPool = Arc::new(MockJobRepo::new());
     let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
 
     assert_eq!(
-      create_follow(&users, &follows, &jobs, &queue, &following_user_handle, &user_id).await,
+      create_follow(
+        &users,
+        &follows,
+        &user_blocks,
+        &jobs,
+        &queue,
+        &following_user_handle,
+        &user_id
+      )
+      .await,
       Err(LogicErr::DbError("Boop".to_string()))
     );
   }
@@ -240,6 +303,9 @@ mod tests {
       ext_apub_following_uri: None,
       ext_apub_inbox_uri: None,
       ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
       created_at: Utc::now(),
       updated_at: Utc::now(),
     };
@@ -252,6 +318,10 @@ mod tests {
       .with(eq("user_handle"))
       .return_const(Ok(Some(following_user)));
 
+    let mut user_block_repo = MockUserBlockRepo::new();
+
+    user_block_repo.expect_user_blocks_user().times(2).return_const(false);
+
     let mut follow_repo = MockFollowRepo::new();
 
     follow_repo
@@ -262,15 +332,107 @@ mod tests {
 
     let users: UserPool = Arc::new(user_repo);
     let follows: FollowPool = Arc::new(follow_repo);
+    let user_blocks: UserBlockPool = Arc::new(user_block_repo);
     let jobs: JobPool = Arc::new(MockJobRepo::new());
     let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
 
     assert_eq!(
-      create_follow(&users, &follows, &jobs, &queue, &following_user_handle, &user_id).await,
+      create_follow(
+        &users,
+        &follows,
+        &user_blocks,
+        &jobs,
+        &queue,
+        &following_user_handle,
+        &user_id
+      )
+      .await,
       Ok(())
     );
   }
 
+  #[async_std::test]
+  async fn test_create_follow_rejects_blocked_user() {
+    let user_id = Uuid::new_v4();
+    let following_user_id = Uuid::new_v4();
+    let following_user_handle = "user_handle".to_string();
+
+    let following_user = User {
+      user_id: following_user_id,
+      fediverse_id: "user@127.0.0.1:8000".to_string(),
+      handle: "a".to_string(),
+      fediverse_uri: "d".to_string(),
+      avatar_url: None,
+      email: Some("b".to_string()),
+      password_hash: Some("c".to_string()),
+      is_external: false,
+      url_1: None,
+      url_2: None,
+      url_3: None,
+      url_4: None,
+      url_5: None,
+      url_1_title: None,
+      url_2_title: None,
+      url_3_title: None,
+      url_4_title: None,
+      url_5_title: None,
+      intro_md: None,
+      intro_html: None,
+      private_key: "d".to_string(),
+      public_key: "e".to_string(),
+      ext_apub_followers_uri: None,
+      ext_apub_following_uri: None,
+      ext_apub_inbox_uri: None,
+      ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+    };
+
+    let mut user_repo = MockUserRepo::new();
+
+    user_repo
+      .expect_fetch_by_handle()
+      .times(1)
+      .with(eq("user_handle"))
+      .return_const(Ok(Some(following_user)));
+
+    let mut user_block_repo = MockUserBlockRepo::new();
+
+    user_block_repo
+      .expect_user_blocks_user()
+      .with(eq(user_id), eq(following_user_id))
+      .return_const(false);
+    user_block_repo
+      .expect_user_blocks_user()
+      .with(eq(following_user_id), eq(user_id))
+      .return_const(true);
+
+    let users: UserPool = Arc::new(user_repo);
+    let follows: FollowPool = Arc::new(MockFollowRepo::new());
+    let user_blocks: UserBlockPool = Arc::new(user_block_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      create_follow(
+        &users,
+        &follows,
+        &user_blocks,
+        &jobs,
+        &queue,
+        &following_user_handle,
+        &user_id
+      )
+      .await,
+      Err(LogicErr::InvalidOperation(
+        "Users can't follow blocked users".to_string()
+      ))
+    );
+  }
+
   #[async_std::test]
   async fn test_delete_follow_rejects_for_missing_user() {
     let user_id = Uuid::new_v4();
@@ -329,6 +491,9 @@ mod tests {
       ext_apub_following_uri: None,
       ext_apub_inbox_uri: None,
       ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
       created_at: Utc::now(),
       updated_at: Utc::now(),
     };
@@ -394,6 +559,9 @@ mod tests {
       ext_apub_following_uri: None,
       ext_apub_inbox_uri: None,
       ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
       created_at: Utc::now(),
       updated_at: Utc::now(),
     };
@@ -0,0 +1,106 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use uuid::Uuid;
+
+use crate::{db::post_repository::PostPool, model::post_event::PostEvent, settings::SETTINGS};
+
+use super::LogicErr;
+
+lazy_static! {
+  /// Matches `#tag`, so long as the tag isn't part of a word, a URL fragment, an HTML entity or a link
+  static ref HASHTAG_REGEX: Regex = Regex::new(r"(^|[^\w&/#\[])#(\w+)").unwrap();
+}
+
+pub struct LinkedHashtags {
+  pub content_md: String,
+  /// The normalized names of the hashtags found in the content
+  pub hashtags: Vec<String>,
+}
+
+/// Hashtags are matched case-insensitively, so `#Rust` and `#rust` end up in the same feed
+pub fn normalize_hashtag(name: &str) -> Option<String> {
+  let name = name.trim_start_matches('#');
+
+  if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+    return None;
+  }
+
+  // Purely numeric tags are far more likely to be issue numbers or rankings than topics
+  if name.chars().all(|c| c.is_ascii_digit()) {
+    return None;
+  }
+
+  Some(name.to_lowercase())
+}
+
+/// Rewrites each `#tag` in a post's markdown into a link to the tag's feed, and collects the tags that were found
+pub fn link_hashtags(content_md: &str) -> LinkedHashtags {
+  let mut hashtags: Vec<String> = vec![];
+
+  let linked_md = HASHTAG_REGEX.replace_all(content_md, |caps: &Captures| match normalize_hashtag(&caps[2]) {
+    Some(name) => {
+      let link = format!("{}[#{}]({}/tags/{})", &caps[1], &caps[2], SETTINGS.server.fqdn, name);
+
+      if !hashtags.contains(&name) {
+        hashtags.push(name);
+      }
+
+      link
+    }
+    None => caps[0].to_string(),
+  });
+
+  LinkedHashtags {
+    content_md: linked_md.to_string(),
+    hashtags,
+  }
+}
+
+pub async fn get_hashtag_posts(
+  hashtag: &str,
+  own_user_id: &Option<Uuid>,
+  limit: i64,
+  skip: i64,
+  posts: &PostPool,
+) -> Result<Vec<PostEvent>, LogicErr> {
+  match normalize_hashtag(hashtag) {
+    Some(hashtag) => posts.fetch_hashtag_feed(&hashtag, own_user_id, limit, skip).await,
+    None => Ok(vec![]),
+  }
+}
+
+pub async fn get_hashtag_posts_count(
+  hashtag: &str,
+  own_user_id: &Option<Uuid>,
+  posts: &PostPool,
+) -> Result<i64, LogicErr> {
+  match normalize_hashtag(hashtag) {
+    Some(hashtag) => posts.count_hashtag_feed(&hashtag, own_user_id).await,
+    None => Ok(0),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::logic::hashtag::{link_hashtags, normalize_hashtag};
+
+  #[test]
+  fn normalize_hashtag_lowercases() {
+    assert_eq!(normalize_hashtag("#RustLang"), Some("rustlang".to_string()));
+  }
+
+  #[test]
+  fn normalize_hashtag_rejects_numeric_tags() {
+    assert_eq!(normalize_hashtag("#1234"), None);
+  }
+
+  #[test]
+  fn link_hashtags_collects_unique_tags() {
+    let linked = link_hashtags("#Rust is great, #rust #2023 see example.com/#anchor and [#link](/a)");
+
+    assert_eq!(linked.hashtags, vec!["rust".to_string()]);
+    assert!(linked.content_md.contains("[#Rust]("));
+    assert!(linked.content_md.contains("#2023"));
+    assert!(linked.content_md.contains("example.com/#anchor"));
+  }
+}
@@ -0,0 +1,101 @@
+use rsa::{
+  pkcs1::{EncodeRsaPrivateKey, EncodeRsaPublicKey},
+  pkcs8::LineEnding,
+  RsaPrivateKey, RsaPublicKey,
+};
+
+use super::LogicErr;
+use crate::{db::instance_actor_repository::InstanceActorPool, model::instance_actor::InstanceActor};
+
+/// Fetches the instance actor, generating its key pair the first time the server boots.
+pub async fn get_instance_actor(instance_actors: &InstanceActorPool) -> Result<InstanceActor, LogicErr> {
+  if let Some(actor) = instance_actors.fetch_instance_actor().await {
+    return Ok(actor);
+  }
+
+  let mut rng = rand::thread_rng();
+  let bits = 2048;
+  let priv_key = match RsaPrivateKey::new(&mut rng, bits) {
+    Ok(key) => key,
+    Err(err) => return Err(LogicErr::InternalError(err.to_string())),
+  };
+  let pub_key = RsaPublicKey::from(&priv_key);
+
+  let priv_key = match priv_key.to_pkcs1_pem(LineEnding::LF) {
+    Ok(key) => key.to_string(),
+    Err(err) => return Err(LogicErr::InternalError(err.to_string())),
+  };
+
+  let pub_key = match pub_key.to_pkcs1_pem(LineEnding::LF) {
+    Ok(key) => key.to_string(),
+    Err(err) => return Err(LogicErr::InternalError(err.to_string())),
+  };
+
+  instance_actors.create_instance_actor(&priv_key, &pub_key).await
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::instance_actor_repository::{InstanceActorPool, MockInstanceActorRepo},
+    logic::{instance_actor::get_instance_actor, LogicErr},
+    model::instance_actor::InstanceActor,
+  };
+
+  fn build_instance_actor() -> InstanceActor {
+    InstanceActor {
+      instance_actor_id: Uuid::new_v4(),
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      private_key: "private".to_string(),
+      public_key: "public".to_string(),
+    }
+  }
+
+  #[async_std::test]
+  async fn test_get_instance_actor_returns_existing_actor() {
+    let actor = build_instance_actor();
+
+    let mut instance_actor_repo = MockInstanceActorRepo::new();
+
+    instance_actor_repo
+      .expect_fetch_instance_actor()
+      .times(1)
+      .return_const(Some(actor.clone()));
+
+    instance_actor_repo.expect_create_instance_actor().times(0);
+
+    let instance_actors: InstanceActorPool = Arc::new(instance_actor_repo);
+
+    assert_eq!(get_instance_actor(&instance_actors).await, Ok(actor));
+  }
+
+  #[async_std::test]
+  async fn test_get_instance_actor_rejects_db_err_passthrough() {
+    let mut instance_actor_repo = MockInstanceActorRepo::new();
+
+    instance_actor_repo
+      .expect_fetch_instance_actor()
+      .times(1)
+      .return_const(None);
+
+    instance_actor_repo
+      .expect_create_instance_actor()
+      .times(1)
+      .with(always(), always())
+      .return_const(Err(LogicErr::DbError("Failed".to_string())));
+
+    let instance_actors: InstanceActorPool = Arc::new(instance_actor_repo);
+
+    assert_eq!(
+      get_instance_actor(&instance_actors).await,
+      Err(LogicErr::DbError("Failed".to_string()))
+    );
+  }
+}
@@ -0,0 +1,507 @@
+use std::{collections::HashMap, ops::Range};
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, mention_repository::MentionPool,
+    post_repository::PostPool, user_repository::UserPool,
+  },
+  federation::activitypub::actor::federate_webfinger_user,
+  helpers::types::RELATIVE_API_ROOT_FQDN,
+  model::{mention::Mention, user::User, webfinger::WebfingerRecordLink},
+  settings::SETTINGS,
+};
+
+use super::{hashtag::link_hashtags, LogicErr};
+
+lazy_static! {
+  /// Matches `@handle` and `@handle@host`, so long as the mention isn't part of an email address, a URL or a link
+  static ref MENTION_REGEX: Regex =
+    Regex::new(r"(^|[^\w@/\[])@(\w+)(?:@([A-Za-z0-9\-]+(?:\.[A-Za-z0-9\-]+)+))?").unwrap();
+}
+
+/// The most distinct users a single post or comment can mention. Anything past this is left as it was written, so that
+/// one post can't have us look up an unbounded number of accounts.
+const MAX_MENTIONS: usize = 20;
+
+pub struct LinkedMentions {
+  pub content_md: String,
+  /// The users mentioned in the content that we were able to resolve
+  pub mentioned: Vec<User>,
+}
+
+fn backtick_run(bytes: &[u8], idx: usize) -> usize {
+  bytes[idx..].iter().take_while(|b| **b == b'`').count()
+}
+
+/// Finds where the code span opened by `run` backticks closes, which is at the next run of exactly as many backticks
+fn find_code_span_end(bytes: &[u8], from: usize, run: usize, limit: usize) -> Option<usize> {
+  let mut idx = from;
+
+  while idx < limit {
+    match bytes[idx] {
+      b'`' => {
+        let len = backtick_run(&bytes[..limit], idx);
+        if len == run {
+          return Some(idx + len);
+        }
+
+        idx += len;
+      }
+      _ => idx += 1,
+    }
+  }
+
+  None
+}
+
+/// Finds the byte ranges of the code blocks and code spans in markdown. Anything that looks like a mention inside them
+/// is shown exactly as it was written.
+fn markdown_code_ranges(content_md: &str) -> Vec<Range<usize>> {
+  let mut blocks: Vec<Range<usize>> = vec![];
+  // The fence character and length of the fenced code block we're in, along with where the block starts
+  let mut fence: Option<(char, usize, usize)> = None;
+  let mut in_indented_code = false;
+  let mut prev_blank = true;
+  let mut offset = 0;
+
+  for line in content_md.split_inclusive('\n') {
+    let start = offset;
+    offset += line.len();
+
+    let trimmed = line.trim_start_matches(' ');
+    let indent = line.len() - trimmed.len();
+    let is_blank = line.trim().is_empty();
+
+    if let Some((fence_char, fence_len, fence_start)) = fence {
+      let run = trimmed.chars().take_while(|c| *c == fence_char).count();
+
+      if indent < 4 && run >= fence_len && trimmed[run..].trim().is_empty() {
+        blocks.push(fence_start..offset);
+        fence = None;
+      }
+
+      continue;
+    }
+
+    if indent < 4 {
+      if let Some(fence_char) = trimmed.chars().next().filter(|c| *c == '`' || *c == '~') {
+        let run = trimmed.chars().take_while(|c| *c == fence_char).count();
+
+        // A run of backticks followed by more backticks on the same line is a code span rather than a fence
+        if run >= 3 && !(fence_char == '`' && trimmed[run..].contains('`')) {
+          fence = Some((fence_char, run, start));
+          in_indented_code = false;
+          prev_blank = false;
+          continue;
+        }
+      }
+    }
+
+    // Indented code can't interrupt a paragraph, so it has to follow a blank line or more indented code
+    let indented = indent >= 4 || trimmed.starts_with('\t');
+    if !is_blank && indented && (prev_blank || in_indented_code) {
+      blocks.push(start..offset);
+      in_indented_code = true;
+    } else if !is_blank {
+      in_indented_code = false;
+    }
+
+    prev_blank = is_blank;
+  }
+
+  // A fence that's never closed runs to the end of the document
+  if let Some((_, _, fence_start)) = fence {
+    blocks.push(fence_start..content_md.len());
+  }
+
+  let bytes = content_md.as_bytes();
+  let mut spans: Vec<Range<usize>> = vec![];
+  let mut idx = 0;
+
+  while idx < bytes.len() {
+    if let Some(block) = blocks.iter().find(|block| block.contains(&idx)) {
+      idx = block.end;
+      continue;
+    }
+
+    match bytes[idx] {
+      b'\\' => idx += 2,
+      b'`' => {
+        let run = backtick_run(bytes, idx);
+        // Code spans can't carry on into the next code block
+        let limit = blocks
+          .iter()
+          .map(|block| block.start)
+          .filter(|start| *start > idx)
+          .min()
+          .unwrap_or(bytes.len());
+
+        match find_code_span_end(bytes, idx + run, run, limit) {
+          Some(end) => {
+            spans.push(idx..end);
+            idx = end;
+          }
+          None => idx += run,
+        }
+      }
+      _ => idx += 1,
+    }
+  }
+
+  blocks.extend(spans);
+  blocks
+}
+
+/// Finds the `@handle` and `@handle@host` mentions in markdown, skipping any in code
+fn find_mentions(content_md: &str) -> Vec<Captures> {
+  let code = markdown_code_ranges(content_md);
+
+  MENTION_REGEX
+    .captures_iter(content_md)
+    .filter(|caps| {
+      let start = caps.get(2).map(|handle| handle.start()).unwrap_or_default();
+      !code.iter().any(|range| range.contains(&start))
+    })
+    .collect()
+}
+
+fn mention_host<'a>(caps: &'a Captures) -> Option<&'a str> {
+  match caps.get(3).map(|host| host.as_str()) {
+    Some(host) if host == *RELATIVE_API_ROOT_FQDN => None,
+    host => host,
+  }
+}
+
+fn mention_key(handle: &str, host: Option<&str>) -> String {
+  match host {
+    Some(host) => format!("@{}@{}", handle, host.to_lowercase()),
+    None => format!("@{}", handle),
+  }
+}
+
+fn mention_profile_uri(user: &User) -> String {
+  match user.is_external {
+    true => user.fediverse_uri.to_owned(),
+    false => WebfingerRecordLink::build_profile_page_link(&user.handle)
+      .href
+      .unwrap_or_default(),
+  }
+}
+
+async fn resolve_mention(
+  handle: &str,
+  host: Option<&str>,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  resolve_remote: bool,
+) -> Option<User> {
+  match host {
+    Some(host) if resolve_remote => federate_webfinger_user(handle, host, users, domain_blocks).await.ok(),
+    Some(host) => users
+      .fetch_by_fediverse_id(&format!("@{}@{}", handle, host))
+      .await
+      .ok()
+      .flatten(),
+    None => users
+      .fetch_by_fediverse_id(&format!("@{}@{}", handle, SETTINGS.server.fqdn))
+      .await
+      .ok()
+      .flatten()
+      .filter(|user| !user.is_external),
+  }
+}
+
+async fn link_mentions_with(
+  content_md: &str,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  resolve_remote: bool,
+) -> LinkedMentions {
+  let mentions = find_mentions(content_md);
+  let mut attempted: Vec<String> = vec![];
+  let mut resolved: HashMap<String, User> = HashMap::new();
+
+  for caps in mentions.iter() {
+    let handle = &caps[2];
+    let host = mention_host(caps);
+    let key = mention_key(handle, host);
+
+    if attempted.contains(&key) {
+      continue;
+    }
+
+    if attempted.len() >= MAX_MENTIONS {
+      break;
+    }
+
+    if let Some(user) = resolve_mention(handle, host, users, domain_blocks, resolve_remote).await {
+      resolved.insert(key.clone(), user);
+    }
+
+    attempted.push(key);
+  }
+
+  let mut linked_md = String::with_capacity(content_md.len());
+  let mut last = 0;
+
+  for caps in mentions.iter() {
+    let user = match resolved.get(&mention_key(&caps[2], mention_host(caps))) {
+      Some(user) => user,
+      None => continue,
+    };
+
+    let mention = match caps.get(0) {
+      Some(mention) => mention,
+      None => continue,
+    };
+
+    linked_md.push_str(&content_md[last..mention.start()]);
+    linked_md.push_str(&format!(
+      "{}[{}]({})",
+      &caps[1],
+      &caps[0][caps[1].len()..],
+      mention_profile_uri(user)
+    ));
+    last = mention.end();
+  }
+
+  linked_md.push_str(&content_md[last..]);
+
+  let mut mentioned: Vec<User> = vec![];
+  for user in resolved.into_values() {
+    if !mentioned.iter().any(|u| u.user_id == user.user_id) {
+      mentioned.push(user);
+    }
+  }
+
+  LinkedMentions {
+    content_md: linked_md,
+    mentioned,
+  }
+}
+
+/// Rewrites each `@handle` or `@handle@host` mention of a user we already know about in a post or comment's markdown
+/// into a link to the mentioned user's profile. Mentions we can't resolve are left as they were written, and remote
+/// users we haven't come across yet are resolved by [resolve_post_mentions] and [resolve_comment_mentions] once the post
+/// or comment is federated, as finding them means asking their server.
+pub async fn link_mentions(content_md: &str, users: &UserPool, domain_blocks: &DomainBlockPool) -> LinkedMentions {
+  link_mentions_with(content_md, users, domain_blocks, false).await
+}
+
+/// Looks up the remote users mentioned in one of our users' posts that we didn't know about when it was written, then
+/// links and records their mentions
+pub async fn resolve_post_mentions(
+  post_id: &Uuid,
+  posts: &PostPool,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
+  let mut post = match posts.find_optional_by_id(post_id).await {
+    Some(post) if !post.is_external => post,
+    _ => return Ok(()),
+  };
+
+  let linked_mentions = link_mentions_with(&post.content_md, users, domain_blocks, true).await;
+
+  let already_mentioned = mentions.fetch_post_mentioned_users(post_id).await?;
+  let newly_mentioned: Vec<User> = linked_mentions
+    .mentioned
+    .into_iter()
+    .filter(|user| !already_mentioned.iter().any(|u| u.user_id == user.user_id))
+    .collect();
+
+  if newly_mentioned.is_empty() {
+    return Ok(());
+  }
+
+  let linked_hashtags = link_hashtags(&linked_mentions.content_md);
+  post.content_html = markdown::to_html(&linked_hashtags.content_md);
+  posts.update_post_content(&post).await?;
+
+  record_mentions(&newly_mentioned, &post.user_id, &Some(*post_id), &None, mentions).await
+}
+
+/// Looks up the remote users mentioned in one of our users' comments that we didn't know about when it was written,
+/// then links and records their mentions
+pub async fn resolve_comment_mentions(
+  comment_id: &Uuid,
+  comments: &CommentPool,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
+  let mut comment = match comments.find_optional_by_id(comment_id).await {
+    Some(comment) if !comment.is_external => comment,
+    _ => return Ok(()),
+  };
+
+  let linked = link_mentions_with(&comment.content_md, users, domain_blocks, true).await;
+
+  let already_mentioned = mentions.fetch_comment_mentioned_users(comment_id).await?;
+  let newly_mentioned: Vec<User> = linked
+    .mentioned
+    .into_iter()
+    .filter(|user| !already_mentioned.iter().any(|u| u.user_id == user.user_id))
+    .collect();
+
+  if newly_mentioned.is_empty() {
+    return Ok(());
+  }
+
+  comment.content_html = markdown::to_html(&linked.content_md);
+  comments.update_comment_content(&comment).await?;
+
+  record_mentions(&newly_mentioned, &comment.user_id, &None, &Some(*comment_id), mentions).await
+}
+
+/// Records the users mentioned in a post or comment. Local users are notified of the mention, and remote users are
+/// delivered the post or comment directly when it's federated.
+pub async fn record_mentions(
+  mentioned: &[User],
+  author_id: &Uuid,
+  post_id: &Option<Uuid>,
+  comment_id: &Option<Uuid>,
+  mentions: &MentionPool,
+) -> Result<(), LogicErr> {
+  // Users don't need to be told that they've mentioned themselves
+  for user in mentioned.iter().filter(|user| &user.user_id != author_id) {
+    mentions
+      .create_mention(&user.user_id, author_id, post_id, comment_id)
+      .await?;
+  }
+
+  Ok(())
+}
+
+pub async fn get_user_mentions(
+  user_id: &Uuid,
+  limit: i64,
+  skip: i64,
+  mentions: &MentionPool,
+) -> Result<Vec<Mention>, LogicErr> {
+  mentions.fetch_user_mentions(user_id, limit, skip).await
+}
+
+pub async fn count_user_mentions(user_id: &Uuid, mentions: &MentionPool) -> Result<i64, LogicErr> {
+  mentions.count_user_mentions(user_id).await
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use mockall::predicate::*;
+
+  use crate::{
+    db::{
+      domain_block_repository::{DomainBlockPool, MockDomainBlockRepo},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    logic::mention::{find_mentions, link_mentions, MAX_MENTIONS},
+  };
+
+  fn mentioned_handles(content_md: &str) -> Vec<String> {
+    find_mentions(content_md)
+      .iter()
+      .map(|caps| caps[0][caps[1].len()..].to_string())
+      .collect()
+  }
+
+  #[test]
+  fn find_mentions_matches_local_and_remote_handles() {
+    assert_eq!(
+      mentioned_handles("@alice and @bob@remote.example, hi!"),
+      vec!["@alice".to_string(), "@bob@remote.example".to_string()]
+    );
+  }
+
+  #[test]
+  fn find_mentions_skips_emails_urls_and_links() {
+    assert_eq!(
+      mentioned_handles("mail me@example.com, see https://a.example/@alice or [@bob](/profile/bob)"),
+      Vec::<String>::new()
+    );
+  }
+
+  #[test]
+  fn find_mentions_skips_code_spans() {
+    assert_eq!(
+      mentioned_handles("use `@decorator` or ``@a ` b`` but tell @alice"),
+      vec!["@alice".to_string()]
+    );
+  }
+
+  #[test]
+  fn find_mentions_skips_fenced_code_blocks() {
+    let content_md = "@alice\n\n```python\n@decorator\ndef f(): pass\n```\n\n~~~\n@bob\n~~~\n@carol";
+
+    assert_eq!(
+      mentioned_handles(content_md),
+      vec!["@alice".to_string(), "@carol".to_string()]
+    );
+  }
+
+  #[test]
+  fn find_mentions_skips_unclosed_fenced_code_blocks() {
+    assert_eq!(mentioned_handles("@alice\n```\n@bob"), vec!["@alice".to_string()]);
+  }
+
+  #[test]
+  fn find_mentions_skips_indented_code_blocks() {
+    let content_md = "hi @alice\n\n    @decorator\n    def f(): pass\n\n@bob";
+
+    assert_eq!(
+      mentioned_handles(content_md),
+      vec!["@alice".to_string(), "@bob".to_string()]
+    );
+  }
+
+  #[test]
+  fn find_mentions_keeps_mentions_after_escaped_backticks() {
+    assert_eq!(mentioned_handles("\\`@alice\\`"), vec!["@alice".to_string()]);
+  }
+
+  #[async_std::test]
+  async fn link_mentions_looks_up_unknown_remote_users_locally() {
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_fediverse_id()
+      .with(eq("@bob@remote.example"))
+      .times(1)
+      .return_const(Ok(None));
+
+    let users: UserPool = Arc::new(user_repo);
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+
+    let linked = link_mentions("hi @bob@remote.example", &users, &domain_blocks).await;
+
+    assert_eq!(linked.content_md, "hi @bob@remote.example".to_string());
+    assert!(linked.mentioned.is_empty());
+  }
+
+  #[async_std::test]
+  async fn link_mentions_caps_lookups() {
+    let content_md = (0..MAX_MENTIONS + 5)
+      .map(|idx| format!("@user{} @user{}", idx, idx))
+      .collect::<Vec<String>>()
+      .join(" ");
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_fediverse_id()
+      .times(MAX_MENTIONS)
+      .return_const(Ok(None));
+
+    let users: UserPool = Arc::new(user_repo);
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+
+    let linked = link_mentions(&content_md, &users, &domain_blocks).await;
+
+    assert_eq!(linked.content_md, content_md);
+  }
+}
@@ -3,11 +3,19 @@ use std::error::Error;
 use strum::Display;
 
 pub mod app;
+pub mod block;
 pub mod comment;
+pub mod domain_block;
 pub mod follow;
+pub mod hashtag;
+pub mod instance_actor;
 pub mod job;
 pub mod like;
+pub mod mention;
+pub mod poll;
 pub mod post;
+pub mod relay;
+pub mod report;
 pub mod user;
 
 #[derive(Debug, PartialEq, Eq, Clone, Display)]
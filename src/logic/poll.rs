@@ -0,0 +1,323 @@
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+  db::{follow_repository::FollowPool, job_repository::JobPool, poll_repository::PollPool, post_repository::PostPool},
+  helpers::api::map_db_err,
+  model::{
+    access_type::AccessType,
+    job::{JobStatus, NewJob},
+    poll::PostPoll,
+    queue_job::{QueueJob, QueueJobType},
+  },
+  work_queue::queue::Queue,
+};
+
+use super::LogicErr;
+
+/// The most options a poll created by one of our users can have
+pub const MAX_POLL_OPTIONS: usize = 10;
+/// The longest an option's name can be, in characters
+pub const MAX_POLL_OPTION_LENGTH: usize = 512;
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct NewPollRequest {
+  pub options: Vec<String>,
+  #[serde(default)]
+  pub multiple_choice: bool,
+  /// How long the poll stays open for, in seconds. Polls without an expiry stay open until their post is deleted.
+  pub expires_in: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct PollVoteRequest {
+  pub choices: Vec<Uuid>,
+}
+
+/// Checks a poll a user wants to attach to a new post, so that a post isn't created for a poll we'd then reject
+pub fn validate_new_poll(req: &NewPollRequest) -> Result<(), LogicErr> {
+  if req.options.len() < 2 || req.options.len() > MAX_POLL_OPTIONS {
+    return Err(LogicErr::InvalidOperation(format!(
+      "Polls must have between 2 and {} options",
+      MAX_POLL_OPTIONS
+    )));
+  }
+
+  if req
+    .options
+    .iter()
+    .any(|option| option.trim().is_empty() || option.chars().count() > MAX_POLL_OPTION_LENGTH)
+  {
+    return Err(LogicErr::InvalidOperation(format!(
+      "Poll options must be between 1 and {} characters long",
+      MAX_POLL_OPTION_LENGTH
+    )));
+  }
+
+  let mut names: Vec<&str> = req.options.iter().map(|option| option.trim()).collect();
+  names.sort_unstable();
+  names.dedup();
+  if names.len() != req.options.len() {
+    return Err(LogicErr::InvalidOperation("Poll options must be unique".to_string()));
+  }
+
+  if matches!(req.expires_in, Some(expires_in) if expires_in <= 0) {
+    return Err(LogicErr::InvalidOperation(
+      "Polls must expire in the future".to_string(),
+    ));
+  }
+
+  Ok(())
+}
+
+pub async fn create_poll(post_id: &Uuid, req: &NewPollRequest, polls: &PollPool) -> Result<Uuid, LogicErr> {
+  let options: Vec<String> = req.options.iter().map(|option| option.trim().to_string()).collect();
+  let expires_at = req
+    .expires_in
+    .map(|expires_in| Utc::now() + Duration::seconds(expires_in));
+
+  polls
+    .create_poll(post_id, req.multiple_choice, &expires_at, &options)
+    .await
+}
+
+/// Records a user's votes in a post's poll. Votes in polls from other servers are also sent to the poll's author, as
+/// their server is the one that keeps count.
+pub async fn vote_in_poll(
+  posts: &PostPool,
+  follows: &FollowPool,
+  polls: &PollPool,
+  jobs: &JobPool,
+  queue: &Queue,
+  post_id: &Uuid,
+  user_id: &Uuid,
+  req: &PollVoteRequest,
+) -> Result<PostPoll, LogicErr> {
+  let post = match posts.find_optional_by_id(post_id).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  // If the voting user doesn't own the post and the post isn't publicly available, don't let the user vote
+  if (post.visibility == AccessType::Private || post.visibility == AccessType::Shadow) && &post.user_id != user_id {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  // If the post is only available to the author's followers and the user isn't a follower of the author, don't let the
+  // user vote
+  if post.visibility == AccessType::FollowersOnly
+    && &post.user_id != user_id
+    && !follows.user_follows_poster(post_id, user_id).await
+  {
+    return Err(LogicErr::MissingRecord);
+  }
+
+  let poll = match polls.fetch_poll_by_post(post_id).await? {
+    Some(poll) => poll,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  if poll.is_closed() {
+    return Err(LogicErr::InvalidOperation("Poll has closed".to_string()));
+  }
+
+  let mut choices = req.choices.clone();
+  choices.sort_unstable();
+  choices.dedup();
+
+  if choices.is_empty() || (!poll.multiple_choice && choices.len() > 1) {
+    return Err(LogicErr::InvalidOperation(
+      "Invalid number of choices for poll".to_string(),
+    ));
+  }
+
+  let options = polls.fetch_poll_options(&poll.poll_id).await?;
+  if !choices
+    .iter()
+    .all(|choice| options.iter().any(|option| &option.option_id == choice))
+  {
+    return Err(LogicErr::InvalidOperation("Choice is not part of the poll".to_string()));
+  }
+
+  let voted = match polls
+    .create_votes(&poll.poll_id, &choices, user_id, &None, false)
+    .await?
+  {
+    Some(voted) => voted,
+    None => return Err(LogicErr::InvalidOperation("User has already voted".to_string())),
+  };
+
+  if post.is_external {
+    for choice in voted.iter() {
+      polls.increment_option_remote_votes(choice).await?;
+    }
+
+    let job_id = jobs
+      .create(NewJob {
+        created_by_id: Some(user_id.to_owned()),
+        status: JobStatus::NotStarted,
+        record_id: Some(post_id.to_owned()),
+        associated_record_id: None,
+      })
+      .await
+      .map_err(map_db_err)?;
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::FederatePollVote)
+      .build();
+
+    queue.send_job(job).await?;
+  }
+
+  match posts.fetch_post(post_id, &Some(user_id.to_owned())).await? {
+    Some(post) => post.poll.ok_or(LogicErr::MissingRecord),
+    None => Err(LogicErr::MissingRecord),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use chrono::Utc;
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      follow_repository::{FollowPool, MockFollowRepo},
+      job_repository::{JobPool, MockJobRepo},
+      poll_repository::{MockPollRepo, PollPool},
+      post_repository::{MockPostRepo, PostPool},
+    },
+    logic::{
+      poll::{validate_new_poll, vote_in_poll, NewPollRequest, PollVoteRequest},
+      LogicErr,
+    },
+    model::{
+      access_type::AccessType,
+      poll::{Poll, PollOption},
+      post::Post,
+    },
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  fn poll_request(options: &[&str], expires_in: Option<i64>) -> NewPollRequest {
+    NewPollRequest {
+      options: options.iter().map(|option| option.to_string()).collect(),
+      multiple_choice: false,
+      expires_in,
+    }
+  }
+
+  #[test]
+  fn validate_new_poll_accepts_valid_poll() {
+    assert_eq!(validate_new_poll(&poll_request(&["Yes", "No"], Some(3600))), Ok(()));
+  }
+
+  #[test]
+  fn validate_new_poll_rejects_single_option() {
+    assert!(matches!(
+      validate_new_poll(&poll_request(&["Yes"], None)),
+      Err(LogicErr::InvalidOperation(_))
+    ));
+  }
+
+  #[test]
+  fn validate_new_poll_rejects_duplicate_options() {
+    assert!(matches!(
+      validate_new_poll(&poll_request(&["Yes", "Yes "], None)),
+      Err(LogicErr::InvalidOperation(_))
+    ));
+  }
+
+  #[test]
+  fn validate_new_poll_rejects_past_expiry() {
+    assert!(matches!(
+      validate_new_poll(&poll_request(&["Yes", "No"], Some(0))),
+      Err(LogicErr::InvalidOperation(_))
+    ));
+  }
+
+  #[async_std::test]
+  async fn vote_in_poll_rejects_second_vote() {
+    let post_id = Uuid::new_v4();
+    let poll_id = Uuid::new_v4();
+    let option_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .returning(move |_| {
+        Some(Post {
+          post_id,
+          user_id: Uuid::new_v4(),
+          orbit_id: None,
+          uri: format!("/feed/{}", post_id),
+          is_external: false,
+          title: None,
+          content_md: "Yes or no?".to_string(),
+          content_html: "<p>Yes or no?</p>".to_string(),
+          visibility: AccessType::PublicFederated,
+          created_at: Utc::now(),
+          updated_at: Utc::now(),
+          deletion_scheduled_at: None,
+        })
+      });
+    post_repo.expect_fetch_post().times(0);
+
+    let mut poll_repo = MockPollRepo::new();
+    poll_repo
+      .expect_fetch_poll_by_post()
+      .with(eq(post_id))
+      .times(1)
+      .return_const(Ok(Some(Poll {
+        poll_id,
+        post_id,
+        multiple_choice: false,
+        expires_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+      })));
+    poll_repo
+      .expect_fetch_poll_options()
+      .with(eq(poll_id))
+      .times(1)
+      .return_const(Ok(vec![PollOption {
+        option_id,
+        poll_id,
+        position: 0,
+        name: "Yes".to_string(),
+        remote_votes: 0,
+      }]));
+    // The user's earlier vote is found while the poll is locked, so the vote isn't counted
+    poll_repo
+      .expect_create_votes()
+      .withf(move |id, options, user, uri, additional| {
+        id == &poll_id && options.to_vec() == vec![option_id] && user == &user_id && uri.is_none() && !additional
+      })
+      .times(1)
+      .return_const(Ok(None));
+    poll_repo.expect_increment_option_remote_votes().times(0);
+
+    let posts: PostPool = Arc::new(post_repo);
+    let follows: FollowPool = Arc::new(MockFollowRepo::new());
+    let polls: PollPool = Arc::new(poll_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let req = PollVoteRequest {
+      choices: vec![option_id],
+    };
+
+    assert_eq!(
+      vote_in_poll(&posts, &follows, &polls, &jobs, &queue, &post_id, &user_id, &req).await,
+      Err(LogicErr::InvalidOperation("User has already voted".to_string()))
+    );
+  }
+}
@@ -5,21 +5,31 @@ use serde::{Deserialize, Serialize};
 use std::{pin::Pin, str::FromStr};
 use uuid::Uuid;
 
-use super::LogicErr;
+use super::{
+  hashtag::link_hashtags,
+  mention::{link_mentions, record_mentions},
+  poll::{create_poll, validate_new_poll, NewPollRequest},
+  LogicErr,
+};
 use crate::{
-  activitypub::object::ObjectType,
+  activitypub::{document::ActivityPubDocument, object::ObjectType},
   cdn::cdn_store::Cdn,
   db::{
-    job_repository::JobPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
-    tombstone_repository::TombstonePool,
+    domain_block_repository::DomainBlockPool, event_repository::EventPool, follow_repository::FollowPool,
+    hashtag_repository::HashtagPool, job_repository::JobPool, mention_repository::MentionPool,
+    poll_repository::PollPool, post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    post_revision_repository::PostRevisionPool, tombstone_repository::TombstonePool, user_repository::UserPool,
   },
+  federation::activitypub::build_ext_boost_activity,
   helpers::api::{map_db_err, map_ext_err},
   model::{
     access_type::AccessType,
     job::{JobStatus, NewJob},
     post_attachment::PostAttachment,
     post_event::PostEvent,
+    post_revision::PostRevision,
     queue_job::{QueueJob, QueueJobType},
+    user::User,
   },
   work_queue::queue::Queue,
 };
@@ -31,6 +41,14 @@ pub struct NewPostRequest {
   pub visibility: AccessType,
   pub orbit_id: Option<Uuid>,
   pub attachment_count: i64,
+  #[serde(default)]
+  pub poll: Option<NewPollRequest>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct UpdatePostRequest {
+  pub title: Option<String>,
+  pub content_md: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -74,22 +92,38 @@ pub async fn get_user_friends_posts_count(user_id: &Uuid, posts: &PostPool) -> R
   posts.count_user_friends_feed(user_id).await
 }
 
-pub async fn get_global_posts(limit: i64, skip: i64, posts: &PostPool) -> Result<Vec<PostEvent>, LogicErr> {
-  posts.fetch_global_federated_feed(limit, skip).await
+pub async fn get_global_posts(
+  own_user_id: &Option<Uuid>,
+  limit: i64,
+  skip: i64,
+  posts: &PostPool,
+) -> Result<Vec<PostEvent>, LogicErr> {
+  posts.fetch_global_federated_feed(own_user_id, limit, skip).await
 }
 
-pub async fn get_global_posts_count(posts: &PostPool) -> Result<i64, LogicErr> {
-  posts.count_global_federated_feed().await
+pub async fn get_global_posts_count(own_user_id: &Option<Uuid>, posts: &PostPool) -> Result<i64, LogicErr> {
+  posts.count_global_federated_feed(own_user_id).await
 }
 
 pub async fn create_post(
   posts: &PostPool,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  mentions: &MentionPool,
+  hashtags: &HashtagPool,
+  polls: &PollPool,
   jobs: &JobPool,
   queue: &Queue,
   req: &NewPostRequest,
   user_id: &Uuid,
 ) -> Result<CreatePostResult, LogicErr> {
-  let content_html = markdown::to_html(&req.content_md);
+  if let Some(poll) = &req.poll {
+    validate_new_poll(poll)?;
+  }
+
+  let linked_mentions = link_mentions(&req.content_md, users, domain_blocks).await;
+  let linked_hashtags = link_hashtags(&linked_mentions.content_md);
+  let content_html = markdown::to_html(&linked_hashtags.content_md);
 
   let post_id = posts
     .create_post(
@@ -102,6 +136,16 @@ pub async fn create_post(
     )
     .await?;
 
+  record_mentions(&linked_mentions.mentioned, user_id, &Some(post_id), &None, mentions).await?;
+
+  if !linked_hashtags.hashtags.is_empty() {
+    hashtags.set_post_hashtags(&post_id, &linked_hashtags.hashtags).await?;
+  }
+
+  if let Some(poll) = &req.poll {
+    create_poll(&post_id, poll, polls).await?;
+  }
+
   if req.attachment_count > 0 {
     return Ok(CreatePostResult::WaitingForImages(post_id));
   }
@@ -300,6 +344,144 @@ pub async fn delete_post(
   queue.send_job(job).await
 }
 
+/// Edits one of a user's posts. The post's previous title and content are kept as a revision, and the edit is sent to
+/// everyone the post was originally delivered to.
+pub async fn update_post(
+  posts: &PostPool,
+  post_revisions: &PostRevisionPool,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  mentions: &MentionPool,
+  hashtags: &HashtagPool,
+  jobs: &JobPool,
+  queue: &Queue,
+  req: &UpdatePostRequest,
+  post_id: &Uuid,
+  user_id: &Uuid,
+) -> Result<Option<PostEvent>, LogicErr> {
+  let mut post = match posts.find_optional_by_id(post_id).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  if &post.user_id != user_id || post.is_external {
+    return Err(LogicErr::MissingRecord);
+  }
+
+  if post.content_md == req.content_md && post.title == req.title {
+    return posts.fetch_post(post_id, &Some(*user_id)).await;
+  }
+
+  post_revisions.create_revision(&post).await?;
+
+  let linked_mentions = link_mentions(&req.content_md, users, domain_blocks).await;
+  let linked_hashtags = link_hashtags(&linked_mentions.content_md);
+
+  post.title = req.title.clone();
+  post.content_md = req.content_md.clone();
+  post.content_html = markdown::to_html(&linked_hashtags.content_md);
+  post.updated_at = Utc::now();
+
+  posts.update_post_content(&post).await?;
+
+  // Users that were already mentioned have been told about the post, so only newly mentioned users are recorded
+  let already_mentioned = mentions.fetch_post_mentioned_users(post_id).await?;
+  let newly_mentioned: Vec<User> = linked_mentions
+    .mentioned
+    .into_iter()
+    .filter(|user| !already_mentioned.iter().any(|u| u.user_id == user.user_id))
+    .collect();
+
+  record_mentions(&newly_mentioned, user_id, &Some(*post_id), &None, mentions).await?;
+  hashtags.set_post_hashtags(post_id, &linked_hashtags.hashtags).await?;
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(user_id.to_owned()),
+      status: JobStatus::NotStarted,
+      record_id: Some(post_id.to_owned()),
+      associated_record_id: post.orbit_id,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::UpdatePost)
+    .build();
+
+  queue.send_job(job).await?;
+
+  posts.fetch_post(post_id, &Some(*user_id)).await
+}
+
+/// Fetches the previous versions of a post, so long as the user is able to see the post itself
+pub async fn get_post_revisions(
+  post_id: &Uuid,
+  own_user_id: &Option<Uuid>,
+  limit: i64,
+  skip: i64,
+  posts: &PostPool,
+  follows: &FollowPool,
+  post_revisions: &PostRevisionPool,
+) -> Result<Vec<PostRevision>, LogicErr> {
+  let post = match posts.find_optional_by_id(post_id).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  let is_owner = own_user_id.as_ref() == Some(&post.user_id);
+
+  let can_view = match post.visibility {
+    AccessType::PublicFederated | AccessType::PublicLocal | AccessType::Unlisted => true,
+    AccessType::FollowersOnly => match own_user_id {
+      Some(own_user_id) => is_owner || follows.user_follows_poster(post_id, own_user_id).await,
+      None => false,
+    },
+    _ => is_owner,
+  };
+
+  if !can_view {
+    return Err(LogicErr::MissingRecord);
+  }
+
+  post_revisions.fetch_post_revisions(post_id, limit, skip).await
+}
+
+pub async fn get_post_revisions_count(post_id: &Uuid, post_revisions: &PostRevisionPool) -> Result<i64, LogicErr> {
+  post_revisions.count_post_revisions(post_id).await
+}
+
+/// Fetches the Announce for one of our users' boosts of a post, which is served at the Announce's ID
+pub async fn activitypub_get_post_boost(
+  post_id: &Uuid,
+  user_id: &Uuid,
+  posts: &PostPool,
+  users: &UserPool,
+  events: &EventPool,
+) -> Result<ActivityPubDocument, LogicErr> {
+  let post = match posts.find_optional_by_id(post_id).await {
+    Some(post) => post,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  if !events.user_boosted_post(user_id, post_id).await {
+    return Err(LogicErr::MissingRecord);
+  }
+
+  let user = users.fetch_by_id(user_id).await?;
+
+  // Remote users' boosts are served by their own server
+  if user.is_external {
+    return Err(LogicErr::MissingRecord);
+  }
+
+  match build_ext_boost_activity(&post, &user) {
+    Some(activity) => Ok(ActivityPubDocument::new(activity)),
+    None => Err(LogicErr::MissingRecord),
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use std::sync::Arc;
@@ -311,20 +493,34 @@ mod tests {
   use uuid::Uuid;
 
   use crate::{
+    activitypub::reference::Reference,
     cdn::cdn_store::{Cdn, MockCdnStore},
     db::{
+      domain_block_repository::{DomainBlockPool, MockDomainBlockRepo},
+      event_repository::{EventPool, MockEventRepo},
+      follow_repository::{FollowPool, MockFollowRepo},
+      hashtag_repository::{HashtagPool, MockHashtagRepo},
       job_repository::{JobPool, MockJobRepo},
+      mention_repository::{MentionPool, MockMentionRepo},
+      poll_repository::{MockPollRepo, PollPool},
       post_attachment_repository::{MockPostAttachmentRepo, PostAttachmentPool},
       post_repository::{MockPostRepo, PostPool},
+      post_revision_repository::{MockPostRevisionRepo, PostRevisionPool},
+      user_repository::{MockUserRepo, UserPool},
     },
     logic::{
       post::{
-        create_post, get_global_posts, get_global_posts_count, get_post, get_user_posts, get_user_posts_count,
-        upload_post_files, NewPostRequest,
+        activitypub_get_post_boost, create_post, get_global_posts, get_global_posts_count, get_post,
+        get_post_revisions, get_user_posts, get_user_posts_count, update_post, upload_post_files, NewPostRequest,
+        UpdatePostRequest,
       },
       LogicErr,
     },
-    model::{access_type::AccessType, event_type::EventType, post_event::PostEvent},
+    model::{
+      access_type::AccessType, event_type::EventType, post::Post, post_event::PostEvent, queue_job::QueueJobType,
+      user::User,
+    },
+    settings::SETTINGS,
     work_queue::queue::{MockQueueBackend, Queue},
   };
 
@@ -411,6 +607,8 @@ mod tests {
       liked: Some(false),
       comments: 1,
       attachments: vec![],
+      hashtags: vec![],
+      poll: None,
       orbit_id: None,
       orbit_name: None,
       orbit_uri: None,
@@ -472,13 +670,13 @@ mod tests {
     post_repo
       .expect_fetch_global_federated_feed()
       .times(1)
-      .with(eq(1), eq(2))
+      .with(eq(None), eq(1), eq(2))
       .return_const(Err(LogicErr::DbError("Boop".to_string())));
 
     let posts: PostPool = Arc::new(post_repo);
 
     assert_eq!(
-      get_global_posts(1, 2, &posts).await,
+      get_global_posts(&None, 1, 2, &posts).await,
       Err(LogicErr::DbError("Boop".to_string()))
     );
   }
@@ -489,12 +687,12 @@ mod tests {
     post_repo
       .expect_fetch_global_federated_feed()
       .times(1)
-      .with(eq(1), eq(2))
+      .with(eq(None), eq(1), eq(2))
       .return_const(Ok(vec![]));
 
     let posts: PostPool = Arc::new(post_repo);
 
-    assert_eq!(get_global_posts(1, 2, &posts).await, Ok(vec![]));
+    assert_eq!(get_global_posts(&None, 1, 2, &posts).await, Ok(vec![]));
   }
 
   #[async_std::test]
@@ -508,7 +706,7 @@ mod tests {
     let posts: PostPool = Arc::new(post_repo);
 
     assert_eq!(
-      get_global_posts_count(&posts).await,
+      get_global_posts_count(&None, &posts).await,
       Err(LogicErr::DbError("Boop".to_string()))
     );
   }
@@ -523,7 +721,7 @@ mod tests {
 
     let posts: PostPool = Arc::new(post_repo);
 
-    assert_eq!(get_global_posts_count(&posts).await, Ok(123));
+    assert_eq!(get_global_posts_count(&None, &posts).await, Ok(123));
   }
 
   #[async_std::test]
@@ -536,6 +734,7 @@ mod tests {
       orbit_id: None,
       attachment_count: 0,
       title: None,
+      poll: None,
     };
     let content_md_eq = new_post.content_md.clone();
     let visibility_eq = new_post.visibility.clone();
@@ -557,9 +756,26 @@ mod tests {
       .return_const(Err(LogicErr::DbError("Boop".to_string())));
 
     let posts: PostPool = Arc::new(post_repo);
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
+    let hashtags: HashtagPool = Arc::new(MockHashtagRepo::new());
+    let polls: PollPool = Arc::new(MockPollRepo::new());
 
     assert_eq!(
-      create_post(&posts, &jobs, &queue, &new_post, &user_id).await,
+      create_post(
+        &posts,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &hashtags,
+        &polls,
+        &jobs,
+        &queue,
+        &new_post,
+        &user_id
+      )
+      .await,
       Err(LogicErr::DbError("Boop".to_string()))
     );
   }
@@ -576,6 +792,7 @@ mod tests {
       orbit_id: None,
       attachment_count: 0,
       title: None,
+      poll: None,
     };
     let content_md_eq = new_post.content_md.clone();
     let visibility_eq = new_post.visibility.clone();
@@ -608,8 +825,26 @@ mod tests {
     let posts: PostPool = Arc::new(post_repo);
     let jobs: JobPool = Arc::new(job_repo);
     let queue = Queue::new_inner(Box::new(queue_be));
-
-    assert!(create_post(&posts, &jobs, &queue, &new_post, &user_id).await.is_ok(),);
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
+    let hashtags: HashtagPool = Arc::new(MockHashtagRepo::new());
+    let polls: PollPool = Arc::new(MockPollRepo::new());
+
+    assert!(create_post(
+      &posts,
+      &users,
+      &domain_blocks,
+      &mentions,
+      &hashtags,
+      &polls,
+      &jobs,
+      &queue,
+      &new_post,
+      &user_id
+    )
+    .await
+    .is_ok(),);
   }
 
   #[async_std::test]
@@ -697,4 +932,324 @@ mod tests {
       Err(LogicErr::InternalError("Failed to process all attachments".to_string()))
     );
   }
+
+  fn test_post(post_id: Uuid, user_id: Uuid, visibility: AccessType) -> Post {
+    Post {
+      post_id,
+      user_id,
+      orbit_id: None,
+      uri: format!("/feed/{}", post_id),
+      is_external: false,
+      title: None,
+      content_md: "hello".to_string(),
+      content_html: "<p>hello</p>".to_string(),
+      visibility,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+      deletion_scheduled_at: None,
+    }
+  }
+
+  #[async_std::test]
+  async fn update_post_fails_not_owner() {
+    let user_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .returning(move |_| Some(test_post(post_id, Uuid::new_v4(), AccessType::PublicFederated)));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let post_revisions: PostRevisionPool = Arc::new(MockPostRevisionRepo::new());
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
+    let hashtags: HashtagPool = Arc::new(MockHashtagRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let req = UpdatePostRequest {
+      title: None,
+      content_md: "goodbye".to_string(),
+    };
+
+    assert_eq!(
+      update_post(
+        &posts,
+        &post_revisions,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &hashtags,
+        &jobs,
+        &queue,
+        &req,
+        &post_id,
+        &user_id
+      )
+      .await,
+      Err(LogicErr::MissingRecord)
+    );
+  }
+
+  #[async_std::test]
+  async fn update_post_succeeds() {
+    let user_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+    let job_id = Uuid::new_v4();
+
+    let mut post_repo = MockPostRepo::new();
+    let mut post_revision_repo = MockPostRevisionRepo::new();
+    let mut mention_repo = MockMentionRepo::new();
+    let mut hashtag_repo = MockHashtagRepo::new();
+    let mut job_repo = MockJobRepo::new();
+    let mut queue_be = MockQueueBackend::new();
+
+    post_repo
+      .expect_find_optional_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .returning(move |_| Some(test_post(post_id, user_id, AccessType::PublicFederated)));
+
+    post_revision_repo
+      .expect_create_revision()
+      .withf(|post| post.content_md == "hello")
+      .times(1)
+      .return_const(Ok(()));
+
+    post_repo
+      .expect_update_post_content()
+      .withf(|post| post.content_md == "goodbye" && post.content_html.contains("goodbye"))
+      .times(1)
+      .return_const(Ok(()));
+
+    mention_repo
+      .expect_fetch_post_mentioned_users()
+      .with(eq(post_id))
+      .times(1)
+      .returning(|_| Ok(vec![]));
+
+    hashtag_repo
+      .expect_set_post_hashtags()
+      .with(eq(post_id), always())
+      .times(1)
+      .return_const(Ok(()));
+
+    job_repo
+      .expect_create()
+      .withf(move |job| job.record_id == Some(post_id) && job.created_by_id == Some(user_id))
+      .times(1)
+      .return_const(Ok(job_id));
+
+    queue_be
+      .expect_send_job()
+      .withf(move |job| job.job_id == job_id && job.job_type == QueueJobType::UpdatePost)
+      .times(1)
+      .return_const(Ok(()));
+
+    post_repo
+      .expect_fetch_post()
+      .with(eq(post_id), eq(Some(user_id)))
+      .times(1)
+      .returning(|_, _| Ok(None));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let post_revisions: PostRevisionPool = Arc::new(post_revision_repo);
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(mention_repo);
+    let hashtags: HashtagPool = Arc::new(hashtag_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(queue_be));
+
+    let req = UpdatePostRequest {
+      title: None,
+      content_md: "goodbye".to_string(),
+    };
+
+    assert_eq!(
+      update_post(
+        &posts,
+        &post_revisions,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &hashtags,
+        &jobs,
+        &queue,
+        &req,
+        &post_id,
+        &user_id
+      )
+      .await,
+      Ok(None)
+    );
+  }
+
+  #[async_std::test]
+  async fn update_post_unchanged_is_noop() {
+    let user_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+
+    let mut post_repo = MockPostRepo::new();
+    let mut post_revision_repo = MockPostRevisionRepo::new();
+    let mut job_repo = MockJobRepo::new();
+
+    post_repo
+      .expect_find_optional_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .returning(move |_| Some(test_post(post_id, user_id, AccessType::PublicFederated)));
+
+    post_revision_repo.expect_create_revision().times(0);
+    post_repo.expect_update_post_content().times(0);
+    job_repo.expect_create().times(0);
+
+    post_repo
+      .expect_fetch_post()
+      .with(eq(post_id), eq(Some(user_id)))
+      .times(1)
+      .returning(|_, _| Ok(None));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let post_revisions: PostRevisionPool = Arc::new(post_revision_repo);
+    let users: UserPool = Arc::new(MockUserRepo::new());
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let mentions: MentionPool = Arc::new(MockMentionRepo::new());
+    let hashtags: HashtagPool = Arc::new(MockHashtagRepo::new());
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let req = UpdatePostRequest {
+      title: None,
+      content_md: "hello".to_string(),
+    };
+
+    assert_eq!(
+      update_post(
+        &posts,
+        &post_revisions,
+        &users,
+        &domain_blocks,
+        &mentions,
+        &hashtags,
+        &jobs,
+        &queue,
+        &req,
+        &post_id,
+        &user_id
+      )
+      .await,
+      Ok(None)
+    );
+  }
+
+  #[async_std::test]
+  async fn get_post_revisions_fails_private_post() {
+    let user_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_find_optional_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .returning(move |_| Some(test_post(post_id, Uuid::new_v4(), AccessType::Private)));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let follows: FollowPool = Arc::new(MockFollowRepo::new());
+    let post_revisions: PostRevisionPool = Arc::new(MockPostRevisionRepo::new());
+
+    assert_eq!(
+      get_post_revisions(&post_id, &Some(user_id), 20, 0, &posts, &follows, &post_revisions).await,
+      Err(LogicErr::MissingRecord)
+    );
+  }
+
+  #[async_std::test]
+  async fn activitypub_get_post_boost_fails_not_boosted() {
+    let user_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+
+    let mut post_repo = MockPostRepo::new();
+    let mut event_repo = MockEventRepo::new();
+    let mut user_repo = MockUserRepo::new();
+
+    post_repo
+      .expect_find_optional_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .returning(move |_| Some(test_post(post_id, user_id, AccessType::PublicFederated)));
+
+    event_repo
+      .expect_user_boosted_post()
+      .with(eq(user_id), eq(post_id))
+      .times(1)
+      .return_const(false);
+
+    user_repo.expect_fetch_by_id().times(0);
+
+    let posts: PostPool = Arc::new(post_repo);
+    let users: UserPool = Arc::new(user_repo);
+    let events: EventPool = Arc::new(event_repo);
+
+    assert_eq!(
+      activitypub_get_post_boost(&post_id, &user_id, &posts, &users, &events).await,
+      Err(LogicErr::MissingRecord)
+    );
+  }
+
+  #[async_std::test]
+  async fn activitypub_get_post_boost_succeeds() {
+    let user_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+
+    let mut post_repo = MockPostRepo::new();
+    let mut event_repo = MockEventRepo::new();
+    let mut user_repo = MockUserRepo::new();
+
+    post_repo
+      .expect_find_optional_by_id()
+      .with(eq(post_id))
+      .times(1)
+      .returning(move |_| Some(test_post(post_id, Uuid::new_v4(), AccessType::PublicFederated)));
+
+    event_repo
+      .expect_user_boosted_post()
+      .with(eq(user_id), eq(post_id))
+      .times(1)
+      .return_const(true);
+
+    user_repo
+      .expect_fetch_by_id()
+      .with(eq(user_id))
+      .times(1)
+      .returning(move |_| Ok(User::test_local(user_id)));
+
+    let posts: PostPool = Arc::new(post_repo);
+    let users: UserPool = Arc::new(user_repo);
+    let events: EventPool = Arc::new(event_repo);
+
+    let doc = activitypub_get_post_boost(&post_id, &user_id, &posts, &users, &events)
+      .await
+      .unwrap();
+
+    assert_eq!(
+      doc.object.id,
+      Some(format!(
+        "{}/feed/{}/boosts/{}",
+        SETTINGS.server.api_fqdn, post_id, user_id
+      ))
+    );
+    assert_eq!(
+      doc.object.cc,
+      Some(Reference::Remote(format!(
+        "{}/user/{}/followers",
+        SETTINGS.server.api_fqdn, user_id
+      )))
+    );
+  }
 }
@@ -0,0 +1,216 @@
+use chrono::Utc;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    domain_block_repository::DomainBlockPool, job_repository::JobPool, relay_repository::RelayPool,
+    user_repository::UserPool,
+  },
+  federation::activitypub::{activitypub_uri_is_suspended, federate_relay_inbox},
+  helpers::api::map_db_err,
+  model::{
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+    relay::Relay,
+    relay_status::RelayStatus,
+  },
+  work_queue::queue::Queue,
+};
+
+use super::{user::require_admin, LogicErr};
+
+pub async fn get_relays(user_id: &Uuid, users: &UserPool, relays: &RelayPool) -> Result<Vec<Relay>, LogicErr> {
+  require_admin(user_id, users).await?;
+
+  relays.fetch_relays().await
+}
+
+/// Subscribes the instance to a relay, given the URI of the relay's actor. The subscription stays pending until the
+/// relay accepts it.
+pub async fn subscribe_relay(
+  user_id: &Uuid,
+  actor_uri: &str,
+  users: &UserPool,
+  relays: &RelayPool,
+  domain_blocks: &DomainBlockPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<Relay, LogicErr> {
+  require_admin(user_id, users).await?;
+
+  match Url::parse(actor_uri) {
+    Ok(url) if url.scheme() == "https" || url.scheme() == "http" => {}
+    _ => return Err(LogicErr::InvalidOperation("Invalid relay URI".to_string())),
+  }
+
+  if activitypub_uri_is_suspended(actor_uri, domain_blocks).await {
+    return Err(LogicErr::InvalidOperation(
+      "The relay's server has been suspended".to_string(),
+    ));
+  }
+
+  if relays.fetch_relay_by_actor_uri(actor_uri).await.is_some() {
+    return Err(LogicErr::InvalidOperation(
+      "Already subscribed to this relay".to_string(),
+    ));
+  }
+
+  let inbox_uri = federate_relay_inbox(actor_uri).await?;
+
+  let relay = Relay {
+    relay_id: Uuid::new_v4(),
+    actor_uri: actor_uri.to_string(),
+    inbox_uri,
+    status: RelayStatus::Pending,
+    created_at: Utc::now(),
+    updated_at: Utc::now(),
+  };
+
+  relays.create_relay(&relay).await?;
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(*user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(relay.relay_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::FederateFollowRelay)
+    .build();
+
+  queue.send_job(job).await?;
+
+  Ok(relay)
+}
+
+pub async fn unsubscribe_relay(
+  user_id: &Uuid,
+  relay_id: &Uuid,
+  users: &UserPool,
+  relays: &RelayPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  require_admin(user_id, users).await?;
+
+  let relay = match relays.fetch_relay(relay_id).await {
+    Some(relay) => relay,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  relays.delete_relay(relay_id).await?;
+
+  // Relays that never accepted our subscription have nothing to undo
+  if relay.status != RelayStatus::Accepted {
+    return Ok(());
+  }
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(*user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(relay.relay_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let data = serde_json::to_value(&relay).map_err(|err| LogicErr::InternalError(err.to_string()))?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::FederateUnfollowRelay)
+    .data(data)
+    .build();
+
+  queue.send_job(job).await
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      domain_block_repository::{DomainBlockPool, MockDomainBlockRepo},
+      job_repository::{JobPool, MockJobRepo},
+      relay_repository::{MockRelayRepo, RelayPool},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    logic::{
+      relay::{subscribe_relay, unsubscribe_relay},
+      LogicErr,
+    },
+    model::user::User,
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  #[async_std::test]
+  async fn test_subscribe_relay_rejects_non_admin() {
+    let user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .times(1)
+      .with(eq(user_id))
+      .return_const(Ok(User::test_local(user_id)));
+
+    let mut relay_repo = MockRelayRepo::new();
+    relay_repo.expect_create_relay().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let relays: RelayPool = Arc::new(relay_repo);
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      subscribe_relay(
+        &user_id,
+        "https://relay.example/actor",
+        &users,
+        &relays,
+        &domain_blocks,
+        &jobs,
+        &queue
+      )
+      .await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_unsubscribe_relay_rejects_non_admin() {
+    let user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .times(1)
+      .with(eq(user_id))
+      .return_const(Ok(User::test_local(user_id)));
+
+    let mut relay_repo = MockRelayRepo::new();
+    relay_repo.expect_delete_relay().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let relays: RelayPool = Arc::new(relay_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      unsubscribe_relay(&user_id, &Uuid::new_v4(), &users, &relays, &jobs, &queue).await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+}
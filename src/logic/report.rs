@@ -0,0 +1,191 @@
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+  db::{job_repository::JobPool, post_repository::PostPool, report_repository::ReportPool, user_repository::UserPool},
+  helpers::api::map_db_err,
+  model::{
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+    report::Report,
+  },
+  work_queue::queue::Queue,
+};
+
+use super::LogicErr;
+
+/// Reports a user, and optionally some of their posts, to the instance's moderators. Reports on remote users can be
+/// forwarded to the user's own server, in which case they're sent anonymously on behalf of the instance.
+pub async fn create_report(
+  user_id: &Uuid,
+  target_user_handle: &str,
+  post_ids: &[Uuid],
+  comment: &Option<String>,
+  forward: bool,
+  users: &UserPool,
+  posts: &PostPool,
+  reports: &ReportPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let target_user = match users.fetch_by_handle(target_user_handle).await? {
+    Some(user) => user,
+    None => return Err(LogicErr::MissingRecord),
+  };
+
+  if target_user.user_id == *user_id {
+    return Err(LogicErr::InvalidOperation("Users can't report themselves".to_string()));
+  }
+
+  for post_id in post_ids {
+    if posts.fetch_owner_by_id(post_id).await != Some(target_user.user_id) {
+      return Err(LogicErr::InvalidOperation(
+        "Reported posts must belong to the reported user".to_string(),
+      ));
+    }
+  }
+
+  let report = Report {
+    report_id: Uuid::new_v4(),
+    reporter_user_id: Some(*user_id),
+    reporter_uri: None,
+    target_user_id: target_user.user_id,
+    post_ids: post_ids.to_vec(),
+    comment: comment.to_owned(),
+    uri: None,
+    is_external: false,
+    forwarded: forward && target_user.is_external,
+    created_at: Utc::now(),
+    updated_at: Utc::now(),
+  };
+
+  reports.create_report(&report).await?;
+
+  if !report.forwarded {
+    return Ok(());
+  }
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(*user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(report.report_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::FederateReport)
+    .build();
+
+  queue.send_job(job).await
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use mockall::predicate::*;
+  use uuid::Uuid;
+
+  use crate::{
+    db::{
+      job_repository::{JobPool, MockJobRepo},
+      post_repository::{MockPostRepo, PostPool},
+      report_repository::{MockReportRepo, ReportPool},
+      user_repository::{MockUserRepo, UserPool},
+    },
+    logic::{report::create_report, LogicErr},
+    model::user::User,
+    work_queue::queue::{MockQueueBackend, Queue},
+  };
+
+  #[async_std::test]
+  async fn test_create_report_rejects_for_missing_user() {
+    let user_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_handle()
+      .times(1)
+      .with(eq("user@example.com"))
+      .return_const(Ok(None));
+
+    let mut report_repo = MockReportRepo::new();
+    report_repo.expect_create_report().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let posts: PostPool = Arc::new(MockPostRepo::new());
+    let reports: ReportPool = Arc::new(report_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      create_report(
+        &user_id,
+        "user@example.com",
+        &[],
+        &None,
+        true,
+        &users,
+        &posts,
+        &reports,
+        &jobs,
+        &queue
+      )
+      .await,
+      Err(LogicErr::MissingRecord)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_create_report_rejects_posts_from_other_users() {
+    let user_id = Uuid::new_v4();
+    let target_user_id = Uuid::new_v4();
+    let post_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_handle()
+      .times(1)
+      .with(eq("user@example.com"))
+      .return_const(Ok(Some(User::test_remote(target_user_id, "example.com"))));
+
+    let mut post_repo = MockPostRepo::new();
+    post_repo
+      .expect_fetch_owner_by_id()
+      .times(1)
+      .with(eq(post_id))
+      .return_const(Some(Uuid::new_v4()));
+
+    let mut report_repo = MockReportRepo::new();
+    report_repo.expect_create_report().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let posts: PostPool = Arc::new(post_repo);
+    let reports: ReportPool = Arc::new(report_repo);
+    let jobs: JobPool = Arc::new(MockJobRepo::new());
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      create_report(
+        &user_id,
+        "user@example.com",
+        &[post_id],
+        &None,
+        true,
+        &users,
+        &posts,
+        &reports,
+        &jobs,
+        &queue
+      )
+      .await,
+      Err(LogicErr::InvalidOperation(
+        "Reported posts must belong to the reported user".to_string()
+      ))
+    );
+  }
+}
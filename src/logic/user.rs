@@ -6,12 +6,39 @@ use rsa::{
   rand_core::OsRng,
   RsaPrivateKey, RsaPublicKey,
 };
+use url::Url;
 use uuid::Uuid;
 
-use crate::{db::user_repository::UserPool, model::user::User, net::jwt::JwtFactory, settings::SETTINGS};
+use crate::{
+  activitypub::{object::ObjectType, reference::Reference},
+  db::{
+    domain_block_repository::DomainBlockPool, job_repository::JobPool, session_repository::SessionPool,
+    tombstone_repository::TombstonePool, user_repository::UserPool,
+  },
+  federation::activitypub::actor::{federate_update_user_actor, federate_user_actor},
+  helpers::api::{map_db_err, relative_to_absolute_uri},
+  model::{
+    job::{JobStatus, NewJob},
+    queue_job::{QueueJob, QueueJobType},
+    user::User,
+  },
+  net::jwt::JwtFactory,
+  settings::SETTINGS,
+  work_queue::queue::Queue,
+};
 
 use super::LogicErr;
 
+/// Ensures the given user is one of the instance's administrators, as configured in the app settings
+pub async fn require_admin(user_id: &Uuid, users: &UserPool) -> Result<(), LogicErr> {
+  let user = users.fetch_by_id(user_id).await?;
+
+  match !user.is_external && SETTINGS.app.admin_handles.contains(&user.handle) {
+    true => Ok(()),
+    false => Err(LogicErr::UnauthorizedError),
+  }
+}
+
 pub async fn get_user_by_id(id: &Uuid, users: &UserPool) -> Result<User, LogicErr> {
   users.fetch_by_id(id).await
 }
@@ -108,18 +135,212 @@ pub async fn register_user(
   JwtFactory::generate_jwt_short_lived(username)
 }
 
+/// Saves changes to a user's profile, queueing an Update so that remote servers refresh their copy of the user
+pub async fn update_user_profile(user: &User, users: &UserPool, jobs: &JobPool, queue: &Queue) -> Result<(), LogicErr> {
+  if user.is_external {
+    return Err(LogicErr::InvalidOperation(
+      "Only local profiles can be updated".to_string(),
+    ));
+  }
+
+  // Other servers show profile links as clickable links, so anything other than a web page is refused
+  let links = [&user.url_1, &user.url_2, &user.url_3, &user.url_4, &user.url_5];
+  let is_web_page =
+    |uri: &String| matches!(Url::parse(uri), Ok(url) if url.scheme() == "http" || url.scheme() == "https");
+
+  if !links.into_iter().flatten().all(is_web_page) {
+    return Err(LogicErr::InvalidOperation(
+      "Profile links must be http or https URLs".to_string(),
+    ));
+  }
+
+  users.update_from(user).await?;
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(user.user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(user.user_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::FederateUpdateProfile)
+    .build();
+
+  queue.send_job(job).await
+}
+
+/// Replaces the set of remote accounts a user declares as also being them, which other servers check before they'll
+/// let one of those accounts move to us.
+pub async fn update_user_aliases(
+  user_id: &Uuid,
+  aliases: &[String],
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let mut user = users.fetch_by_id(user_id).await?;
+
+  let mut also_known_as = vec![];
+
+  for alias in aliases {
+    if alias.starts_with(&SETTINGS.server.api_fqdn) {
+      return Err(LogicErr::InvalidOperation(
+        "Aliases must be remote accounts".to_string(),
+      ));
+    }
+
+    let alias_user = federate_user_actor(&Some(Reference::Remote(alias.to_owned())), users, domain_blocks).await?;
+
+    if !also_known_as.contains(&alias_user.fediverse_uri) {
+      also_known_as.push(alias_user.fediverse_uri);
+    }
+  }
+
+  user.also_known_as = also_known_as;
+
+  update_user_profile(&user, users, jobs, queue).await
+}
+
+/// Moves a local user's account to a remote account, which must already list the user as one of its aliases. The
+/// user's followers are asked to follow the new account in the background.
+pub async fn move_user_account(
+  user_id: &Uuid,
+  target: &str,
+  users: &UserPool,
+  domain_blocks: &DomainBlockPool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let mut user = users.fetch_by_id(user_id).await?;
+
+  if user.moved_to_uri.is_some() {
+    return Err(LogicErr::InvalidOperation("Account has already been moved".to_string()));
+  }
+
+  if target.starts_with(&SETTINGS.server.api_fqdn) {
+    return Err(LogicErr::InvalidOperation(
+      "Accounts can only be moved to another server".to_string(),
+    ));
+  }
+
+  // The target's aliases have most likely only just been changed, so we always want the latest copy of it here
+  let target_user =
+    federate_update_user_actor(&Some(Reference::Remote(target.to_owned())), users, domain_blocks).await?;
+
+  if !target_user
+    .also_known_as
+    .contains(&relative_to_absolute_uri(&user.fediverse_uri))
+  {
+    return Err(LogicErr::InvalidOperation(
+      "Target account does not list this account as an alias".to_string(),
+    ));
+  }
+
+  user.moved_to_uri = Some(target_user.fediverse_uri.clone());
+  users.update_from(&user).await?;
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(user.user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(target_user.user_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::FederateMoveProfile)
+    .build();
+
+  queue.send_job(job).await
+}
+
+/// Deletes a local user's account once they've confirmed their password. The user's actor is tombstoned and their
+/// sessions revoked straight away, while their content is removed and the deletion federated in the background.
+pub async fn delete_user_account(
+  user_id: &Uuid,
+  password: &str,
+  users: &UserPool,
+  sessions: &SessionPool,
+  tombstones: &TombstonePool,
+  jobs: &JobPool,
+  queue: &Queue,
+) -> Result<(), LogicErr> {
+  let user = users.fetch_by_id(user_id).await?;
+
+  let current_hash = match &user.password_hash {
+    Some(hash) => hash,
+    None => return Err(LogicErr::UnauthorizedError),
+  };
+
+  let hash = match PasswordHash::new(current_hash) {
+    Ok(hash) => hash,
+    Err(_) => return Err(LogicErr::InternalError("Invalid password hash".to_string())),
+  };
+
+  if Argon2::default().verify_password(password.as_bytes(), &hash).is_err() {
+    return Err(LogicErr::UnauthorizedError);
+  }
+
+  if tombstones.fetch_for_fediverse_uri(&user.fediverse_uri).await.is_none() {
+    tombstones
+      .create_tombstone(&user.fediverse_uri, &ObjectType::Person.to_string())
+      .await?;
+  }
+
+  sessions.delete_user_sessions(user_id).await?;
+
+  let job_id = jobs
+    .create(NewJob {
+      created_by_id: Some(user.user_id),
+      status: JobStatus::NotStarted,
+      record_id: Some(user.user_id),
+      associated_record_id: None,
+    })
+    .await
+    .map_err(map_db_err)?;
+
+  let job = QueueJob::builder()
+    .job_id(job_id)
+    .job_type(QueueJobType::DeleteUser)
+    .build();
+
+  queue.send_job(job).await
+}
+
 #[cfg(test)]
 mod tests {
   use std::sync::Arc;
 
   use mockall::predicate::*;
+  use uuid::Uuid;
 
   use crate::{
-    db::user_repository::{MockUserRepo, UserPool},
+    db::{
+      domain_block_repository::{DomainBlockPool, MockDomainBlockRepo},
+      job_repository::{JobPool, MockJobRepo},
+      session_repository::{MockSessionRepo, SessionPool},
+      tombstone_repository::{MockTombstoneRepo, TombstonePool},
+      user_repository::{MockUserRepo, UserPool},
+    },
     logic::{
-      user::{authorize_user, get_user_by_handle, get_user_by_webfinger},
+      user::{
+        authorize_user, delete_user_account, get_user_by_handle, get_user_by_webfinger, move_user_account,
+        update_user_profile,
+      },
       LogicErr,
     },
+    model::{queue_job::QueueJobType, user::User},
+    settings::SETTINGS,
+    work_queue::queue::{MockQueueBackend, Queue},
   };
 
   #[async_std::test]
@@ -249,4 +470,151 @@ mod tests {
 
     assert!(authorize_user("handle", "test", &users).await.is_ok());
   }
+
+  #[async_std::test]
+  async fn test_delete_user_account_rejects_invalid_password() {
+    let user = User::test_local(Uuid::new_v4());
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .times(1)
+      .with(eq(user.user_id))
+      .return_const(Ok(user.clone()));
+
+    let mut tombstone_repo = MockTombstoneRepo::new();
+    tombstone_repo.expect_create_tombstone().times(0);
+
+    let mut session_repo = MockSessionRepo::new();
+    session_repo.expect_delete_user_sessions().times(0);
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo.expect_create().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let tombstones: TombstonePool = Arc::new(tombstone_repo);
+    let sessions: SessionPool = Arc::new(session_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      delete_user_account(&user.user_id, "test___", &users, &sessions, &tombstones, &jobs, &queue).await,
+      Err(LogicErr::UnauthorizedError)
+    );
+  }
+
+  #[async_std::test]
+  async fn test_move_user_account_rejects_local_target() {
+    let user = User::test_local(Uuid::new_v4());
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_fetch_by_id()
+      .times(1)
+      .with(eq(user.user_id))
+      .return_const(Ok(user.clone()));
+    user_repo.expect_update_from().times(0);
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo.expect_create().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let domain_blocks: DomainBlockPool = Arc::new(MockDomainBlockRepo::new());
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    let target = format!("{}/user/other", SETTINGS.server.api_fqdn);
+
+    assert_eq!(
+      move_user_account(&user.user_id, &target, &users, &domain_blocks, &jobs, &queue).await,
+      Err(LogicErr::InvalidOperation(
+        "Accounts can only be moved to another server".to_string()
+      ))
+    );
+  }
+
+  #[async_std::test]
+  async fn test_update_user_profile_rejects_external_user() {
+    let user = User::test_remote(Uuid::new_v4(), "example.com");
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo.expect_update_from().times(0);
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo.expect_create().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      update_user_profile(&user, &users, &jobs, &queue).await,
+      Err(LogicErr::InvalidOperation(
+        "Only local profiles can be updated".to_string()
+      ))
+    );
+  }
+
+  #[async_std::test]
+  async fn test_update_user_profile_rejects_non_web_links() {
+    let user = User {
+      url_1: Some("https://example.com".to_string()),
+      url_2: Some("javascript:alert(1)".to_string()),
+      ..User::test_local(Uuid::new_v4())
+    };
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo.expect_update_from().times(0);
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo.expect_create().times(0);
+
+    let users: UserPool = Arc::new(user_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(MockQueueBackend::new()));
+
+    assert_eq!(
+      update_user_profile(&user, &users, &jobs, &queue).await,
+      Err(LogicErr::InvalidOperation(
+        "Profile links must be http or https URLs".to_string()
+      ))
+    );
+  }
+
+  #[async_std::test]
+  async fn test_update_user_profile_queues_update() {
+    let user = User {
+      url_1: Some("https://example.com".to_string()),
+      ..User::test_local(Uuid::new_v4())
+    };
+    let user_id = user.user_id;
+    let job_id = Uuid::new_v4();
+
+    let mut user_repo = MockUserRepo::new();
+    user_repo
+      .expect_update_from()
+      .times(1)
+      .with(eq(user.clone()))
+      .return_const(Ok(user.clone()));
+
+    let mut job_repo = MockJobRepo::new();
+    job_repo
+      .expect_create()
+      .withf(move |job| job.created_by_id == Some(user_id) && job.record_id == Some(user_id))
+      .times(1)
+      .return_const(Ok(job_id));
+
+    let mut queue_be = MockQueueBackend::new();
+    queue_be
+      .expect_send_job()
+      .withf(move |job| job.job_id == job_id && job.job_type == QueueJobType::FederateUpdateProfile)
+      .times(1)
+      .return_const(Ok(()));
+
+    let users: UserPool = Arc::new(user_repo);
+    let jobs: JobPool = Arc::new(job_repo);
+    let queue = Queue::new_inner(Box::new(queue_be));
+
+    assert_eq!(update_user_profile(&user, &users, &jobs, &queue).await, Ok(()));
+  }
 }
@@ -29,26 +29,35 @@ use db::repository::Repository;
 use deadpool::Runtime;
 use deadpool_postgres::{ManagerConfig, RecyclingMethod};
 use env_logger::WriteStyle;
+use federation::activitypub::set_instance_private_key;
 use helpers::types::{ACTIVITYPUB_ACCEPT_GUARD, HTML_GUARD};
+use log::error;
 use log::LevelFilter;
+use logic::instance_actor::get_instance_actor;
 use net::jwt_session::JwtSession;
 use rabbitmq::clients::RabbitMQClient;
 use routes::activitypub::{
   api_activitypub_federate_orbit_inbox, api_activitypub_federate_shared_inbox, api_activitypub_federate_user_inbox,
   api_activitypub_get_comment, api_activitypub_get_comments, api_activitypub_get_federated_orbit_posts,
-  api_activitypub_get_federated_user_liked_posts, api_activitypub_get_federated_user_posts, api_activitypub_get_orbit,
-  api_activitypub_get_orbit_members, api_activitypub_get_post, api_activitypub_get_user_followers,
-  api_activitypub_get_user_following, api_activitypub_get_user_profile,
+  api_activitypub_get_federated_user_liked_posts, api_activitypub_get_federated_user_posts,
+  api_activitypub_get_hashtag_posts, api_activitypub_get_instance_actor, api_activitypub_get_orbit,
+  api_activitypub_get_orbit_members, api_activitypub_get_post, api_activitypub_get_post_boost,
+  api_activitypub_get_user_followers, api_activitypub_get_user_following, api_activitypub_get_user_profile,
 };
 use routes::apps::api_create_app;
+use routes::block::{api_create_block, api_delete_block};
 use routes::comment::{
   api_create_comment, api_create_comment_like, api_delete_comment, api_delete_comment_like, api_get_comment,
   api_get_comments,
 };
+use routes::domain_block::{
+  api_create_domain_block, api_delete_domain_block, api_get_domain_blocks, api_import_domain_blocks,
+};
 use routes::follow::{api_create_follow, api_delete_follow};
 use routes::host_meta::api_get_host_meta;
 use routes::job::api_job_query_status;
 use routes::like::{api_create_like, api_delete_like};
+use routes::mention::api_get_mentions;
 use routes::nodeinfo::{api_get_nodeinfo, api_get_nodeinfo_2_1};
 use routes::oauth::{api_oauth_authorize, api_oauth_authorize_post, api_oauth_token};
 use routes::orbit::{
@@ -56,21 +65,25 @@ use routes::orbit::{
   api_get_orbit_moderators, api_get_orbit_named, api_get_orbits, api_get_popular_orbits, api_get_user_orbits,
   api_join_orbit, api_leave_orbit, api_update_orbit, api_update_orbit_assets, api_update_orbit_moderator,
 };
+use routes::poll::api_vote_in_poll;
 use routes::post::{
-  api_boost_post, api_create_post, api_delete_post, api_get_global_feed, api_get_orbit_feed, api_get_orbit_feed_by_id,
-  api_get_post, api_get_user_friends_feed, api_get_user_liked_posts, api_get_user_own_feed, api_get_user_post,
-  api_get_user_posts, api_unboost_post, api_upload_post_image,
+  api_boost_post, api_create_post, api_delete_post, api_get_global_feed, api_get_hashtag_feed, api_get_orbit_feed,
+  api_get_orbit_feed_by_id, api_get_post, api_get_post_revisions, api_get_user_friends_feed, api_get_user_liked_posts,
+  api_get_user_own_feed, api_get_user_post, api_get_user_posts, api_unboost_post, api_update_post,
+  api_upload_post_image,
 };
 use routes::public::web_serve_static;
 use routes::redirect::{
-  api_redirect_to_federated_user_liked_posts, api_redirect_to_federated_user_posts, api_redirect_to_orbit,
-  api_redirect_to_orbit_members, api_redirect_to_post, api_redirect_to_post_comment, api_redirect_to_post_comments,
-  api_redirect_to_user, api_redirect_to_user_followers, api_redirect_to_user_following,
+  api_redirect_to_federated_user_liked_posts, api_redirect_to_federated_user_posts, api_redirect_to_hashtag,
+  api_redirect_to_orbit, api_redirect_to_orbit_members, api_redirect_to_post, api_redirect_to_post_comment,
+  api_redirect_to_post_comments, api_redirect_to_user, api_redirect_to_user_followers, api_redirect_to_user_following,
 };
+use routes::relay::{api_get_relays, api_subscribe_relay, api_unsubscribe_relay};
+use routes::report::{api_create_report, api_get_reports};
 use routes::status::api_get_server_status;
 use routes::user::{
-  api_get_profile, api_get_user_followers, api_get_user_following, api_get_user_profile, api_get_user_stats,
-  api_update_profile, api_update_profile_assets,
+  api_delete_profile, api_get_profile, api_get_user_followers, api_get_user_following, api_get_user_profile,
+  api_get_user_stats, api_move_profile, api_update_profile, api_update_profile_aliases, api_update_profile_assets,
 };
 use routes::webfinger::api_webfinger_query_resource;
 use settings::SETTINGS;
@@ -126,6 +139,20 @@ async fn main() -> std::io::Result<()> {
   let orbit_moderators = Repository::new_orbit_moderator_pool(&pool);
   let user_orbits = Repository::new_user_orbit_pool(&pool);
   let tombstones = Repository::new_tombstone_pool(&pool);
+  let instance_actors = Repository::new_instance_actor_pool(&pool);
+  let user_blocks = Repository::new_user_block_pool(&pool);
+  let domain_blocks = Repository::new_domain_block_pool(&pool);
+  let reports = Repository::new_report_pool(&pool);
+  let relays = Repository::new_relay_pool(&pool);
+  let mentions = Repository::new_mention_pool(&pool);
+  let hashtags = Repository::new_hashtag_pool(&pool);
+  let polls = Repository::new_poll_pool(&pool);
+  let post_revisions = Repository::new_post_revision_pool(&pool);
+
+  match get_instance_actor(&instance_actors).await {
+    Ok(actor) => set_instance_private_key(actor.private_key),
+    Err(err) => error!("Failed to load instance actor: {}", err),
+  }
 
   HttpServer::new(move || {
     let cors = Cors::default()
@@ -155,6 +182,15 @@ async fn main() -> std::io::Result<()> {
       .app_data(web::Data::new(orbit_moderators.clone()))
       .app_data(web::Data::new(user_orbits.clone()))
       .app_data(web::Data::new(tombstones.clone()))
+      .app_data(web::Data::new(instance_actors.clone()))
+      .app_data(web::Data::new(user_blocks.clone()))
+      .app_data(web::Data::new(domain_blocks.clone()))
+      .app_data(web::Data::new(reports.clone()))
+      .app_data(web::Data::new(relays.clone()))
+      .app_data(web::Data::new(mentions.clone()))
+      .app_data(web::Data::new(hashtags.clone()))
+      .app_data(web::Data::new(polls.clone()))
+      .app_data(web::Data::new(post_revisions.clone()))
       .app_data(web::Data::new(Cdn::new()))
       .app_data(web::Data::new(Queue::new()))
       .service(
@@ -232,6 +268,54 @@ async fn main() -> std::io::Result<()> {
           .route(web::post().to(api_create_follow))
           .route(web::delete().to(api_delete_follow)),
       )
+      .service(
+        web::resource("/api/users/{user_handle}/blocks")
+          .name("user_blocks")
+          .route(web::post().to(api_create_block))
+          .route(web::delete().to(api_delete_block)),
+      )
+      .service(
+        web::resource("/api/mentions")
+          .name("mentions")
+          .route(web::get().to(api_get_mentions)),
+      )
+      .service(
+        web::resource("/api/reports")
+          .name("reports")
+          .route(web::post().to(api_create_report)),
+      )
+      .service(
+        web::resource("/api/admin/reports")
+          .name("admin_reports")
+          .route(web::get().to(api_get_reports)),
+      )
+      .service(
+        web::resource("/api/admin/relays")
+          .name("admin_relays")
+          .route(web::get().to(api_get_relays))
+          .route(web::post().to(api_subscribe_relay)),
+      )
+      .service(
+        web::resource("/api/admin/relays/{relay_id}")
+          .name("admin_relay")
+          .route(web::delete().to(api_unsubscribe_relay)),
+      )
+      .service(
+        web::resource("/api/admin/domain_blocks")
+          .name("admin_domain_blocks")
+          .route(web::get().to(api_get_domain_blocks))
+          .route(web::post().to(api_create_domain_block)),
+      )
+      .service(
+        web::resource("/api/admin/domain_blocks/import")
+          .name("admin_domain_blocks_import")
+          .route(web::post().to(api_import_domain_blocks)),
+      )
+      .service(
+        web::resource("/api/admin/domain_blocks/{domain}")
+          .name("admin_domain_block")
+          .route(web::delete().to(api_delete_domain_block)),
+      )
       .service(
         web::resource("/api/users/{user_handle}/followers")
           .name("user_followers")
@@ -279,6 +363,17 @@ async fn main() -> std::io::Result<()> {
           .name("federated_feed")
           .route(web::get().to(api_get_global_feed)),
       )
+      .service(
+        web::resource("/api/tags/{hashtag}")
+          .name("hashtag_feed")
+          .route(
+            web::get()
+              .guard(ACTIVITYPUB_ACCEPT_GUARD)
+              .to(api_activitypub_get_hashtag_posts),
+          )
+          .route(web::get().guard(HTML_GUARD).to(api_redirect_to_hashtag))
+          .route(web::get().to(api_get_hashtag_feed)),
+      )
       .service(
         web::resource("/api/orbits/{orbit_shortcode}/feed")
           .name("orbit_feed")
@@ -291,6 +386,7 @@ async fn main() -> std::io::Result<()> {
           .route(web::get().guard(HTML_GUARD).to(api_redirect_to_post))
           .route(web::get().to(api_get_post))
           .route(web::post().to(api_upload_post_image))
+          .route(web::patch().to(api_update_post))
           .route(web::delete().to(api_delete_post)),
       )
       .service(
@@ -316,12 +412,27 @@ async fn main() -> std::io::Result<()> {
           .route(web::get().to(api_get_comments))
           .route(web::post().to(api_create_comment)),
       )
+      .service(
+        web::resource("/api/feed/{post_id}/poll/votes")
+          .name("post_poll_votes")
+          .route(web::post().to(api_vote_in_poll)),
+      )
+      .service(
+        web::resource("/api/feed/{post_id}/revisions")
+          .name("post_revisions")
+          .route(web::get().to(api_get_post_revisions)),
+      )
       .service(
         web::resource("/api/feed/{post_id}/boost")
           .name("post_boosts")
           .route(web::post().to(api_boost_post))
           .route(web::delete().to(api_unboost_post)),
       )
+      .service(
+        web::resource("/api/feed/{post_id}/boosts/{user_id}")
+          .name("post_boost")
+          .route(web::get().to(api_activitypub_get_post_boost)),
+      )
       .service(
         web::resource("/api/feed/{post_id}/comments/{comment_id}/likes")
           .name("post_comment_likes")
@@ -344,7 +455,18 @@ async fn main() -> std::io::Result<()> {
         web::resource("/api/profile")
           .name("profile")
           .route(web::get().to(api_get_profile))
-          .route(web::post().to(api_update_profile)),
+          .route(web::post().to(api_update_profile))
+          .route(web::delete().to(api_delete_profile)),
+      )
+      .service(
+        web::resource("/api/profile/aliases")
+          .name("profile_aliases")
+          .route(web::put().to(api_update_profile_aliases)),
+      )
+      .service(
+        web::resource("/api/profile/move")
+          .name("profile_move")
+          .route(web::post().to(api_move_profile)),
       )
       .service(
         web::resource("/api/profile/assets")
@@ -440,6 +562,11 @@ async fn main() -> std::io::Result<()> {
           .name("federate_activitypub")
           .route(web::post().to(api_activitypub_federate_orbit_inbox)),
       )
+      .service(
+        web::resource("/api/actor")
+          .name("instance_actor")
+          .route(web::get().to(api_activitypub_get_instance_actor)),
+      )
       .service(
         web::resource("/api/federate/activitypub/shared-inbox")
           .name("federate_activitypub")
@@ -5,14 +5,18 @@ use uuid::Uuid;
 
 use crate::db::FromRow;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 /// Represents a user's comment on a post
 pub struct Comment {
   pub comment_id: Uuid,
   pub user_id: Uuid,
   pub post_id: Uuid,
+  /// The comment this comment is replying to, if it isn't replying to the post directly
+  pub parent_comment_id: Option<Uuid>,
   pub content_md: String,
   pub content_html: String,
+  pub uri: String,
+  pub is_external: bool,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
 }
@@ -23,8 +27,11 @@ impl FromRow for Comment {
       comment_id: row.get("comment_id"),
       user_id: row.get("user_id"),
       post_id: row.get("post_id"),
+      parent_comment_id: row.get("parent_comment_id"),
       content_md: row.get("content_md"),
       content_html: row.get("content_html"),
+      uri: row.get("uri"),
+      is_external: row.get("is_external"),
       created_at: row.get("created_at"),
       updated_at: row.get("updated_at"),
     })
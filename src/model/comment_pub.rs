@@ -13,7 +13,7 @@ use crate::{
     reference::Reference,
   },
   db::FromRow,
-  settings::SETTINGS,
+  helpers::api::relative_to_absolute_uri,
 };
 
 use super::access_type::AccessType;
@@ -24,12 +24,20 @@ pub struct CommentPub {
   pub comment_id: Uuid,
   pub user_id: Uuid,
   pub post_id: Uuid,
+  pub post_uri: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub parent_comment_id: Option<Uuid>,
+  #[serde(skip)]
+  pub parent_comment_uri: Option<String>,
   pub content_md: String,
   pub content_html: String,
+  pub uri: String,
+  pub is_external: bool,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
   pub user_handle: String,
   pub user_fediverse_id: String,
+  pub user_fediverse_uri: String,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub user_avatar_url: Option<String>,
   pub likes: i64,
@@ -37,6 +45,11 @@ pub struct CommentPub {
   pub liked: Option<bool>,
   #[serde(skip)]
   pub visibility: AccessType,
+  /// The number of direct replies to this comment. When this is more than the number of `replies` included, the rest
+  /// can be loaded by fetching this comment's thread.
+  pub reply_count: i64,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub replies: Vec<CommentPub>,
 }
 
 impl FromRow for CommentPub {
@@ -45,30 +58,36 @@ impl FromRow for CommentPub {
       comment_id: row.get("comment_id"),
       user_id: row.get("user_id"),
       post_id: row.get("post_id"),
+      post_uri: row.get("post_uri"),
+      parent_comment_id: row.get("parent_comment_id"),
+      parent_comment_uri: row.get("parent_comment_uri"),
       content_md: row.get("content_md"),
       content_html: row.get("content_html"),
+      uri: row.get("uri"),
+      is_external: row.get("is_external"),
       created_at: row.get("created_at"),
       updated_at: row.get("updated_at"),
       user_handle: row.get("user_handle"),
       user_fediverse_id: row.get("user_fediverse_id"),
+      user_fediverse_uri: row.get("user_fediverse_uri"),
       user_avatar_url: row.get("user_avatar_url"),
       likes: row.get("likes"),
       liked: row.get("liked"),
       visibility: AccessType::from_str(row.get("visibility")).unwrap_or_default(),
+      reply_count: row.get("reply_count"),
+      replies: vec![],
     })
   }
 }
 
 impl ActivityConvertible for CommentPub {
   fn to_object(&self, actor: &str) -> Option<Object> {
-    let id = format!(
-      "{}/feed/{}/comments/{}",
-      SETTINGS.server.api_fqdn, self.post_id, self.comment_id
-    );
+    let id = relative_to_absolute_uri(&self.uri);
 
-    let attributed_to_uri = format!("{}/user/{}", SETTINGS.server.api_fqdn, self.user_id);
+    let attributed_to_uri = relative_to_absolute_uri(&self.user_fediverse_uri);
     let cc_uri = format!("{}/followers", actor);
-    let in_reply_to_uri = format!("{}/feed/{}", SETTINGS.server.api_fqdn, self.post_id);
+    // Replies to other comments point at the comment they're replying to, so that other servers can thread them
+    let in_reply_to_uri = relative_to_absolute_uri(self.parent_comment_uri.as_ref().unwrap_or(&self.post_uri));
 
     let to = match self.visibility {
       AccessType::Shadow => None,
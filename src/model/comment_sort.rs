@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// The order the comments of a thread are listed in
+#[derive(Deserialize, Serialize, EnumString, Display, Debug, PartialEq, Eq, Clone, Copy)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CommentSort {
+  /// Newest comments first
+  New,
+  /// Most liked comments first
+  Top,
+  /// Comments that have drawn plenty of both likes and replies, split evenly between them, first. We don't have
+  /// downvotes, so replies stand in for the pushback against a comment
+  Controversial,
+}
+
+impl Default for CommentSort {
+  fn default() -> Self {
+    CommentSort::New
+  }
+}
@@ -0,0 +1,33 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use super::domain_block_severity::DomainBlockSeverity;
+use crate::db::FromRow;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// Represents an instance-wide block on a remote server and its subdomains
+pub struct DomainBlock {
+  pub domain_block_id: Uuid,
+  pub domain: String,
+  pub severity: DomainBlockSeverity,
+  pub public_comment: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl FromRow for DomainBlock {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(DomainBlock {
+      domain_block_id: row.get("domain_block_id"),
+      domain: row.get("domain"),
+      severity: DomainBlockSeverity::from_str(row.get("severity")).unwrap_or_default(),
+      public_comment: row.get("public_comment"),
+      created_at: row.get("created_at"),
+      updated_at: row.get("updated_at"),
+    })
+  }
+}
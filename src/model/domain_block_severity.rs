@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+#[derive(Deserialize, Serialize, EnumString, Display, Debug, PartialEq, Eq, Clone)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DomainBlockSeverity {
+  /// Nothing is accepted from or delivered to the server
+  Suspend,
+  /// The server's content is accepted, but is hidden from the global feed
+  Silence,
+  /// The server's content is accepted, but any media attached to it is dropped
+  RejectMedia,
+}
+
+impl Default for DomainBlockSeverity {
+  fn default() -> Self {
+    DomainBlockSeverity::Silence
+  }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::{
+  activitypub::{
+    activity_convertible::ActivityConvertible,
+    actor::ActorProps,
+    key::KeyProps,
+    object::{Object, ObjectType},
+    reference::Reference,
+  },
+  db::FromRow,
+  helpers::{api::relative_to_absolute_uri, types::RELATIVE_API_ROOT_FQDN},
+  settings::SETTINGS,
+};
+
+use super::webfinger::{WebfingerRecord, WebfingerRecordLink};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// Represents the server-wide actor that signs requests on behalf of the instance rather than a specific user or orbit
+pub struct InstanceActor {
+  pub instance_actor_id: Uuid,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+  pub private_key: String,
+  pub public_key: String,
+}
+
+impl InstanceActor {
+  /// The instance actor's URI, relative to the API root in the same manner as a local user's fediverse URI
+  pub fn fediverse_uri() -> String {
+    "/actor".to_string()
+  }
+
+  pub fn key_id() -> String {
+    format!("{}#main-key", relative_to_absolute_uri(&InstanceActor::fediverse_uri()))
+  }
+
+  /// The instance actor is named after the instance's domain, i.e. `acct:example.com@example.com`
+  pub fn webfinger_subject() -> String {
+    format!("acct:{}@{}", *RELATIVE_API_ROOT_FQDN, *RELATIVE_API_ROOT_FQDN)
+  }
+
+  pub fn to_webfinger() -> WebfingerRecord {
+    WebfingerRecord {
+      aliases: Some(vec![WebfingerRecordLink::build_instance_actor_self_uri()]),
+      subject: InstanceActor::webfinger_subject(),
+      links: [
+        WebfingerRecordLink::build_instance_actor_self_link(),
+        WebfingerRecordLink::build_instance_actor_page_link(),
+      ]
+      .into(),
+    }
+  }
+}
+
+impl FromRow for InstanceActor {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(InstanceActor {
+      instance_actor_id: row.get("instance_actor_id"),
+      created_at: row.get("created_at"),
+      updated_at: row.get("updated_at"),
+      private_key: row.get("private_key"),
+      public_key: row.get("public_key"),
+    })
+  }
+}
+
+impl ActivityConvertible for InstanceActor {
+  fn to_object(&self, _actor: &str) -> Option<Object> {
+    let id = relative_to_absolute_uri(&InstanceActor::fediverse_uri());
+    let public_inbox_uri = format!("{}/federate/activitypub/shared-inbox", SETTINGS.server.api_fqdn);
+    let mut endpoints = HashMap::new();
+    endpoints.insert(
+      "sharedInbox".to_string(),
+      serde_json::Value::String(public_inbox_uri.clone()),
+    );
+
+    let key_props = KeyProps::builder()
+      .id(Some(InstanceActor::key_id()))
+      .owner(Some(id.clone()))
+      .public_key_pem(Some(self.public_key.clone()))
+      .build();
+
+    Some(
+      Object::builder()
+        .id(Some(id.clone()))
+        .kind(Some(ObjectType::Application.to_string()))
+        .url(Some(Reference::Remote(id)))
+        .actors(Some(
+          ActorProps::builder()
+            .endpoints(Some(Reference::Map(endpoints)))
+            .inbox(Some(Reference::Remote(public_inbox_uri)))
+            .preferred_username(Some(RELATIVE_API_ROOT_FQDN.to_owned()))
+            .build(),
+        ))
+        .key(Some(key_props))
+        .build(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_to_webfinger_returns_data() {
+    temp_env::with_vars(vec![("RUN_MODE", Some("production"))], || {
+      let finger = InstanceActor::to_webfinger();
+
+      assert_eq!(&finger.subject, "acct:0.0.0.0:8080@0.0.0.0:8080");
+      assert!(finger.aliases.is_some());
+      assert_eq!(finger.aliases.unwrap().len(), 1);
+      assert_eq!(finger.links.len(), 2);
+
+      assert_eq!(finger.links[0].rel, "self");
+      assert_eq!(finger.links[0].link_type, "application/activity+json");
+      assert!(finger.links[0].href.is_some());
+      assert_eq!(finger.links[0].href.as_ref().unwrap(), "http://0.0.0.0:8080/api/actor");
+
+      assert_eq!(finger.links[1].rel, "http://webfinger.net/rel/profile-page");
+      assert_eq!(finger.links[1].link_type, "text/html");
+      assert!(finger.links[1].href.is_some());
+      assert_eq!(finger.links[1].href.as_ref().unwrap(), "http://0.0.0.0:8080");
+    });
+  }
+}
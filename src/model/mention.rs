@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::db::FromRow;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// Represents one of our users being mentioned in a post or comment, which is kept so that they can be notified of it.
+/// Exactly one of `post_id` and `comment_id` is set.
+pub struct Mention {
+  pub mention_id: Uuid,
+  /// The user that was mentioned
+  pub user_id: Uuid,
+  /// The user that wrote the post or comment, who may be a remote user
+  pub author_user_id: Uuid,
+  pub post_id: Option<Uuid>,
+  pub comment_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl FromRow for Mention {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(Mention {
+      mention_id: row.get("mention_id"),
+      user_id: row.get("user_id"),
+      author_user_id: row.get("author_user_id"),
+      post_id: row.get("post_id"),
+      comment_id: row.get("comment_id"),
+      created_at: row.get("created_at"),
+    })
+  }
+}
@@ -2,19 +2,29 @@ pub mod access_type;
 pub mod app;
 pub mod comment;
 pub mod comment_pub;
+pub mod comment_sort;
+pub mod domain_block;
+pub mod domain_block_severity;
 pub mod event;
 pub mod event_type;
 pub mod follow;
+pub mod instance_actor;
 pub mod job;
 pub mod like;
+pub mod mention;
 pub mod orbit;
 pub mod orbit_moderator;
 pub mod orbit_pub;
+pub mod poll;
 pub mod post;
 pub mod post_attachment;
 pub mod post_create_request;
 pub mod post_event;
+pub mod post_revision;
 pub mod queue_job;
+pub mod relay;
+pub mod relay_status;
+pub mod report;
 pub mod response;
 pub mod session;
 pub mod tombstone;
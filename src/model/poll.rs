@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::Entry::Vacant, HashMap};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::{
+  db::{FromRow, FromRows},
+  logic::LogicErr,
+};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// Represents a poll attached to a post, which is either one of ours or one that was federated to us from another
+/// server
+pub struct Poll {
+  pub poll_id: Uuid,
+  pub post_id: Uuid,
+  pub multiple_choice: bool,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl Poll {
+  pub fn is_closed(&self) -> bool {
+    matches!(self.expires_at, Some(expires_at) if expires_at <= Utc::now())
+  }
+}
+
+impl FromRow for Poll {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(Poll {
+      poll_id: row.get("poll_id"),
+      post_id: row.get("post_id"),
+      multiple_choice: row.get("multiple_choice"),
+      expires_at: row.get("expires_at"),
+      created_at: row.get("created_at"),
+      updated_at: row.get("updated_at"),
+    })
+  }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct PollOption {
+  pub option_id: Uuid,
+  pub poll_id: Uuid,
+  pub position: i32,
+  pub name: String,
+  pub remote_votes: i32,
+}
+
+impl FromRow for PollOption {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(PollOption {
+      option_id: row.get("option_id"),
+      poll_id: row.get("poll_id"),
+      position: row.get("position"),
+      name: row.get("name"),
+      remote_votes: row.get("remote_votes"),
+    })
+  }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// Represents a user's vote for one of a poll's options. Votes cast on remote servers for our polls carry the URI of
+/// the activity they were sent in.
+pub struct PollVote {
+  pub vote_id: Uuid,
+  pub poll_id: Uuid,
+  pub option_id: Uuid,
+  pub user_id: Uuid,
+  pub uri: Option<String>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl FromRow for PollVote {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(PollVote {
+      vote_id: row.get("vote_id"),
+      poll_id: row.get("poll_id"),
+      option_id: row.get("option_id"),
+      user_id: row.get("user_id"),
+      uri: row.get("uri"),
+      created_at: row.get("created_at"),
+    })
+  }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct PostPollOption {
+  pub option_id: Uuid,
+  pub name: String,
+  pub votes: i64,
+  pub voted: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// A poll as it's presented alongside its post, with the number of votes each option has received and whether the
+/// viewing user has voted in it
+pub struct PostPoll {
+  pub poll_id: Uuid,
+  #[serde(skip)]
+  pub post_id: Uuid,
+  pub multiple_choice: bool,
+  pub expires_at: Option<DateTime<Utc>>,
+  pub closed: bool,
+  pub voted: bool,
+  pub options: Vec<PostPollOption>,
+}
+
+impl FromRows for PostPoll {
+  fn from_rows(rows: Vec<Row>) -> Result<Vec<Self>, LogicErr> {
+    let mut ret: Vec<PostPoll> = vec![];
+    let mut lookup = HashMap::<Uuid, usize>::new();
+
+    for row in rows.into_iter() {
+      let poll_id: Uuid = row.get("poll_id");
+      if let Vacant(e) = lookup.entry(poll_id) {
+        let expires_at: Option<DateTime<Utc>> = row.get("expires_at");
+
+        e.insert(ret.len());
+        ret.push(PostPoll {
+          poll_id,
+          post_id: row.get("post_id"),
+          multiple_choice: row.get("multiple_choice"),
+          expires_at,
+          closed: matches!(expires_at, Some(expires_at) if expires_at <= Utc::now()),
+          voted: false,
+          options: vec![],
+        });
+      }
+
+      let poll = match ret.get_mut(lookup[&poll_id]) {
+        Some(poll) => poll,
+        None => continue,
+      };
+
+      let option = PostPollOption {
+        option_id: row.get("option_id"),
+        name: row.get("name"),
+        votes: row.get("votes"),
+        voted: row.get("voted"),
+      };
+
+      poll.voted = poll.voted || option.voted;
+      poll.options.push(option);
+    }
+
+    Ok(ret)
+  }
+}
@@ -11,7 +11,9 @@ use crate::{
   activitypub::{
     activity_convertible::ActivityConvertible,
     collection::CollectionProps,
-    object::{Object, ObjectSource},
+    link::LinkProps,
+    object::{Object, ObjectSource, ObjectType},
+    question::{QuestionClosed, QuestionProps},
     rdf_string::RdfString,
     reference::Reference,
   },
@@ -21,7 +23,7 @@ use crate::{
   settings::SETTINGS,
 };
 
-use super::{access_type::AccessType, event_type::EventType, post_attachment::PostAttachment};
+use super::{access_type::AccessType, event_type::EventType, poll::PostPoll, post_attachment::PostAttachment};
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
 pub struct PostEvent {
@@ -59,6 +61,9 @@ pub struct PostEvent {
   pub orbit_fediverse_uri: Option<String>,
   pub orbit_avatar_uri: Option<String>,
   pub attachments: Vec<PostAttachment>,
+  pub hashtags: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub poll: Option<PostPoll>,
 }
 
 impl FromRow for PostEvent {
@@ -92,6 +97,8 @@ impl FromRow for PostEvent {
       orbit_fediverse_uri: row.get("orbit_fediverse_uri"),
       orbit_avatar_uri: row.get("orbit_avatar_uri"),
       attachments: vec![],
+      hashtags: row.get("hashtags"),
+      poll: None,
     })
   }
 }
@@ -228,9 +235,78 @@ impl ActivityConvertible for PostEvent {
       })
       .collect();
 
-    let object_kind = Some(match self.orbit_id.is_some() {
-      true => "Article".to_owned(),
-      false => "Note".to_owned(),
+    let hashtag_refs = self
+      .hashtags
+      .iter()
+      .map(|hashtag| {
+        Reference::Embedded(Box::new(
+          Object::builder()
+            .kind(Some(ObjectType::Hashtag.to_string()))
+            .name(Some(format!("#{}", hashtag)))
+            .link(Some(
+              LinkProps::builder()
+                .href(Some(Reference::Remote(format!(
+                  "{}/tags/{}",
+                  SETTINGS.server.api_fqdn, hashtag
+                ))))
+                .build(),
+            ))
+            .build(),
+        ))
+      })
+      .collect::<Vec<Reference<Object>>>();
+
+    let tag = match hashtag_refs.is_empty() {
+      true => None,
+      false => Some(Reference::Mixed(hashtag_refs)),
+    };
+
+    // Polls are sent as Questions, with each option's vote count carried in its replies collection
+    let question = self.poll.as_ref().map(|poll| {
+      let option_refs = poll
+        .options
+        .iter()
+        .map(|option| {
+          Reference::Embedded(Box::new(
+            Object::builder()
+              .kind(Some(ObjectType::Note.to_string()))
+              .name(Some(option.name.clone()))
+              .replies(Some(Box::new(
+                Object::builder()
+                  .kind(Some("Collection".to_string()))
+                  .collection(Some(
+                    CollectionProps::builder()
+                      .total_items(Some(option.votes.try_into().unwrap_or_default()))
+                      .build(),
+                  ))
+                  .build(),
+              )))
+              .build(),
+          ))
+        })
+        .collect::<Vec<Reference<Object>>>();
+
+      let closed = match (poll.closed, poll.expires_at) {
+        (true, Some(expires_at)) => Some(QuestionClosed::Date(expires_at)),
+        _ => None,
+      };
+
+      match poll.multiple_choice {
+        true => QuestionProps::builder()
+          .any_of(Some(Reference::Mixed(option_refs)))
+          .closed(closed)
+          .build(),
+        false => QuestionProps::builder()
+          .one_of(Some(Reference::Mixed(option_refs)))
+          .closed(closed)
+          .build(),
+      }
+    });
+
+    let object_kind = Some(match (self.poll.is_some(), self.orbit_id.is_some()) {
+      (true, _) => ObjectType::Question.to_string(),
+      (false, true) => "Article".to_owned(),
+      (false, false) => "Note".to_owned(),
     });
 
     Some(
@@ -251,8 +327,15 @@ impl ActivityConvertible for PostEvent {
             .build(),
         ))
         .published(Some(self.created_at))
+        .updated(match self.updated_at > self.created_at {
+          true => Some(self.updated_at),
+          false => None,
+        })
         .attachment(Some(Reference::Mixed(attachment_refs)))
+        .tag(tag)
         .audience(audience)
+        .end_time(self.poll.as_ref().and_then(|poll| poll.expires_at))
+        .question(question)
         .build(),
     )
   }
@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::db::FromRow;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// Represents a version of a post as it was before it was edited, whether the edit was made here or on the server the
+/// post came from
+pub struct PostRevision {
+  pub revision_id: Uuid,
+  pub post_id: Uuid,
+  pub title: Option<String>,
+  pub content_md: String,
+  pub content_html: String,
+  /// When this version of the post was written
+  pub created_at: DateTime<Utc>,
+}
+
+impl FromRow for PostRevision {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(PostRevision {
+      revision_id: row.get("revision_id"),
+      post_id: row.get("post_id"),
+      title: row.get("title"),
+      content_md: row.get("content_md"),
+      content_html: row.get("content_html"),
+      created_at: row.get("created_at"),
+    })
+  }
+}
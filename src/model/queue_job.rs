@@ -24,6 +24,21 @@ pub enum QueueJobType {
   RefreshExternalProfile,
   RefreshExternalOrbits,
   RefreshExternalOrbit,
+  FederateCreateComment,
+  FederateDeleteComment,
+  FederateUpdateProfile,
+  DeleteUser,
+  FederateMoveProfile,
+  FederateReport,
+  FederateFollowRelay,
+  FederateUnfollowRelay,
+  FederateRelayPost,
+  BackfillOutbox,
+  BackfillOrbit,
+  BackfillExternalOrbits,
+  FederateGroupAnnounce,
+  FederatePollVote,
+  UpdatePost,
 }
 
 impl Default for QueueJobType {
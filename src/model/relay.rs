@@ -0,0 +1,42 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use super::relay_status::RelayStatus;
+use crate::{db::FromRow, settings::SETTINGS};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// Represents the instance's subscription to a LitePub or Mastodon-style relay, which shares public posts between all
+/// of its subscribed servers
+pub struct Relay {
+  pub relay_id: Uuid,
+  pub actor_uri: String,
+  pub inbox_uri: String,
+  pub status: RelayStatus,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl Relay {
+  /// The URI of the Follow activity the instance actor sent to subscribe to the relay, which is also used when
+  /// unsubscribing
+  pub fn follow_activity_uri(&self) -> String {
+    format!("{}/relays/{}", SETTINGS.server.api_fqdn, self.relay_id)
+  }
+}
+
+impl FromRow for Relay {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(Relay {
+      relay_id: row.get("relay_id"),
+      actor_uri: row.get("actor_uri"),
+      inbox_uri: row.get("inbox_uri"),
+      status: RelayStatus::from_str(row.get("status")).unwrap_or_default(),
+      created_at: row.get("created_at"),
+      updated_at: row.get("updated_at"),
+    })
+  }
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+#[derive(Deserialize, Serialize, EnumString, Display, Debug, PartialEq, Eq, Clone)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RelayStatus {
+  /// We've asked to subscribe to the relay, but it hasn't responded yet
+  Pending,
+  /// The relay accepted our subscription, so we exchange public posts with it
+  Accepted,
+  /// The relay refused our subscription
+  Rejected,
+}
+
+impl Default for RelayStatus {
+  fn default() -> Self {
+    RelayStatus::Pending
+  }
+}
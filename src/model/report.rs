@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::db::FromRow;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone)]
+/// Represents a moderation report on a user and optionally some of their posts. Reports either come from one of our
+/// users, or from a remote server via a Flag activity.
+pub struct Report {
+  pub report_id: Uuid,
+  pub reporter_user_id: Option<Uuid>,
+  /// The actor that sent the Flag for external reports, which is usually the remote server's instance actor
+  pub reporter_uri: Option<String>,
+  pub target_user_id: Uuid,
+  pub post_ids: Vec<Uuid>,
+  pub comment: Option<String>,
+  pub uri: Option<String>,
+  pub is_external: bool,
+  /// Whether the report has been sent on to the reported user's server
+  pub forwarded: bool,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: DateTime<Utc>,
+}
+
+impl FromRow for Report {
+  fn from_row(row: Row) -> Option<Self> {
+    Some(Report {
+      report_id: row.get("report_id"),
+      reporter_user_id: row.get("reporter_user_id"),
+      reporter_uri: row.get("reporter_uri"),
+      target_user_id: row.get("target_user_id"),
+      post_ids: row.get("post_ids"),
+      comment: row.get("comment"),
+      uri: row.get("uri"),
+      is_external: row.get("is_external"),
+      forwarded: row.get("forwarded"),
+      created_at: row.get("created_at"),
+      updated_at: row.get("updated_at"),
+    })
+  }
+}
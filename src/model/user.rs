@@ -6,7 +6,8 @@ use uuid::Uuid;
 
 use crate::{
   activitypub::{
-    activity_convertible::ActivityConvertible, actor::ActorProps, key::KeyProps, object::Object, reference::Reference,
+    activity_convertible::ActivityConvertible, actor::ActorProps, key::KeyProps, object::Object, rdf_string::RdfString,
+    reference::Reference,
   },
   db::FromRow,
   helpers::api::{relative_cdn_to_absolute_cdn_uri, relative_to_absolute_uri},
@@ -43,6 +44,11 @@ pub struct User {
   pub ext_apub_following_uri: Option<String>,
   pub ext_apub_inbox_uri: Option<String>,
   pub ext_apub_outbox_uri: Option<String>,
+  pub ext_apub_shared_inbox_uri: Option<String>,
+  /// The absolute URIs of the other actors this user has declared as being themselves, used to verify account moves
+  pub also_known_as: Vec<String>,
+  /// The absolute URI of the actor this user has moved their account to
+  pub moved_to_uri: Option<String>,
   pub created_at: DateTime<Utc>,
   pub updated_at: DateTime<Utc>,
 }
@@ -60,6 +66,100 @@ impl User {
       .into(),
     }
   }
+
+  /// Builds PropertyValue attachments for the profile links, which is how Mastodon and friends display them
+  fn links_to_attachments(&self) -> Option<Reference<Object>> {
+    let links = [
+      (&self.url_1, &self.url_1_title),
+      (&self.url_2, &self.url_2_title),
+      (&self.url_3, &self.url_3_title),
+      (&self.url_4, &self.url_4_title),
+      (&self.url_5, &self.url_5_title),
+    ];
+
+    let attachments: Vec<Reference<Object>> = links
+      .into_iter()
+      .filter_map(|(url, title)| {
+        url.as_ref().map(|url| {
+          let mut props = HashMap::new();
+          props.insert(
+            "type".to_string(),
+            serde_json::Value::String("PropertyValue".to_string()),
+          );
+          props.insert(
+            "name".to_string(),
+            serde_json::Value::String(title.clone().unwrap_or_else(|| url.clone())),
+          );
+          props.insert("value".to_string(), serde_json::Value::String(url.clone()));
+          Reference::Map(props)
+        })
+      })
+      .collect();
+
+    match attachments.is_empty() {
+      true => None,
+      false => Some(Reference::Mixed(attachments)),
+    }
+  }
+}
+
+#[cfg(test)]
+impl User {
+  /// Builds one of our own users for tests, with a password hash matching "test"
+  pub fn test_local(user_id: Uuid) -> User {
+    User {
+      user_id,
+      fediverse_id: "@user@127.0.0.1:8000".to_string(),
+      handle: "user".to_string(),
+      fediverse_uri: "/user/user".to_string(),
+      avatar_url: None,
+      email: None,
+      password_hash: Some(
+        "$argon2id$v=19$m=4096,t=3,p=1$AAAAAAAAAAA$AZy4qHIzKBofdyGe6tO7fhh3Xl+3356Mi9SDONRcREE".to_string(),
+      ),
+      is_external: false,
+      url_1: None,
+      url_2: None,
+      url_3: None,
+      url_4: None,
+      url_5: None,
+      url_1_title: None,
+      url_2_title: None,
+      url_3_title: None,
+      url_4_title: None,
+      url_5_title: None,
+      intro_md: None,
+      intro_html: None,
+      private_key: "private".to_string(),
+      public_key: "public".to_string(),
+      ext_apub_followers_uri: None,
+      ext_apub_following_uri: None,
+      ext_apub_inbox_uri: None,
+      ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
+      created_at: Utc::now(),
+      updated_at: Utc::now(),
+    }
+  }
+
+  /// Builds a user from another server for tests, with their actor at https://{host}/users/user
+  pub fn test_remote(user_id: Uuid, host: &str) -> User {
+    let fediverse_uri = format!("https://{}/users/user", host);
+
+    User {
+      fediverse_id: format!("@user@{}", host),
+      handle: format!("user@{}", host),
+      password_hash: None,
+      is_external: true,
+      private_key: "".to_string(),
+      ext_apub_followers_uri: Some(format!("{}/followers", fediverse_uri)),
+      ext_apub_inbox_uri: Some(format!("{}/inbox", fediverse_uri)),
+      fediverse_uri,
+      ..User::test_local(user_id)
+    }
+  }
 }
 
 impl FromRow for User {
@@ -91,6 +191,9 @@ impl FromRow for User {
       ext_apub_following_uri: row.get("ext_apub_following_uri"),
       ext_apub_inbox_uri: row.get("ext_apub_inbox_uri"),
       ext_apub_outbox_uri: row.get("ext_apub_outbox_uri"),
+      ext_apub_shared_inbox_uri: row.get("ext_apub_shared_inbox_uri"),
+      also_known_as: row.get("also_known_as"),
+      moved_to_uri: row.get("moved_to_uri"),
       created_at: row.get("created_at"),
       updated_at: row.get("updated_at"),
     })
@@ -134,6 +237,8 @@ impl ActivityConvertible for User {
         .icon(icon)
         .url(Some(Reference::Remote(id)))
         .name(Some(self.handle.clone()))
+        .summary(self.intro_html.clone().map(RdfString::Raw))
+        .attachment(self.links_to_attachments())
         .actors(Some(
           ActorProps::builder()
             .endpoints(Some(Reference::Map(endpoints)))
@@ -143,6 +248,13 @@ impl ActivityConvertible for User {
             .outbox(Some(Reference::Remote(outbox_uri)))
             .liked(Some(Reference::Remote(liked_uri)))
             .preferred_username(Some(self.handle.clone()))
+            .also_known_as(match self.also_known_as.is_empty() {
+              true => None,
+              false => Some(Reference::Mixed(
+                self.also_known_as.iter().cloned().map(Reference::Remote).collect(),
+              )),
+            })
+            .moved_to(self.moved_to_uri.clone().map(Reference::Remote))
             .build(),
         ))
         .key(Some(key_props))
@@ -186,6 +298,9 @@ mod tests {
       ext_apub_following_uri: None,
       ext_apub_inbox_uri: None,
       ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
       created_at: Utc::now(),
       updated_at: Utc::now(),
     };
@@ -41,6 +41,9 @@ pub struct UserAccountPub {
   pub intro_md: Option<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub intro_html: Option<String>,
+  pub also_known_as: Vec<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub moved_to_uri: Option<String>,
   pub created_at: DateTime<Utc>,
 }
 
@@ -65,6 +68,8 @@ impl From<User> for UserAccountPub {
       url_5_title: u.url_5_title,
       intro_md: u.intro_md,
       intro_html: u.intro_html,
+      also_known_as: u.also_known_as,
+      moved_to_uri: u.moved_to_uri,
       created_at: u.created_at,
     }
   }
@@ -106,6 +111,9 @@ mod tests {
       ext_apub_following_uri: None,
       ext_apub_inbox_uri: None,
       ext_apub_outbox_uri: None,
+      ext_apub_shared_inbox_uri: None,
+      also_known_as: vec![],
+      moved_to_uri: None,
       created_at: Utc::now(),
       updated_at: Utc::now(),
     };
@@ -68,6 +68,28 @@ impl WebfingerRecordLink {
     }
   }
 
+  pub fn build_instance_actor_self_uri() -> String {
+    format!("{}/actor", SETTINGS.server.api_fqdn)
+  }
+
+  pub fn build_instance_actor_self_link() -> WebfingerRecordLink {
+    WebfingerRecordLink {
+      rel: "self".to_string(),
+      link_type: "application/activity+json".to_string(),
+      href: Some(format!("{}/actor", SETTINGS.server.api_fqdn)),
+      template: None,
+    }
+  }
+
+  pub fn build_instance_actor_page_link() -> WebfingerRecordLink {
+    WebfingerRecordLink {
+      rel: "http://webfinger.net/rel/profile-page".to_string(),
+      link_type: "text/html".to_string(),
+      href: Some(SETTINGS.server.fqdn.to_owned()),
+      template: None,
+    }
+  }
+
   pub fn build_orbit_feed_link(id: &Uuid) -> WebfingerRecordLink {
     WebfingerRecordLink {
       rel: "feed".to_string(),
@@ -1,11 +1,126 @@
 use actix_web::HttpRequest;
+use chrono::{DateTime, Duration, Utc};
 use http::HeaderMap;
 use http_signing::{Key, PublicKey, Signature};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use url::Url;
 
 use crate::{model::queue_job::OriginDataEntry, settings::SETTINGS};
 
+/// How old a signed request's Date header can be before we consider it stale
+const SIGNATURE_EXPIRATION_WINDOW_HOURS: i64 = 12;
+/// How far into the future a signed request's Date header can be, to account for clock drift between servers
+const SIGNATURE_CLOCK_SKEW_HOURS: i64 = 1;
+/// The most unexpired signatures we remember at once for replay detection, beyond which signed requests are rejected
+pub const MAX_RECORDED_SIGNATURES: i64 = 1_000_000;
+
+fn parse_signature_params(value: &str) -> HashMap<String, String> {
+  value
+    .trim_start_matches("Signature ")
+    .split(',')
+    .filter_map(|param| {
+      let components: Vec<&str> = param.trim().splitn(2, '=').collect();
+      if components.len() != 2 {
+        return None;
+      }
+
+      Some((components[0].to_lowercase(), components[1].trim_matches('"').to_owned()))
+    })
+    .collect()
+}
+
+fn has_required_signed_headers(params: &HashMap<String, String>, required: &[&str]) -> bool {
+  let signed_headers: Vec<String> = match params.get("headers") {
+    Some(headers) => headers.split(' ').map(|h| h.to_lowercase()).collect(),
+    None => return false,
+  };
+
+  required.iter().all(|h| signed_headers.iter().any(|s| s == h))
+}
+
+pub fn verify_http_digest(digest_header: &str, body: &[u8]) -> bool {
+  let mut body_hasher = Sha256::new();
+  body_hasher.update(body);
+  let body_hash_digest = base64::encode(body_hasher.finalize());
+
+  // Senders may supply more than one digest algorithm, we only need the SHA-256 one to match
+  digest_header.split(',').any(|digest| {
+    let components: Vec<&str> = digest.trim().splitn(2, '=').collect();
+    components.len() == 2 && components[0].eq_ignore_ascii_case("SHA-256") && components[1] == body_hash_digest
+  })
+}
+
+pub fn verify_http_date(date_header: &str, now: DateTime<Utc>) -> bool {
+  let date = match DateTime::parse_from_rfc2822(date_header) {
+    Ok(date) => date.with_timezone(&Utc),
+    Err(_) => return false,
+  };
+
+  date > now - Duration::hours(SIGNATURE_EXPIRATION_WINDOW_HOURS)
+    && date < now + Duration::hours(SIGNATURE_CLOCK_SKEW_HOURS)
+}
+
+/// How long a signature needs to be remembered for replay detection. Anything older is rejected by the Date check.
+pub fn signature_use_expiry(now: DateTime<Utc>) -> DateTime<Utc> {
+  now + Duration::hours(SIGNATURE_EXPIRATION_WINDOW_HOURS + SIGNATURE_CLOCK_SKEW_HOURS)
+}
+
+/// Checks the parts of an inbound signed request that the signature itself can't vouch for: that the minimum set of
+/// headers were signed, that the request is recent, and that the body matches its digest. This needs to happen as the
+/// request is received, as by the time the signature is verified in a job the request may be legitimately stale.
+/// Replays are detected once the signature has been verified, see `federate_record_signature_use`.
+pub fn verify_http_request(req: &HttpRequest, body: Option<&[u8]>) -> bool {
+  if !SETTINGS.app.secure {
+    return true;
+  }
+
+  let headers = req.headers();
+
+  let signature_header = match headers.get("signature").or_else(|| headers.get("authorization")) {
+    Some(value) => match value.to_str() {
+      Ok(value) => value,
+      Err(_) => return false,
+    },
+    None => return false,
+  };
+
+  let params = parse_signature_params(signature_header);
+
+  let required: &[&str] = match body {
+    Some(_) => &["(request-target)", "host", "date", "digest"],
+    None => &["(request-target)", "host", "date"],
+  };
+
+  if !has_required_signed_headers(&params, required) {
+    return false;
+  }
+
+  let date = match headers.get("date").and_then(|v| v.to_str().ok()) {
+    Some(date) => date,
+    None => return false,
+  };
+
+  let now = Utc::now();
+
+  if !verify_http_date(date, now) {
+    return false;
+  }
+
+  if let Some(body) = body {
+    let digest = match headers.get("digest").and_then(|v| v.to_str().ok()) {
+      Some(digest) => digest,
+      None => return false,
+    };
+
+    if !verify_http_digest(digest, body) {
+      return false;
+    }
+  }
+
+  params.contains_key("keyid") && params.contains_key("signature")
+}
+
 pub fn build_origin_data(req: &HttpRequest) -> Option<HashMap<String, OriginDataEntry>> {
   match SETTINGS.app.secure {
     true => {
@@ -113,7 +228,7 @@ pub fn verify_http_signature(context: &Option<HashMap<String, OriginDataEntry>>,
   sig.verify(&key).unwrap_or(false)
 }
 
-pub fn extract_http_signature_origin(context: &Option<HashMap<String, OriginDataEntry>>) -> Option<String> {
+pub fn extract_http_signature_key_id(context: &Option<HashMap<String, OriginDataEntry>>) -> Option<String> {
   let context = match context {
     Some(ctx) => ctx,
     None => return None,
@@ -167,12 +282,28 @@ pub fn extract_http_signature_origin(context: &Option<HashMap<String, OriginData
     .headers(headers)
     .build();
 
-  let key = match sig.key_id() {
+  sig.key_id().map(|k| k.to_owned())
+}
+
+/// Fetches the signature itself from a request's signature header, which is what we use to detect replayed requests
+pub fn extract_http_signature_value(context: &Option<HashMap<String, OriginDataEntry>>) -> Option<String> {
+  let headers = match context.as_ref().and_then(|ctx| ctx.get("headers")) {
+    Some(OriginDataEntry::Map(headers)) => headers,
+    _ => return None,
+  };
+
+  let header = headers.get("signature").or_else(|| headers.get("authorization"))?;
+
+  parse_signature_params(header).remove("signature")
+}
+
+pub fn extract_http_signature_origin(context: &Option<HashMap<String, OriginDataEntry>>) -> Option<String> {
+  let key = match extract_http_signature_key_id(context) {
     Some(key) => key,
     None => return None,
   };
 
-  let uri = match Url::parse(key) {
+  let uri = match Url::parse(&key) {
     Ok(uri) => uri,
     Err(_) => return None,
   };
@@ -184,7 +315,15 @@ pub fn extract_http_signature_origin(context: &Option<HashMap<String, OriginData
 mod tests {
   use std::collections::HashMap;
 
-  use crate::{model::queue_job::OriginDataEntry, net::http_sig::verify_http_signature};
+  use chrono::{TimeZone, Utc};
+
+  use crate::{
+    model::queue_job::OriginDataEntry,
+    net::http_sig::{
+      extract_http_signature_value, has_required_signed_headers, parse_signature_params, verify_http_date,
+      verify_http_digest, verify_http_signature,
+    },
+  };
 
   #[test]
   pub fn verifies_signature() {
@@ -248,4 +387,61 @@ mod tests {
 
     assert!(verify_http_signature(&Some(context), public_key_pem));
   }
+
+  #[test]
+  pub fn verifies_digest() {
+    let body = b"{\"hello\":\"world\"}";
+
+    assert!(verify_http_digest(
+      "SHA-256=k6I5cakU5erL8KjSUVTNownDwccvu5kU1Hxg88toFYg=",
+      body
+    ));
+    assert!(!verify_http_digest(
+      "SHA-256=8ABV5BVEYHozFIb5xm2epgd7eb2SYQgGt4K5ndxCwx0=",
+      body
+    ));
+  }
+
+  #[test]
+  pub fn rejects_stale_dates() {
+    let now = Utc.with_ymd_and_hms(2022, 12, 16, 2, 35, 46).unwrap();
+
+    assert!(verify_http_date("Fri, 16 Dec 2022 02:35:46 GMT", now));
+    assert!(!verify_http_date("Thu, 15 Dec 2022 02:35:46 GMT", now));
+    assert!(!verify_http_date("Sat, 17 Dec 2022 02:35:46 GMT", now));
+    assert!(!verify_http_date("yesterday", now));
+  }
+
+  #[test]
+  pub fn requires_minimum_signed_headers() {
+    let params = parse_signature_params(
+      r#"keyId="http://mastodon.test/users/boop#main-key",algorithm="rsa-sha256",headers="(request-target) date digest content-type",signature="abc""#,
+    );
+
+    assert_eq!(params["keyid"], "http://mastodon.test/users/boop#main-key");
+    assert!(has_required_signed_headers(
+      &params,
+      &["(request-target)", "date", "digest"]
+    ));
+    assert!(!has_required_signed_headers(
+      &params,
+      &["(request-target)", "host", "date", "digest"]
+    ));
+  }
+
+  #[test]
+  pub fn extracts_signature_value() {
+    let mut headers = HashMap::new();
+    headers.insert(
+      "signature".to_string(),
+      r#"keyId="http://mastodon.test/users/boop#main-key",headers="(request-target) date",signature="abc==""#
+        .to_string(),
+    );
+
+    let mut context = HashMap::new();
+    context.insert("headers".to_string(), OriginDataEntry::Map(headers));
+
+    assert_eq!(extract_http_signature_value(&Some(context)), Some("abc==".to_string()));
+    assert_eq!(extract_http_signature_value(&None), None);
+  }
 }
@@ -0,0 +1,409 @@
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+/// The proof type used for object integrity proofs, as described by FEP-8b32
+const INTEGRITY_PROOF_TYPE: &str = "DataIntegrityProof";
+/// The only cryptosuite we support, which signs JCS-canonicalized JSON with an Ed25519 key
+const INTEGRITY_PROOF_CRYPTOSUITE: &str = "eddsa-jcs-2022";
+/// Proofs are only accepted for asserting the document was authored by the signer
+const INTEGRITY_PROOF_PURPOSE: &str = "assertionMethod";
+/// The multicodec prefix identifying an Ed25519 public key in a `publicKeyMultibase` value
+const ED25519_MULTICODEC_PREFIX: [u8; 2] = [0xed, 0x01];
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+fn find_integrity_proof(doc: &Value) -> Option<&Map<String, Value>> {
+  let proofs = match doc.get("proof")? {
+    Value::Array(proofs) => proofs.iter().collect(),
+    proof => vec![proof],
+  };
+
+  proofs.into_iter().filter_map(|proof| proof.as_object()).find(|proof| {
+    proof.get("type").and_then(|v| v.as_str()) == Some(INTEGRITY_PROOF_TYPE)
+      && proof.get("cryptosuite").and_then(|v| v.as_str()) == Some(INTEGRITY_PROOF_CRYPTOSUITE)
+      && proof.get("proofPurpose").and_then(|v| v.as_str()) == Some(INTEGRITY_PROOF_PURPOSE)
+  })
+}
+
+/// Determines the verification method, i.e. the key ID, of the integrity proof attached to a document, if it has one
+/// we're able to verify
+pub fn extract_integrity_proof_verification_method(doc: &Value) -> Option<String> {
+  find_integrity_proof(doc)?
+    .get("verificationMethod")
+    .and_then(|v| v.as_str())
+    .map(|v| v.to_owned())
+}
+
+fn context_values(context: &Value) -> Vec<&Value> {
+  match context {
+    Value::Array(values) => values.iter().collect(),
+    value => vec![value],
+  }
+}
+
+/// Verifies the integrity proof attached to a document against the given `publicKeyMultibase` encoded Ed25519 key
+pub fn verify_integrity_proof(doc: &Value, public_key_multibase: &str) -> bool {
+  let proof = match find_integrity_proof(doc) {
+    Some(proof) => proof,
+    None => return false,
+  };
+
+  let signature = match proof
+    .get("proofValue")
+    .and_then(|v| v.as_str())
+    .and_then(decode_multibase)
+  {
+    Some(signature) => signature,
+    None => return false,
+  };
+
+  let public_key = match decode_ed25519_multikey(public_key_multibase) {
+    Some(public_key) => public_key,
+    None => return false,
+  };
+
+  let mut unsecured_doc = match doc.as_object() {
+    Some(doc) => doc.clone(),
+    None => return false,
+  };
+  unsecured_doc.remove("proof");
+
+  let mut proof_config = proof.clone();
+  proof_config.remove("proofValue");
+
+  // The proof can only narrow the context of the document it's attached to, otherwise it could change what the
+  // document's properties mean
+  if let Some(proof_context) = proof_config.get("@context") {
+    let doc_context = match unsecured_doc.get("@context") {
+      Some(context) => context_values(context),
+      None => return false,
+    };
+
+    let proof_context = context_values(proof_context);
+
+    if doc_context.len() < proof_context.len() || doc_context.iter().zip(proof_context.iter()).any(|(a, b)| a != b) {
+      return false;
+    }
+  }
+
+  if let Some(context) = proof_config
+    .get("@context")
+    .or_else(|| unsecured_doc.get("@context"))
+    .cloned()
+  {
+    unsecured_doc.insert("@context".to_string(), context.clone());
+    proof_config.insert("@context".to_string(), context);
+  }
+
+  let mut data = Sha256::digest(canonicalize_json(&Value::Object(proof_config)).as_bytes()).to_vec();
+  data.extend(Sha256::digest(
+    canonicalize_json(&Value::Object(unsecured_doc)).as_bytes(),
+  ));
+
+  UnparsedPublicKey::new(&ED25519, public_key)
+    .verify(&data, &signature)
+    .is_ok()
+}
+
+/// Serializes JSON using the JSON Canonicalization Scheme (RFC 8785), so that documents signed by other servers produce
+/// the same bytes here regardless of how they were formatted in transit
+pub fn canonicalize_json(value: &Value) -> String {
+  let mut out = String::new();
+  write_canonical_json(value, &mut out);
+  out
+}
+
+fn write_canonical_json(value: &Value, out: &mut String) {
+  match value {
+    Value::Object(map) => {
+      let mut keys: Vec<&String> = map.keys().collect();
+      keys.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+
+      out.push('{');
+      for (idx, key) in keys.into_iter().enumerate() {
+        if idx > 0 {
+          out.push(',');
+        }
+
+        out.push_str(&serde_json::to_string(key).unwrap_or_default());
+        out.push(':');
+        write_canonical_json(&map[key], out);
+      }
+      out.push('}');
+    }
+    Value::Array(values) => {
+      out.push('[');
+      for (idx, value) in values.iter().enumerate() {
+        if idx > 0 {
+          out.push(',');
+        }
+
+        write_canonical_json(value, out);
+      }
+      out.push(']');
+    }
+    // JCS treats every number as a double, even integers too large for one to hold exactly
+    Value::Number(number) => match number.as_f64() {
+      Some(v) => write_canonical_number(v, out),
+      None => out.push_str(&number.to_string()),
+    },
+    value => out.push_str(&value.to_string()),
+  }
+}
+
+/// Formats a number the way JavaScript's `Number.prototype.toString` does, as JCS requires. Rust already finds the
+/// shortest digits that round-trip, so we only need to decide where the decimal point and exponent go.
+fn write_canonical_number(v: f64, out: &mut String) {
+  if v == 0.0 {
+    out.push('0');
+    return;
+  }
+
+  if v < 0.0 {
+    out.push('-');
+  }
+
+  let scientific = format!("{:e}", v.abs());
+  let (mantissa, exponent) = match scientific.split_once('e') {
+    Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().unwrap_or_default()),
+    None => (scientific.as_str(), 0),
+  };
+
+  let digits = mantissa.replace('.', "");
+  let k = digits.len() as i32;
+  // The position of the decimal point relative to the start of the digits
+  let n = exponent + 1;
+
+  if k <= n && n <= 21 {
+    out.push_str(&digits);
+    out.push_str(&"0".repeat((n - k) as usize));
+  } else if 0 < n && n <= 21 {
+    out.push_str(&digits[..n as usize]);
+    out.push('.');
+    out.push_str(&digits[n as usize..]);
+  } else if -6 < n && n <= 0 {
+    out.push_str("0.");
+    out.push_str(&"0".repeat(-n as usize));
+    out.push_str(&digits);
+  } else {
+    out.push_str(&digits[..1]);
+    if k > 1 {
+      out.push('.');
+      out.push_str(&digits[1..]);
+    }
+    out.push('e');
+    out.push(if n > 0 { '+' } else { '-' });
+    out.push_str(&(n - 1).abs().to_string());
+  }
+}
+
+fn decode_base58(value: &str) -> Option<Vec<u8>> {
+  let mut bytes: Vec<u8> = vec![];
+
+  for c in value.bytes() {
+    let mut carry = BASE58_ALPHABET.iter().position(|a| *a == c)? as u32;
+
+    for byte in bytes.iter_mut() {
+      carry += (*byte as u32) * 58;
+      *byte = (carry & 0xff) as u8;
+      carry >>= 8;
+    }
+
+    while carry > 0 {
+      bytes.push((carry & 0xff) as u8);
+      carry >>= 8;
+    }
+  }
+
+  bytes.extend(value.bytes().take_while(|c| *c == b'1').map(|_| 0));
+  bytes.reverse();
+
+  Some(bytes)
+}
+
+/// Decodes a multibase value, of which we only support the base58btc encoding used for integrity proofs
+fn decode_multibase(value: &str) -> Option<Vec<u8>> {
+  match value.strip_prefix('z') {
+    Some(value) => decode_base58(value),
+    None => None,
+  }
+}
+
+fn decode_ed25519_multikey(value: &str) -> Option<Vec<u8>> {
+  let bytes = decode_multibase(value)?;
+
+  match bytes.strip_prefix(&ED25519_MULTICODEC_PREFIX) {
+    Some(key) if key.len() == 32 => Some(key.to_vec()),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ring::signature::{Ed25519KeyPair, KeyPair};
+  use serde_json::{json, Value};
+  use sha2::{Digest, Sha256};
+
+  use super::{
+    canonicalize_json, extract_integrity_proof_verification_method, verify_integrity_proof, BASE58_ALPHABET,
+    ED25519_MULTICODEC_PREFIX,
+  };
+
+  fn encode_base58(value: &[u8]) -> String {
+    let mut digits: Vec<u8> = vec![];
+
+    for byte in value {
+      let mut carry = *byte as u32;
+
+      for digit in digits.iter_mut() {
+        carry += (*digit as u32) << 8;
+        *digit = (carry % 58) as u8;
+        carry /= 58;
+      }
+
+      while carry > 0 {
+        digits.push((carry % 58) as u8);
+        carry /= 58;
+      }
+    }
+
+    digits.extend(value.iter().take_while(|b| **b == 0).map(|_| 0));
+
+    digits
+      .iter()
+      .rev()
+      .map(|d| BASE58_ALPHABET[*d as usize] as char)
+      .collect()
+  }
+
+  fn build_key_pair() -> (Ed25519KeyPair, String) {
+    let key_pair = Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap();
+    let mut public_key = ED25519_MULTICODEC_PREFIX.to_vec();
+    public_key.extend(key_pair.public_key().as_ref());
+
+    (key_pair, format!("z{}", encode_base58(&public_key)))
+  }
+
+  fn sign(doc: &Value, key_pair: &Ed25519KeyPair) -> Value {
+    let proof_config = json!({
+      "@context": doc["@context"],
+      "type": "DataIntegrityProof",
+      "cryptosuite": "eddsa-jcs-2022",
+      "verificationMethod": "https://a.test/users/a#ed25519-key",
+      "proofPurpose": "assertionMethod",
+      "created": "2023-01-01T00:00:00Z",
+    });
+
+    let mut data = Sha256::digest(canonicalize_json(&proof_config).as_bytes()).to_vec();
+    data.extend(Sha256::digest(canonicalize_json(doc).as_bytes()));
+
+    let mut proof = proof_config;
+    proof["proofValue"] = Value::String(format!("z{}", encode_base58(key_pair.sign(&data).as_ref())));
+
+    let mut doc = doc.clone();
+    doc["proof"] = proof;
+    doc
+  }
+
+  fn build_activity() -> Value {
+    json!({
+      "@context": ["https://www.w3.org/ns/activitystreams", "https://w3id.org/security/data-integrity/v1"],
+      "id": "https://a.test/activities/1",
+      "type": "Create",
+      "actor": "https://a.test/users/a",
+      "object": {
+        "id": "https://a.test/notes/1",
+        "type": "Note",
+        "attributedTo": "https://a.test/users/a",
+        "content": "Hello",
+        "to": ["https://www.w3.org/ns/activitystreams#Public"],
+      },
+    })
+  }
+
+  #[test]
+  fn test_canonicalize_json_sorts_keys_and_numbers() {
+    let value = json!({ "b": [1.0, 2.5, "x"], "a": { "d": null, "c": true } });
+
+    assert_eq!(
+      canonicalize_json(&value),
+      r#"{"a":{"c":true,"d":null},"b":[1,2.5,"x"]}"#.to_string()
+    );
+  }
+
+  #[test]
+  fn test_canonicalize_json_formats_numbers_like_javascript() {
+    let value = json!([
+      1e21,
+      1e20,
+      123.456,
+      0.000001,
+      1e-7,
+      -1.5e-7,
+      4.5e300,
+      -0.0,
+      9007199254740993u64
+    ]);
+
+    assert_eq!(
+      canonicalize_json(&value),
+      "[1e+21,100000000000000000000,123.456,0.000001,1e-7,-1.5e-7,4.5e+300,0,9007199254740992]".to_string()
+    );
+  }
+
+  #[test]
+  fn test_verify_integrity_proof_accepts_signed_document() {
+    let (key_pair, public_key) = build_key_pair();
+    let doc = sign(&build_activity(), &key_pair);
+
+    assert_eq!(
+      extract_integrity_proof_verification_method(&doc),
+      Some("https://a.test/users/a#ed25519-key".to_string())
+    );
+    assert!(verify_integrity_proof(&doc, &public_key));
+  }
+
+  #[test]
+  fn test_verify_integrity_proof_rejects_forged_object() {
+    let (key_pair, public_key) = build_key_pair();
+    let mut doc = sign(&build_activity(), &key_pair);
+    doc["object"]["content"] = Value::String("Forged".to_string());
+
+    assert!(!verify_integrity_proof(&doc, &public_key));
+  }
+
+  #[test]
+  fn test_verify_integrity_proof_rejects_widened_audience() {
+    let (key_pair, public_key) = build_key_pair();
+    let mut activity = build_activity();
+    activity["object"]["to"] = json!(["https://a.test/users/a/followers"]);
+
+    let mut doc = sign(&activity, &key_pair);
+    doc["object"]["to"] = json!(["https://www.w3.org/ns/activitystreams#Public"]);
+
+    assert!(!verify_integrity_proof(&doc, &public_key));
+  }
+
+  #[test]
+  fn test_verify_integrity_proof_rejects_other_key() {
+    let (key_pair, _) = build_key_pair();
+    let other_key_pair = Ed25519KeyPair::from_seed_unchecked(&[8; 32]).unwrap();
+    let mut other_public_key = ED25519_MULTICODEC_PREFIX.to_vec();
+    other_public_key.extend(other_key_pair.public_key().as_ref());
+
+    let doc = sign(&build_activity(), &key_pair);
+
+    assert!(!verify_integrity_proof(
+      &doc,
+      &format!("z{}", encode_base58(&other_public_key))
+    ));
+  }
+
+  #[test]
+  fn test_verify_integrity_proof_rejects_unsigned_document() {
+    let (_, public_key) = build_key_pair();
+
+    assert_eq!(extract_integrity_proof_verification_method(&build_activity()), None);
+    assert!(!verify_integrity_proof(&build_activity(), &public_key));
+  }
+}
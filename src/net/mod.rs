@@ -1,4 +1,5 @@
 pub mod http_sig;
+pub mod integrity_proof;
 pub mod jwt;
 pub mod jwt_session;
 pub mod jwt_session_err;
@@ -1,4 +1,5 @@
 use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::{
@@ -14,10 +15,12 @@ use crate::{
     tombstone::TombstoneProps,
   },
   db::{
-    comment_repository::CommentPool, follow_repository::FollowPool, job_repository::JobPool,
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, event_repository::EventPool,
+    follow_repository::FollowPool, instance_actor_repository::InstanceActorPool, job_repository::JobPool,
     orbit_repository::OrbitPool, post_repository::PostPool, session_repository::SessionPool,
     tombstone_repository::TombstonePool, user_orbit_repository::UserOrbitPool, user_repository::UserPool,
   },
+  federation::activitypub::{activitypub_uri_is_suspended, actor::federate_signing_key},
   helpers::{
     api::relative_to_absolute_uri,
     auth::query_auth,
@@ -26,51 +29,127 @@ use crate::{
   },
   logic::{
     comment::{activitypub_get_comment, activitypub_get_comments},
-    post::get_post,
+    hashtag::{get_hashtag_posts, get_hashtag_posts_count, normalize_hashtag},
+    instance_actor::get_instance_actor,
+    post::{activitypub_get_post_boost, get_post},
     user::get_user_by_id,
   },
   model::{
     access_type::AccessType,
     job::{JobStatus, NewJob},
-    queue_job::{QueueJob, QueueJobType},
+    queue_job::{OriginDataEntry, QueueJob, QueueJobType},
+    tombstone::Tombstone,
+  },
+  net::{
+    http_sig::{build_origin_data, extract_http_signature_key_id, verify_http_request, verify_http_signature},
+    jwt::JwtContext,
   },
-  net::{http_sig::build_origin_data, jwt::JwtContext},
   settings::SETTINGS,
   work_queue::queue::Queue,
 };
 
 use super::{comment::CommentsQuery, orbit::MembersQuery, post::PostsQuery, user::FollowersQuery};
 
+fn api_activitypub_build_tombstone(tombstone: Tombstone) -> ActivityPubDocument {
+  let obj = Object::builder()
+    .kind(Some(ObjectType::Tombstone.to_string()))
+    .id(Some(relative_to_absolute_uri(&tombstone.fediverse_uri)))
+    .tombstone(Some(
+      TombstoneProps::builder()
+        .former_kind(Some(tombstone.former_type))
+        .deleted(Some(tombstone.deleted_at))
+        .build(),
+    ))
+    .build();
+
+  ActivityPubDocument::new(obj)
+}
+
 async fn api_activitypub_return_tombstone_or_not_found(uri: String, tombstones: &TombstonePool) -> HttpResponse {
   match tombstones.fetch_for_fediverse_uri(&uri).await {
-    Some(tombstone) => {
-      let obj = Object::builder()
-        .kind(Some(ObjectType::Tombstone.to_string()))
-        .id(Some(relative_to_absolute_uri(&tombstone.fediverse_uri)))
-        .tombstone(Some(
-          TombstoneProps::builder()
-            .former_kind(Some(tombstone.former_type))
-            .deleted(Some(tombstone.deleted_at))
-            .build(),
-        ))
-        .build();
+    Some(tombstone) => HttpResponse::Ok().json(api_activitypub_build_tombstone(tombstone)),
+    None => build_api_not_found(uri),
+  }
+}
 
-      let doc = ActivityPubDocument::new(obj);
+async fn api_activitypub_verify_signed_fetch(req: &HttpRequest, users: &UserPool) -> bool {
+  if !SETTINGS.app.secure || !SETTINGS.app.authorized_fetch {
+    return true;
+  }
+
+  if !verify_http_request(req, None) {
+    return false;
+  }
+
+  let origin_data = build_origin_data(req);
+
+  let key_id = match extract_http_signature_key_id(&origin_data) {
+    Some(key_id) => key_id,
+    None => return false,
+  };
+
+  match federate_signing_key(&key_id, users).await {
+    Some(public_key) => verify_http_signature(&origin_data, &public_key),
+    None => false,
+  }
+}
+
+/// Determines whether an inbound activity comes from a suspended server, going by both the activity's actor and the
+/// key that signed the request, so that anything those servers send us is turned away before it reaches the queue.
+async fn api_activitypub_sender_is_suspended(
+  data: &serde_json::Value,
+  origin_data: &Option<HashMap<String, OriginDataEntry>>,
+  domain_blocks: &DomainBlockPool,
+) -> bool {
+  let actor_uri = match data.get("actor") {
+    Some(serde_json::Value::String(uri)) => Some(uri.as_str()),
+    Some(serde_json::Value::Object(obj)) => obj.get("id").and_then(|v| v.as_str()),
+    _ => None,
+  };
 
-      HttpResponse::Ok().json(doc)
+  if let Some(uri) = actor_uri {
+    if activitypub_uri_is_suspended(uri, domain_blocks).await {
+      return true;
     }
-    None => build_api_not_found(uri),
+  }
+
+  match extract_http_signature_key_id(origin_data) {
+    Some(key_id) => activitypub_uri_is_suspended(&key_id, domain_blocks).await,
+    None => false,
+  }
+}
+
+pub async fn api_activitypub_get_instance_actor(instance_actors: web::Data<InstanceActorPool>) -> impl Responder {
+  let actor = match get_instance_actor(&instance_actors).await {
+    Ok(actor) => actor,
+    Err(err) => return map_api_err(err),
+  };
+
+  match actor.to_object("") {
+    Some(obj) => {
+      let doc = ActivityPubDocument::new(obj);
+      HttpResponse::Ok()
+        .insert_header(("Content-Type", ACTIVITY_JSON_CONTENT_TYPE))
+        .json(doc)
+    }
+    None => HttpResponse::NotFound().finish(),
   }
 }
 
 pub async fn api_activitypub_get_post(
+  req: HttpRequest,
   sessions: web::Data<SessionPool>,
   posts: web::Data<PostPool>,
   follows: web::Data<FollowPool>,
   tombstones: web::Data<TombstonePool>,
   post_id: web::Path<Uuid>,
   jwt: web::ReqData<JwtContext>,
+  users: web::Data<UserPool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let user_props = query_auth(&jwt, &sessions).await;
   let current_user_id = match user_props {
     Some(p) => Some(p.uid),
@@ -117,11 +196,36 @@ pub async fn api_activitypub_get_post(
   }
 }
 
+pub async fn api_activitypub_get_post_boost(
+  req: HttpRequest,
+  posts: web::Data<PostPool>,
+  events: web::Data<EventPool>,
+  ids: web::Path<(Uuid, Uuid)>,
+  users: web::Data<UserPool>,
+) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
+  match activitypub_get_post_boost(&ids.0, &ids.1, &posts, &users, &events).await {
+    Ok(doc) => HttpResponse::Ok()
+      .insert_header(("Content-Type", ACTIVITY_JSON_CONTENT_TYPE))
+      .json(doc),
+    Err(err) => map_api_err(err),
+  }
+}
+
 pub async fn api_activitypub_get_federated_user_posts(
+  req: HttpRequest,
   posts: web::Data<PostPool>,
   query: web::Query<PostsQuery>,
   user_id: web::Path<Uuid>,
+  users: web::Data<UserPool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
   let posts_count = match posts.count_user_public_feed(&user_id, &None).await {
@@ -151,19 +255,25 @@ pub async fn api_activitypub_get_federated_user_posts(
 }
 
 pub async fn api_activitypub_get_federated_orbit_posts(
+  req: HttpRequest,
   posts: web::Data<PostPool>,
   query: web::Query<PostsQuery>,
   orbit_id: web::Path<Uuid>,
+  users: web::Data<UserPool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
-  let posts_count = match posts.count_global_federated_orbit_feed(&orbit_id).await {
+  let posts_count = match posts.count_global_federated_orbit_feed(&orbit_id, &None).await {
     Ok(count) => count,
     Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
   };
 
   let posts = match posts
-    .fetch_global_federated_orbit_feed(&orbit_id, page_size, page * page_size)
+    .fetch_global_federated_orbit_feed(&orbit_id, &None, page_size, page * page_size)
     .await
   {
     Ok(posts) => posts,
@@ -183,11 +293,58 @@ pub async fn api_activitypub_get_federated_orbit_posts(
     .json(doc)
 }
 
+pub async fn api_activitypub_get_hashtag_posts(
+  req: HttpRequest,
+  posts: web::Data<PostPool>,
+  query: web::Query<PostsQuery>,
+  hashtag: web::Path<String>,
+  users: web::Data<UserPool>,
+) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
+  let hashtag = match normalize_hashtag(&hashtag) {
+    Some(hashtag) => hashtag,
+    None => return build_api_not_found(hashtag.to_string()),
+  };
+
+  let page = query.page.unwrap_or(0);
+  let page_size = query.page_size.unwrap_or(20);
+  let posts_count = match get_hashtag_posts_count(&hashtag, &None, &posts).await {
+    Ok(count) => count,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  let posts = match get_hashtag_posts(&hashtag, &None, page_size, page * page_size, &posts).await {
+    Ok(posts) => posts,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  let doc = create_activitypub_ordered_collection_page_feed(
+    &format!("{}/tags/{}", SETTINGS.server.api_fqdn, hashtag),
+    page.try_into().unwrap_or_default(),
+    page_size.try_into().unwrap_or_default(),
+    posts_count.try_into().unwrap_or_default(),
+    posts,
+  );
+
+  HttpResponse::Ok()
+    .insert_header(("Content-Type", ACTIVITY_JSON_CONTENT_TYPE))
+    .json(doc)
+}
+
 pub async fn api_activitypub_get_federated_user_liked_posts(
+  req: HttpRequest,
   posts: web::Data<PostPool>,
   query: web::Query<PostsQuery>,
   user_id: web::Path<Uuid>,
+  users: web::Data<UserPool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
   let posts_count = match posts.count_user_public_likes_feed(&user_id, &None).await {
@@ -218,10 +375,15 @@ pub async fn api_activitypub_get_federated_user_liked_posts(
 }
 
 pub async fn api_activitypub_get_user_followers(
+  req: HttpRequest,
   users: web::Data<UserPool>,
   user_id: web::Path<Uuid>,
   query: web::Query<FollowersQuery>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
   let users_count = users.fetch_followers_count(&user_id).await;
@@ -246,10 +408,15 @@ pub async fn api_activitypub_get_user_followers(
 }
 
 pub async fn api_activitypub_get_user_following(
+  req: HttpRequest,
   users: web::Data<UserPool>,
   user_id: web::Path<Uuid>,
   query: web::Query<FollowersQuery>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
   let users_count = users.fetch_following_count(&user_id).await;
@@ -274,10 +441,22 @@ pub async fn api_activitypub_get_user_following(
 }
 
 pub async fn api_activitypub_get_user_profile(
+  req: HttpRequest,
   users: web::Data<UserPool>,
   user_id: web::Path<Uuid>,
   tombstones: web::Data<TombstonePool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
+  // Deleted accounts are tombstoned before their content has finished being removed, so this has to be checked first
+  if let Some(tombstone) = tombstones.fetch_for_fediverse_uri(&format!("/user/{}", user_id)).await {
+    return HttpResponse::Gone()
+      .insert_header(("Content-Type", ACTIVITY_JSON_CONTENT_TYPE))
+      .json(api_activitypub_build_tombstone(tombstone));
+  }
+
   match get_user_by_id(&user_id, &users).await {
     Ok(user) => match user.to_object("") {
       Some(obj) => {
@@ -293,10 +472,16 @@ pub async fn api_activitypub_get_user_profile(
 }
 
 pub async fn api_activitypub_get_orbit(
+  req: HttpRequest,
   orbits: web::Data<OrbitPool>,
   orbit_id: web::Path<Uuid>,
   tombstones: web::Data<TombstonePool>,
+  users: web::Data<UserPool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   match orbits.fetch_orbit(&orbit_id).await {
     Ok(orbit) => match orbit {
       Some(orbit) => match orbit.to_object("") {
@@ -315,10 +500,16 @@ pub async fn api_activitypub_get_orbit(
 }
 
 pub async fn api_activitypub_get_orbit_members(
+  req: HttpRequest,
   user_orbits: web::Data<UserOrbitPool>,
   orbit_id: web::Path<Uuid>,
   query: web::Query<MembersQuery>,
+  users: web::Data<UserPool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
   let users_count = match user_orbits.count_users(&orbit_id).await {
@@ -346,12 +537,18 @@ pub async fn api_activitypub_get_orbit_members(
 }
 
 pub async fn api_activitypub_get_comment(
+  req: HttpRequest,
   sessions: web::Data<SessionPool>,
   comments: web::Data<CommentPool>,
   posts: web::Data<PostPool>,
   ids: web::Path<(Uuid, Uuid)>,
   jwt: web::ReqData<JwtContext>,
+  users: web::Data<UserPool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let own_user_id = match query_auth(&jwt, &sessions).await {
     Some(props) => Some(props.uid),
     None => None,
@@ -364,13 +561,19 @@ pub async fn api_activitypub_get_comment(
 }
 
 pub async fn api_activitypub_get_comments(
+  req: HttpRequest,
   sessions: web::Data<SessionPool>,
   comments: web::Data<CommentPool>,
   posts: web::Data<PostPool>,
   query: web::Query<CommentsQuery>,
   post_id: web::Path<Uuid>,
   jwt: web::ReqData<JwtContext>,
+  users: web::Data<UserPool>,
 ) -> impl Responder {
+  if !api_activitypub_verify_signed_fetch(&req, &users).await {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
   let own_user_id = match query_auth(&jwt, &sessions).await {
     Some(props) => Some(props.uid),
     None => None,
@@ -385,15 +588,29 @@ pub async fn api_activitypub_get_comments(
 pub async fn api_activitypub_federate_shared_inbox(
   req: HttpRequest,
   jobs: web::Data<JobPool>,
-  data: web::Json<serde_json::Value>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  body: web::Bytes,
   queue: web::Data<Queue>,
 ) -> impl Responder {
+  if !verify_http_request(&req, Some(&body[..])) {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
+  let data: serde_json::Value = match serde_json::from_slice(&body) {
+    Ok(data) => data,
+    Err(err) => return build_api_err(400, err.to_string(), None),
+  };
+
   let origin_data = build_origin_data(&req);
 
   if SETTINGS.app.secure && origin_data.is_none() {
     return build_api_err(401, "signature".to_string(), None);
   }
 
+  if api_activitypub_sender_is_suspended(&data, &origin_data, &domain_blocks).await {
+    return HttpResponse::Forbidden().finish();
+  }
+
   let job_id = match jobs
     .create(NewJob {
       created_by_id: None,
@@ -410,7 +627,7 @@ pub async fn api_activitypub_federate_shared_inbox(
   let job = QueueJob::builder()
     .job_id(job_id)
     .job_type(QueueJobType::FederateActivityPub)
-    .data((*data).to_owned())
+    .data(data)
     .origin(SETTINGS.server.api_root_fqdn.to_owned())
     .origin_data(origin_data)
     .build();
@@ -427,16 +644,30 @@ pub async fn api_activitypub_federate_shared_inbox(
 pub async fn api_activitypub_federate_user_inbox(
   req: HttpRequest,
   user_id: web::Path<Uuid>,
-  data: web::Json<serde_json::Value>,
+  body: web::Bytes,
   jobs: web::Data<JobPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
   queue: web::Data<Queue>,
 ) -> impl Responder {
+  if !verify_http_request(&req, Some(&body[..])) {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
+  let data: serde_json::Value = match serde_json::from_slice(&body) {
+    Ok(data) => data,
+    Err(err) => return build_api_err(400, err.to_string(), None),
+  };
+
   let origin_data = build_origin_data(&req);
 
   if SETTINGS.app.secure && origin_data.is_none() {
     return build_api_err(401, "signature".to_string(), None);
   }
 
+  if api_activitypub_sender_is_suspended(&data, &origin_data, &domain_blocks).await {
+    return HttpResponse::Forbidden().finish();
+  }
+
   let job_id = match jobs
     .create(NewJob {
       created_by_id: None,
@@ -453,7 +684,7 @@ pub async fn api_activitypub_federate_user_inbox(
   let job = QueueJob::builder()
     .job_id(job_id)
     .job_type(QueueJobType::FederateActivityPub)
-    .data((*data).to_owned())
+    .data(data)
     .origin(req.connection_info().host().to_string())
     .context(vec![user_id.to_string()])
     .origin_data(origin_data)
@@ -468,16 +699,30 @@ pub async fn api_activitypub_federate_user_inbox(
 pub async fn api_activitypub_federate_orbit_inbox(
   req: HttpRequest,
   orbit_id: web::Path<Uuid>,
-  data: web::Json<serde_json::Value>,
+  body: web::Bytes,
   jobs: web::Data<JobPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
   queue: web::Data<Queue>,
 ) -> impl Responder {
+  if !verify_http_request(&req, Some(&body[..])) {
+    return build_api_err(401, "signature".to_string(), None);
+  }
+
+  let data: serde_json::Value = match serde_json::from_slice(&body) {
+    Ok(data) => data,
+    Err(err) => return build_api_err(400, err.to_string(), None),
+  };
+
   let origin_data = build_origin_data(&req);
 
   if SETTINGS.app.secure && origin_data.is_none() {
     return build_api_err(401, "signature".to_string(), None);
   }
 
+  if api_activitypub_sender_is_suspended(&data, &origin_data, &domain_blocks).await {
+    return HttpResponse::Forbidden().finish();
+  }
+
   let job_id = match jobs
     .create(NewJob {
       created_by_id: None,
@@ -494,7 +739,7 @@ pub async fn api_activitypub_federate_orbit_inbox(
   let job = QueueJob::builder()
     .job_id(job_id)
     .job_type(QueueJobType::FederateActivityPub)
-    .data((*data).to_owned())
+    .data(data)
     .origin(req.connection_info().host().to_string())
     .context(vec![orbit_id.to_string()])
     .origin_data(origin_data)
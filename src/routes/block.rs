@@ -0,0 +1,53 @@
+use crate::{
+  db::{
+    follow_repository::FollowPool, job_repository::JobPool, session_repository::SessionPool,
+    user_block_repository::UserBlockPool, user_repository::UserPool,
+  },
+  helpers::auth::require_auth,
+  helpers::core::map_api_err,
+  logic::block::{create_block, delete_block},
+  net::jwt::JwtContext,
+  work_queue::queue::Queue,
+};
+use actix_web::{web, HttpResponse, Responder};
+
+pub async fn api_create_block(
+  sessions: web::Data<SessionPool>,
+  follows: web::Data<FollowPool>,
+  users: web::Data<UserPool>,
+  user_blocks: web::Data<UserBlockPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  user_handle: web::Path<String>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match create_block(&users, &follows, &user_blocks, &jobs, &queue, &user_handle, &props.uid).await {
+    Ok(_) => HttpResponse::Created().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_delete_block(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  user_blocks: web::Data<UserBlockPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  user_handle: web::Path<String>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match delete_block(&users, &user_blocks, &jobs, &queue, &user_handle, &props.uid).await {
+    Ok(_) => HttpResponse::Ok().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
@@ -1,15 +1,18 @@
 use crate::{
   db::{
-    comment_repository::CommentPool, follow_repository::FollowPool, post_repository::PostPool,
-    session_repository::SessionPool,
+    comment_repository::CommentPool, domain_block_repository::DomainBlockPool, follow_repository::FollowPool,
+    job_repository::JobPool, mention_repository::MentionPool, post_repository::PostPool,
+    session_repository::SessionPool, user_repository::UserPool,
   },
   helpers::auth::{query_auth, require_auth},
   helpers::core::{build_api_err, map_api_err},
-  logic::comment::{
-    create_comment, create_comment_like, delete_comment, delete_comment_like, get_comment, get_comments,
+  logic::{
+    comment::{create_comment, create_comment_like, delete_comment, delete_comment_like, get_comment, get_comments},
+    LogicErr,
   },
-  model::response::ObjectResponse,
+  model::{comment_sort::CommentSort, response::ObjectResponse},
   net::jwt::JwtContext,
+  work_queue::queue::Queue,
 };
 use actix_web::{web, HttpResponse, Responder};
 use serde::Deserialize;
@@ -18,12 +21,17 @@ use uuid::Uuid;
 #[derive(Deserialize)]
 pub struct NewPost {
   content_md: String,
+  parent_comment_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CommentsQuery {
   pub page: Option<i64>,
   pub page_size: Option<i64>,
+  /// Lists the replies to this comment rather than the post's top-level comments
+  pub parent_id: Option<Uuid>,
+  pub sort: Option<CommentSort>,
+  pub depth: Option<i64>,
 }
 
 pub async fn api_create_comment(
@@ -31,6 +39,11 @@ pub async fn api_create_comment(
   comments: web::Data<CommentPool>,
   follows: web::Data<FollowPool>,
   posts: web::Data<PostPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  mentions: web::Data<MentionPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
   post_id: web::Path<Uuid>,
   contents: web::Json<NewPost>,
   jwt: web::ReqData<JwtContext>,
@@ -40,8 +53,24 @@ pub async fn api_create_comment(
     Err(res) => return res,
   };
 
-  match create_comment(&posts, &follows, &comments, &post_id, &props.uid, &contents.content_md).await {
+  match create_comment(
+    &posts,
+    &follows,
+    &comments,
+    &users,
+    &domain_blocks,
+    &mentions,
+    &jobs,
+    &queue,
+    &post_id,
+    &props.uid,
+    &contents.content_md,
+    &contents.parent_comment_id,
+  )
+  .await
+  {
     Ok(comment) => HttpResponse::Ok().json(ObjectResponse { data: comment }),
+    Err(LogicErr::MissingRecord) => map_api_err(LogicErr::MissingRecord),
     Err(err) => build_api_err(500, err.to_string(), Some(err.to_string())),
   }
 }
@@ -49,6 +78,8 @@ pub async fn api_create_comment(
 pub async fn api_delete_comment(
   sessions: web::Data<SessionPool>,
   comments: web::Data<CommentPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
   ids: web::Path<(Uuid, Uuid)>,
   jwt: web::ReqData<JwtContext>,
 ) -> impl Responder {
@@ -57,7 +88,7 @@ pub async fn api_delete_comment(
     Err(res) => return res,
   };
 
-  match delete_comment(&comments, &ids.0, &ids.1, &props.uid).await {
+  match delete_comment(&comments, &jobs, &queue, &ids.0, &ids.1, &props.uid).await {
     Ok(_) => HttpResponse::Ok().finish(),
     Err(err) => map_api_err(err),
   }
@@ -75,7 +106,18 @@ pub async fn api_get_comments(
     None => None,
   };
 
-  match get_comments(&comments, &post_id, &own_user_id, &query.page, &query.page_size).await {
+  match get_comments(
+    &comments,
+    &post_id,
+    &own_user_id,
+    &query.parent_id,
+    &query.sort,
+    &query.depth,
+    &query.page,
+    &query.page_size,
+  )
+  .await
+  {
     Ok(response) => HttpResponse::Ok().json(response),
     Err(err) => map_api_err(err),
   }
@@ -0,0 +1,106 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::{
+  db::{domain_block_repository::DomainBlockPool, session_repository::SessionPool, user_repository::UserPool},
+  helpers::{
+    auth::require_auth,
+    core::{build_api_err, map_api_err},
+  },
+  logic::domain_block::{create_domain_block, delete_domain_block, get_domain_blocks, import_domain_blocks},
+  model::{domain_block_severity::DomainBlockSeverity, response::ObjectResponse},
+  net::jwt::JwtContext,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct DomainBlockRequest {
+  pub domain: String,
+  pub severity: DomainBlockSeverity,
+  pub public_comment: Option<String>,
+}
+
+pub async fn api_get_domain_blocks(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match get_domain_blocks(&props.uid, &users, &domain_blocks).await {
+    Ok(blocks) => HttpResponse::Ok().json(ObjectResponse { data: blocks }),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_create_domain_block(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  req: web::Json<DomainBlockRequest>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match create_domain_block(
+    &props.uid,
+    &req.domain,
+    &req.severity,
+    &req.public_comment,
+    &users,
+    &domain_blocks,
+  )
+  .await
+  {
+    Ok(_) => HttpResponse::Created().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_delete_domain_block(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  domain: web::Path<String>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match delete_domain_block(&props.uid, &domain, &users, &domain_blocks).await {
+    Ok(_) => HttpResponse::Ok().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
+
+/// Imports a domain blocklist CSV, as exported by Mastodon's admin interface, from the request body
+pub async fn api_import_domain_blocks(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  body: web::Bytes,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  let csv = match std::str::from_utf8(&body) {
+    Ok(csv) => csv,
+    Err(err) => return build_api_err(400, err.to_string(), None),
+  };
+
+  match import_domain_blocks(&props.uid, csv, &users, &domain_blocks).await {
+    Ok(imported) => HttpResponse::Ok().json(ObjectResponse { data: imported }),
+    Err(err) => map_api_err(err),
+  }
+}
@@ -1,9 +1,10 @@
 use crate::{
   db::{
-    follow_repository::FollowPool, job_repository::JobPool, session_repository::SessionPool, user_repository::UserPool,
+    follow_repository::FollowPool, job_repository::JobPool, session_repository::SessionPool,
+    user_block_repository::UserBlockPool, user_repository::UserPool,
   },
   helpers::auth::require_auth,
-  helpers::core::build_api_err,
+  helpers::core::{build_api_err, map_api_err},
   logic::follow::{create_follow, delete_follow},
   net::jwt::JwtContext,
   work_queue::queue::Queue,
@@ -14,6 +15,7 @@ pub async fn api_create_follow(
   sessions: web::Data<SessionPool>,
   follows: web::Data<FollowPool>,
   users: web::Data<UserPool>,
+  user_blocks: web::Data<UserBlockPool>,
   jobs: web::Data<JobPool>,
   queue: web::Data<Queue>,
   user_handle: web::Path<String>,
@@ -24,9 +26,9 @@ pub async fn api_create_follow(
     Err(res) => return res,
   };
 
-  match create_follow(&users, &follows, &jobs, &queue, &user_handle, &props.uid).await {
+  match create_follow(&users, &follows, &user_blocks, &jobs, &queue, &user_handle, &props.uid).await {
     Ok(_) => HttpResponse::Created().finish(),
-    Err(err) => build_api_err(500, err.to_string(), Some(err.to_string())),
+    Err(err) => map_api_err(err),
   }
 }
 
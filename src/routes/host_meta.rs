@@ -9,8 +9,9 @@ pub async fn api_get_host_meta() -> impl Responder {
       "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
 <XRD xmlns=\"http://docs.oasis-open.org/ns/xri/xrd-1.0\">
   <Link rel=\"lrdd\" template=\"{}/.well-known/webfinger?resource={{uri}}\"/>
+  <Link rel=\"self\" type=\"application/activity+json\" href=\"{}/actor\"/>
 </XRD>",
-      SETTINGS.server.api_root_fqdn
+      SETTINGS.server.api_root_fqdn, SETTINGS.server.api_fqdn
     ))
 }
 
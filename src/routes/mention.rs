@@ -0,0 +1,47 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+
+use crate::{
+  db::{mention_repository::MentionPool, session_repository::SessionPool},
+  helpers::{auth::require_auth, core::build_api_err, math::div_up},
+  logic::mention::{count_user_mentions, get_user_mentions},
+  model::response::ListResponse,
+  net::jwt::JwtContext,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct MentionsQuery {
+  pub page: Option<i64>,
+  pub page_size: Option<i64>,
+}
+
+pub async fn api_get_mentions(
+  sessions: web::Data<SessionPool>,
+  mentions: web::Data<MentionPool>,
+  query: web::Query<MentionsQuery>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  let page = query.page.unwrap_or(0);
+  let page_size = query.page_size.unwrap_or(20);
+  let mentions_count = match count_user_mentions(&props.uid, &mentions).await {
+    Ok(count) => count,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  let mentions = match get_user_mentions(&props.uid, page_size, page * page_size, &mentions).await {
+    Ok(mentions) => mentions,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  HttpResponse::Ok().json(ListResponse {
+    data: mentions,
+    page,
+    total_items: mentions_count,
+    total_pages: div_up(mentions_count, page_size) + 1,
+  })
+}
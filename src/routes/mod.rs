@@ -1,16 +1,22 @@
 pub mod activitypub;
 pub mod apps;
+pub mod block;
 pub mod comment;
+pub mod domain_block;
 pub mod follow;
 pub mod host_meta;
 pub mod job;
 pub mod like;
+pub mod mention;
 pub mod nodeinfo;
 pub mod oauth;
 pub mod orbit;
+pub mod poll;
 pub mod post;
 pub mod public;
 pub mod redirect;
+pub mod relay;
+pub mod report;
 pub mod status;
 pub mod user;
 pub mod webfinger;
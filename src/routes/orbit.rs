@@ -30,6 +30,7 @@ use crate::{
     user_account_pub::UserAccountPub,
   },
   net::jwt::JwtContext,
+  settings::SETTINGS,
   work_queue::queue::Queue,
 };
 
@@ -532,10 +533,37 @@ pub async fn api_join_orbit(
     }
   }
 
-  match user_orbits.create_user_orbit(&orbit_id, &session.uid).await {
-    Ok(_) => HttpResponse::Created().finish(),
-    Err(err) => build_api_err(500, err.to_string(), None),
+  if let Err(err) = user_orbits.create_user_orbit(&orbit_id, &session.uid).await {
+    return build_api_err(500, err.to_string(), None);
   }
+
+  // Posts made to remote orbits before any of our users joined never reached us, so we import the recent ones
+  if orbit.is_external && SETTINGS.app.outbox_backfill_depth > 0 {
+    let job_id = match jobs
+      .create(NewJob {
+        created_by_id: Some(session.uid),
+        status: JobStatus::NotStarted,
+        record_id: Some(*orbit_id),
+        associated_record_id: None,
+      })
+      .await
+      .map_err(map_db_err)
+    {
+      Ok(id) => id,
+      Err(err) => return build_api_err(500, err.to_string(), None),
+    };
+
+    let job = QueueJob::builder()
+      .job_id(job_id)
+      .job_type(QueueJobType::BackfillOrbit)
+      .build();
+
+    if let Err(err) = queue.send_job(job).await {
+      return build_api_err(500, err.to_string(), None);
+    }
+  }
+
+  HttpResponse::Created().finish()
 }
 
 pub async fn api_leave_orbit(
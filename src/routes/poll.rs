@@ -0,0 +1,35 @@
+use crate::{
+  db::{
+    follow_repository::FollowPool, job_repository::JobPool, poll_repository::PollPool, post_repository::PostPool,
+    session_repository::SessionPool,
+  },
+  helpers::auth::require_auth,
+  helpers::core::build_api_err,
+  logic::poll::{vote_in_poll, PollVoteRequest},
+  net::jwt::JwtContext,
+  work_queue::queue::Queue,
+};
+use actix_web::{web, HttpResponse, Responder};
+use uuid::Uuid;
+
+pub async fn api_vote_in_poll(
+  sessions: web::Data<SessionPool>,
+  follows: web::Data<FollowPool>,
+  posts: web::Data<PostPool>,
+  polls: web::Data<PollPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  post_id: web::Path<Uuid>,
+  req: web::Json<PollVoteRequest>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match vote_in_poll(&posts, &follows, &polls, &jobs, &queue, &post_id, &props.uid, &req).await {
+    Ok(poll) => HttpResponse::Created().json(poll),
+    Err(err) => build_api_err(500, err.to_string(), Some(err.to_string())),
+  }
+}
@@ -6,19 +6,26 @@ use uuid::Uuid;
 use crate::{
   cdn::cdn_store::Cdn,
   db::{
-    follow_repository::FollowPool, job_repository::JobPool, orbit_repository::OrbitPool,
-    post_attachment_repository::PostAttachmentPool, post_repository::PostPool, session_repository::SessionPool,
-    tombstone_repository::TombstonePool, user_repository::UserPool,
+    domain_block_repository::DomainBlockPool, follow_repository::FollowPool, hashtag_repository::HashtagPool,
+    job_repository::JobPool, mention_repository::MentionPool, orbit_repository::OrbitPool, poll_repository::PollPool,
+    post_attachment_repository::PostAttachmentPool, post_repository::PostPool,
+    post_revision_repository::PostRevisionPool, session_repository::SessionPool, tombstone_repository::TombstonePool,
+    user_repository::UserPool,
   },
   helpers::{
     auth::{query_auth, require_auth},
     core::{build_api_err, build_api_not_found, map_api_err},
     math::div_up,
   },
-  logic::post::{
-    create_post, delete_post, get_global_posts, get_global_posts_count, get_post, get_user_friends_posts,
-    get_user_friends_posts_count, get_user_posts, get_user_posts_count, upload_post_files, CreatePostResult,
-    NewPostRequest, NewPostResponse,
+  logic::{
+    hashtag::{get_hashtag_posts, get_hashtag_posts_count},
+    post::{
+      create_post, delete_post, get_global_posts, get_global_posts_count, get_post, get_post_revisions,
+      get_post_revisions_count, get_user_friends_posts, get_user_friends_posts_count, get_user_posts,
+      get_user_posts_count, update_post, upload_post_files, CreatePostResult, NewPostRequest, NewPostResponse,
+      UpdatePostRequest,
+    },
+    LogicErr,
   },
   model::{
     access_type::AccessType,
@@ -202,15 +209,25 @@ pub async fn api_get_user_post(
   }
 }
 
-pub async fn api_get_global_feed(posts: web::Data<PostPool>, query: web::Query<PostsQuery>) -> impl Responder {
+pub async fn api_get_global_feed(
+  sessions: web::Data<SessionPool>,
+  posts: web::Data<PostPool>,
+  query: web::Query<PostsQuery>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let user_id = match query_auth(&jwt, &sessions).await {
+    Some(props) => Some(props.uid),
+    None => None,
+  };
+
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
-  let posts_count = match get_global_posts_count(&posts).await {
+  let posts_count = match get_global_posts_count(&user_id, &posts).await {
     Ok(count) => count,
     Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
   };
 
-  let posts = match get_global_posts(page_size, page * page_size, &posts).await {
+  let posts = match get_global_posts(&user_id, page_size, page * page_size, &posts).await {
     Ok(posts) => posts,
     Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
   };
@@ -224,11 +241,18 @@ pub async fn api_get_global_feed(posts: web::Data<PostPool>, query: web::Query<P
 }
 
 pub async fn api_get_orbit_feed(
+  sessions: web::Data<SessionPool>,
   posts: web::Data<PostPool>,
   orbits: web::Data<OrbitPool>,
   orbit_shortcode: web::Path<String>,
   query: web::Query<PostsQuery>,
+  jwt: web::ReqData<JwtContext>,
 ) -> impl Responder {
+  let user_id = match query_auth(&jwt, &sessions).await {
+    Some(props) => Some(props.uid),
+    None => None,
+  };
+
   let orbit_id = match orbits.fetch_orbit_id_from_shortcode(&orbit_shortcode).await {
     Some(id) => id,
     None => return build_api_not_found(orbit_shortcode.to_string()),
@@ -236,13 +260,13 @@ pub async fn api_get_orbit_feed(
 
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
-  let posts_count = match posts.count_global_federated_orbit_feed(&orbit_id).await {
+  let posts_count = match posts.count_global_federated_orbit_feed(&orbit_id, &user_id).await {
     Ok(count) => count,
     Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
   };
 
   let posts = match posts
-    .fetch_global_federated_orbit_feed(&orbit_id, page_size, page * page_size)
+    .fetch_global_federated_orbit_feed(&orbit_id, &user_id, page_size, page * page_size)
     .await
   {
     Ok(posts) => posts,
@@ -258,19 +282,26 @@ pub async fn api_get_orbit_feed(
 }
 
 pub async fn api_get_orbit_feed_by_id(
+  sessions: web::Data<SessionPool>,
   posts: web::Data<PostPool>,
   orbit_id: web::Path<Uuid>,
   query: web::Query<PostsQuery>,
+  jwt: web::ReqData<JwtContext>,
 ) -> impl Responder {
+  let user_id = match query_auth(&jwt, &sessions).await {
+    Some(props) => Some(props.uid),
+    None => None,
+  };
+
   let page = query.page.unwrap_or(0);
   let page_size = query.page_size.unwrap_or(20);
-  let posts_count = match posts.count_global_federated_orbit_feed(&orbit_id).await {
+  let posts_count = match posts.count_global_federated_orbit_feed(&orbit_id, &user_id).await {
     Ok(count) => count,
     Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
   };
 
   let posts = match posts
-    .fetch_global_federated_orbit_feed(&orbit_id, page_size, page * page_size)
+    .fetch_global_federated_orbit_feed(&orbit_id, &user_id, page_size, page * page_size)
     .await
   {
     Ok(posts) => posts,
@@ -285,6 +316,38 @@ pub async fn api_get_orbit_feed_by_id(
   })
 }
 
+pub async fn api_get_hashtag_feed(
+  sessions: web::Data<SessionPool>,
+  posts: web::Data<PostPool>,
+  hashtag: web::Path<String>,
+  query: web::Query<PostsQuery>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let user_id = match query_auth(&jwt, &sessions).await {
+    Some(props) => Some(props.uid),
+    None => None,
+  };
+
+  let page = query.page.unwrap_or(0);
+  let page_size = query.page_size.unwrap_or(20);
+  let posts_count = match get_hashtag_posts_count(&hashtag, &user_id, &posts).await {
+    Ok(count) => count,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  let posts = match get_hashtag_posts(&hashtag, &user_id, page_size, page * page_size, &posts).await {
+    Ok(posts) => posts,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  HttpResponse::Ok().json(ListResponse {
+    data: posts,
+    page,
+    total_items: posts_count,
+    total_pages: div_up(posts_count, page_size) + 1,
+  })
+}
+
 pub async fn api_get_user_posts(
   sessions: web::Data<SessionPool>,
   posts: web::Data<PostPool>,
@@ -370,6 +433,11 @@ pub async fn api_get_user_liked_posts(
 pub async fn api_create_post(
   sessions: web::Data<SessionPool>,
   posts: web::Data<PostPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  mentions: web::Data<MentionPool>,
+  hashtags: web::Data<HashtagPool>,
+  polls: web::Data<PollPool>,
   req: web::Json<NewPostRequest>,
   jwt: web::ReqData<JwtContext>,
   queue: web::Data<Queue>,
@@ -380,7 +448,20 @@ pub async fn api_create_post(
     Err(res) => return res,
   };
 
-  match create_post(&posts, &jobs, &queue, &req, &props.uid).await {
+  match create_post(
+    &posts,
+    &users,
+    &domain_blocks,
+    &mentions,
+    &hashtags,
+    &polls,
+    &jobs,
+    &queue,
+    &req,
+    &props.uid,
+  )
+  .await
+  {
     Ok(result) => match result {
       CreatePostResult::WaitingForImages(post_id) => HttpResponse::Ok().json(NewPostResponse { id: post_id }),
       CreatePostResult::JobQueued(job_id) => HttpResponse::Ok().json(JobResponse { job_id }),
@@ -409,6 +490,92 @@ pub async fn api_delete_post(
   }
 }
 
+pub async fn api_update_post(
+  sessions: web::Data<SessionPool>,
+  posts: web::Data<PostPool>,
+  post_revisions: web::Data<PostRevisionPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  mentions: web::Data<MentionPool>,
+  hashtags: web::Data<HashtagPool>,
+  post_id: web::Path<Uuid>,
+  req: web::Json<UpdatePostRequest>,
+  jwt: web::ReqData<JwtContext>,
+  queue: web::Data<Queue>,
+  jobs: web::Data<JobPool>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match update_post(
+    &posts,
+    &post_revisions,
+    &users,
+    &domain_blocks,
+    &mentions,
+    &hashtags,
+    &jobs,
+    &queue,
+    &req,
+    &post_id,
+    &props.uid,
+  )
+  .await
+  {
+    Ok(Some(post)) => HttpResponse::Ok().json(ObjectResponse { data: post }),
+    Ok(None) => build_api_not_found(post_id.to_string()),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_get_post_revisions(
+  sessions: web::Data<SessionPool>,
+  posts: web::Data<PostPool>,
+  follows: web::Data<FollowPool>,
+  post_revisions: web::Data<PostRevisionPool>,
+  post_id: web::Path<Uuid>,
+  query: web::Query<PostsQuery>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let user_id = match query_auth(&jwt, &sessions).await {
+    Some(props) => Some(props.uid),
+    None => None,
+  };
+
+  let page = query.page.unwrap_or(0);
+  let page_size = query.page_size.unwrap_or(20);
+
+  let revisions = match get_post_revisions(
+    &post_id,
+    &user_id,
+    page_size,
+    page * page_size,
+    &posts,
+    &follows,
+    &post_revisions,
+  )
+  .await
+  {
+    Ok(revisions) => revisions,
+    Err(LogicErr::MissingRecord) => return build_api_not_found(post_id.to_string()),
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  let revisions_count = match get_post_revisions_count(&post_id, &post_revisions).await {
+    Ok(count) => count,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  HttpResponse::Ok().json(ListResponse {
+    data: revisions,
+    page,
+    total_items: revisions_count,
+    total_pages: div_up(revisions_count, page_size) + 1,
+  })
+}
+
 pub async fn api_upload_post_image(
   form: MultipartForm<PostUpload>,
   post_id: web::Path<Uuid>,
@@ -116,3 +116,9 @@ pub async fn api_redirect_to_user(user_id: web::Path<Uuid>, users: web::Data<Use
     .append_header(("location", format!("{}/users/{}", SETTINGS.server.fqdn, handle)))
     .finish()
 }
+
+pub async fn api_redirect_to_hashtag(hashtag: web::Path<String>) -> impl Responder {
+  HttpResponse::Found()
+    .append_header(("location", format!("{}/tags/{}", SETTINGS.server.fqdn, hashtag)))
+    .finish()
+}
@@ -0,0 +1,89 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    domain_block_repository::DomainBlockPool, job_repository::JobPool, relay_repository::RelayPool,
+    session_repository::SessionPool, user_repository::UserPool,
+  },
+  helpers::{auth::require_auth, core::map_api_err},
+  logic::relay::{get_relays, subscribe_relay, unsubscribe_relay},
+  model::response::ObjectResponse,
+  net::jwt::JwtContext,
+  work_queue::queue::Queue,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct RelayRequest {
+  /// The URI of the relay's actor, e.g. `https://relay.example/actor`
+  pub actor_uri: String,
+}
+
+pub async fn api_get_relays(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  relays: web::Data<RelayPool>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match get_relays(&props.uid, &users, &relays).await {
+    Ok(relays) => HttpResponse::Ok().json(ObjectResponse { data: relays }),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_subscribe_relay(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  relays: web::Data<RelayPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  req: web::Json<RelayRequest>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match subscribe_relay(
+    &props.uid,
+    &req.actor_uri,
+    &users,
+    &relays,
+    &domain_blocks,
+    &jobs,
+    &queue,
+  )
+  .await
+  {
+    Ok(relay) => HttpResponse::Created().json(ObjectResponse { data: relay }),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_unsubscribe_relay(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  relays: web::Data<RelayPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  relay_id: web::Path<Uuid>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match unsubscribe_relay(&props.uid, &relay_id, &users, &relays, &jobs, &queue).await {
+    Ok(_) => HttpResponse::Ok().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
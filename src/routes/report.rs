@@ -0,0 +1,106 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+  db::{
+    job_repository::JobPool, post_repository::PostPool, report_repository::ReportPool, session_repository::SessionPool,
+    user_repository::UserPool,
+  },
+  helpers::{
+    auth::require_auth,
+    core::{build_api_err, map_api_err},
+    math::div_up,
+  },
+  logic::{report::create_report, user::require_admin},
+  model::response::ListResponse,
+  net::jwt::JwtContext,
+  work_queue::queue::Queue,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct ReportRequest {
+  pub user_handle: String,
+  #[serde(default)]
+  pub post_ids: Vec<Uuid>,
+  pub comment: Option<String>,
+  /// Whether reports on remote users should also be sent to the user's own server
+  #[serde(default)]
+  pub forward: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportsQuery {
+  pub page: Option<i64>,
+  pub page_size: Option<i64>,
+}
+
+pub async fn api_create_report(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  posts: web::Data<PostPool>,
+  reports: web::Data<ReportPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  req: web::Json<ReportRequest>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  match create_report(
+    &props.uid,
+    &req.user_handle,
+    &req.post_ids,
+    &req.comment,
+    req.forward,
+    &users,
+    &posts,
+    &reports,
+    &jobs,
+    &queue,
+  )
+  .await
+  {
+    Ok(_) => HttpResponse::Created().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_get_reports(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  reports: web::Data<ReportPool>,
+  query: web::Query<ReportsQuery>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let props = match require_auth(&jwt, &sessions).await {
+    Ok(props) => props,
+    Err(res) => return res,
+  };
+
+  if let Err(err) = require_admin(&props.uid, &users).await {
+    return map_api_err(err);
+  }
+
+  let page = query.page.unwrap_or(0);
+  let page_size = query.page_size.unwrap_or(20);
+  let reports_count = match reports.count_reports().await {
+    Ok(count) => count,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  let reports = match reports.fetch_reports(page_size, page * page_size).await {
+    Ok(reports) => reports,
+    Err(err) => return build_api_err(500, err.to_string(), Some(err.to_string())),
+  };
+
+  HttpResponse::Ok().json(ListResponse {
+    data: reports,
+    page,
+    total_items: reports_count,
+    total_pages: div_up(reports_count, page_size) + 1,
+  })
+}
@@ -8,19 +8,26 @@ use uuid::Uuid;
 
 use crate::{
   cdn::cdn_store::Cdn,
-  db::{session_repository::SessionPool, user_repository::UserPool, user_stats_repository::UserStatsPool},
+  db::{
+    domain_block_repository::DomainBlockPool, job_repository::JobPool, session_repository::SessionPool,
+    tombstone_repository::TombstonePool, user_repository::UserPool, user_stats_repository::UserStatsPool,
+  },
   helpers::{
     auth::{query_auth, require_auth},
-    core::{build_api_err, build_api_not_found},
+    core::{build_api_err, build_api_not_found, map_api_err},
     math::div_up,
   },
-  logic::user::{get_user_by_handle, get_user_by_id},
+  logic::user::{
+    delete_user_account, get_user_by_handle, get_user_by_id, move_user_account, update_user_aliases,
+    update_user_profile,
+  },
   model::{
     response::{ListResponse, ObjectResponse},
     user_account_pub::UserAccountPub,
   },
   net::jwt::JwtContext,
   settings::SETTINGS,
+  work_queue::queue::Queue,
 };
 
 #[derive(Debug, Deserialize)]
@@ -55,6 +62,21 @@ pub struct ProfileUpdateRequest {
   pub url_5_title: Option<ProfileUpdateProp>,
 }
 
+#[derive(Deserialize)]
+pub struct ProfileAliasesRequest {
+  pub aliases: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ProfileMoveRequest {
+  pub target: String,
+}
+
+#[derive(Deserialize)]
+pub struct ProfileDeleteRequest {
+  pub password: String,
+}
+
 #[derive(MultipartForm)]
 pub struct ProfileAssetsUpload {
   #[multipart(rename = "images[]")]
@@ -80,6 +102,8 @@ pub async fn api_get_profile(
 pub async fn api_update_profile(
   sessions: web::Data<SessionPool>,
   users: web::Data<UserPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
   req: web::Json<ProfileUpdateRequest>,
   jwt: web::ReqData<JwtContext>,
 ) -> impl Responder {
@@ -200,15 +224,17 @@ pub async fn api_update_profile(
     };
   }
 
-  match users.update_from(&user).await {
+  match update_user_profile(&user, &users, &jobs, &queue).await {
     Ok(_) => HttpResponse::Ok().finish(),
-    Err(err) => build_api_err(500, err.to_string(), Some(err.to_string())),
+    Err(err) => map_api_err(err),
   }
 }
 
 pub async fn api_update_profile_assets(
   sessions: web::Data<SessionPool>,
   users: web::Data<UserPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
   cdn: web::Data<Cdn>,
   form: MultipartForm<ProfileAssetsUpload>,
   jwt: web::ReqData<JwtContext>,
@@ -258,9 +284,79 @@ pub async fn api_update_profile_assets(
 
   user.avatar_url = Some(avatar_uri);
 
-  match users.update_from(&user).await {
+  match update_user_profile(&user, &users, &jobs, &queue).await {
+    Ok(_) => HttpResponse::Ok().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_update_profile_aliases(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  req: web::Json<ProfileAliasesRequest>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let session = match require_auth(&jwt, &sessions).await {
+    Ok(session) => session,
+    Err(res) => return res,
+  };
+
+  match update_user_aliases(&session.uid, &req.aliases, &users, &domain_blocks, &jobs, &queue).await {
+    Ok(_) => HttpResponse::Ok().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_move_profile(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  domain_blocks: web::Data<DomainBlockPool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  req: web::Json<ProfileMoveRequest>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let session = match require_auth(&jwt, &sessions).await {
+    Ok(session) => session,
+    Err(res) => return res,
+  };
+
+  match move_user_account(&session.uid, &req.target, &users, &domain_blocks, &jobs, &queue).await {
+    Ok(_) => HttpResponse::Ok().finish(),
+    Err(err) => map_api_err(err),
+  }
+}
+
+pub async fn api_delete_profile(
+  sessions: web::Data<SessionPool>,
+  users: web::Data<UserPool>,
+  tombstones: web::Data<TombstonePool>,
+  jobs: web::Data<JobPool>,
+  queue: web::Data<Queue>,
+  req: web::Json<ProfileDeleteRequest>,
+  jwt: web::ReqData<JwtContext>,
+) -> impl Responder {
+  let session = match require_auth(&jwt, &sessions).await {
+    Ok(session) => session,
+    Err(res) => return res,
+  };
+
+  match delete_user_account(
+    &session.uid,
+    &req.password,
+    &users,
+    &sessions,
+    &tombstones,
+    &jobs,
+    &queue,
+  )
+  .await
+  {
     Ok(_) => HttpResponse::Ok().finish(),
-    Err(err) => build_api_err(500, err.to_string(), Some(err.to_string())),
+    Err(err) => map_api_err(err),
   }
 }
 
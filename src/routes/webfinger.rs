@@ -3,8 +3,12 @@ use serde::Deserialize;
 
 use crate::{
   db::{orbit_repository::OrbitPool, user_repository::UserPool},
-  helpers::core::{build_api_err, build_api_not_found},
+  helpers::{
+    api::relative_to_absolute_uri,
+    core::{build_api_err, build_api_not_found},
+  },
   logic::user::get_user_by_webfinger,
+  model::instance_actor::InstanceActor,
   settings::SETTINGS,
 };
 
@@ -18,6 +22,12 @@ pub async fn api_webfinger_query_resource(
   orbits: web::Data<OrbitPool>,
   query: web::Query<WebfingerQuery>,
 ) -> impl Responder {
+  if query.resource == InstanceActor::webfinger_subject()
+    || query.resource == relative_to_absolute_uri(&InstanceActor::fediverse_uri())
+  {
+    return HttpResponse::Ok().json(InstanceActor::to_webfinger());
+  }
+
   if query.resource.starts_with("acct") {
     return match get_user_by_webfinger(&query.resource, &users).await {
       Ok(user) => match user {
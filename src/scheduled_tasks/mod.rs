@@ -1,4 +1,5 @@
 pub mod scheduler;
+mod task_trigger_backfill_external_orbits_event;
 mod task_trigger_clean_jobs_event;
 mod task_trigger_refresh_external_orbits_event;
 mod task_trigger_refresh_external_profiles_event;
@@ -3,6 +3,7 @@ use std::time::Duration;
 use tokio::task::JoinHandle;
 
 use super::{
+  task_trigger_backfill_external_orbits_event::schedule_task_trigger_backfill_external_orbits_event,
   task_trigger_clean_jobs_event::schedule_task_trigger_clean_jobs_event,
   task_trigger_refresh_external_orbits_event::schedule_task_trigger_refresh_external_orbits_event,
   task_trigger_refresh_external_profiles_event::schedule_task_trigger_refresh_external_profiles_event,
@@ -18,6 +19,7 @@ impl JobScheduler {
 
     schedule_task_trigger_clean_jobs_event(&mut scheduler);
     schedule_task_trigger_refresh_external_orbits_event(&mut scheduler);
+    schedule_task_trigger_backfill_external_orbits_event(&mut scheduler);
     schedule_task_trigger_refresh_external_profiles_event(&mut scheduler);
 
     let handle = tokio::spawn(async move {
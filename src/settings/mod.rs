@@ -88,6 +88,22 @@ pub struct Application {
   pub imagemagick_exe_path: String,
   pub secure: bool,
   pub verify_external_https_certificates: bool,
+  /// Requires ActivityPub GET requests to be signed, akin to Mastodon's AUTHORIZED_FETCH mode. Only takes effect when
+  /// `secure` is enabled.
+  #[serde(default)]
+  pub authorized_fetch: bool,
+  /// Handles of the local users allowed to manage instance-wide settings such as domain blocks
+  #[serde(default)]
+  pub admin_handles: Vec<String>,
+  /// How many of a remote user's or orbit's most recent outbox items to look through for posts to import when one of
+  /// our users follows or joins them, and when periodically catching up on joined orbits. Setting this to 0 disables
+  /// backfilling.
+  #[serde(default = "default_outbox_backfill_depth")]
+  pub outbox_backfill_depth: usize,
+}
+
+fn default_outbox_backfill_depth() -> usize {
+  20
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -152,6 +168,9 @@ impl Settings {
         imagemagick_exe_path: "convert".to_string(),
         secure: false,
         verify_external_https_certificates: false,
+        authorized_fetch: false,
+        admin_handles: vec![],
+        outbox_backfill_depth: default_outbox_backfill_depth(),
       },
     }
   }
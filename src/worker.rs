@@ -24,8 +24,10 @@ use crate::worker_internal::services::{DB, QUEUE};
 use aws::clients::AWSClient;
 use cdn::cdn_store::Cdn;
 use env_logger::WriteStyle;
+use federation::activitypub::set_instance_private_key;
 use log::error;
 use log::LevelFilter;
+use logic::instance_actor::get_instance_actor;
 use rabbitmq::clients::RabbitMQClient;
 use scheduled_tasks::scheduler::JobScheduler;
 use settings::SETTINGS;
@@ -47,6 +49,11 @@ async fn main() -> std::io::Result<()> {
   AWSClient::create_sqs_client().await;
   RabbitMQClient::create_rabbitmq_client().await;
 
+  match get_instance_actor(&DB.instance_actors).await {
+    Ok(actor) => set_instance_private_key(actor.private_key),
+    Err(err) => error!("Failed to load instance actor: {}", err),
+  }
+
   let cdn = Cdn::new();
 
   let _ = match SETTINGS.queue.schedule_jobs {